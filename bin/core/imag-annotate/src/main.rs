@@ -44,6 +44,7 @@ extern crate toml_query;
 extern crate libimagentryannotation;
 extern crate libimagentryedit;
 extern crate libimagerror;
+extern crate libimagentryquery;
 #[macro_use] extern crate libimagrt;
 extern crate libimagstore;
 extern crate libimagutil;
@@ -64,6 +65,7 @@ use libimagerror::errors::ErrorMsg as EM;
 use libimagerror::iter::TraceIterator;
 use libimagrt::runtime::Runtime;
 use libimagrt::setup::generate_runtime_setup;
+use libimagentryquery::search::SavedSearchResolver;
 use libimagstore::store::FileLockEntry;
 use libimagstore::iter::get::StoreIdGetIteratorExtension;
 use libimagentrylink::internal::InternalLinker;
@@ -76,6 +78,7 @@ fn main() {
                                     &version,
                                     "Add annotations to entries",
                                     ui::build_ui);
+    rt.register_id_resolver(SavedSearchResolver);
 
     rt.cli()
         .subcommand_name()
@@ -40,6 +40,7 @@ extern crate log;
 
 extern crate libimagentrycategory;
 extern crate libimagerror;
+extern crate libimagentryquery;
 #[macro_use] extern crate libimagrt;
 extern crate libimagstore;
 extern crate libimaginteraction;
@@ -49,6 +50,7 @@ use libimagerror::exit::ExitUnwrap;
 use libimagerror::io::ToExitCode;
 use libimagrt::runtime::Runtime;
 use libimagrt::setup::generate_runtime_setup;
+use libimagentryquery::search::SavedSearchResolver;
 
 mod ui;
 
@@ -67,6 +69,7 @@ fn main() {
                                     &version,
                                     "Add a category to entries and manage categories",
                                     ui::build_ui);
+    rt.register_id_resolver(SavedSearchResolver);
 
     rt.cli()
         .subcommand_name()
@@ -56,6 +56,8 @@ use libimagerror::exit::ExitUnwrap;
 use libimagstore::store::FileLockEntry;
 use libimagstore::storeid::StoreId;
 use libimagentrylink::internal::*;
+use libimagentrylink::internal::store_check::StoreLinkConsistentExt;
+use libimagentrylink::internal::store_check::repair_link_issues_interactive;
 
 use toml::Value;
 use toml_query::read::TomlValueReadExt;
@@ -240,6 +242,40 @@ fn main() {
         do_write!(out, "{} verified entries", verified_count);
         do_write!(out, "{} unverified entries", unverified_count);
     }
+
+    if rt.cli().is_present("check-links") || rt.cli().is_present("fix-links") {
+        let exit_code = check_links(&rt, rt.cli().is_present("fix-links"), rt.cli().is_present("yes"));
+        ::std::process::exit(exit_code);
+    }
+}
+
+/// List the link issues in the store and repair them, if `fix` is set
+///
+/// Returns a non-zero exit code if there are issues left in the store.
+fn check_links(rt: &Runtime, fix: bool, yes: bool) -> i32 {
+    let issues  = rt.store().find_link_issues().map_err_trace_exit_unwrap();
+    let mut out = rt.stdout();
+
+    do_write!(out, "");
+    do_write!(out, "{} link issues", issues.len());
+    for issue in issues.iter() {
+        do_write!(out, "    {}", issue);
+    }
+
+    if issues.is_empty() {
+        return 0
+    }
+
+    if !fix {
+        return 1
+    }
+
+    let report = repair_link_issues_interactive(rt.store(), issues, yes, rt.stdin(), &mut rt.stdout())
+        .map_err_trace_exit_unwrap();
+
+    do_write!(out, "{}", report);
+
+    if report.is_complete() { 0 } else { 1 }
 }
 
 fn get_config(rt: &Runtime, s: &'static str) -> Option<String> {
@@ -17,9 +17,28 @@
 // Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
 //
 
-use clap::App;
+use clap::{Arg, App};
 
 pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
     app
+        .arg(Arg::with_name("check-links")
+             .long("check-links")
+             .takes_value(false)
+             .required(false)
+             .help("Also check the links in the store for dead, one-directional and duplicate links (might be time-consuming)"))
+
+        .arg(Arg::with_name("fix-links")
+             .long("fix-links")
+             .takes_value(false)
+             .required(false)
+             .help("Check the links in the store and repair the issues found, asking for each issue"))
+
+        .arg(Arg::with_name("yes")
+             .long("yes")
+             .short("y")
+             .takes_value(false)
+             .required(false)
+             .requires("fix-links")
+             .help("Do not ask before repairing a link issue"))
 }
 
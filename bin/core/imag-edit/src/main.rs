@@ -39,6 +39,7 @@ extern crate clap;
 
 extern crate libimagentryedit;
 extern crate libimagerror;
+extern crate libimagentryquery;
 #[macro_use] extern crate libimagrt;
 extern crate libimagstore;
 extern crate libimagutil;
@@ -48,6 +49,7 @@ use libimagerror::iter::TraceIterator;
 use libimagentryedit::edit::Edit;
 use libimagentryedit::edit::EditHeader;
 use libimagrt::setup::generate_runtime_setup;
+use libimagentryquery::search::SavedSearchResolver;
 use libimagstore::storeid::StoreIdIterator;
 use libimagstore::iter::get::StoreIdGetIteratorExtension;
 
@@ -59,11 +61,12 @@ fn main() {
                                     &version,
                                     "Edit store entries with $EDITOR",
                                     ui::build_ui);
+    rt.register_id_resolver(SavedSearchResolver);
 
     let edit_header = rt.cli().is_present("edit-header");
     let edit_header_only = rt.cli().is_present("edit-header-only");
 
-    let sids = rt.ids::<::ui::PathProvider>().map_err_trace_exit_unwrap();
+    let sids = rt.ids_or_pick::<::ui::PathProvider>().map_err_trace_exit_unwrap();
 
     StoreIdIterator::new(Box::new(sids.into_iter().map(Ok)))
         .into_get_iter(rt.store())
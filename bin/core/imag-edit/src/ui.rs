@@ -55,17 +55,15 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
 pub struct PathProvider;
 impl IdPathProvider for PathProvider {
     fn get_ids(matches: &ArgMatches) -> Vec<StoreId> {
+        // Without ids, the user picks the entries interactively
         matches
             .values_of("entry")
-            .ok_or_else(|| {
-                error!("No StoreId found");
-                ::std::process::exit(1)
+            .map(|vals| {
+                vals.map(PathBuf::from)
+                    .map(|pb| pb.into_storeid())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err_trace_exit_unwrap()
             })
-            .unwrap()
-            .into_iter()
-            .map(PathBuf::from)
-            .map(|pb| pb.into_storeid())
-            .collect::<Result<Vec<_>, _>>()
-            .map_err_trace_exit_unwrap()
+            .unwrap_or_else(Vec::new)
     }
 }
@@ -43,6 +43,7 @@ extern crate libimagentrygps;
 #[macro_use] extern crate libimagrt;
 extern crate libimagutil;
 extern crate libimagerror;
+extern crate libimagentryquery;
 extern crate libimagstore;
 
 use std::io::Write;
@@ -55,6 +56,7 @@ use failure::err_msg;
 use libimagentrygps::types::*;
 use libimagentrygps::entry::*;
 use libimagrt::setup::generate_runtime_setup;
+use libimagentryquery::search::SavedSearchResolver;
 use libimagrt::runtime::Runtime;
 use libimagerror::trace::MapErrTrace;
 use libimagerror::exit::ExitUnwrap;
@@ -68,6 +70,7 @@ fn main() {
                                     &version,
                                     "Add GPS coordinates to entries",
                                     ui::build_ui);
+    rt.register_id_resolver(SavedSearchResolver);
 
     rt.cli()
         .subcommand_name()
@@ -41,17 +41,21 @@ extern crate regex;
 extern crate libimagstore;
 #[macro_use] extern crate libimagrt;
 extern crate libimagerror;
+extern crate libimagentryquery;
 
 use std::io::Write;
 
 use regex::Regex;
 
 use libimagrt::runtime::Runtime;
+use libimagrt::idstream::IdAttributes;
 use libimagrt::setup::generate_runtime_setup;
 use libimagstore::store::Entry;
 use libimagerror::trace::MapErrTrace;
 use libimagerror::exit::ExitUnwrap;
 use libimagerror::io::ToExitCode;
+use libimagentryquery::cli::query_from_matches;
+use libimagentryquery::cli::entry_matches;
 
 mod ui;
 
@@ -84,12 +88,15 @@ fn main() {
             ::std::process::exit(1)
         });
 
+    let query = query_from_matches(rt.cli()).map_err_trace_exit_unwrap();
+
     let overall_count = rt
         .store()
         .entries()
         .map_err_trace_exit_unwrap()
         .into_get_iter()
         .filter_map(|res| res.map_err_trace_exit_unwrap())
+        .filter(|entry| entry_matches(query.as_ref(), entry).map_err_trace_exit_unwrap())
         .filter(|entry| pattern.is_match(entry.get_content()))
         .map(|entry| show(&rt, &entry, &pattern, &opts, &mut count))
         .count();
@@ -125,6 +132,12 @@ fn show(rt: &Runtime, e: &Entry, re: &Regex, opts: &Options, count: &mut usize)
         *count += 1;
     }
 
-    let _ = rt.report_touched(e.get_location()).unwrap_or_exit();
+    let mut attributes = IdAttributes::default();
+    attributes.matches = re
+        .find_iter(e.get_content())
+        .map(|m| (m.start(), m.end()))
+        .collect();
+
+    let _ = rt.report_touched_with(e.get_location(), attributes).unwrap_or_exit();
 }
 
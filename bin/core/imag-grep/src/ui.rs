@@ -37,6 +37,8 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
              .multiple(false)
              .help("Count matches"))
 
+        .arg(::libimagentryquery::cli::query_arg())
+
         .arg(Arg::with_name("pattern")
              .index(1)
              .takes_value(false)
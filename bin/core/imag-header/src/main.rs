@@ -37,10 +37,11 @@ extern crate clap;
 extern crate toml;
 extern crate toml_query;
 extern crate filters;
-extern crate failure;
+#[macro_use] extern crate failure;
 
 extern crate libimagentryedit;
 extern crate libimagerror;
+extern crate libimagentryquery;
 #[macro_use] extern crate libimagrt;
 extern crate libimagstore;
 extern crate libimagutil;
@@ -53,25 +54,33 @@ use filters::filter::Filter;
 use failure::Error;
 
 use libimagerror::exit::ExitCode;
+use libimagerror::exit::ExitUnwrap;
 use libimagerror::io::ToExitCode;
 use libimagerror::iter::TraceIterator;
 use libimagerror::trace::MapErrTrace;
+use libimagerror::trace::trace_error;
 use libimagrt::runtime::Runtime;
 use libimagrt::setup::generate_runtime_setup;
+use libimagentryquery::search::SavedSearchResolver;
 use libimagstore::iter::get::StoreIdGetIteratorExtension;
 use libimagstore::store::FileLockEntry;
 use libimagstore::storeid::StoreIdIterator;
 
+use toml::Value;
 use toml_query::read::TomlValueReadExt;
 
+mod modify;
 mod ui;
 
+use modify::ValueType;
+
 fn main() {
     let version = make_imag_version!();
     let rt = generate_runtime_setup("imag-header",
                                     &version,
                                     "Plumbing tool for reading/writing structured data in entries",
                                     ui::build_ui);
+    rt.register_id_resolver(SavedSearchResolver);
 
     let list_output_with_ids     = rt.cli().is_present("list-id");
     let list_output_with_ids_fmt = rt.cli().value_of("list-id-format");
@@ -94,6 +103,12 @@ fn main() {
         ("float", Some(mtch))  => float(&rt, mtch, iter),
         ("string", Some(mtch)) => string(&rt, mtch, iter),
         ("bool", Some(mtch))   => boolean(&rt, mtch, iter),
+        ("set", Some(mtch))        => set(&rt, mtch, iter),
+        ("unset", Some(mtch))      => unset(&rt, mtch, iter),
+        ("rename-key", Some(mtch)) => rename_key(&rt, mtch, iter),
+        ("append", Some(mtch))     => append(&rt, mtch, iter),
+        ("remove", Some(mtch))     => remove(&rt, mtch, iter),
+        ("coerce", Some(mtch))     => coerce(&rt, mtch, iter),
         (other, _mtchs) => {
             debug!("Unknown command");
             rt.handle_unknown_subcommand("imag-header", other, rt.cli())
@@ -351,10 +366,116 @@ fn boolean<'a, 'e, I>(rt: &Runtime, mtch: &ArgMatches<'a>, iter: I) -> i32
     })
 }
 
+fn set<'a, 'e, I>(rt: &Runtime, mtch: &ArgMatches<'a>, iter: I) -> i32
+    where I: Iterator<Item = FileLockEntry<'e>>
+{
+    debug!("Processing headers: setting value");
+    let header_path = get_header_path(mtch, "header-value-path");
+    let value       = get_values(mtch).pop().unwrap(); // safe by clap
+
+    modify_headers(rt, iter, |header| modify::set(header, header_path, value.clone()))
+}
+
+fn unset<'a, 'e, I>(rt: &Runtime, mtch: &ArgMatches<'a>, iter: I) -> i32
+    where I: Iterator<Item = FileLockEntry<'e>>
+{
+    debug!("Processing headers: removing value");
+    let header_path = get_header_path(mtch, "header-value-path");
+
+    modify_headers(rt, iter, |header| modify::unset(header, header_path).map(|old| old.is_some()))
+}
+
+fn rename_key<'a, 'e, I>(rt: &Runtime, mtch: &ArgMatches<'a>, iter: I) -> i32
+    where I: Iterator<Item = FileLockEntry<'e>>
+{
+    debug!("Processing headers: renaming key");
+    let header_path = get_header_path(mtch, "header-value-path");
+    let new_path    = get_header_path(mtch, "header-value-path-new");
+
+    modify_headers(rt, iter, |header| modify::rename(header, header_path, new_path).map(|_| true))
+}
+
+fn append<'a, 'e, I>(rt: &Runtime, mtch: &ArgMatches<'a>, iter: I) -> i32
+    where I: Iterator<Item = FileLockEntry<'e>>
+{
+    debug!("Processing headers: appending to array");
+    let header_path = get_header_path(mtch, "header-value-path");
+    let values      = get_values(mtch);
 
+    modify_headers(rt, iter, |header| modify::append(header, header_path, values.clone()))
+}
+
+fn remove<'a, 'e, I>(rt: &Runtime, mtch: &ArgMatches<'a>, iter: I) -> i32
+    where I: Iterator<Item = FileLockEntry<'e>>
+{
+    debug!("Processing headers: removing from array");
+    let header_path = get_header_path(mtch, "header-value-path");
+    let values      = get_values(mtch);
+
+    modify_headers(rt, iter, |header| modify::remove(header, header_path, &values).map(|n| n != 0))
+}
+
+fn coerce<'a, 'e, I>(rt: &Runtime, mtch: &ArgMatches<'a>, iter: I) -> i32
+    where I: Iterator<Item = FileLockEntry<'e>>
+{
+    debug!("Processing headers: converting value");
+    let header_path = get_header_path(mtch, "header-value-path");
+    let value_type  = get_value_type(mtch);
+
+    modify_headers(rt, iter, |header| modify::coerce_at(header, header_path, value_type))
+}
 
 // helpers
 //
+
+/// Apply `f` to the header of each entry
+///
+/// Entries for which `f` reports a change are reported as touched. A failure for one entry does
+/// not stop the others from being processed, but results in a non-zero exit code. The header of
+/// an entry `f` failed on is restored, so a partial modification is never written back.
+fn modify_headers<'e, I, F>(rt: &Runtime, iter: I, f: F) -> i32
+    where I: Iterator<Item = FileLockEntry<'e>>,
+          F: Fn(&mut Value) -> ::failure::Fallible<bool>
+{
+    iter.fold(0, |accu, mut entry| {
+        trace!("Processing headers: working on {:?}", entry.get_location());
+        let original = entry.get_header().clone();
+        match f(entry.get_header_mut()) {
+            Ok(true) => {
+                debug!("Processing headers: changed {}", entry.get_location());
+                let _ = rt.report_touched(entry.get_location()).unwrap_or_exit();
+                accu
+            },
+            Ok(false) => {
+                debug!("Processing headers: nothing to change in {}", entry.get_location());
+                accu
+            },
+            Err(e) => {
+                *entry.get_header_mut() = original;
+                trace_error(&e);
+                error!("Could not modify header of {}", entry.get_location());
+                1
+            },
+        }
+    })
+}
+
+fn get_value_type<'a>(mtch: &ArgMatches<'a>) -> ValueType {
+    mtch.value_of("header-value-type")
+        .map(ValueType::from_str)
+        .unwrap_or(Ok(ValueType::Auto))
+        .map_err_trace_exit_unwrap()
+}
+
+fn get_values<'a>(mtch: &ArgMatches<'a>) -> Vec<Value> {
+    let value_type = get_value_type(mtch);
+    mtch.values_of("header-value")
+        .unwrap() // safe by clap
+        .map(|v| modify::parse_value(v, value_type))
+        .collect::<::failure::Fallible<Vec<_>>>()
+        .map_err_trace_exit_unwrap()
+}
+
 fn get_header_path<'a>(mtch: &'a ArgMatches<'a>, path: &'static str) -> &'a str {
     let header_path = mtch.value_of(path).unwrap(); // safe by clap
     debug!("Processing headers: header path = {}", header_path);
@@ -0,0 +1,330 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2019 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+//! Header modifications
+//!
+//! These functions work on the header of a single entry, `main()` applies them to all entries
+//! passed to imag-header.
+
+use std::str::FromStr;
+
+use toml::Value;
+use toml_query::insert::TomlValueInsertExt;
+use toml_query::read::TomlValueReadExt;
+use failure::Fallible as Result;
+use failure::Error;
+
+use libimagerror::kind::ErrorKind;
+use libimagerror::kind::kind_err;
+
+/// The type values from the commandline are converted to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueType {
+    /// Guess the type: TOML literals (numbers, booleans, arrays, quoted strings, dates) are
+    /// parsed as such, everything else is a string
+    Auto,
+    String,
+    Integer,
+    Float,
+    Boolean,
+}
+
+impl FromStr for ValueType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<ValueType> {
+        match s {
+            "auto"   => Ok(ValueType::Auto),
+            "string" => Ok(ValueType::String),
+            "int"    => Ok(ValueType::Integer),
+            "float"  => Ok(ValueType::Float),
+            "bool"   => Ok(ValueType::Boolean),
+            other    => Err(kind_err(ErrorKind::Parse, format!("Unknown type: {}", other))),
+        }
+    }
+}
+
+/// Parse a value from the commandline as `t`
+pub fn parse_value(s: &str, t: ValueType) -> Result<Value> {
+    match t {
+        ValueType::Auto => {
+            let doc = format!("v = {}", s);
+            let parsed = ::toml::de::from_str::<Value>(&doc)
+                .ok()
+                .and_then(|mut doc| doc.as_table_mut().and_then(|t| t.remove("v")));
+
+            Ok(parsed.unwrap_or_else(|| Value::String(String::from(s))))
+        },
+        other => coerce(Value::String(String::from(s)), other),
+    }
+}
+
+/// Convert a value to the type `t`
+///
+/// Fails if the value cannot be represented as `t`, for example a string which is not a number as
+/// integer or a float with a fractional part as integer.
+pub fn coerce(v: Value, t: ValueType) -> Result<Value> {
+    let fail = |v: &Value| {
+        kind_err(ErrorKind::Parse, format!("Cannot convert '{}' to {:?}", v, t))
+    };
+
+    match (t, v) {
+        (ValueType::Auto, Value::String(s)) => parse_value(&s, ValueType::Auto),
+        (ValueType::Auto, v)                => Ok(v),
+
+        (ValueType::String, Value::String(s))   => Ok(Value::String(s)),
+        (ValueType::String, Value::Integer(i))  => Ok(Value::String(i.to_string())),
+        (ValueType::String, Value::Float(f))    => Ok(Value::String(f.to_string())),
+        (ValueType::String, Value::Boolean(b))  => Ok(Value::String(b.to_string())),
+        (ValueType::String, Value::Datetime(d)) => Ok(Value::String(d.to_string())),
+
+        (ValueType::Integer, Value::Integer(i)) => Ok(Value::Integer(i)),
+        (ValueType::Integer, Value::Float(f))   => if f.fract() == 0.0 {
+            Ok(Value::Integer(f as i64))
+        } else {
+            Err(fail(&Value::Float(f)))
+        },
+        (ValueType::Integer, Value::String(s))  => i64::from_str(s.trim())
+            .map(Value::Integer)
+            .map_err(|_| fail(&Value::String(s.clone()))),
+
+        (ValueType::Float, Value::Float(f))     => Ok(Value::Float(f)),
+        (ValueType::Float, Value::Integer(i))   => Ok(Value::Float(i as f64)),
+        (ValueType::Float, Value::String(s))    => f64::from_str(s.trim())
+            .map(Value::Float)
+            .map_err(|_| fail(&Value::String(s.clone()))),
+
+        (ValueType::Boolean, Value::Boolean(b)) => Ok(Value::Boolean(b)),
+        (ValueType::Boolean, Value::Integer(i)) if i == 0 || i == 1 => Ok(Value::Boolean(i == 1)),
+        (ValueType::Boolean, Value::String(s))  => match s.trim() {
+            "true"  => Ok(Value::Boolean(true)),
+            "false" => Ok(Value::Boolean(false)),
+            _       => Err(fail(&Value::String(s.clone()))),
+        },
+
+        (_, v) => Err(fail(&v)),
+    }
+}
+
+/// Set the value at `path`, creating tables on the way if necessary
+///
+/// Returns whether the header changed.
+pub fn set(header: &mut Value, path: &str, value: Value) -> Result<bool> {
+    let old = header.insert(path, value.clone())?;
+    Ok(old.as_ref() != Some(&value))
+}
+
+/// Remove the value at `path`
+///
+/// Unlike `toml_query`s delete, this also removes non-empty tables and arrays. Returns the removed
+/// value, if there was one.
+pub fn unset(header: &mut Value, path: &str) -> Result<Option<Value>> {
+    let (parent, key) = match path.rfind('.') {
+        Some(i) => (Some(&path[..i]), &path[i + 1..]),
+        None    => (None, path),
+    };
+
+    let parent = match parent {
+        Some(p) => match header.read_mut(p)? {
+            Some(v) => v,
+            None    => return Ok(None),
+        },
+        None => header,
+    };
+
+    if key.starts_with('[') && key.ends_with(']') {
+        let idx = usize::from_str(&key[1..key.len() - 1])
+            .map_err(|_| kind_err(ErrorKind::Parse, format!("Invalid array index in '{}'", path)))?;
+
+        match *parent {
+            Value::Array(ref mut a) if idx < a.len() => Ok(Some(a.remove(idx))),
+            Value::Array(_)                          => Ok(None),
+            _ => Err(format_err!("Header value at '{}' is not an array", &path[..path.len() - key.len() - 1])),
+        }
+    } else {
+        match *parent {
+            Value::Table(ref mut t) => Ok(t.remove(key)),
+            _                       => Ok(None),
+        }
+    }
+}
+
+/// Move the value at `from` to `to`
+///
+/// Fails if there is no value at `from` or if there is a value at `to` already.
+///
+/// The header is left untouched if the value cannot be inserted at `to`, for example because the
+/// path crosses a value which is not a table.
+pub fn rename(header: &mut Value, from: &str, to: &str) -> Result<()> {
+    if header.read(from)?.is_none() {
+        return Err(kind_err(ErrorKind::NotFound, format!("No header value at '{}'", from)))
+    }
+
+    if header.read(to)?.is_some() {
+        return Err(kind_err(ErrorKind::AlreadyExists, format!("Header value at '{}' exists already", to)))
+    }
+
+    let mut moved = header.clone();
+    let value     = unset(&mut moved, from)?
+        .ok_or_else(|| kind_err(ErrorKind::NotFound, format!("No header value at '{}'", from)))?;
+    let _         = moved.insert(to, value)?;
+    *header       = moved;
+    Ok(())
+}
+
+/// Append `values` to the array at `path`, creating the array if necessary
+pub fn append(header: &mut Value, path: &str, values: Vec<Value>) -> Result<bool> {
+    if values.is_empty() {
+        return Ok(false)
+    }
+
+    match header.read_mut(path)? {
+        Some(&mut Value::Array(ref mut a)) => {
+            a.extend(values);
+            return Ok(true)
+        },
+        Some(_) => return Err(format_err!("Header value at '{}' is not an array", path)),
+        None    => {},
+    }
+
+    header.insert(path, Value::Array(values)).map(|_| true).map_err(Error::from)
+}
+
+/// Remove all elements which are equal to one of `values` from the array at `path`
+///
+/// Returns the number of removed elements.
+pub fn remove(header: &mut Value, path: &str, values: &[Value]) -> Result<usize> {
+    match header.read_mut(path)? {
+        Some(&mut Value::Array(ref mut a)) => {
+            let len = a.len();
+            a.retain(|v| !values.contains(v));
+            Ok(len - a.len())
+        },
+        Some(_) => Err(format_err!("Header value at '{}' is not an array", path)),
+        None    => Ok(0),
+    }
+}
+
+/// Convert the value at `path` to `t`
+///
+/// Returns whether the value changed.
+pub fn coerce_at(header: &mut Value, path: &str, t: ValueType) -> Result<bool> {
+    let old = match header.read(path)? {
+        Some(v) => v.clone(),
+        None    => return Ok(false),
+    };
+
+    let new = coerce(old.clone(), t)?;
+    if new == old {
+        Ok(false)
+    } else {
+        header.insert(path, new).map(|_| true).map_err(Error::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(s: &str) -> Value {
+        ::toml::de::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn test_parse_value() {
+        assert_eq!(Value::Integer(1), parse_value("1", ValueType::Auto).unwrap());
+        assert_eq!(Value::Boolean(true), parse_value("true", ValueType::Auto).unwrap());
+        assert_eq!(Value::String("foo bar".into()), parse_value("foo bar", ValueType::Auto).unwrap());
+        assert_eq!(Value::String("1".into()), parse_value("\"1\"", ValueType::Auto).unwrap());
+        assert_eq!(Value::Array(vec![Value::Integer(1), Value::Integer(2)]),
+                   parse_value("[1, 2]", ValueType::Auto).unwrap());
+
+        assert_eq!(Value::String("1".into()), parse_value("1", ValueType::String).unwrap());
+        assert_eq!(Value::Float(1.0), parse_value("1", ValueType::Float).unwrap());
+        assert!(parse_value("foo", ValueType::Integer).is_err());
+        assert!(parse_value("yes", ValueType::Boolean).is_err());
+    }
+
+    #[test]
+    fn test_coerce() {
+        assert_eq!(Value::Integer(2), coerce(Value::Float(2.0), ValueType::Integer).unwrap());
+        assert!(coerce(Value::Float(2.5), ValueType::Integer).is_err());
+        assert_eq!(Value::Integer(42), coerce(Value::String("42".into()), ValueType::Integer).unwrap());
+        assert_eq!(Value::String("42".into()), coerce(Value::Integer(42), ValueType::String).unwrap());
+        assert_eq!(Value::Boolean(false), coerce(Value::Integer(0), ValueType::Boolean).unwrap());
+        assert!(coerce(Value::Array(vec![]), ValueType::String).is_err());
+    }
+
+    #[test]
+    fn test_set_and_unset() {
+        let mut h = header("[a]\nb = 1\n");
+        assert!(set(&mut h, "a.c.d", Value::Integer(2)).unwrap());
+        assert!(!set(&mut h, "a.c.d", Value::Integer(2)).unwrap());
+        assert_eq!(Some(&Value::Integer(2)), h.read("a.c.d").unwrap());
+
+        // non-empty tables are removed as well
+        assert!(unset(&mut h, "a.c").unwrap().is_some());
+        assert!(h.read("a.c").unwrap().is_none());
+        assert!(unset(&mut h, "a.c").unwrap().is_none());
+        assert!(unset(&mut h, "x.y").unwrap().is_none());
+
+        let mut h = header("[a]\nl = [1, 2, 3]\n");
+        assert_eq!(Some(Value::Integer(2)), unset(&mut h, "a.l.[1]").unwrap());
+        assert_eq!(header("[a]\nl = [1, 3]\n"), h);
+    }
+
+    #[test]
+    fn test_rename() {
+        let mut h = header("[a]\nb = 1\nc = 2\n");
+        assert!(rename(&mut h, "a.b", "x.y").is_ok());
+        assert_eq!(header("[a]\nc = 2\n[x]\ny = 1\n"), h);
+        assert!(rename(&mut h, "a.c", "x.y").is_err());
+
+        // A missing value is reported as such, even if the target exists
+        let e = rename(&mut h, "a.b", "x.y").unwrap_err();
+        assert_eq!(ErrorKind::NotFound, ErrorKind::of(&e));
+        assert!(format!("{}", e).contains("a.b"));
+
+        let before = h.clone();
+        assert!(rename(&mut h, "x.y", "a.c.d").is_err());
+        assert_eq!(before, h);
+    }
+
+    #[test]
+    fn test_append_and_remove() {
+        let mut h = header("[a]\nb = 1\n");
+        assert!(append(&mut h, "a.l", vec![Value::Integer(1), Value::Integer(2)]).unwrap());
+        assert!(append(&mut h, "a.l", vec![Value::Integer(1)]).unwrap());
+        assert_eq!(header("[a]\nb = 1\nl = [1, 2, 1]\n"), h);
+        assert!(append(&mut h, "a.b", vec![Value::Integer(1)]).is_err());
+
+        assert_eq!(2, remove(&mut h, "a.l", &[Value::Integer(1)]).unwrap());
+        assert_eq!(0, remove(&mut h, "a.nope", &[Value::Integer(1)]).unwrap());
+        assert_eq!(header("[a]\nb = 1\nl = [2]\n"), h);
+    }
+
+    #[test]
+    fn test_coerce_at() {
+        let mut h = header("[a]\nb = \"5\"\n");
+        assert!(coerce_at(&mut h, "a.b", ValueType::Integer).unwrap());
+        assert!(!coerce_at(&mut h, "a.b", ValueType::Integer).unwrap());
+        assert_eq!(header("[a]\nb = 5\n"), h);
+        assert!(!coerce_at(&mut h, "a.nope", ValueType::Integer).unwrap());
+    }
+}
@@ -26,6 +26,8 @@ use libimagstore::storeid::IntoStoreId;
 use libimagrt::runtime::IdPathProvider;
 use libimagerror::trace::MapErrTrace;
 
+const VALUE_TYPES : &'static [&'static str] = &["auto", "string", "int", "float", "bool"];
+
 pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
     app
         .arg(Arg::with_name("id")
@@ -230,6 +232,130 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                          .required(false)
                          .help("Check whether the flag is unset (false)"))
                    )
+
+        .subcommand(SubCommand::with_name("set")
+                    .about("Set a header value, creating it if it does not exist")
+                    .version("0.1")
+                    .arg(Arg::with_name("header-value-path")
+                         .index(1)
+                         .takes_value(true)
+                         .required(true)
+                         .multiple(false)
+                         .help("Path of the header value")
+                         .value_name("PATH"))
+                    .arg(Arg::with_name("header-value")
+                         .index(2)
+                         .takes_value(true)
+                         .required(true)
+                         .multiple(false)
+                         .help("The value to set")
+                         .value_name("VALUE"))
+                    .arg(value_type_arg())
+                   )
+
+        .subcommand(SubCommand::with_name("unset")
+                    .about("Remove a header value (including tables and arrays)")
+                    .version("0.1")
+                    .arg(Arg::with_name("header-value-path")
+                         .index(1)
+                         .takes_value(true)
+                         .required(true)
+                         .multiple(false)
+                         .help("Path of the header value")
+                         .value_name("PATH"))
+                   )
+
+        .subcommand(SubCommand::with_name("rename-key")
+                    .about("Move a header value to another path")
+                    .version("0.1")
+                    .arg(Arg::with_name("header-value-path")
+                         .index(1)
+                         .takes_value(true)
+                         .required(true)
+                         .multiple(false)
+                         .help("Path of the header value")
+                         .value_name("PATH"))
+                    .arg(Arg::with_name("header-value-path-new")
+                         .index(2)
+                         .takes_value(true)
+                         .required(true)
+                         .multiple(false)
+                         .help("New path of the header value. Must not exist yet")
+                         .value_name("NEWPATH"))
+                   )
+
+        .subcommand(SubCommand::with_name("append")
+                    .about("Append values to a header array, creating it if it does not exist")
+                    .version("0.1")
+                    .arg(Arg::with_name("header-value-path")
+                         .index(1)
+                         .takes_value(true)
+                         .required(true)
+                         .multiple(false)
+                         .help("Path of the header array")
+                         .value_name("PATH"))
+                    .arg(Arg::with_name("header-value")
+                         .index(2)
+                         .takes_value(true)
+                         .required(true)
+                         .multiple(true)
+                         .help("The values to append")
+                         .value_name("VALUE"))
+                    .arg(value_type_arg())
+                   )
+
+        .subcommand(SubCommand::with_name("remove")
+                    .about("Remove all occurrences of values from a header array")
+                    .version("0.1")
+                    .arg(Arg::with_name("header-value-path")
+                         .index(1)
+                         .takes_value(true)
+                         .required(true)
+                         .multiple(false)
+                         .help("Path of the header array")
+                         .value_name("PATH"))
+                    .arg(Arg::with_name("header-value")
+                         .index(2)
+                         .takes_value(true)
+                         .required(true)
+                         .multiple(true)
+                         .help("The values to remove")
+                         .value_name("VALUE"))
+                    .arg(value_type_arg())
+                   )
+
+        .subcommand(SubCommand::with_name("coerce")
+                    .about("Convert a header value to another type")
+                    .version("0.1")
+                    .arg(Arg::with_name("header-value-path")
+                         .index(1)
+                         .takes_value(true)
+                         .required(true)
+                         .multiple(false)
+                         .help("Path of the header value")
+                         .value_name("PATH"))
+                    .arg(Arg::with_name("header-value-type")
+                         .index(2)
+                         .takes_value(true)
+                         .required(true)
+                         .multiple(false)
+                         .possible_values(VALUE_TYPES)
+                         .help("The type to convert the value to")
+                         .value_name("TYPE"))
+                   )
+}
+
+fn value_type_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("header-value-type")
+        .long("type")
+        .short("t")
+        .takes_value(true)
+        .required(false)
+        .multiple(false)
+        .possible_values(VALUE_TYPES)
+        .default_value("auto")
+        .help("The type of the values. 'auto' parses TOML literals (numbers, booleans, arrays, quoted strings, dates) and treats everything else as string")
+        .value_name("TYPE")
 }
 
 pub struct PathProvider;
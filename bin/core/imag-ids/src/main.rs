@@ -36,37 +36,45 @@
 
 extern crate clap;
 extern crate filters;
-#[macro_use] extern crate nom;
-#[macro_use] extern crate log;
-#[macro_use] extern crate is_match;
 extern crate toml;
 extern crate toml_query;
+extern crate serde_json;
 extern crate failure;
-
-#[cfg(test)]
-extern crate env_logger;
+#[macro_use] extern crate log;
 
 extern crate libimagerror;
 extern crate libimagstore;
+extern crate libimagentryquery;
+extern crate libimagutil;
 #[macro_use] extern crate libimagrt;
 
 use std::io::Write;
 use std::process::exit;
+use std::str::FromStr;
 
 use filters::filter::Filter;
+use filters::failable::filter::FailableFilter;
 
 use libimagstore::storeid::StoreId;
+use libimagrt::runtime::Runtime;
 use libimagrt::setup::generate_runtime_setup;
+use libimagentryquery::search::SavedSearchResolver;
 use libimagerror::trace::MapErrTrace;
 use libimagerror::iter::TraceIterator;
 use libimagerror::exit::ExitUnwrap;
 use libimagerror::io::ToExitCode;
+use libimagerror::kind::ErrorKind;
+use libimagerror::kind::kind_err;
+use libimagentryquery::filter::Query;
 
 mod id_filters;
+mod report;
 mod ui;
 
 use ui::build_ui;
 use id_filters::IsInCollectionsFilter;
+use report::SortKey;
+use report::SortOrder;
 
 fn main() {
     let version = make_imag_version!();
@@ -74,6 +82,7 @@ fn main() {
                                     &version,
                                     "print all ids",
                                     build_ui);
+    rt.register_id_resolver(SavedSearchResolver);
 
     let print_storepath = rt.cli().is_present("print-storepath");
 
@@ -83,15 +92,15 @@ fn main() {
         .map(|v| v.collect::<Vec<&str>>());
 
     let collection_filter = IsInCollectionsFilter::new(values);
-    let query_filter      : Option<id_filters::header_filter_lang::Query> = rt
+    let query_filter      : Option<Query> = rt
         .cli()
         .subcommand_matches("where")
         .map(|matches| {
             let query = matches.value_of("where-filter").unwrap(); // safe by clap
-            id_filters::header_filter_lang::parse(&query)
+            Query::parse(&query).map_err_trace_exit_unwrap()
         });
 
-    let iterator = if rt.ids_from_stdin() {
+    let ids = if rt.ids_from_stdin() {
         debug!("Fetching IDs from stdin...");
         let ids = rt.ids::<::ui::PathProvider>().map_err_trace_exit_unwrap();
         Box::new(ids.into_iter().map(Ok))
@@ -114,10 +123,34 @@ fn main() {
                     exit(1)
                 });
 
-            qf.filter(&entry)
+            qf.filter(&entry).map_err_trace_exit_unwrap()
         }
     })
-    .map(|id| if print_storepath {
+    .collect::<Vec<StoreId>>();
+
+    let ids = match rt.cli().value_of("sort-by").map(SortKey::from) {
+        None      => ids,
+        Some(key) => {
+            let order = match rt.cli().value_of("sort-order") {
+                Some("desc") => SortOrder::Descending,
+                _            => SortOrder::Ascending,
+            };
+
+            debug!("Sorting {} ids by {:?}, {:?}", ids.len(), key, order);
+            report::sort_ids(&rt, ids, &key, order).map_err_trace_exit_unwrap()
+        },
+    };
+
+    let offset = usize_arg(&rt, "offset").unwrap_or(0);
+    let limit  = usize_arg(&rt, "limit").unwrap_or(::std::usize::MAX);
+    let ids    = ids.into_iter().skip(offset).take(limit);
+
+    let fields = rt.cli().values_of("fields").map(|v| v.collect::<Vec<&str>>());
+    if fields.is_some() || rt.cli().is_present("json") {
+        return print_fields(&rt, ids, fields.unwrap_or_else(Vec::new))
+    }
+
+    let iterator = ids.map(|id| if print_storepath {
         (Some(rt.store().path()), id)
     } else {
         (None, id)
@@ -143,3 +176,48 @@ fn main() {
     })
 }
 
+fn usize_arg(rt: &Runtime, name: &str) -> Option<usize> {
+    rt.cli().value_of(name).map(|s| {
+        usize::from_str(s)
+            .map_err(|_| kind_err(ErrorKind::Parse, format!("Not a positive number: {}", s)))
+            .map_err_trace_exit_unwrap()
+    })
+}
+
+/// Print the ids together with the header values at `fields`, as tab separated rows or as JSON
+///
+/// If the output is piped, the values are attached to the ids in the id stream instead.
+fn print_fields<I>(rt: &Runtime, ids: I, fields: Vec<&str>)
+    where I: Iterator<Item = StoreId>
+{
+    let json       = rt.cli().is_present("json");
+    let mut stdout = rt.stdout();
+    let mut rows   = vec![];
+
+    for id in ids {
+        let values = report::read_fields(rt, &id, &fields).map_err_trace_exit_unwrap();
+        let name   = id.local_display_string();
+
+        // When piped, the values travel with the ids instead of being printed
+        rt.report_touched_with(&id, report::field_attributes(&fields, &values)).unwrap_or_exit();
+
+        if rt.output_is_pipe() {
+            continue
+        }
+
+        if json {
+            rows.push(report::render_json(&name, &fields, values));
+        } else {
+            writeln!(stdout, "{}", report::render_row(&name, &values))
+                .to_exit_code()
+                .unwrap_or_exit();
+        }
+    }
+
+    if json && !rt.output_is_pipe() {
+        let out = serde_json::to_string(&rows)
+            .map_err(failure::Error::from)
+            .map_err_trace_exit_unwrap();
+        writeln!(stdout, "{}", out).to_exit_code().unwrap_or_exit();
+    }
+}
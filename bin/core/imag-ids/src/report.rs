@@ -0,0 +1,281 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2019 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+//! Sorting of ids and printing of header fields, for using imag-ids as a report generator
+
+use std::cmp::Ordering;
+use std::fs;
+use std::time::UNIX_EPOCH;
+
+use toml::Value;
+use toml_query::read::TomlValueReadExt;
+use serde_json::Value as JsonValue;
+use serde_json::Map;
+use failure::Fallible as Result;
+
+use libimagrt::idstream::IdAttributes;
+use libimagrt::runtime::Runtime;
+use libimagstore::storeid::StoreId;
+use libimagerror::kind::ErrorKind;
+use libimagerror::kind::kind_err;
+
+/// What the ids can be sorted by
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SortKey {
+    Id,
+    ModificationTime,
+    ContentLength,
+    Header(String),
+}
+
+impl<'a> From<&'a str> for SortKey {
+    fn from(s: &'a str) -> SortKey {
+        match s {
+            "id"             => SortKey::Id,
+            "mtime"          => SortKey::ModificationTime,
+            "content-length" => SortKey::ContentLength,
+            other            => SortKey::Header(String::from(other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+/// Sort `ids` by `key`
+///
+/// Entries which do not have a value for the key (for example because the header field is not
+/// set) are always sorted last. Entries with equal values are ordered by id.
+pub fn sort_ids(rt: &Runtime, ids: Vec<StoreId>, key: &SortKey, order: SortOrder) -> Result<Vec<StoreId>> {
+    let mut keyed = ids
+        .into_iter()
+        .map(|id| sort_value(rt, &id, key).map(|v| (v, id)))
+        .collect::<Result<Vec<_>>>()?;
+
+    keyed.sort_by(|&(ref a_val, ref a_id), &(ref b_val, ref b_id)| {
+        let ord = match (a_val.as_ref(), b_val.as_ref()) {
+            (None, None)       => Ordering::Equal,
+            (None, Some(_))    => return Ordering::Greater,
+            (Some(_), None)    => return Ordering::Less,
+            (Some(a), Some(b)) => compare_values(a, b),
+        };
+
+        let ord = ord.then_with(|| a_id.cmp(b_id));
+        match order {
+            SortOrder::Ascending  => ord,
+            SortOrder::Descending => ord.reverse(),
+        }
+    });
+
+    Ok(keyed.into_iter().map(|(_, id)| id).collect())
+}
+
+fn sort_value(rt: &Runtime, id: &StoreId, key: &SortKey) -> Result<Option<Value>> {
+    match *key {
+        SortKey::Id => Ok(Some(Value::String(id.local_display_string()))),
+
+        SortKey::ModificationTime => {
+            // Entries which are not on disk (yet) have no modification time
+            let mtime = fs::metadata(rt.store().path().join(id.local()))
+                .and_then(|md| md.modified())
+                .ok()
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map(|dur| Value::Integer(dur.as_secs() as i64));
+            Ok(mtime)
+        },
+
+        SortKey::ContentLength => {
+            let entry = get_entry(rt, id)?;
+            Ok(Some(Value::Integer(entry.get_content().len() as i64)))
+        },
+
+        SortKey::Header(ref path) => {
+            let entry = get_entry(rt, id)?;
+            let value = entry.get_header().read(path)?.cloned();
+            Ok(value)
+        },
+    }
+}
+
+fn get_entry<'a>(rt: &'a Runtime, id: &StoreId) -> Result<::libimagstore::store::FileLockEntry<'a>> {
+    rt.store()
+        .get(id.clone())?
+        .ok_or_else(|| kind_err(ErrorKind::NotFound, format!("Entry '{}' does not exist", id)))
+}
+
+/// Compare two header values
+///
+/// Values of the same type are compared by value, values of different types by their type.
+fn compare_values(a: &Value, b: &Value) -> Ordering {
+    fn type_rank(v: &Value) -> usize {
+        match *v {
+            Value::Boolean(_)  => 0,
+            Value::Integer(_)  => 1,
+            Value::Float(_)    => 1,
+            Value::Datetime(_) => 2,
+            Value::String(_)   => 3,
+            Value::Array(_)    => 4,
+            Value::Table(_)    => 5,
+        }
+    }
+
+    match (a, b) {
+        (&Value::Boolean(a), &Value::Boolean(b))           => a.cmp(&b),
+        (&Value::Integer(a), &Value::Integer(b))           => a.cmp(&b),
+        (&Value::Float(a), &Value::Float(b))               => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+        (&Value::Integer(a), &Value::Float(b))             => (a as f64).partial_cmp(&b).unwrap_or(Ordering::Equal),
+        (&Value::Float(a), &Value::Integer(b))             => a.partial_cmp(&(b as f64)).unwrap_or(Ordering::Equal),
+        (&Value::Datetime(ref a), &Value::Datetime(ref b)) => a.to_string().cmp(&b.to_string()),
+        (&Value::String(ref a), &Value::String(ref b))     => a.cmp(b),
+        (&Value::Array(ref a), &Value::Array(ref b))       => {
+            a.iter()
+                .zip(b.iter())
+                .map(|(a, b)| compare_values(a, b))
+                .find(|ord| *ord != Ordering::Equal)
+                .unwrap_or_else(|| a.len().cmp(&b.len()))
+        },
+        (a, b) => type_rank(a).cmp(&type_rank(b)),
+    }
+}
+
+/// Read the header fields `fields` of the entry `id`
+///
+/// Fields which are not set are `None`.
+pub fn read_fields(rt: &Runtime, id: &StoreId, fields: &[&str]) -> Result<Vec<Option<Value>>> {
+    let entry = get_entry(rt, id)?;
+    fields
+        .iter()
+        .map(|field| entry.get_header().read(field).map(|v| v.cloned()).map_err(From::from))
+        .collect()
+}
+
+/// Render a row of the tab separated field output
+///
+/// Backslashes, tabs and line breaks in the values are escaped (`\\`, `\t`, `\n`, `\r`), so each
+/// row stays on one line and has one column per field.
+pub fn render_row(id: &str, values: &[Option<Value>]) -> String {
+    let mut row = escape_field(id);
+    for value in values {
+        row.push('\t');
+        match *value {
+            Some(Value::String(ref s)) => row.push_str(&escape_field(s)),
+            Some(ref other)            => row.push_str(&escape_field(&other.to_string())),
+            None                       => {},
+        }
+    }
+    row
+}
+
+fn escape_field(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            c    => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// The values of `fields` which are set, as attributes for the id stream
+pub fn field_attributes(fields: &[&str], values: &[Option<Value>]) -> IdAttributes {
+    let header = fields
+        .iter()
+        .zip(values.iter())
+        .filter_map(|(field, value)| value.as_ref().map(|v| (String::from(*field), v.clone())))
+        .collect();
+
+    IdAttributes { header, ..IdAttributes::default() }
+}
+
+/// Render the id and the fields as JSON object
+pub fn render_json(id: &str, fields: &[&str], values: Vec<Option<Value>>) -> JsonValue {
+    let mut map = Map::new();
+    let _ = map.insert(String::from("id"), JsonValue::String(String::from(id)));
+    for (field, value) in fields.iter().zip(values.into_iter()) {
+        let value = value.map(toml_to_json).unwrap_or(JsonValue::Null);
+        let _ = map.insert(String::from(*field), value);
+    }
+    JsonValue::Object(map)
+}
+
+fn toml_to_json(value: Value) -> JsonValue {
+    match value {
+        Value::String(s)   => JsonValue::String(s),
+        Value::Integer(i)  => JsonValue::from(i),
+        Value::Float(f)    => JsonValue::from(f),
+        Value::Boolean(b)  => JsonValue::Bool(b),
+        Value::Datetime(d) => JsonValue::String(d.to_string()),
+        Value::Array(a)    => JsonValue::Array(a.into_iter().map(toml_to_json).collect()),
+        Value::Table(t)    => {
+            JsonValue::Object(t.into_iter().map(|(k, v)| (k, toml_to_json(v))).collect())
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sort_key_from_str() {
+        assert_eq!(SortKey::Id, SortKey::from("id"));
+        assert_eq!(SortKey::ModificationTime, SortKey::from("mtime"));
+        assert_eq!(SortKey::ContentLength, SortKey::from("content-length"));
+        assert_eq!(SortKey::Header(String::from("todo.due")), SortKey::from("todo.due"));
+    }
+
+    #[test]
+    fn test_compare_values() {
+        assert_eq!(Ordering::Less, compare_values(&Value::Integer(1), &Value::Integer(2)));
+        assert_eq!(Ordering::Less, compare_values(&Value::Integer(1), &Value::Float(1.5)));
+        assert_eq!(Ordering::Greater, compare_values(&Value::String("b".into()), &Value::String("a".into())));
+        assert_eq!(Ordering::Less, compare_values(&Value::Integer(100), &Value::String("a".into())));
+
+        let a = Value::Array(vec![Value::Integer(1), Value::Integer(2)]);
+        let b = Value::Array(vec![Value::Integer(1)]);
+        assert_eq!(Ordering::Greater, compare_values(&a, &b));
+    }
+
+    #[test]
+    fn test_render() {
+        let values = vec![Some(Value::String("foo".into())), None, Some(Value::Integer(3))];
+        assert_eq!("a/b\tfoo\t\t3", render_row("a/b", &values));
+
+        let json = render_json("a/b", &["x", "y", "z"], values);
+        assert_eq!(r#"{"id":"a/b","x":"foo","y":null,"z":3}"#, ::serde_json::to_string(&json).unwrap());
+
+        let values = vec![Some(Value::String("one\ttwo\nthree\\".into()))];
+        assert_eq!("a/b\tone\\ttwo\\nthree\\\\", render_row("a/b", &values));
+    }
+
+    #[test]
+    fn test_field_attributes() {
+        let values = vec![Some(Value::String("foo".into())), None];
+        let attrs  = field_attributes(&["x", "y"], &values);
+        assert_eq!(1, attrs.header.len());
+        assert_eq!(Some(&Value::String("foo".into())), attrs.header.get("x"));
+    }
+}
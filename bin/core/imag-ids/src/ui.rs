@@ -40,6 +40,62 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
              .value_names(&["COLLECTION"])
              .help("Filter for ids which are only in these collections"))
 
+        .arg(Arg::with_name("sort-by")
+             .long("sort-by")
+             .short("s")
+             .required(false)
+             .takes_value(true)
+             .multiple(false)
+             .value_name("KEY")
+             .help("Sort the ids by 'id', 'mtime', 'content-length' or the value at a header path"))
+
+        .arg(Arg::with_name("sort-order")
+             .long("order")
+             .required(false)
+             .takes_value(true)
+             .multiple(false)
+             .possible_values(&["asc", "desc"])
+             .default_value("asc")
+             .requires("sort-by")
+             .value_name("ORDER")
+             .help("Sort ascending or descending"))
+
+        .arg(Arg::with_name("offset")
+             .long("offset")
+             .required(false)
+             .takes_value(true)
+             .multiple(false)
+             .value_name("N")
+             .validator(::libimagutil::cli_validators::is_integer)
+             .help("Skip the first N ids"))
+
+        .arg(Arg::with_name("limit")
+             .long("limit")
+             .short("n")
+             .required(false)
+             .takes_value(true)
+             .multiple(false)
+             .value_name("N")
+             .validator(::libimagutil::cli_validators::is_integer)
+             .help("Print at most N ids"))
+
+        .arg(Arg::with_name("fields")
+             .long("fields")
+             .short("f")
+             .required(false)
+             .takes_value(true)
+             .multiple(true)
+             .use_delimiter(true)
+             .value_name("HEADER PATH")
+             .help("Print these header values next to the ids, separated by tabs. If the output is piped, the values are passed on with the ids in the structured id stream"))
+
+        .arg(Arg::with_name("json")
+             .long("json")
+             .required(false)
+             .takes_value(false)
+             .multiple(false)
+             .help("Print the ids and the values of '--fields' as JSON"))
+
         .subcommand(SubCommand::with_name("where")
                     .arg(Arg::with_name("where-filter")
                          .index(1)
@@ -47,9 +103,9 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                          .takes_value(true)
                          .multiple(false)
                          .value_names(&["QUERY"])
-                         .help("Query the header of the entries and filter them"))
+                         .help("Filter the entries with a query"))
                    )
-        .after_help(include_str!("../static/language-doc.md"))
+        .after_help(::libimagentryquery::LANGUAGE_DOC)
 }
 
 pub struct PathProvider;
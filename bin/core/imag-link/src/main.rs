@@ -39,14 +39,15 @@ extern crate clap;
 extern crate url;
 extern crate failure;
 #[macro_use] extern crate prettytable;
-#[cfg(test)] extern crate toml;
-#[cfg(test)] extern crate toml_query;
+extern crate toml;
+extern crate toml_query;
 #[cfg(test)] extern crate env_logger;
 
 extern crate libimagentrylink;
 #[macro_use] extern crate libimagrt;
 extern crate libimagstore;
 extern crate libimagerror;
+extern crate libimagentryquery;
 
 #[cfg(test)]
 #[macro_use]
@@ -57,24 +58,41 @@ extern crate libimagutil;
 
 use std::io::Write;
 use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
 
+use clap::ArgMatches;
 use failure::Error;
 use failure::err_msg;
 
+use libimagentrylink::analysis::LinkAnalysis;
+use libimagentrylink::archive::ArchiveFormat;
+use libimagentrylink::archive::Archiver;
+use libimagentrylink::check::LinkChecker;
+use libimagentrylink::check::external_link_ids;
+use libimagentrylink::check::broken_links;
 use libimagentrylink::external::ExternalLinker;
+use libimagentrylink::external::Link as ExternalLink;
+use libimagentrylink::external::is_external_link_storeid;
+use libimagentrylink::graph::GraphBuilder;
 use libimagentrylink::internal::InternalLinker;
 use libimagentrylink::internal::store_check::StoreLinkConsistentExt;
+use libimagentrylink::internal::store_check::repair_link_issues_interactive;
+use libimagentrylink::relation::Relation;
 use libimagerror::trace::{MapErrTrace, trace_error};
 use libimagerror::exit::ExitUnwrap;
 use libimagerror::io::ToExitCode;
 use libimagrt::runtime::Runtime;
 use libimagrt::setup::generate_runtime_setup;
+use libimagentryquery::search::SavedSearchResolver;
 use libimagstore::store::FileLockEntry;
 use libimagstore::storeid::StoreId;
 use libimagutil::warn_exit::warn_exit;
 use libimagutil::warn_result::*;
 
 use url::Url;
+use toml::Value;
+use toml_query::read::TomlValueReadExt;
 use failure::Fallible as Result;
 
 mod ui;
@@ -87,17 +105,9 @@ fn main() {
                                     &version,
                                     "Link entries",
                                     build_ui);
+    rt.register_id_resolver(SavedSearchResolver);
     if rt.cli().is_present("check-consistency") {
-        let exit_code = match rt.store().check_link_consistency() {
-            Ok(_) => {
-                info!("Store is consistent");
-                0
-            }
-            Err(e) => {
-                trace_error(&e);
-                1
-            }
-        };
+        let exit_code = check_link_consistency(&rt, rt.cli().is_present("fix"), rt.cli().is_present("yes"));
         ::std::process::exit(exit_code);
     }
 
@@ -105,10 +115,19 @@ fn main() {
         .subcommand_name()
         .map(|name| {
             match name {
-                "remove" => remove_linking(&rt),
-                "unlink" => unlink(&rt),
-                "list"   => list_linkings(&rt),
-                other    => {
+                "remove"         => remove_linking(&rt),
+                "unlink"         => unlink(&rt),
+                "list"           => list_linkings(&rt),
+                "graph"          => graph(&rt),
+                "neighbours"     => neighbours(&rt),
+                "path"           => path(&rt),
+                "components"     => components(&rt),
+                "isolated"       => isolated(&rt),
+                "rank"           => rank(&rt),
+                "check-external" => check_external(&rt),
+                "list-broken"    => list_broken(&rt),
+                "archive"        => archive(&rt),
+                other            => {
                     debug!("Unknown command");
                     let _ = rt.handle_unknown_subcommand("imag-link", other, rt.cli())
                         .map_err_trace_exit_unwrap()
@@ -128,6 +147,36 @@ fn main() {
         .map_err_trace_exit_unwrap();
 }
 
+/// List the link issues in the store and repair them, if `fix` is set
+///
+/// Returns a non-zero exit code if there are issues left in the store.
+fn check_link_consistency(rt: &Runtime, fix: bool, yes: bool) -> i32 {
+    let issues = rt.store().find_link_issues().map_err_trace_exit_unwrap();
+    if issues.is_empty() {
+        info!("Store is consistent");
+        return 0
+    }
+
+    for issue in issues.iter() {
+        let _ = writeln!(rt.stdout(), "{}", issue)
+            .to_exit_code()
+            .unwrap_or_exit();
+    }
+
+    if !fix {
+        return 1
+    }
+
+    let report = repair_link_issues_interactive(rt.store(), issues, yes, rt.stdin(), &mut rt.stdout())
+        .map_err_trace_exit_unwrap();
+
+    let _ = writeln!(rt.stdout(), "{}", report)
+        .to_exit_code()
+        .unwrap_or_exit();
+
+    if report.is_complete() { 0 } else { 1 }
+}
+
 fn get_entry_by_name<'a>(rt: &'a Runtime, name: &str) -> Result<Option<FileLockEntry<'a>>> {
     use libimagstore::storeid::StoreId;
 
@@ -138,9 +187,15 @@ fn get_entry_by_name<'a>(rt: &'a Runtime, name: &str) -> Result<Option<FileLockE
     result
 }
 
+fn get_relation(matches: &ArgMatches) -> Option<Relation> {
+    matches.value_of("relation").map(|r| Relation::from_str(r).map_err_trace_exit_unwrap())
+}
+
 fn link_from_to<'a, I>(rt: &'a Runtime, from: &'a str, to: I)
     where I: Iterator<Item = &'a str>
 {
+    let relation = get_relation(rt.cli());
+
     let mut from_entry = match get_entry_by_name(rt, from).map_err_trace_exit_unwrap() {
         Some(e) => e,
         None    => {
@@ -153,6 +208,11 @@ fn link_from_to<'a, I>(rt: &'a Runtime, from: &'a str, to: I)
         debug!("Handling 'to' entry: {:?}", entry);
         if !rt.store().get(PathBuf::from(entry)).map_err_trace_exit_unwrap().is_some() {
             debug!("Linking externally: {:?} -> {:?}", from, entry);
+            if relation.is_some() {
+                error!("Relations are only supported for links between entries: {}", entry);
+                ::std::process::exit(1)
+            }
+
             let url = Url::parse(entry).unwrap_or_else(|e| {
                 error!("Error parsing URL: {:?}", e);
                 ::std::process::exit(1);
@@ -182,9 +242,10 @@ fn link_from_to<'a, I>(rt: &'a Runtime, from: &'a str, to: I)
                     ::std::process::exit(1)
                 },
             };
-            let _ = from_entry
-                .add_internal_link(&mut to_entry)
-                .map_err_trace_exit_unwrap();
+            let _ = match relation {
+                Some(ref relation) => from_entry.add_internal_related_link(&mut to_entry, relation.clone()),
+                None               => from_entry.add_internal_link(&mut to_entry),
+            }.map_err_trace_exit_unwrap();
 
             let _ = rt.report_touched(to_entry.get_location()).unwrap_or_exit();
         }
@@ -197,9 +258,10 @@ fn link_from_to<'a, I>(rt: &'a Runtime, from: &'a str, to: I)
 }
 
 fn remove_linking(rt: &Runtime) {
-    let mut from = rt.cli()
-        .subcommand_matches("remove")
-        .unwrap() // safe, we know there is an "remove" subcommand
+    let scmd     = rt.cli().subcommand_matches("remove").unwrap(); // safe, we know there is an "remove" subcommand
+    let relation = get_relation(scmd);
+
+    let mut from = scmd
         .value_of("from")
         .map(PathBuf::from)
         .map(|id| {
@@ -217,16 +279,19 @@ fn remove_linking(rt: &Runtime) {
         .for_each(|id| match rt.store().get(id.clone()) {
             Err(e) => trace_error(&e),
             Ok(Some(mut to_entry)) => {
-                let _ = to_entry
-                    .remove_internal_link(&mut from)
-                    .map_err_trace_exit_unwrap();
+                let _ = match relation {
+                    Some(ref relation) => from.remove_internal_related_link(&mut to_entry, relation),
+                    None               => to_entry.remove_internal_link(&mut from),
+                }.map_err_trace_exit_unwrap();
 
                 let _ = rt.report_touched(to_entry.get_location()).unwrap_or_exit();
             },
             Ok(None) => {
                 // looks like this is not an entry, but a filesystem URI and therefor an
                 // external link...?
-                if id.local().is_file() {
+                if relation.is_some() {
+                    warn!("Entry not found: {:?}", id);
+                } else if id.local().is_file() {
                     let pb = id.local().to_str().unwrap_or_else(|| {
                         warn!("Not StoreId and not a Path: {}", id);
                         ::std::process::exit(1);
@@ -247,7 +312,7 @@ fn remove_linking(rt: &Runtime) {
 }
 
 fn unlink(rt: &Runtime) {
-    rt.ids::<::ui::PathProvider>().map_err_trace_exit_unwrap().into_iter().for_each(|id| {
+    rt.ids_or_pick::<::ui::PathProvider>().map_err_trace_exit_unwrap().into_iter().for_each(|id| {
         rt.store()
             .get(id.clone())
             .map_err_trace_exit_unwrap()
@@ -269,14 +334,21 @@ fn list_linkings(rt: &Runtime) {
 
     let list_externals  = cmd.is_present("list-externals-too");
     let list_plain      = cmd.is_present("list-plain");
+    let relation        = get_relation(cmd);
 
     let mut tab = ::prettytable::Table::new();
-    tab.set_titles(row!["#", "Link"]);
+    tab.set_titles(row!["#", "Link", "Relation"]);
 
-    rt.ids::<::ui::PathProvider>().map_err_trace_exit_unwrap().into_iter().for_each(|id| {
+    rt.ids_or_pick::<::ui::PathProvider>().map_err_trace_exit_unwrap().into_iter().for_each(|id| {
         match rt.store().get(id.clone()) {
             Ok(Some(entry)) => {
-                for (i, link) in entry.get_internal_links().map_err_trace_exit_unwrap().enumerate() {
+                let links = match relation {
+                    Some(ref relation) => entry.get_internal_related_links(relation),
+                    None               => entry.get_internal_links(),
+                }.map_err_trace_exit_unwrap();
+
+                for (i, link) in links.enumerate() {
+                    let rel = link.relation().map(Relation::to_string);
                     let link = link
                         .to_str()
                         .map_warn_err(|e| format!("Failed to convert StoreId to string: {:?}", e))
@@ -284,16 +356,19 @@ fn list_linkings(rt: &Runtime) {
 
                     if let Some(link) = link {
                         if list_plain {
-                            let _ = writeln!(rt.stdout(), "{: <3}: {}", i, link)
-                                .to_exit_code()
-                                .unwrap_or_exit();
+                            let _ = match rel {
+                                Some(ref rel) => writeln!(rt.stdout(), "{: <3}: {} ({})", i, link, rel),
+                                None          => writeln!(rt.stdout(), "{: <3}: {}", i, link),
+                            }
+                            .to_exit_code()
+                            .unwrap_or_exit();
                         } else {
-                            tab.add_row(row![i, link]);
+                            tab.add_row(row![i, link, rel.unwrap_or_default()]);
                         }
                     }
                 }
 
-                if list_externals {
+                if list_externals && relation.is_none() {
                     entry.get_external_links(rt.store())
                         .map_err_trace_exit_unwrap()
                         .enumerate()
@@ -307,7 +382,7 @@ fn list_linkings(rt: &Runtime) {
                                     .to_exit_code()
                                     .unwrap_or_exit();
                             } else {
-                                tab.add_row(row![i, link]);
+                                tab.add_row(row![i, link, ""]);
                             }
                         })
                 }
@@ -331,6 +406,354 @@ fn list_linkings(rt: &Runtime) {
     }
 }
 
+fn graph(rt: &Runtime) {
+    let cmd = rt.cli()
+        .subcommand_matches("graph")
+        .unwrap(); // safed by clap
+
+    let roots = rt.ids::<::ui::PathProvider>().map_err_trace_exit_unwrap();
+
+    let label_fields = cmd
+        .values_of("label-field")
+        .map(|vals| vals.map(String::from).collect())
+        .unwrap_or_else(|| get_config_label_fields(rt));
+
+    let mut builder = GraphBuilder::new(rt.store())
+        .with_externals(cmd.is_present("externals"))
+        .with_label_fields(label_fields);
+
+    if !roots.is_empty() {
+        builder = builder.with_roots(roots.clone());
+    }
+
+    if let Some(depth) = get_usize_arg(cmd, "depth") {
+        builder = builder.with_depth(depth);
+    }
+
+    let graph = builder.build().map_err_trace_exit_unwrap();
+
+    let output = match cmd.value_of("format") {
+        Some("graphml") => graph.to_graphml(),
+        Some("json")    => graph.to_json().map_err_trace_exit_unwrap(),
+        _               => graph.to_dot(cmd.is_present("cluster")),
+    };
+
+    let _ = write!(rt.stdout(), "{}", output)
+        .to_exit_code()
+        .unwrap_or_exit();
+
+    for id in roots {
+        let _ = rt.report_touched(&id).unwrap_or_exit();
+    }
+}
+
+fn get_config_label_fields(rt: &Runtime) -> Vec<String> {
+    rt.config()
+        .and_then(|cfg| {
+            cfg.read("link.graph.label_fields")
+                .map_err(Error::from)
+                .map_err_trace_exit_unwrap()
+                .map(|v| match v {
+                    &Value::Array(ref fields) => fields
+                        .iter()
+                        .map(|f| match f {
+                            &Value::String(ref s) => s.clone(),
+                            _ => {
+                                error!("Config type wrong: 'link.graph.label_fields' should be an array of strings");
+                                ::std::process::exit(1)
+                            }
+                        })
+                        .collect(),
+                    _ => {
+                        error!("Config type wrong: 'link.graph.label_fields' should be an array of strings");
+                        ::std::process::exit(1)
+                    }
+                })
+        })
+        .unwrap_or_else(Vec::new)
+}
+
+fn neighbours(rt: &Runtime) {
+    let cmd = rt.cli()
+        .subcommand_matches("neighbours")
+        .unwrap(); // safed by clap
+
+    let depth    = get_usize_arg(cmd, "depth").unwrap_or(1);
+    let analysis = LinkAnalysis::from_store(rt.store()).map_err_trace_exit_unwrap();
+
+    rt.ids_or_pick::<::ui::PathProvider>().map_err_trace_exit_unwrap().into_iter().for_each(|id| {
+        if !analysis.contains(&id) {
+            warn!("Not found: {}", id);
+            return
+        }
+
+        for (neighbour, distance) in analysis.neighbourhood(&id, depth) {
+            let _ = writeln!(rt.stdout(), "{: <3} {}", distance, neighbour)
+                .to_exit_code()
+                .unwrap_or_exit();
+
+            let _ = rt.report_touched(&neighbour).unwrap_or_exit();
+        }
+    });
+}
+
+fn path(rt: &Runtime) {
+    let ids = rt.ids::<::ui::PathProvider>().map_err_trace_exit_unwrap();
+    let (from, to) = match (ids.get(0), ids.get(1)) {
+        (Some(from), Some(to)) => (from, to),
+        _                      => warn_exit("Two entries are required", 1),
+    };
+
+    let analysis = LinkAnalysis::from_store(rt.store()).map_err_trace_exit_unwrap();
+    match analysis.shortest_path(from, to) {
+        Some(path) => for (i, id) in path.iter().enumerate() {
+            let _ = writeln!(rt.stdout(), "{: <3} {}", i, id)
+                .to_exit_code()
+                .unwrap_or_exit();
+
+            let _ = rt.report_touched(id).unwrap_or_exit();
+        },
+        None => warn_exit(&format!("No link path between {} and {}", from, to), 1),
+    }
+}
+
+fn components(rt: &Runtime) {
+    let cmd = rt.cli()
+        .subcommand_matches("components")
+        .unwrap(); // safed by clap
+
+    let min_size = get_usize_arg(cmd, "min-size").unwrap_or(1);
+    let analysis = LinkAnalysis::from_store(rt.store()).map_err_trace_exit_unwrap();
+
+    for (i, component) in analysis.components().into_iter().filter(|c| c.len() >= min_size).enumerate() {
+        let _ = writeln!(rt.stdout(), "Component {} ({} entries):", i, component.len())
+            .to_exit_code()
+            .unwrap_or_exit();
+
+        for id in component {
+            let _ = writeln!(rt.stdout(), "    {}", id)
+                .to_exit_code()
+                .unwrap_or_exit();
+
+            let _ = rt.report_touched(&id).unwrap_or_exit();
+        }
+    }
+}
+
+fn isolated(rt: &Runtime) {
+    let analysis = LinkAnalysis::from_store(rt.store()).map_err_trace_exit_unwrap();
+
+    for id in analysis.isolated() {
+        let _ = writeln!(rt.stdout(), "{}", id)
+            .to_exit_code()
+            .unwrap_or_exit();
+
+        let _ = rt.report_touched(&id).unwrap_or_exit();
+    }
+}
+
+fn rank(rt: &Runtime) {
+    let cmd = rt.cli()
+        .subcommand_matches("rank")
+        .unwrap(); // safed by clap
+
+    let limit    = get_usize_arg(cmd, "limit").unwrap_or(10);
+    let analysis = LinkAnalysis::from_store(rt.store()).map_err_trace_exit_unwrap();
+
+    let ranking = if cmd.is_present("pagerank") {
+        analysis.pagerank(0.85, 50)
+            .into_iter()
+            .map(|(id, rank)| (id, format!("{:.4}", rank)))
+            .collect::<Vec<_>>()
+    } else {
+        analysis.degrees()
+            .into_iter()
+            .map(|(id, degree)| (id, degree.to_string()))
+            .collect::<Vec<_>>()
+    };
+
+    for (id, rank) in ranking.into_iter().take(limit) {
+        let _ = writeln!(rt.stdout(), "{: >6} {}", rank, id)
+            .to_exit_code()
+            .unwrap_or_exit();
+
+        let _ = rt.report_touched(&id).unwrap_or_exit();
+    }
+}
+
+fn check_external(rt: &Runtime) {
+    let cmd = rt.cli()
+        .subcommand_matches("check-external")
+        .unwrap(); // safed by clap
+
+    let timeout     = get_usize_arg(cmd, "timeout").unwrap_or(10);
+    let concurrency = get_usize_arg(cmd, "concurrency").unwrap_or(4);
+    let delay       = get_usize_arg(cmd, "delay").unwrap_or(1000);
+
+    let entries = rt.ids::<::ui::PathProvider>().map_err_trace_exit_unwrap();
+    let ids = if entries.is_empty() {
+        external_link_ids(rt.store()).map_err_trace_exit_unwrap()
+    } else {
+        let mut ids = vec![];
+        for id in entries {
+            match rt.store().get(id.clone()).map_err_trace_exit_unwrap() {
+                Some(entry) => {
+                    let links = entry.get_internal_links().map_err_trace_exit_unwrap();
+                    ids.extend(links.map(|l| l.get_store_id().clone()).filter(is_external_link_storeid));
+                },
+                None => warn!("Not found: {}", id),
+            }
+
+            let _ = rt.report_touched(&id).unwrap_or_exit();
+        }
+        ids
+    };
+
+    let results = LinkChecker::new()
+        .with_timeout(Duration::from_secs(timeout as u64))
+        .with_concurrency(concurrency)
+        .with_delay(Duration::from_millis(delay as u64))
+        .check_entries(rt.store(), ids)
+        .map_err_trace_exit_unwrap();
+
+    let mut broken = 0;
+    for (id, result) in results {
+        let url = rt.store()
+            .get(id)
+            .map_err_trace_exit_unwrap()
+            .map(|e| e.get_url().map_err_trace_exit_unwrap())
+            .and_then(|url| url.map(Url::into_string))
+            .unwrap_or_default();
+
+        let state = match (result.status, result.error.as_ref()) {
+            (_, Some(e))       => e.clone(),
+            (Some(status), _)  => status.to_string(),
+            (None, None)       => String::from("unknown"),
+        };
+
+        if result.is_broken() {
+            broken += 1;
+        }
+
+        let _ = writeln!(rt.stdout(), "{} {}", state, url)
+            .to_exit_code()
+            .unwrap_or_exit();
+    }
+
+    info!("{} broken links", broken);
+}
+
+fn list_broken(rt: &Runtime) {
+    let cmd = rt.cli()
+        .subcommand_matches("list-broken")
+        .unwrap(); // safed by clap
+
+    let list_plain = cmd.is_present("list-plain");
+
+    let mut tab = ::prettytable::Table::new();
+    tab.set_titles(row!["Link", "Status", "Checked", "Linked from"]);
+
+    for (id, url, result) in broken_links(rt.store()).map_err_trace_exit_unwrap() {
+        let status = result.error
+            .clone()
+            .or_else(|| result.status.map(|s| s.to_string()))
+            .unwrap_or_default();
+
+        // The entries which link to the external link
+        let linked_from = rt.store()
+            .get(id)
+            .map_err_trace_exit_unwrap()
+            .map(|e| {
+                e.get_internal_links()
+                    .map_err_trace_exit_unwrap()
+                    .map(|l| l.get_store_id().clone())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        for id in linked_from.iter() {
+            let _ = rt.report_touched(id).unwrap_or_exit();
+        }
+
+        let linked_from = linked_from.iter().map(StoreId::to_string).collect::<Vec<_>>().join(", ");
+
+        if list_plain {
+            let _ = writeln!(rt.stdout(), "{} ({}) {}", url, status, linked_from)
+                .to_exit_code()
+                .unwrap_or_exit();
+        } else {
+            tab.add_row(row![url, status, result.checked, linked_from]);
+        }
+    }
+
+    if !list_plain {
+        let out      = rt.stdout();
+        let mut lock = out.lock();
+        tab.print(&mut lock)
+            .to_exit_code()
+            .unwrap_or_exit();
+    }
+}
+
+fn archive(rt: &Runtime) {
+    let cmd = rt.cli()
+        .subcommand_matches("archive")
+        .unwrap(); // safed by clap
+
+    let format = cmd
+        .value_of("format")
+        .map(|f| ArchiveFormat::from_str(f).map_err_trace_exit_unwrap())
+        .unwrap_or(ArchiveFormat::Text);
+    let timeout  = get_usize_arg(cmd, "timeout").unwrap_or(30);
+    let archiver = Archiver::new()
+        .with_format(format)
+        .with_timeout(Duration::from_secs(timeout as u64));
+
+    let mut ids = vec![];
+    for id in rt.ids_or_pick::<::ui::PathProvider>().map_err_trace_exit_unwrap() {
+        if is_external_link_storeid(&id) {
+            ids.push(id);
+            continue
+        }
+
+        match rt.store().get(id.clone()).map_err_trace_exit_unwrap() {
+            Some(entry) => {
+                let links = entry.get_internal_links().map_err_trace_exit_unwrap();
+                ids.extend(links.map(|l| l.get_store_id().clone()).filter(is_external_link_storeid));
+            },
+            None => warn!("Not found: {}", id),
+        }
+    }
+
+    let mut exit_code = 0;
+    for id in ids {
+        match archiver.archive(rt.store(), &id) {
+            Ok(archive) => {
+                let _ = writeln!(rt.stdout(), "{}", archive.get_location())
+                    .to_exit_code()
+                    .unwrap_or_exit();
+
+                let _ = rt.report_touched(archive.get_location()).unwrap_or_exit();
+            },
+            Err(e) => {
+                error!("Failed to archive {}", id);
+                trace_error(&e);
+                exit_code = 1;
+            },
+        }
+    }
+
+    ::std::process::exit(exit_code);
+}
+
+fn get_usize_arg(matches: &ArgMatches, name: &str) -> Option<usize> {
+    matches.value_of(name).map(|v| {
+        usize::from_str(v)
+            .map_err(Error::from)
+            .map_err_trace_exit_unwrap()
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::link_from_to;
@@ -427,6 +850,34 @@ mod tests {
         debug!("Test finished")
     }
 
+    fn related_links_toml_value(link: &'static str, relation: &'static str) -> Value {
+        let mut tab = ::std::collections::BTreeMap::new();
+        tab.insert("link".to_owned(), Value::String(link.to_owned()));
+        tab.insert("relation".to_owned(), Value::String(relation.to_owned()));
+        Value::Array(vec![Value::Table(tab)])
+    }
+
+    #[test]
+    fn test_linking_with_relation() {
+        setup_logging();
+        let rt = generate_test_runtime(vec!["--relation", "blocks", "test1", "test2"])
+            .unwrap();
+
+        let test_id1 = create_test_default_entry(&rt, "test1").unwrap();
+        let test_id2 = create_test_default_entry(&rt, "test2").unwrap();
+
+        link_from_to(&rt, "test1", vec!["test2"].into_iter());
+
+        let test_entry1 = rt.store().get(test_id1).unwrap().unwrap();
+        let test_links1 = get_entry_links(&test_entry1).unwrap();
+
+        let test_entry2 = rt.store().get(test_id2).unwrap().unwrap();
+        let test_links2 = get_entry_links(&test_entry2).unwrap();
+
+        assert_eq!(*test_links1, related_links_toml_value("test2", "blocks"));
+        assert_eq!(*test_links2, related_links_toml_value("test1", "blocked-by"));
+    }
+
     #[test]
     fn test_linking_links() {
         setup_logging();
@@ -45,6 +45,14 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                      .multiple(true)
                      .help("Remove links to these entries")
                      .value_name("ENTRIES"))
+                .arg(Arg::with_name("relation")
+                     .long("relation")
+                     .short("r")
+                     .takes_value(true)
+                     .required(false)
+                     .multiple(false)
+                     .help("Only remove links with this relation (and the inverse links back)")
+                     .value_name("RELATION"))
                 )
         .subcommand(SubCommand::with_name("unlink")
                 .about("Remove all links from an entry")
@@ -52,9 +60,9 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                 .arg(Arg::with_name("from")
                      .index(1)
                      .takes_value(true)
-                     .required(true)
+                     .required(false)
                      .multiple(true)
-                     .help("Remove links from these entries")
+                     .help("Remove links from these entries. If not passed, the entries are picked interactively")
                      .value_name("ENTRY"))
                 )
 
@@ -65,8 +73,8 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                      .index(1)
                      .takes_value(true)
                      .multiple(true)
-                     .required(true)
-                     .help("List these entries, seperate by comma")
+                     .required(false)
+                     .help("List these entries, seperate by comma. If not passed, the entries are picked interactively")
                      .value_name("ENTRIES"))
 
                 .arg(Arg::with_name("list-externals-too")
@@ -75,6 +83,223 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                      .required(false)
                      .help("Also list external links (debugging helper that might be removed at some point"))
 
+                .arg(Arg::with_name("list-plain")
+                     .long("plain")
+                     .multiple(false)
+                     .takes_value(false)
+                     .required(false)
+                     .help("List plain rather than in ASCII table"))
+
+                .arg(Arg::with_name("relation")
+                     .long("relation")
+                     .short("r")
+                     .takes_value(true)
+                     .required(false)
+                     .multiple(false)
+                     .help("Only list links with this relation")
+                     .value_name("RELATION"))
+                )
+
+        .subcommand(SubCommand::with_name("graph")
+                .about("Export the link graph of entries")
+                .version("0.1")
+                .arg(Arg::with_name("entries")
+                     .index(1)
+                     .takes_value(true)
+                     .multiple(true)
+                     .required(false)
+                     .help("Start walking the links at these entries. If not passed, the whole store is exported")
+                     .value_name("ENTRIES"))
+
+                .arg(Arg::with_name("depth")
+                     .long("depth")
+                     .short("d")
+                     .takes_value(true)
+                     .multiple(false)
+                     .required(false)
+                     .help("Only follow links up to this many hops away from the passed entries")
+                     .value_name("N"))
+
+                .arg(Arg::with_name("externals")
+                     .long("externals")
+                     .takes_value(false)
+                     .required(false)
+                     .help("Include external links in the graph"))
+
+                .arg(Arg::with_name("format")
+                     .long("format")
+                     .short("f")
+                     .takes_value(true)
+                     .multiple(false)
+                     .required(false)
+                     .possible_values(&["dot", "graphml", "json"])
+                     .default_value("dot")
+                     .help("Output format")
+                     .value_name("FORMAT"))
+
+                .arg(Arg::with_name("label-field")
+                     .long("label-field")
+                     .short("l")
+                     .takes_value(true)
+                     .multiple(true)
+                     .number_of_values(1)
+                     .required(false)
+                     .help("Label nodes with this header field. Can be passed multiple times, the first field present in an entry is used. Defaults to 'link.graph.label_fields' from the configuration")
+                     .value_name("HEADER-PATH"))
+
+                .arg(Arg::with_name("cluster")
+                     .long("cluster")
+                     .takes_value(false)
+                     .required(false)
+                     .help("Cluster the nodes by collection (DOT output only)"))
+                )
+
+        .subcommand(SubCommand::with_name("neighbours")
+                .about("List all entries within a number of links of an entry")
+                .version("0.1")
+                .arg(Arg::with_name("entries")
+                     .index(1)
+                     .takes_value(true)
+                     .multiple(true)
+                     .required(false)
+                     .help("List the neighbourhood of these entries. If not passed, the entries are picked interactively")
+                     .value_name("ENTRIES"))
+                .arg(Arg::with_name("depth")
+                     .long("depth")
+                     .short("d")
+                     .takes_value(true)
+                     .multiple(false)
+                     .required(false)
+                     .default_value("1")
+                     .help("Maximum number of links between the entry and its neighbours")
+                     .value_name("N"))
+                )
+
+        .subcommand(SubCommand::with_name("path")
+                .about("Print the shortest link path between two entries")
+                .version("0.1")
+                .arg(Arg::with_name("from")
+                     .index(1)
+                     .takes_value(true)
+                     .required(true)
+                     .multiple(false)
+                     .help("Start of the path")
+                     .value_name("ENTRY"))
+                .arg(Arg::with_name("to")
+                     .index(2)
+                     .takes_value(true)
+                     .required(true)
+                     .multiple(false)
+                     .help("End of the path")
+                     .value_name("ENTRY"))
+                )
+
+        .subcommand(SubCommand::with_name("components")
+                .about("List the groups of entries which are connected by links")
+                .version("0.1")
+                .arg(Arg::with_name("min-size")
+                     .long("min-size")
+                     .takes_value(true)
+                     .multiple(false)
+                     .required(false)
+                     .help("Only list groups with at least this many entries")
+                     .value_name("N"))
+                )
+
+        .subcommand(SubCommand::with_name("isolated")
+                .about("List entries which have no links")
+                .version("0.1")
+                )
+
+        .subcommand(SubCommand::with_name("rank")
+                .about("Rank entries by their number of links or by PageRank")
+                .version("0.1")
+                .arg(Arg::with_name("pagerank")
+                     .long("pagerank")
+                     .takes_value(false)
+                     .required(false)
+                     .help("Rank by PageRank instead of by number of links"))
+                .arg(Arg::with_name("limit")
+                     .long("limit")
+                     .short("n")
+                     .takes_value(true)
+                     .multiple(false)
+                     .required(false)
+                     .default_value("10")
+                     .help("Only list this many entries")
+                     .value_name("N"))
+                )
+
+        .subcommand(SubCommand::with_name("check-external")
+                .about("Check whether external links still resolve and record the results")
+                .version("0.1")
+                .arg(Arg::with_name("entries")
+                     .index(1)
+                     .takes_value(true)
+                     .multiple(true)
+                     .required(false)
+                     .help("Check the external links of these entries. If not passed, all external links in the store are checked")
+                     .value_name("ENTRIES"))
+                .arg(Arg::with_name("timeout")
+                     .long("timeout")
+                     .takes_value(true)
+                     .multiple(false)
+                     .required(false)
+                     .default_value("10")
+                     .help("Timeout for a request in seconds")
+                     .value_name("SECONDS"))
+                .arg(Arg::with_name("concurrency")
+                     .long("concurrency")
+                     .short("j")
+                     .takes_value(true)
+                     .multiple(false)
+                     .required(false)
+                     .default_value("4")
+                     .help("Number of hosts to check at the same time")
+                     .value_name("N"))
+                .arg(Arg::with_name("delay")
+                     .long("delay")
+                     .takes_value(true)
+                     .multiple(false)
+                     .required(false)
+                     .default_value("1000")
+                     .help("Time to wait between two requests to the same host, in milliseconds")
+                     .value_name("MILLISECONDS"))
+                )
+
+        .subcommand(SubCommand::with_name("archive")
+                .about("Save an offline copy of the pages external links point to")
+                .version("0.1")
+                .arg(Arg::with_name("entries")
+                     .index(1)
+                     .takes_value(true)
+                     .multiple(true)
+                     .required(false)
+                     .help("Archive these external links, or the external links of these entries. If not passed, the entries are picked interactively")
+                     .value_name("ENTRIES"))
+                .arg(Arg::with_name("format")
+                     .long("format")
+                     .short("f")
+                     .takes_value(true)
+                     .multiple(false)
+                     .required(false)
+                     .possible_values(&["text", "html"])
+                     .default_value("text")
+                     .help("Save the text of the page or the page as HTML, with images and stylesheets inlined")
+                     .value_name("FORMAT"))
+                .arg(Arg::with_name("timeout")
+                     .long("timeout")
+                     .takes_value(true)
+                     .multiple(false)
+                     .required(false)
+                     .default_value("30")
+                     .help("Timeout for a request in seconds")
+                     .value_name("SECONDS"))
+                )
+
+        .subcommand(SubCommand::with_name("list-broken")
+                .about("List the external links which were found broken by the last check")
+                .version("0.1")
                 .arg(Arg::with_name("list-plain")
                      .long("plain")
                      .multiple(false)
@@ -90,6 +315,21 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
              .required(false)
              .help("Check the link-consistency in the store (might be time-consuming)"))
 
+        .arg(Arg::with_name("fix")
+             .long("fix")
+             .takes_value(false)
+             .required(false)
+             .requires("check-consistency")
+             .help("Repair the link issues found by --check-consistency, asking for each issue"))
+
+        .arg(Arg::with_name("yes")
+             .long("yes")
+             .short("y")
+             .takes_value(false)
+             .required(false)
+             .requires("fix")
+             .help("Do not ask before repairing a link issue"))
+
         .arg(Arg::with_name("from")
              .index(1)
              .takes_value(true)
@@ -107,6 +347,16 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
              .help("Link to this entries")
              .requires("from")
              .value_name("ENTRIES"))
+
+        .arg(Arg::with_name("relation")
+             .long("relation")
+             .short("r")
+             .takes_value(true)
+             .required(false)
+             .multiple(false)
+             .requires("to")
+             .help("Link with this relation: parent, child, blocks, blocked-by, references, referenced-by, duplicate-of, has-duplicate or a custom one. The linked entries link back with the inverse relation")
+             .value_name("RELATION"))
 }
 
 /// PathProvider
@@ -137,16 +387,13 @@ impl IdPathProvider for PathProvider {
             ("unlink", Some(subm)) => {
                 let ids = subm
                     .values_of("from")
-                    .ok_or_else(|| {
-                        error!("No StoreId found");
-                        ::std::process::exit(1)
+                    .map(|vals| {
+                        vals.map(PathBuf::from)
+                            .map(|pb| pb.into_storeid())
+                            .collect::<Result<Vec<_>, _>>()
+                            .map_err_trace_exit_unwrap()
                     })
-                    .unwrap()
-                    .into_iter()
-                    .map(PathBuf::from)
-                    .map(|pb| pb.into_storeid())
-                    .collect::<Result<Vec<_>, _>>()
-                    .map_err_trace_exit_unwrap();
+                    .unwrap_or_else(Vec::new);
 
                 Some(ids)
             },
@@ -154,12 +401,35 @@ impl IdPathProvider for PathProvider {
             ("list", Some(subm)) => {
                 let ids = subm
                     .values_of("entries")
-                    .ok_or_else(|| {
-                        error!("No StoreId found");
-                        ::std::process::exit(1)
+                    .map(|vals| {
+                        vals.map(PathBuf::from)
+                            .map(|pb| pb.into_storeid())
+                            .collect::<Result<Vec<_>, _>>()
+                            .map_err_trace_exit_unwrap()
                     })
-                    .unwrap()
+                    .unwrap_or_else(Vec::new);
+
+                Some(ids)
+            },
+
+            ("neighbours", Some(subm)) => {
+                let ids = subm
+                    .values_of("entries")
+                    .map(|vals| {
+                        vals.map(PathBuf::from)
+                            .map(|pb| pb.into_storeid())
+                            .collect::<Result<Vec<_>, _>>()
+                            .map_err_trace_exit_unwrap()
+                    })
+                    .unwrap_or_else(Vec::new);
+
+                Some(ids)
+            },
+
+            ("path", Some(subm)) => {
+                let ids = vec![subm.value_of("from"), subm.value_of("to")]
                     .into_iter()
+                    .filter_map(|v| v)
                     .map(PathBuf::from)
                     .map(|pb| pb.into_storeid())
                     .collect::<Result<Vec<_>, _>>()
@@ -168,6 +438,36 @@ impl IdPathProvider for PathProvider {
                 Some(ids)
             },
 
+            ("check-external", Some(subm)) | ("archive", Some(subm)) => {
+                let ids = subm
+                    .values_of("entries")
+                    .map(|vals| {
+                        vals.map(PathBuf::from)
+                            .map(|pb| pb.into_storeid())
+                            .collect::<Result<Vec<_>, _>>()
+                            .map_err_trace_exit_unwrap()
+                    })
+                    .unwrap_or_else(Vec::new);
+
+                Some(ids)
+            },
+
+            ("components", _) | ("isolated", _) | ("rank", _) | ("list-broken", _) => Some(Vec::new()),
+
+            ("graph", Some(subm)) => {
+                let ids = subm
+                    .values_of("entries")
+                    .map(|vals| {
+                        vals.map(PathBuf::from)
+                            .map(|pb| pb.into_storeid())
+                            .collect::<Result<Vec<_>, _>>()
+                            .map_err_trace_exit_unwrap()
+                    })
+                    .unwrap_or_else(Vec::new);
+
+                Some(ids)
+            },
+
             _ => None,
         };
 
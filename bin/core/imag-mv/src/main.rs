@@ -40,9 +40,7 @@ extern crate clap;
 #[macro_use] extern crate libimagrt;
 extern crate libimagstore;
 extern crate libimagerror;
-extern crate libimagentrylink;
-
-use std::process::exit;
+extern crate libimagentrymarkdown;
 
 mod ui;
 use ui::build_ui;
@@ -51,13 +49,10 @@ use std::path::PathBuf;
 
 use libimagrt::setup::generate_runtime_setup;
 use libimagerror::trace::MapErrTrace;
-use libimagerror::iter::TraceIterator;
 use libimagerror::exit::ExitUnwrap;
 use libimagstore::storeid::StoreId;
-use libimagstore::store::Store;
-use libimagstore::store::FileLockEntry;
-use libimagentrylink::internal::InternalLinker;
-use libimagstore::iter::get::StoreIdGetIteratorExtension;
+use libimagentrymarkdown::rewrite::move_collection;
+use libimagentrymarkdown::rewrite::move_entry;
 
 fn main() {
     let version = make_imag_version!();
@@ -84,73 +79,21 @@ fn main() {
         .unwrap() // unwrap safe by clap
         .map_err_trace_exit_unwrap();
 
-    // remove links to entry, and re-add them later
-    let mut linked_entries = {
-        rt.store()
-            .get(sourcename.clone())
-            .map_err_trace_exit_unwrap()
-            .unwrap_or_else(|| {
-                error!("Funny things happened: Entry moved to destination did not fail, but entry does not exist");
-                exit(1)
-            })
-            .get_internal_links()
-            .map_err_trace_exit_unwrap()
-            .map(|link| Ok(link.get_store_id().clone()) as Result<_, _>)
-            .into_get_iter(rt.store())
-            .trace_unwrap_exit()
-            .map(|e| {
-                e.unwrap_or_else(|| {
-                    error!("Linked entry does not exist");
-                    exit(1)
-                })
-            })
-            .collect::<Vec<_>>()
-    };
-
-    { // remove links to linked entries from source
-        let mut entry = rt
-            .store()
-            .get(sourcename.clone())
-            .map_err_trace_exit_unwrap()
-            .unwrap_or_else(|| {
-                error!("Source Entry does not exist");
-                exit(1)
-            });
+    // Links to the moved entries are re-created, markdown links in the content of linked entries
+    // are rewritten to point to the new ids
+    let report = if rt.cli().is_present("collection") {
+        move_collection(rt.store(), &sourcename, &destname)
+    } else {
+        move_entry(rt.store(), sourcename, destname)
+    }.map_err_trace_exit_unwrap();
 
-        for link in linked_entries.iter_mut() {
-            let _ = entry.remove_internal_link(link).map_err_trace_exit_unwrap();
-        }
+    for &(_, ref new) in report.moved.iter() {
+        let _ = rt.report_touched(new).unwrap_or_exit();
     }
 
-    let _ = rt
-        .store()
-        .move_by_id(sourcename.clone(), destname.clone())
-        .map_err(|e| { // on error, re-add links
-            debug!("Re-adding links to source entry because moving failed");
-            relink(rt.store(), sourcename.clone(), &mut linked_entries);
-            e
-        })
-        .map_err_trace_exit_unwrap();
-
-    let _ = rt.report_touched(&destname).unwrap_or_exit();
-
-    // re-add links to moved entry
-    relink(rt.store(), destname, &mut linked_entries);
+    for id in report.rewritten.iter() {
+        info!("Rewrote links in {}", id);
+    }
 
     info!("Ok.");
 }
-
-fn relink<'a>(store: &'a Store, target: StoreId, linked_entries: &mut Vec<FileLockEntry<'a>>) {
-    let mut entry = store
-        .get(target)
-        .map_err_trace_exit_unwrap()
-        .unwrap_or_else(|| {
-            error!("Funny things happened: Entry moved to destination did not fail, but entry does not exist");
-            exit(1)
-        });
-
-
-    for mut link in linked_entries {
-        let _ = entry.add_internal_link(&mut link).map_err_trace_exit_unwrap();
-    }
-}
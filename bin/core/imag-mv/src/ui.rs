@@ -36,4 +36,12 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
              .multiple(false)
              .help("Destination name file")
              .value_name("DEST"))
+
+        .arg(Arg::with_name("collection")
+             .long("collection")
+             .short("c")
+             .takes_value(false)
+             .required(false)
+             .multiple(false)
+             .help("Move all entries in the collection ENTRY to the collection DEST"))
 }
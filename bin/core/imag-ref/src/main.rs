@@ -41,6 +41,7 @@ extern crate libimagstore;
 #[macro_use] extern crate libimagrt;
 extern crate libimagentryref;
 extern crate libimagerror;
+extern crate libimagentryquery;
 extern crate libimaginteraction;
 extern crate libimagutil;
 
@@ -53,6 +54,7 @@ use std::io::Write;
 use libimagerror::trace::MapErrTrace;
 use libimagerror::exit::ExitUnwrap;
 use libimagrt::setup::generate_runtime_setup;
+use libimagentryquery::search::SavedSearchResolver;
 use libimagrt::runtime::Runtime;
 use libimagentryref::reference::Ref;
 use libimagentryref::reference::MutRef;
@@ -66,6 +68,7 @@ fn main() {
                                     &version,
                                     "Reference files outside of the store",
                                     build_ui);
+    rt.register_id_resolver(SavedSearchResolver);
     rt.cli()
         .subcommand_name()
         .map(|name| {
@@ -0,0 +1,183 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2019 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+#![forbid(unsafe_code)]
+
+#![deny(
+    non_camel_case_types,
+    non_snake_case,
+    path_statements,
+    trivial_numeric_casts,
+    unstable_features,
+    unused_allocation,
+    unused_import_braces,
+    unused_imports,
+    unused_must_use,
+    unused_mut,
+    unused_qualifications,
+    while_true,
+)]
+
+extern crate clap;
+#[macro_use] extern crate log;
+
+#[macro_use] extern crate libimagrt;
+extern crate libimagerror;
+extern crate libimagstore;
+extern crate libimagentryedit;
+extern crate libimagentryquery;
+extern crate libimagutil;
+
+use std::io::Write;
+
+use clap::ArgMatches;
+
+use libimagrt::runtime::Runtime;
+use libimagrt::setup::generate_runtime_setup;
+use libimagerror::trace::MapErrTrace;
+use libimagerror::iter::TraceIterator;
+use libimagerror::exit::ExitUnwrap;
+use libimagerror::io::ToExitCode;
+use libimagerror::kind::ErrorKind;
+use libimagerror::kind::kind_err;
+use libimagstore::iter::get::StoreIdGetIteratorExtension;
+use libimagentryedit::edit::edit_in_tmpfile;
+use libimagentryquery::search::SavedSearch;
+use libimagentryquery::search::SearchStore;
+use libimagutil::info_result::*;
+
+mod ui;
+
+fn main() {
+    let version = make_imag_version!();
+    let rt = generate_runtime_setup("imag-search",
+                                    &version,
+                                    "Manage saved searches",
+                                    ui::build_ui);
+
+    rt.cli()
+        .subcommand_name()
+        .map(|name| {
+            debug!("Call: {}", name);
+            match name {
+                "create" => create(&rt),
+                "edit"   => edit(&rt),
+                "delete" => delete(&rt),
+                "list"   => list(&rt),
+                "show"   => show(&rt),
+                other    => {
+                    debug!("Unknown command");
+                    let _ = rt.handle_unknown_subcommand("imag-search", other, rt.cli())
+                        .map_err_trace_exit_unwrap()
+                        .code()
+                        .map(::std::process::exit);
+                },
+            };
+        });
+}
+
+fn name_from_cli<'a>(scmd: &'a ArgMatches) -> &'a str {
+    scmd.value_of("name").unwrap() // safe by clap
+}
+
+/// The query is accepted as multiple words, so it does not have to be quoted on the commandline
+fn query_from_cli(scmd: &ArgMatches) -> Option<String> {
+    scmd.values_of("query").map(|words| words.collect::<Vec<_>>().join(" "))
+}
+
+fn create(rt: &Runtime) {
+    let scmd  = rt.cli().subcommand_matches("create").unwrap();
+    let name  = name_from_cli(scmd);
+    let query = query_from_cli(scmd).unwrap(); // safe by clap
+
+    let search = rt.store().new_search(name, &query).map_err_trace_exit_unwrap();
+    let _ = rt.report_touched(search.get_location()).unwrap_or_exit();
+}
+
+fn edit(rt: &Runtime) {
+    let scmd = rt.cli().subcommand_matches("edit").unwrap();
+    let name = name_from_cli(scmd);
+
+    let mut search = rt
+        .store()
+        .get_search(name)
+        .map_err_trace_exit_unwrap()
+        .ok_or_else(|| kind_err(ErrorKind::NotFound, format!("No saved search '{}'", name)))
+        .map_err_trace_exit_unwrap();
+
+    let query = match query_from_cli(scmd) {
+        Some(query) => query,
+        None        => {
+            let mut query = search.get_search_query_string().map_err_trace_exit_unwrap();
+            let _ = edit_in_tmpfile(rt, &mut query).map_err_trace_exit_unwrap();
+            String::from(query.trim())
+        },
+    };
+
+    let _ = search.set_search_query(&query).map_err_trace_exit_unwrap();
+    let _ = rt.report_touched(search.get_location()).unwrap_or_exit();
+}
+
+fn delete(rt: &Runtime) {
+    let scmd = rt.cli().subcommand_matches("delete").unwrap();
+    let _ = rt
+        .store()
+        .delete_search(name_from_cli(scmd))
+        .map_info_str("Ok")
+        .map_err_trace_exit_unwrap();
+}
+
+fn list(rt: &Runtime) {
+    let scmd         = rt.cli().subcommand_matches("list").unwrap();
+    let with_queries = scmd.is_present("list-queries");
+    let mut out      = rt.stdout();
+
+    rt.store()
+        .all_searches()
+        .map_err_trace_exit_unwrap()
+        .into_get_iter(rt.store())
+        .trace_unwrap_exit()
+        .filter_map(|e| e)
+        .for_each(|search| {
+            let name = search.get_search_name().map_err_trace_exit_unwrap();
+
+            let _ = if with_queries {
+                let query = search.get_search_query_string().map_err_trace_exit_unwrap();
+                writeln!(out, "{}: {}", name, query)
+            } else {
+                writeln!(out, "{}", name)
+            }.to_exit_code().unwrap_or_exit();
+
+            let _ = rt.report_touched(search.get_location()).unwrap_or_exit();
+        });
+}
+
+fn show(rt: &Runtime) {
+    let scmd    = rt.cli().subcommand_matches("show").unwrap();
+    let mut out = rt.stdout();
+
+    rt.store()
+        .evaluate_search(name_from_cli(scmd))
+        .map_err_trace_exit_unwrap()
+        .into_iter()
+        .for_each(|id| {
+            let _ = writeln!(out, "{}", id).to_exit_code().unwrap_or_exit();
+            let _ = rt.report_touched(&id).unwrap_or_exit();
+        });
+}
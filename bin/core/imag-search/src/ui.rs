@@ -0,0 +1,97 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2019 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+use clap::{Arg, App, SubCommand};
+
+pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
+    app
+        .subcommand(SubCommand::with_name("create")
+                   .about("Save a query as a named search")
+                   .version("0.1")
+                   .arg(Arg::with_name("name")
+                        .index(1)
+                        .takes_value(true)
+                        .required(true)
+                        .multiple(false)
+                        .value_name("NAME")
+                        .help("The name of the search. Use it as '@NAME' wherever ids are expected"))
+                   .arg(Arg::with_name("query")
+                        .index(2)
+                        .takes_value(true)
+                        .required(true)
+                        .multiple(true)
+                        .value_name("QUERY")
+                        .help("The query. See 'imag ids --help' for the query language"))
+                   )
+
+        .subcommand(SubCommand::with_name("edit")
+                   .about("Change the query of a saved search")
+                   .version("0.1")
+                   .arg(Arg::with_name("name")
+                        .index(1)
+                        .takes_value(true)
+                        .required(true)
+                        .multiple(false)
+                        .value_name("NAME")
+                        .help("The name of the search"))
+                   .arg(Arg::with_name("query")
+                        .index(2)
+                        .takes_value(true)
+                        .required(false)
+                        .multiple(true)
+                        .value_name("QUERY")
+                        .help("The new query. If not passed, the query is opened in the editor"))
+                   )
+
+        .subcommand(SubCommand::with_name("delete")
+                   .about("Delete a saved search")
+                   .version("0.1")
+                   .arg(Arg::with_name("name")
+                        .index(1)
+                        .takes_value(true)
+                        .required(true)
+                        .multiple(false)
+                        .value_name("NAME")
+                        .help("The name of the search"))
+                   )
+
+        .subcommand(SubCommand::with_name("list")
+                   .about("List saved searches")
+                   .version("0.1")
+                   .arg(Arg::with_name("list-queries")
+                        .long("queries")
+                        .short("q")
+                        .takes_value(false)
+                        .required(false)
+                        .multiple(false)
+                        .help("Print the queries as well"))
+                   )
+
+        .subcommand(SubCommand::with_name("show")
+                   .about("Print the ids of the entries which currently match a saved search")
+                   .version("0.1")
+                   .arg(Arg::with_name("name")
+                        .index(1)
+                        .takes_value(true)
+                        .required(true)
+                        .multiple(false)
+                        .value_name("NAME")
+                        .help("The name of the search"))
+                   )
+}
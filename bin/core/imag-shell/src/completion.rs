@@ -0,0 +1,80 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2019 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+
+use std::rc::Rc;
+use std::cell::RefCell;
+
+use rustyline::Result as RLResult;
+use rustyline::Helper;
+use rustyline::completion::Completer;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+
+/// Completion for the shell
+///
+/// The first word is completed from the builtin commands of the shell and the imag commands in
+/// `$PATH`, all other words are completed from the ids in the store.
+pub struct ShellHelper {
+    commands: Vec<String>,
+    ids: Rc<RefCell<Vec<String>>>,
+}
+
+impl ShellHelper {
+    pub fn new(commands: Vec<String>, ids: Rc<RefCell<Vec<String>>>) -> ShellHelper {
+        ShellHelper { commands, ids }
+    }
+}
+
+impl Completer for ShellHelper {
+    type Candidate = String;
+
+    fn complete(&self, line: &str, pos: usize) -> RLResult<(usize, Vec<String>)> {
+        let line  = &line[..pos];
+        let start = line.rfind(char::is_whitespace).map(|i| i + 1).unwrap_or(0);
+        let word  = &line[start..];
+
+        let candidates = if line[..start].trim().is_empty() {
+            self.commands
+                .iter()
+                .filter(|c| c.starts_with(word))
+                .cloned()
+                .collect()
+        } else {
+            self.ids
+                .borrow()
+                .iter()
+                .filter(|id| id.starts_with(word))
+                .cloned()
+                .collect()
+        };
+
+        Ok((start, candidates))
+    }
+}
+
+impl Hinter for ShellHelper {
+    fn hint(&self, _line: &str, _pos: usize) -> Option<String> {
+        None
+    }
+}
+
+impl Highlighter for ShellHelper {}
+
+impl Helper for ShellHelper {}
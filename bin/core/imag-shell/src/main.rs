@@ -0,0 +1,142 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2019 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+
+#![forbid(unsafe_code)]
+
+#![deny(
+    non_camel_case_types,
+    non_snake_case,
+    path_statements,
+    trivial_numeric_casts,
+    unstable_features,
+    unused_allocation,
+    unused_import_braces,
+    unused_imports,
+    unused_must_use,
+    unused_mut,
+    unused_qualifications,
+    while_true,
+)]
+
+extern crate clap;
+#[macro_use] extern crate log;
+#[macro_use] extern crate failure;
+extern crate rustyline;
+extern crate walkdir;
+
+extern crate libimagstore;
+#[macro_use] extern crate libimagrt;
+extern crate libimagerror;
+extern crate libimaginteraction;
+extern crate libimagutil;
+
+use std::env;
+
+use walkdir::WalkDir;
+
+use libimagrt::setup::generate_runtime_setup;
+use libimagerror::trace::MapErrTrace;
+use libimagerror::trace::trace_error;
+use libimaginteraction::readline::Readline;
+use libimagutil::shell_words::split_words;
+
+mod completion;
+mod shell;
+mod ui;
+
+use completion::ShellHelper;
+use shell::Shell;
+use shell::Flow;
+use ui::build_ui;
+
+fn main() {
+    let version = make_imag_version!();
+    let rt = generate_runtime_setup("imag-shell",
+                                    &version,
+                                    "Interactive shell for imag commands",
+                                    build_ui);
+
+    let mut shell = Shell::new(&rt);
+    if let Err(e) = shell.refresh_ids() {
+        trace_error(&e);
+    }
+
+    let mut commands : Vec<String> = shell::BUILTINS.iter().map(|&(name, _)| String::from(name)).collect();
+    commands.extend(get_imag_commands());
+    commands.sort();
+    commands.dedup();
+
+    let helper = ShellHelper::new(commands, shell.completion_ids());
+    let mut readline = Readline::with_helper(rt.config(), Some(helper)).map_err_trace_exit_unwrap();
+
+    loop {
+        let line = match readline.read_line().map_err_trace_exit_unwrap() {
+            Some(line) => line,
+            None       => break,
+        };
+
+        let words = match split_words(&line) {
+            Ok(words) => words,
+            Err(e)    => {
+                error!("{}", e);
+                continue
+            },
+        };
+
+        match shell.execute(words) {
+            Ok(Flow::Continue) => {},
+            Ok(Flow::Exit)     => break,
+            Err(e)             => trace_error(&e),
+        }
+
+        readline.set_prompt(prompt(shell.selection().len()));
+    }
+}
+
+fn prompt(selected: usize) -> String {
+    if selected == 0 {
+        String::from("imag> ")
+    } else {
+        format!("imag [{}]> ", selected)
+    }
+}
+
+/// Get the names of all imag commands in $PATH, which the shell dispatches to
+fn get_imag_commands() -> Vec<String> {
+    let mut commands : Vec<String> = vec![];
+
+    if let Ok(path) = env::var("PATH") {
+        for elem in path.split(":") {
+            WalkDir::new(elem)
+                .max_depth(1)
+                .into_iter()
+                .filter_map(Result::ok)
+                .filter_map(|entry| entry
+                    .file_name()
+                    .to_str()
+                    .and_then(|s| if s.starts_with("imag-") { Some(String::from(&s[5..])) } else { None }))
+                .for_each(|cmd| commands.push(cmd));
+        }
+    }
+
+    commands.sort();
+    commands.dedup();
+    commands
+}
@@ -0,0 +1,228 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2019 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+use std::io::Write;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::process::Command;
+use std::process::Stdio;
+
+use failure::Fallible as Result;
+use failure::ResultExt;
+use failure::Error;
+
+use libimagrt::runtime::Runtime;
+use libimagstore::storeid::StoreId;
+use libimagerror::trace::trace_error;
+
+/// What the shell should do after a command was executed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flow {
+    Continue,
+    Exit,
+}
+
+/// The builtin commands of the shell and their usage
+///
+/// All other commands are dispatched to the imag command of the same name.
+pub const BUILTINS : &'static [(&'static str, &'static str)] = &[
+    ("help",   "help"),
+    ("exit",   "exit"),
+    ("quit",   "quit"),
+    ("select", "select [set|add|remove|clear] [<id>...]"),
+];
+
+/// The state of a shell session
+///
+/// The shell holds the runtime (and therefor the store) open for the whole session and remembers
+/// the ids which were selected or touched by the last command. The ids of all entries in the store
+/// are kept separately, for completion.
+pub struct Shell<'a> {
+    rt: &'a Runtime<'a>,
+    selection: Vec<StoreId>,
+    ids: Rc<RefCell<Vec<String>>>,
+}
+
+impl<'a> Shell<'a> {
+
+    pub fn new(rt: &'a Runtime<'a>) -> Shell<'a> {
+        Shell {
+            rt,
+            selection: vec![],
+            ids: Rc::new(RefCell::new(vec![])),
+        }
+    }
+
+    /// The list of all ids in the store, shared with the completion
+    pub fn completion_ids(&self) -> Rc<RefCell<Vec<String>>> {
+        self.ids.clone()
+    }
+
+    /// Re-read the ids from the store, for completion
+    pub fn refresh_ids(&self) -> Result<()> {
+        let ids = self.rt
+            .store()
+            .entries()?
+            .map(|r| r.map(|id| id.to_string()))
+            .collect::<Result<Vec<String>>>()?;
+
+        *self.ids.borrow_mut() = ids;
+        Ok(())
+    }
+
+    pub fn selection(&self) -> &Vec<StoreId> {
+        &self.selection
+    }
+
+    /// Execute the command `words`
+    pub fn execute(&mut self, words: Vec<String>) -> Result<Flow> {
+        let (command, args) = match words.split_first() {
+            Some((command, args)) => (command.clone(), args),
+            None                  => return Ok(Flow::Continue),
+        };
+
+        debug!("Executing: {} {:?}", command, args);
+
+        match command.as_ref() {
+            "exit" | "quit" => return Ok(Flow::Exit),
+            "help"          => self.help()?,
+            "select"        => self.select(args)?,
+            other           => self.dispatch(other, args)?,
+        }
+
+        Ok(Flow::Continue)
+    }
+
+    fn help(&self) -> Result<()> {
+        let mut out = self.rt.stdout();
+        writeln!(out, "Builtin commands:")?;
+        for &(_, usage) in BUILTINS {
+            writeln!(out, "    {}", usage)?;
+        }
+        writeln!(out, "All other commands are dispatched to the imag command of the same name.")?;
+        writeln!(out, "See 'imag shell --help' for details.")?;
+        Ok(())
+    }
+
+    fn select(&mut self, args: &[String]) -> Result<()> {
+        match args.split_first() {
+            None => self.print_selection(),
+            Some((subcommand, ids)) => {
+                let ids = to_ids(ids)?;
+
+                match subcommand.as_ref() {
+                    "set"    => self.selection = ids,
+                    "add"    => for id in ids {
+                        if !self.selection.contains(&id) {
+                            self.selection.push(id);
+                        }
+                    },
+                    "remove" => self.selection.retain(|id| !ids.contains(id)),
+                    "clear"  => self.selection.clear(),
+                    other    => return Err(format_err!("Unknown subcommand: select {}", other)),
+                }
+
+                Ok(())
+            }
+        }
+    }
+
+    fn print_selection(&self) -> Result<()> {
+        let mut out = self.rt.stdout();
+        for id in self.selection.iter() {
+            writeln!(out, "{}", id)?;
+        }
+        Ok(())
+    }
+
+    /// Call the imag command `imag-<command>`
+    ///
+    /// The command opens the store on its own, so the cache of the shell is flushed before. The
+    /// selection is piped to the command and the ids the command reports as touched are the new
+    /// selection. The normal output of the command goes to stderr, as the command detects that its
+    /// output is piped.
+    fn dispatch(&mut self, command: &str, args: &[String]) -> Result<()> {
+        // Do not hold entries in the cache which the other command might alter
+        let _ = self.rt.store().flush_cache()?;
+
+        let mut cmd = Command::new(format!("imag-{}", command));
+        cmd.arg("--rtp").arg(self.rt.rtp());
+        cmd.arg("--store").arg(self.rt.store().path());
+
+        if let Some(config) = self.rt.cli().value_of(Runtime::arg_config_name()) {
+            cmd.arg("--config").arg(config);
+        }
+
+        if let Some(profile) = self.rt.profile() {
+            cmd.arg("--profile").arg(profile.name());
+        }
+
+        let stdin = if self.selection.is_empty() {
+            Stdio::inherit()
+        } else {
+            Stdio::piped()
+        };
+
+        let mut child = cmd
+            .args(args)
+            .stdin(stdin)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .context(format_err!("Could not call 'imag-{}'", command))?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            for id in self.selection.iter() {
+                writeln!(stdin, "{}", id)?;
+            }
+        } // stdin is closed here
+
+        let output = child
+            .wait_with_output()
+            .context(format_err!("Error while waiting for 'imag-{}'", command))?;
+
+        if !output.status.success() {
+            return Err(format_err!("imag-{} exited with {}", command, output.status))
+        }
+
+        let output = String::from_utf8(output.stdout).map_err(Error::from)?;
+        let ids    = ::libimagrt::idstream::parse(&output)?
+            .1
+            .into_iter()
+            .map(|item| item.into_parts().0)
+            .collect::<Vec<StoreId>>();
+
+        if !ids.is_empty() {
+            self.selection = ids;
+        }
+
+        // The command might have created, moved or deleted entries
+        if let Err(e) = self.refresh_ids() {
+            trace_error(&e);
+        }
+
+        Ok(())
+    }
+
+}
+
+fn to_ids(ids: &[String]) -> Result<Vec<StoreId>> {
+    ids.iter().map(|id| StoreId::new(PathBuf::from(id))).collect()
+}
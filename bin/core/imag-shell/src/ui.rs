@@ -0,0 +1,44 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2019 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+
+use clap::App;
+
+pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
+    app
+        .after_help(r#"
+imag-shell opens the store once and reads commands from the prompt.
+
+Builtin commands:
+
+    help                         Print this help
+    exit, quit                   Leave the shell
+
+    select                       Print the current selection
+    select set <id>...           Replace the selection with these ids
+    select add <id>...           Add ids to the selection
+    select remove <id>...        Remove ids from the selection
+    select clear                 Clear the selection
+
+All other commands are dispatched to the imag command of the same name:
+"diary list" calls "imag-diary list", "tag add work" calls
+"imag-tag add work". The selection is piped into the command and the ids it
+reports become the new selection.
+"#)
+}
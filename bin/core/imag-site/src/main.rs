@@ -0,0 +1,180 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2019 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+#![forbid(unsafe_code)]
+
+#![deny(
+    non_camel_case_types,
+    non_snake_case,
+    path_statements,
+    trivial_numeric_casts,
+    unstable_features,
+    unused_allocation,
+    unused_import_braces,
+    unused_imports,
+    unused_must_use,
+    unused_mut,
+    unused_qualifications,
+    while_true,
+)]
+
+#[macro_use] extern crate log;
+extern crate clap;
+extern crate toml;
+extern crate toml_query;
+extern crate failure;
+
+extern crate libimagstore;
+#[macro_use] extern crate libimagrt;
+extern crate libimagerror;
+extern crate libimagentryquery;
+extern crate libimagentrysite;
+
+use std::path::PathBuf;
+
+use failure::Error;
+use toml::Value;
+use toml_query::read::TomlValueReadExt;
+use toml_query::read::TomlValueReadTypeExt;
+
+use libimagrt::runtime::Runtime;
+use libimagrt::setup::generate_runtime_setup;
+use libimagentryquery::search::SavedSearchResolver;
+use libimagstore::storeid::StoreId;
+use libimagerror::trace::MapErrTrace;
+use libimagerror::exit::ExitUnwrap;
+use libimagentryquery::cli::query_from_matches;
+use libimagentryquery::cli::entry_matches;
+use libimagentrysite::site::SiteExporter;
+use libimagentrysite::site::DEFAULT_COLLECTIONS;
+
+mod ui;
+
+fn main() {
+    let version = make_imag_version!();
+    let rt = generate_runtime_setup("imag-site",
+                                    &version,
+                                    "Export entries to a static HTML site",
+                                    ui::build_ui);
+    rt.register_id_resolver(SavedSearchResolver);
+
+    let out       = PathBuf::from(rt.cli().value_of("out").unwrap()); // safe by clap
+    let title     = rt.cli()
+        .value_of("title")
+        .map(String::from)
+        .or_else(|| get_config(&rt, "site.title"))
+        .unwrap_or_else(|| String::from("imag"));
+    let templates = rt.cli()
+        .value_of("templates")
+        .map(String::from)
+        .or_else(|| get_config(&rt, "site.templates"))
+        .map(PathBuf::from);
+
+    let ids = get_ids(&rt);
+    if ids.is_empty() {
+        warn!("No entries to export");
+        ::std::process::exit(1)
+    }
+
+    let mut exporter = SiteExporter::new(rt.store()).with_title(title);
+    if let Some(templates) = templates {
+        exporter = exporter.with_template_dir(templates);
+    }
+
+    let report = exporter
+        .export(ids.iter().cloned(), &out)
+        .map_err_trace_exit_unwrap();
+
+    for id in ids.iter() {
+        let _ = rt.report_touched(id).unwrap_or_exit();
+    }
+
+    info!("Exported {} to {}", report, out.display());
+}
+
+/// Get the ids of the entries to export: the passed entries and the entries in the passed
+/// collections, or the entries in the content collections (`site.collections` in the
+/// configuration, see `DEFAULT_COLLECTIONS`) if neither are passed, filtered by the query
+fn get_ids(rt: &Runtime) -> Vec<StoreId> {
+    let mut ids = rt.ids::<::ui::PathProvider>().map_err_trace_exit_unwrap();
+
+    if let Some(collections) = rt.cli().values_of("collection") {
+        for collection in collections {
+            ids.extend(entries_in_collection(rt, collection));
+        }
+    } else if ids.is_empty() {
+        for collection in default_collections(rt) {
+            ids.extend(entries_in_collection(rt, &collection));
+        }
+    }
+
+    ids.sort();
+    ids.dedup();
+
+    let query = query_from_matches(rt.cli()).map_err_trace_exit_unwrap();
+    if query.is_none() {
+        return ids
+    }
+
+    ids.into_iter()
+        .filter(|id| {
+            rt.store()
+                .get(id.clone())
+                .map_err_trace_exit_unwrap()
+                .map(|entry| entry_matches(query.as_ref(), &entry).map_err_trace_exit_unwrap())
+                .unwrap_or(false)
+        })
+        .collect()
+}
+
+fn entries_in_collection(rt: &Runtime, collection: &str) -> Vec<StoreId> {
+    rt.store()
+        .entries()
+        .map_err_trace_exit_unwrap()
+        .in_collection(collection)
+        .collect::<Result<Vec<StoreId>, Error>>()
+        .map_err_trace_exit_unwrap()
+}
+
+/// The collections which are exported if no entries are selected
+fn default_collections(rt: &Runtime) -> Vec<String> {
+    let configured = rt.config()
+        .and_then(|cfg| cfg.read("site.collections").map_err(Error::from).map_err_trace_exit_unwrap())
+        .map(|value| match *value {
+            Value::Array(ref a) => a.iter()
+                .map(|v| match *v {
+                    Value::String(ref s) => s.clone(),
+                    _ => {
+                        error!("Config type wrong: 'site.collections' should be an array of strings");
+                        ::std::process::exit(1)
+                    },
+                })
+                .collect::<Vec<String>>(),
+            _ => {
+                error!("Config type wrong: 'site.collections' should be an array of strings");
+                ::std::process::exit(1)
+            },
+        });
+
+    configured.unwrap_or_else(|| DEFAULT_COLLECTIONS.iter().map(|c| String::from(*c)).collect())
+}
+
+fn get_config(rt: &Runtime, path: &'static str) -> Option<String> {
+    rt.config().and_then(|cfg| cfg.read_string(path).map_err(Error::from).map_err_trace_exit_unwrap())
+}
@@ -0,0 +1,86 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2019 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+use std::path::PathBuf;
+
+use clap::{Arg, ArgMatches, App};
+
+use libimagstore::storeid::StoreId;
+use libimagstore::storeid::IntoStoreId;
+use libimagrt::runtime::IdPathProvider;
+use libimagerror::trace::MapErrTrace;
+
+pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
+    app
+        .arg(Arg::with_name("entries")
+             .index(1)
+             .takes_value(true)
+             .required(false)
+             .multiple(true)
+             .value_name("ENTRIES")
+             .help("Export these entries. If neither entries nor collections are passed, the content collections (notes, wiki and diary, or 'site.collections' from the configuration) are exported"))
+
+        .arg(Arg::with_name("collection")
+             .long("collection")
+             .short("c")
+             .takes_value(true)
+             .required(false)
+             .multiple(true)
+             .value_name("COLLECTION")
+             .help("Export all entries in this collection, for example 'wiki/default' or 'diary/work'"))
+
+        .arg(::libimagentryquery::cli::query_arg())
+
+        .arg(Arg::with_name("out")
+             .long("out")
+             .short("o")
+             .takes_value(true)
+             .required(true)
+             .multiple(false)
+             .value_name("DIRECTORY")
+             .help("Write the site to this directory"))
+
+        .arg(Arg::with_name("title")
+             .long("title")
+             .takes_value(true)
+             .required(false)
+             .multiple(false)
+             .value_name("TITLE")
+             .help("The title of the site. Default: 'site.title' from the configuration, or 'imag'"))
+
+        .arg(Arg::with_name("templates")
+             .long("templates")
+             .takes_value(true)
+             .required(false)
+             .multiple(false)
+             .value_name("DIRECTORY")
+             .help("Use the templates from this directory instead of the builtin ones. Default: 'site.templates' from the configuration"))
+}
+
+pub struct PathProvider;
+impl IdPathProvider for PathProvider {
+    fn get_ids(matches: &ArgMatches) -> Vec<StoreId> {
+        matches.values_of("entries")
+            .map(|vals| {
+                vals.map(|s| PathBuf::from(s).into_storeid().map_err_trace_exit_unwrap())
+                    .collect()
+            })
+            .unwrap_or_else(Vec::new)
+    }
+}
@@ -35,8 +35,7 @@ pub fn get(rt: &Runtime) {
     debug!("path = {:?}", path);
 
     let _ = match rt.store().get(path.clone()).map_err_trace_exit_unwrap() {
-        Some(entry) => {
-            print_entry(rt, scmd, entry);
+        Some(entry) => if print_entry(rt, scmd, entry) {
             let _ = rt.report_touched(&path).unwrap_or_exit();
         },
         None        => info!("No entry found"),
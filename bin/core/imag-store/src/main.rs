@@ -43,6 +43,7 @@ extern crate failure;
 #[macro_use] extern crate libimagrt;
 extern crate libimagstore;
 extern crate libimagerror;
+extern crate libimagentryquery;
 
 #[cfg(test)]
 #[macro_use]
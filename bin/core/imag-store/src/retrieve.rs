@@ -29,6 +29,9 @@ use libimagerror::trace::MapErrTrace;
 use libimagerror::io::ToExitCode;
 use libimagerror::exit::ExitUnwrap;
 use libimagutil::debug_result::*;
+use libimagentryquery::cli::query_from_matches;
+use libimagentryquery::cli::entry_matches;
+use libimagentryquery::cli::QUERY_ARG_NAME;
 
 pub fn retrieve(rt: &Runtime) {
     rt.cli()
@@ -40,28 +43,43 @@ pub fn retrieve(rt: &Runtime) {
             let path  = StoreId::new(path).map_err_trace_exit_unwrap();
             debug!("path = {:?}", path);
 
-            rt.store()
-                .retrieve(path.clone())
-                .map(|e| print_entry(rt, scmd, e))
-                .map_dbg_str("No entry")
+            // With a query, the entry is only looked at, as it cannot match if it does not exist
+            let entry = if scmd.is_present(QUERY_ARG_NAME) {
+                rt.store().get(path.clone())
+            } else {
+                rt.store().retrieve(path.clone()).map(Some)
+            };
+
+            let matched = entry
                 .map_dbg(|e| format!("{:?}", e))
-                .map_err_trace_exit_unwrap();
+                .map_err_trace_exit_unwrap()
+                .map(|e| print_entry(rt, scmd, e))
+                .unwrap_or_else(|| {
+                    info!("No entry found");
+                    false
+                });
 
-            let _ = rt.report_touched(&path).unwrap_or_exit();
+            if matched {
+                let _ = rt.report_touched(&path).unwrap_or_exit();
+            }
         });
 }
 
-pub fn print_entry(rt: &Runtime, scmd: &ArgMatches, e: FileLockEntry) {
+/// Print the entry as requested on the commandline, if it matches the query
+///
+/// Returns whether the entry matched.
+pub fn print_entry(rt: &Runtime, scmd: &ArgMatches, e: FileLockEntry) -> bool {
+    let query = query_from_matches(scmd).map_err_trace_exit_unwrap();
+    if !entry_matches(query.as_ref(), &e).map_err_trace_exit_unwrap() {
+        debug!("Entry does not match the query, not printing");
+        return false
+    }
+
     if do_print_raw(scmd) {
         debug!("Printing raw content...");
         let _ = writeln!(rt.stdout(), "{}", e.to_str().map_err_trace_exit_unwrap())
             .to_exit_code()
             .unwrap_or_exit();
-    } else if do_filter(scmd) {
-        debug!("Filtering...");
-        warn!("Filtering via header specs is currently now supported.");
-        warn!("Will fail now!");
-        unimplemented!()
     } else {
         debug!("Printing structured...");
         if do_print_header(scmd) {
@@ -87,6 +105,8 @@ pub fn print_entry(rt: &Runtime, scmd: &ArgMatches, e: FileLockEntry) {
         }
 
     }
+
+    true
 }
 
 fn do_print_header(m: &ArgMatches) -> bool {
@@ -105,7 +125,3 @@ fn do_print_raw(m: &ArgMatches) -> bool {
     m.is_present("raw")
 }
 
-fn do_filter(m: &ArgMatches) -> bool {
-    m.subcommand_matches("filter-header").is_some()
-}
-
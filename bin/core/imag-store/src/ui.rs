@@ -90,21 +90,8 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                         .short("r")
                         .help("Print Entries as they are in the store"))
 
-                   .subcommand(SubCommand::with_name("filter-header")
-                               .about("Retrieve Entries by filtering")
-                               .version("0.1")
-                               .arg(Arg::with_name("header-field-where")
-                                    .long("where")
-                                    .short("w")
-                                    .takes_value(true)
-                                    .help("Filter with 'header.field=foo' where the header field 'header.field' equals 'foo'")
-                               )
-                               .arg(Arg::with_name("header-field-grep")
-                                    .long("grep")
-                                    .short("g")
-                                    .takes_value(true)
-                                    .help("Filter with 'header.field=[a-zA-Z0-9]*' where the header field 'header.field' matches '[a-zA-Z0-9]*'"))
-                               )
+                   .arg(::libimagentryquery::cli::query_arg()
+                        .help("Only print the entry if it matches the query. See 'imag ids --help' for the query language"))
                    )
 
        .subcommand(SubCommand::with_name("get")
@@ -133,22 +120,8 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                         .short("r")
                         .help("Print Entries as they are in the store"))
 
-                   .subcommand(SubCommand::with_name("filter-header")
-                               .about("Retrieve Entries by filtering")
-                               .version("0.1")
-                               .arg(Arg::with_name("header-field-where")
-                                    .long("where")
-                                    .short("w")
-                                    .takes_value(true)
-                                    .help("Filter with 'header.field=foo' where the header field 'header.field' equals 'foo'")
-                                    .value_name("header.field=foo")
-                               )
-                               .arg(Arg::with_name("header-field-grep")
-                                    .long("grep")
-                                    .short("g")
-                                    .takes_value(true)
-                                    .help("Filter with 'header.field=[a-zA-Z0-9]*' where the header field 'header.field' matches '[a-zA-Z0-9]*'"))
-                               )
+                   .arg(::libimagentryquery::cli::query_arg()
+                        .help("Only print the entry if it matches the query. See 'imag ids --help' for the query language"))
                    )
 
        .subcommand(SubCommand::with_name("update")
@@ -189,5 +162,6 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
        .subcommand(SubCommand::with_name("verify")
                    .about("Verify the store")
                    .version("0.1")
+                   .arg(::libimagentryquery::cli::query_arg())
                    )
 }
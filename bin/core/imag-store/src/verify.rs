@@ -24,11 +24,18 @@ use libimagutil::warn_exit::warn_exit;
 use libimagerror::trace::MapErrTrace;
 use libimagerror::exit::ExitUnwrap;
 use libimagerror::iter::TraceIterator;
+use libimagentryquery::cli::query_from_matches;
+use libimagentryquery::cli::entry_matches;
 
 /// Verify the store.
 ///
 /// This function is not intended to be called by normal programs but only by `imag-store`.
 pub fn verify(rt: &Runtime) {
+    let query = rt
+        .cli()
+        .subcommand_matches("verify")
+        .and_then(|scmd| query_from_matches(scmd).map_err_trace_exit_unwrap());
+
     info!("Header | Content length | Path");
     info!("-------+----------------+-----");
     let result = rt
@@ -38,6 +45,7 @@ pub fn verify(rt: &Runtime) {
         .into_get_iter()
         .trace_unwrap_exit()
         .filter_map(|x| x)
+        .filter(|fle| entry_matches(query.as_ref(), fle).map_err_trace_exit_unwrap())
         .all(|fle| {
             let p           = fle.get_location();
             let content_len = fle.get_content().len();
@@ -44,6 +44,7 @@ extern crate libimagstore;
 #[macro_use] extern crate libimagrt;
 extern crate libimagentrytag;
 extern crate libimagerror;
+extern crate libimagentryquery;
 
 #[cfg(test)]
 #[macro_use]
@@ -62,6 +63,7 @@ use std::io::Write;
 
 use libimagrt::runtime::Runtime;
 use libimagrt::setup::generate_runtime_setup;
+use libimagentryquery::search::SavedSearchResolver;
 use libimagentrytag::tagable::Tagable;
 use libimagentrytag::tag::Tag;
 use libimagerror::trace::trace_error;
@@ -83,8 +85,9 @@ fn main() {
                                     &version,
                                     "Direct interface to the store. Use with great care!",
                                     build_ui);
+    rt.register_id_resolver(SavedSearchResolver);
 
-    let ids = rt.ids::<::ui::PathProvider>().map_err_trace_exit_unwrap();
+    let ids = rt.ids_or_pick::<::ui::PathProvider>().map_err_trace_exit_unwrap();
 
     rt.cli()
         .subcommand_name()
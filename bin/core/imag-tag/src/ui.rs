@@ -106,10 +106,13 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
 pub struct PathProvider;
 impl IdPathProvider for PathProvider {
     fn get_ids(matches: &ArgMatches) -> Vec<StoreId> {
+        // Without ids, the user picks the entries interactively
         matches.values_of("id")
-            .unwrap()
-            .map(|s| PathBuf::from(s).into_storeid().map_err_trace_exit_unwrap())
-            .collect()
+            .map(|vals| {
+                vals.map(|s| PathBuf::from(s).into_storeid().map_err_trace_exit_unwrap())
+                    .collect()
+            })
+            .unwrap_or_else(Vec::new)
     }
 }
 
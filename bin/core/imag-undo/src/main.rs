@@ -0,0 +1,135 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2019 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+
+#![forbid(unsafe_code)]
+
+#![deny(
+    non_camel_case_types,
+    non_snake_case,
+    path_statements,
+    trivial_numeric_casts,
+    unstable_features,
+    unused_allocation,
+    unused_import_braces,
+    unused_imports,
+    unused_must_use,
+    unused_mut,
+    unused_qualifications,
+    while_true,
+)]
+
+extern crate clap;
+#[macro_use] extern crate log;
+
+#[macro_use] extern crate libimagrt;
+extern crate libimagerror;
+extern crate libimagutil;
+
+use std::io::Write;
+use std::str::FromStr;
+
+use libimagrt::runtime::Runtime;
+use libimagrt::setup::generate_runtime_setup;
+use libimagrt::undo::UndoLog;
+use libimagrt::undo::UndoRecord;
+use libimagrt::undo::Revert;
+use libimagerror::trace::MapErrTrace;
+use libimagerror::exit::ExitUnwrap;
+use libimagerror::io::ToExitCode;
+
+mod ui;
+
+fn main() {
+    let version = make_imag_version!();
+    let rt = generate_runtime_setup("imag-undo",
+                                    &version,
+                                    "Undo the last imag commands",
+                                    ui::build_ui);
+
+    let log   = UndoLog::new(rt.store().path());
+    let count = rt
+        .cli()
+        .value_of("count")
+        .map(usize::from_str)
+        .unwrap() // default value by clap
+        .unwrap(); // validated by clap
+
+    if rt.cli().is_present("list") {
+        list(&rt, &log, None)
+    } else if rt.is_dry_run() {
+        // The undo journal is restored without the store, so we can only tell what would be done
+        let _ = writeln!(rt.stdout(), "Dry run, would undo:").to_exit_code().unwrap_or_exit();
+        list(&rt, &log, Some(count))
+    } else {
+        undo(&rt, &log, count)
+    }
+}
+
+fn list(rt: &Runtime, log: &UndoLog, limit: Option<usize>) {
+    let records = log.records().map_err_trace_exit_unwrap();
+    let limit   = limit.unwrap_or_else(|| records.len());
+    let mut out = rt.stdout();
+
+    for (i, (_, record)) in records.iter().take(limit).enumerate() {
+        let _ = writeln!(out, "{:>3} {}", i + 1, describe(record)).to_exit_code().unwrap_or_exit();
+
+        for change in record.changes.iter() {
+            let what = match (change.pre.is_some(), change.post.is_some()) {
+                (false, true) => "created",
+                (true, false) => "removed",
+                _             => "modified",
+            };
+
+            let _ = writeln!(out, "      {} {}", what, change.id).to_exit_code().unwrap_or_exit();
+        }
+    }
+}
+
+fn undo(rt: &Runtime, log: &UndoLog, count: usize) {
+    let results = log.revert_last(count).map_err_trace_exit_unwrap();
+    let mut out = rt.stdout();
+
+    if results.is_empty() {
+        info!("Nothing to undo");
+        return
+    }
+
+    for result in results {
+        match result {
+            Revert::Reverted(record) => {
+                let _ = writeln!(out, "Undone: {}", describe(&record)).to_exit_code().unwrap_or_exit();
+            },
+
+            Revert::Conflict(record, ids) => {
+                error!("Cannot undo: {}", describe(&record));
+                error!("The following entries were changed since:");
+                for id in ids {
+                    error!("    {}", id);
+                }
+
+                ::std::process::exit(1)
+            },
+        }
+    }
+}
+
+fn describe(record: &UndoRecord) -> String {
+    format!("{} '{}'", record.timestamp, record.command.join(" "))
+}
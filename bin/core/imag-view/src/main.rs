@@ -43,7 +43,9 @@ extern crate toml_query;
 extern crate failure;
 
 extern crate libimagentryview;
+extern crate libimagentrymarkdown;
 extern crate libimagerror;
+extern crate libimagentryquery;
 #[macro_use] extern crate libimagrt;
 extern crate libimagstore;
 extern crate libimagutil;
@@ -60,6 +62,7 @@ use failure::Error;
 use failure::err_msg;
 
 use libimagrt::setup::generate_runtime_setup;
+use libimagentryquery::search::SavedSearchResolver;
 use libimagerror::trace::MapErrTrace;
 use libimagerror::iter::TraceIterator;
 use libimagerror::io::ToExitCode;
@@ -67,6 +70,7 @@ use libimagerror::exit::ExitUnwrap;
 use libimagentryview::builtin::stdout::StdoutViewer;
 use libimagentryview::builtin::md::MarkdownViewer;
 use libimagentryview::viewer::Viewer;
+use libimagentrymarkdown::outline::Outline;
 use libimagstore::iter::get::StoreIdGetIteratorExtension;
 use libimagstore::store::FileLockEntry;
 
@@ -79,10 +83,11 @@ fn main() {
                                      &version,
                                      "View entries (readonly)",
                                      build_ui);
+    rt.register_id_resolver(SavedSearchResolver);
 
     let view_header  = rt.cli().is_present("view-header");
     let hide_content = rt.cli().is_present("not-view-content");
-    let entries      = rt.ids::<::ui::PathProvider>()
+    let entries      = rt.ids_or_pick::<::ui::PathProvider>()
         .map_err_trace_exit_unwrap()
         .into_iter()
         .map(Ok)
@@ -191,7 +196,30 @@ fn main() {
             sep.repeat(width / sep.len())
         };
 
-        if rt.cli().is_present("compile-md") {
+        if rt.cli().is_present("toc") {
+            let seperator = basesep.map(|s| build_seperator(s, sep_width));
+
+            entries
+                .enumerate()
+                .for_each(|(n, entry)| {
+                    if n != 0 {
+                        seperator
+                            .as_ref()
+                            .map(|s| writeln!(outlock, "{}", s).to_exit_code().unwrap_or_exit());
+                    }
+
+                    for heading in Outline::parse(entry.get_content()).headings {
+                        writeln!(outlock, "{}{} (#{})",
+                                 "  ".repeat(heading.level - 1),
+                                 heading.text,
+                                 heading.anchor)
+                            .to_exit_code()
+                            .unwrap_or_exit();
+                    }
+
+                    rt.report_touched(entry.get_location()).unwrap_or_exit();
+                });
+        } else if rt.cli().is_present("compile-md") {
             let viewer    = MarkdownViewer::new(&rt);
             let seperator = basesep.map(|s| build_seperator(s, sep_width));
 
@@ -69,6 +69,14 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
             .conflicts_with("not-view-content")
             .conflicts_with("autowrap")) // markdown viewer does not support wrapping
 
+        .arg(Arg::with_name("toc")
+            .long("toc")
+            .takes_value(false)
+            .required(false)
+            .help("View the outline of the markdown content (the headings and their anchors) instead of the content")
+            .conflicts_with("compile-md")
+            .conflicts_with("in"))
+
         .arg(Arg::with_name("seperator")
             .long("seperate")
             .short("s")
@@ -91,9 +99,12 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
 pub struct PathProvider;
 impl IdPathProvider for PathProvider {
     fn get_ids(matches: &ArgMatches) -> Vec<StoreId> {
+        // Without ids, the user picks the entries interactively
         matches.values_of("id")
-            .unwrap()
-            .map(|s| PathBuf::from(s).into_storeid().map_err_trace_exit_unwrap())
-            .collect()
+            .map(|vals| {
+                vals.map(|s| PathBuf::from(s).into_storeid().map_err_trace_exit_unwrap())
+                    .collect()
+            })
+            .unwrap_or_else(Vec::new)
     }
 }
@@ -25,6 +25,7 @@ extern crate libimagrt;
 extern crate libimagerror;
 extern crate libimagstore;
 extern crate libimagentrytag;
+extern crate libimagentryquery;
 extern crate libimagutil;
 
 use clap::Shell;
@@ -91,8 +92,12 @@ gen_mods_buildui!(
     ("../../../bin/core/imag-link/src/ui.rs"        , imaglink)        ,
     ("../../../bin/core/imag-mv/src/ui.rs"          , imagmv)          ,
     ("../../../bin/core/imag-ref/src/ui.rs"         , imagref)         ,
+    ("../../../bin/core/imag-search/src/ui.rs"      , imagsearch)      ,
+    ("../../../bin/core/imag-shell/src/ui.rs"       , imagshell)       ,
+    ("../../../bin/core/imag-site/src/ui.rs"        , imagsite)        ,
     ("../../../bin/core/imag-store/src/ui.rs"       , imagstore)       ,
     ("../../../bin/core/imag-tag/src/ui.rs"         , imagtag)         ,
+    ("../../../bin/core/imag-undo/src/ui.rs"        , imagundo)        ,
     ("../../../bin/core/imag-view/src/ui.rs"        , imagview)        ,
     ("../../../bin/domain/imag-bookmark/src/ui.rs"  , imagbookmark)    ,
     ("../../../bin/domain/imag-contact/src/ui.rs"   , imagcontact)     ,
@@ -133,10 +138,14 @@ fn main() {
         .subcommand(build_subcommand!("mv"          , imagmv          , version))
         .subcommand(build_subcommand!("notes"       , imagnotes       , version))
         .subcommand(build_subcommand!("ref"         , imagref         , version))
+        .subcommand(build_subcommand!("search"      , imagsearch      , version))
+        .subcommand(build_subcommand!("shell"       , imagshell       , version))
+        .subcommand(build_subcommand!("site"        , imagsite        , version))
         .subcommand(build_subcommand!("store"       , imagstore       , version))
         .subcommand(build_subcommand!("tag"         , imagtag         , version))
         .subcommand(build_subcommand!("timetrack"   , imagtimetrack   , version))
         .subcommand(build_subcommand!("todo"        , imagtodo        , version))
+        .subcommand(build_subcommand!("undo"        , imagundo        , version))
         .subcommand(build_subcommand!("view"        , imagview        , version))
         .subcommand(build_subcommand!("wiki"        , imagwiki        , version));
 
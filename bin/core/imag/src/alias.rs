@@ -0,0 +1,371 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2019 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+
+//! Aliases and macros of the `imag` command
+//!
+//! Aliases are configured in the `imag.aliases` table of the configuration:
+//!
+//! ```toml
+//! [imag.aliases]
+//! # "imag s" and "imag st" call "imag store"
+//! store = [ "s", "st" ]
+//!
+//! # "imag today <args>" calls "imag diary create --timed m <args>"
+//! today = "diary create --timed m $@"
+//!
+//! # Several commands, piped into each other
+//! [imag.aliases.inbox]
+//! pipe = [ "ids --in-collection notes", "tag add inbox" ]
+//! help = "Tag all notes with 'inbox'"
+//! ```
+//!
+//! A string without whitespace or an array of such strings defines alternative names for the
+//! command named by the key. A string with whitespace defines an alias named by the key which
+//! expands to the command line in the string.
+//!
+//! In command lines, `$1`, `$2`, ... are replaced by the positional arguments passed to the alias,
+//! `$@` by all arguments and `$$` by a literal `$`. If a command line contains no placeholder,
+//! the arguments are appended (to the last command of a pipe).
+
+use std::collections::BTreeMap;
+
+use toml::Value;
+use toml_query::read::TomlValueReadExt;
+
+use libimagutil::shell_words::split_words;
+
+/// What an alias expands to
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expansion {
+    /// A single command line
+    Command(Vec<String>),
+
+    /// Several command lines, where the output of each command is piped into the next one
+    Pipe(Vec<Vec<String>>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alias {
+    expansion: Expansion,
+    help: Option<String>,
+}
+
+/// The aliases from the configuration
+#[derive(Debug, Default)]
+pub struct Aliases {
+    /// Alternative names for commands, alternative name -> command
+    names: BTreeMap<String, String>,
+
+    /// Aliases which expand to command lines
+    aliases: BTreeMap<String, Alias>,
+}
+
+impl Aliases {
+
+    pub fn from_config(config: Option<&Value>) -> Result<Aliases, String> {
+        let cfg   = config.ok_or_else(|| String::from("No configuration found"))?;
+        let value = cfg
+            .read("imag.aliases")
+            .map_err(|_| String::from("Reading from config failed"));
+
+        match value? {
+            None                         => Ok(Aliases::default()),
+            Some(&Value::Table(ref tbl)) => {
+                let mut aliases = Aliases::default();
+
+                for (k, v) in tbl {
+                    match *v {
+                        Value::String(ref s) if s.trim().contains(char::is_whitespace) => {
+                            let alias = Alias {
+                                expansion: Expansion::Command(split_words(s)?),
+                                help: None,
+                            };
+                            aliases.aliases.insert(k.clone(), alias);
+                        },
+
+                        Value::String(ref alias) => {
+                            aliases.names.insert(alias.clone(), k.clone());
+                        },
+
+                        Value::Array(ref names) => {
+                            for name in names {
+                                match *name {
+                                    Value::String(ref s) => {
+                                        aliases.names.insert(s.clone(), k.clone());
+                                    },
+                                    _ => {
+                                        let e = format!("Not all values are a String in 'imag.aliases.{}'", k);
+                                        return Err(e);
+                                    }
+                                }
+                            }
+                        },
+
+                        Value::Table(ref t) => {
+                            let alias = alias_from_table(k, t)?;
+                            aliases.aliases.insert(k.clone(), alias);
+                        },
+
+                        _ => {
+                            let msg = format!("Type Error: 'imag.aliases.{}' is not a table or string", k);
+                            return Err(msg);
+                        },
+                    }
+                }
+
+                Ok(aliases)
+            },
+
+            Some(_) => Err(String::from("Type Error: 'imag.aliases' is not a table")),
+        }
+    }
+
+    /// Get the command for `name`, resolving alternative names
+    pub fn command_name(&self, name: &str) -> String {
+        self.names.get(name).cloned().unwrap_or_else(|| String::from(name))
+    }
+
+    /// Expand the alias `name` with the arguments `args`
+    ///
+    /// Returns the command lines to call, where the first word of each command line is the imag
+    /// command (with alternative names resolved). `None` if there is no such alias.
+    pub fn expand(&self, name: &str, args: &[String]) -> Option<Result<Vec<Vec<String>>, String>> {
+        self.aliases.get(name).map(|alias| {
+            let lines = match alias.expansion {
+                Expansion::Command(ref line) => vec![line.clone()],
+                Expansion::Pipe(ref lines)   => lines.clone(),
+            };
+
+            let has_placeholder = lines.iter().flat_map(|l| l.iter()).any(|w| has_placeholder(w));
+            let last            = lines.len() - 1;
+
+            lines.into_iter()
+                .enumerate()
+                .map(|(i, line)| {
+                    let mut line = substitute(&line, args)?;
+                    if !has_placeholder && i == last {
+                        line.extend(args.iter().cloned());
+                    }
+
+                    match line.first().cloned() {
+                        Some(cmd) => line[0] = self.command_name(&cmd),
+                        None      => return Err(format!("Empty command in alias '{}'", name)),
+                    }
+
+                    Ok(line)
+                })
+                .collect()
+        })
+    }
+
+    /// Get a description for each alias, for the help text
+    pub fn help_lines(&self) -> Vec<String> {
+        let names = self.names
+            .iter()
+            .map(|(name, cmd)| format!("{:15} -> {}", name, cmd));
+
+        let aliases = self.aliases
+            .iter()
+            .map(|(name, alias)| {
+                let expansion = match alias.expansion {
+                    Expansion::Command(ref line) => line.join(" "),
+                    Expansion::Pipe(ref lines)   => lines
+                        .iter()
+                        .map(|l| l.join(" "))
+                        .collect::<Vec<_>>()
+                        .join(" | "),
+                };
+
+                match alias.help {
+                    Some(ref help) => format!("{:15} -> {} ({})", name, expansion, help),
+                    None           => format!("{:15} -> {}", name, expansion),
+                }
+            });
+
+        names.chain(aliases).collect()
+    }
+
+}
+
+fn alias_from_table(name: &str, t: &BTreeMap<String, Value>) -> Result<Alias, String> {
+    let expansion = match (t.get("command"), t.get("pipe")) {
+        (Some(&Value::String(ref s)), None) => Expansion::Command(split_words(s)?),
+        (None, Some(&Value::Array(ref a))) => {
+            let lines = a.iter()
+                .map(|v| match *v {
+                    Value::String(ref s) => split_words(s),
+                    _ => Err(format!("Type Error: 'imag.aliases.{}.pipe' must contain strings", name)),
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+
+            if lines.is_empty() {
+                return Err(format!("'imag.aliases.{}.pipe' is empty", name))
+            }
+
+            Expansion::Pipe(lines)
+        },
+        _ => return Err(format!("'imag.aliases.{}' needs either a 'command' string or a 'pipe' array", name)),
+    };
+
+    let help = match t.get("help") {
+        Some(&Value::String(ref s)) => Some(s.clone()),
+        Some(_)                     => return Err(format!("Type Error: 'imag.aliases.{}.help' is not a string", name)),
+        None                        => None,
+    };
+
+    Ok(Alias { expansion, help })
+}
+
+fn has_placeholder(word: &str) -> bool {
+    let mut chars = word.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '$' {
+            match chars.next() {
+                Some('@')                  => return true,
+                Some(d) if d.is_digit(10)  => return true,
+                _                          => {},
+            }
+        }
+    }
+    false
+}
+
+/// Replace the placeholders in `line` with `args`
+fn substitute(line: &[String], args: &[String]) -> Result<Vec<String>, String> {
+    let mut result = vec![];
+
+    for word in line {
+        if word == "$@" {
+            result.extend(args.iter().cloned());
+            continue
+        }
+
+        let mut s     = String::new();
+        let mut chars = word.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c != '$' {
+                s.push(c);
+                continue
+            }
+
+            match chars.peek().cloned() {
+                Some('$') => {
+                    let _ = chars.next();
+                    s.push('$');
+                },
+                Some('@') => {
+                    let _ = chars.next();
+                    s.push_str(&args.join(" "));
+                },
+                Some(d) if d.is_digit(10) => {
+                    let mut n = String::new();
+                    while let Some(d) = chars.peek().cloned().filter(|c| c.is_digit(10)) {
+                        n.push(d);
+                        let _ = chars.next();
+                    }
+
+                    let idx = n.parse::<usize>().map_err(|e| format!("{}", e))?;
+                    let arg = idx.checked_sub(1)
+                        .and_then(|i| args.get(i))
+                        .ok_or_else(|| format!("Missing argument ${}", idx))?;
+                    s.push_str(arg);
+                },
+                _ => s.push(c),
+            }
+        }
+
+        result.push(s);
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use toml::Value;
+    use toml::de::from_str;
+
+    use super::*;
+
+    fn aliases(s: &str) -> Aliases {
+        let config : Value = from_str(s).unwrap();
+        Aliases::from_config(Some(&config)).unwrap()
+    }
+
+    fn args(a: &[&str]) -> Vec<String> {
+        a.iter().map(|s| String::from(*s)).collect()
+    }
+
+    #[test]
+    fn test_names() {
+        let a = aliases("[imag.aliases]\nstore = [ \"s\", \"st\" ]\ntag = \"t\"");
+        assert_eq!(a.command_name("st"), "store");
+        assert_eq!(a.command_name("t"), "tag");
+        assert_eq!(a.command_name("diary"), "diary");
+        assert!(a.expand("s", &[]).is_none());
+    }
+
+    #[test]
+    fn test_command_alias() {
+        let a = aliases("[imag.aliases]\ntoday = \"diary create --timed m $@\"");
+        let e = a.expand("today", &args(&["-d", "x"])).unwrap().unwrap();
+        assert_eq!(e, vec![args(&["diary", "create", "--timed", "m", "-d", "x"])]);
+    }
+
+    #[test]
+    fn test_command_alias_appends_args() {
+        let a = aliases("[imag.aliases]\nst = \"s create\"\nstore = \"s\"");
+        let e = a.expand("st", &args(&["--path", "x"])).unwrap().unwrap();
+        assert_eq!(e, vec![args(&["store", "create", "--path", "x"])]);
+    }
+
+    #[test]
+    fn test_positional() {
+        let a = aliases("[imag.aliases]\nt = \"tag add $2 --date=$1 $$HOME\"");
+        let e = a.expand("t", &args(&["today", "foo"])).unwrap().unwrap();
+        assert_eq!(e, vec![args(&["tag", "add", "foo", "--date=today", "$HOME"])]);
+
+        assert!(a.expand("t", &args(&["today"])).unwrap().is_err());
+    }
+
+    #[test]
+    fn test_pipe() {
+        let a = aliases(r#"
+            [imag.aliases.inbox]
+            pipe = [ "ids --in-collection $1", "tag add inbox" ]
+            help = "Tag all entries of a collection"
+        "#);
+        let e = a.expand("inbox", &args(&["notes"])).unwrap().unwrap();
+        assert_eq!(e, vec![
+            args(&["ids", "--in-collection", "notes"]),
+            args(&["tag", "add", "inbox"]),
+        ]);
+
+        let help = a.help_lines();
+        assert_eq!(help.len(), 1);
+        assert!(help[0].contains("ids --in-collection $1 | tag add inbox"));
+    }
+
+    #[test]
+    fn test_invalid_table() {
+        let config : Value = from_str("[imag.aliases.foo]\nbar = 1").unwrap();
+        assert!(Aliases::from_config(Some(&config)).is_err());
+    }
+}
@@ -42,6 +42,7 @@ extern crate toml_query;
 
 #[macro_use] extern crate libimagrt;
 extern crate libimagerror;
+extern crate libimagutil;
 
 use std::env;
 use std::process::exit;
@@ -49,7 +50,6 @@ use std::process::Command;
 use std::process::Stdio;
 use std::io::ErrorKind;
 use std::io::{stdout, Stdout, Write};
-use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 use walkdir::WalkDir;
@@ -64,6 +64,10 @@ use libimagerror::exit::ExitUnwrap;
 use libimagerror::trace::trace_error;
 use libimagrt::configuration::InternalConfiguration;
 
+mod alias;
+
+use alias::Aliases;
+
 /// Returns the helptext, putting the Strings in cmds as possible
 /// subcommands into it
 fn help_text(cmds: Vec<String>) -> String {
@@ -175,16 +179,6 @@ fn main() {
         }
         String::from_utf8(v).unwrap_or_else(|_| { eprintln!("UTF8 Error"); exit(1) })
     };
-    {
-        let print_help = app.clone().get_matches().subcommand_name().map(|h| h == "help").unwrap_or(false);
-        if print_help {
-            let _ = writeln!(out, "{}", long_help)
-                .to_exit_code()
-                .unwrap_or_exit();
-            exit(0)
-        }
-    }
-
     let enable_logging = app.enable_logging();
     let matches = app.matches();
 
@@ -193,11 +187,18 @@ fn main() {
         .value_of(Runtime::arg_config_name())
         .map_or_else(|| rtp.clone(), PathBuf::from);
     debug!("Config path = {:?}", configpath);
-    let config = ::libimagrt::configuration::fetch_config(&configpath)
+    let profile = ::libimagrt::runtime::get_profile_match(&matches);
+    let config = ::libimagrt::configuration::fetch_layered_config(
+            &configpath,
+            ::libimagrt::runtime::get_explicit_storepath_match(&matches),
+            ::libimagrt::runtime::get_default_storepath(&rtp),
+            profile.as_ref().map(String::as_str),
+            ::libimagrt::runtime::get_override_specs(&matches))
         .unwrap_or_else(|e| {
             trace_error(&e);
             exit(1)
-        });
+        })
+        .map(|layered| layered.into_parts().0);
 
     if enable_logging {
         Runtime::init_logger(&matches, config.as_ref())
@@ -245,13 +246,35 @@ fn main() {
         exit(0);
     }
 
-    let aliases = match fetch_aliases(config.as_ref()) {
+    if matches.subcommand_name().map(|h| h == "help").unwrap_or(false) {
+        let _ = writeln!(out, "{}", long_help)
+            .to_exit_code()
+            .unwrap_or_exit();
+
+        let alias_help = Aliases::from_config(config.as_ref())
+            .map(|aliases| aliases.help_lines())
+            .unwrap_or_default();
+        if !alias_help.is_empty() {
+            let _ = writeln!(out, "    Aliases:\n")
+                .to_exit_code()
+                .unwrap_or_exit();
+
+            for line in alias_help {
+                let _ = writeln!(out, "\t{}", line)
+                    .to_exit_code()
+                    .unwrap_or_exit();
+            }
+        }
+
+        exit(0)
+    }
+
+    let aliases = match Aliases::from_config(config.as_ref()) {
         Ok(aliases) => aliases,
         Err(e)      => {
-            let _ = writeln!(out, "Error while fetching aliases from configuration file")
+            let _ = writeln!(out, "Error while fetching aliases from configuration file: {}", e)
                 .to_exit_code()
                 .unwrap_or_exit();
-            debug!("Error = {:?}", e);
             let _ = writeln!(out, "Aborting")
                 .to_exit_code()
                 .unwrap_or_exit();
@@ -265,64 +288,40 @@ fn main() {
             // Get all given arguments and further subcommands to pass to
             // the imag-<> binary
             // Providing no arguments is OK, and is therefore ignored here
-            let mut subcommand_args : Vec<String> = match scmd.values_of("") {
+            let subcommand_args : Vec<String> = match scmd.values_of("") {
                 Some(values) => values.map(String::from).collect(),
                 None => Vec::new()
             };
 
-            debug!("Processing forwarding of commandline arguments");
-            forward_commandline_arguments(&matches, &mut subcommand_args);
-
-            let subcommand = String::from(subcommand);
-            let subcommand = aliases.get(&subcommand).cloned().unwrap_or(subcommand);
-
-            debug!("Calling 'imag-{}' with args: {:?}", subcommand, subcommand_args);
-
-            // Create a Command, and pass it the gathered arguments
-            match Command::new(format!("imag-{}", subcommand))
-                .stdin(Stdio::inherit())
-                .stdout(Stdio::inherit())
-                .stderr(Stdio::inherit())
-                .args(&subcommand_args[..])
-                .spawn()
-                .and_then(|mut c| c.wait())
-            {
-                Ok(exit_status) => {
-                    if !exit_status.success() {
-                        debug!("imag-{} exited with non-zero exit code: {:?}", subcommand, exit_status);
-                        eprintln!("imag-{} exited with non-zero exit code", subcommand);
-                        exit(exit_status.code().unwrap_or(1));
-                    }
-                    debug!("Successful exit!");
+            let commands = match aliases.expand(subcommand, &subcommand_args) {
+                Some(Ok(lines)) => lines
+                    .into_iter()
+                    .map(|mut line| {
+                        let args = line.split_off(1);
+                        (line.remove(0), args) // a line has at least the command
+                    })
+                    .collect(),
+
+                Some(Err(e)) => {
+                    let _ = writeln!(out, "Cannot expand alias '{}': {}", subcommand, e)
+                        .to_exit_code()
+                        .unwrap_or_exit();
+                    exit(1)
                 },
 
-                Err(e) => {
-                    debug!("Error calling the subcommand");
-                    match e.kind() {
-                        ErrorKind::NotFound => {
-                            let _ = writeln!(out, "No such command: 'imag-{}'", subcommand)
-                                .to_exit_code()
-                                .unwrap_or_exit();
-                            let _ = writeln!(out, "See 'imag --help' for available subcommands")
-                                .to_exit_code()
-                                .unwrap_or_exit();
-                            exit(1);
-                        },
-                        ErrorKind::PermissionDenied => {
-                            let _ = writeln!(out, "No permission to execute: 'imag-{}'", subcommand)
-                                .to_exit_code()
-                                .unwrap_or_exit();
-                            exit(1);
-                        },
-                        _ => {
-                            let _ = writeln!(out, "Error spawning: {:?}", e)
-                                .to_exit_code()
-                                .unwrap_or_exit();
-                            exit(1);
-                        }
-                    }
-                }
-            }
+                None => vec![(aliases.command_name(subcommand), subcommand_args)],
+            };
+
+            debug!("Processing forwarding of commandline arguments");
+            let commands = commands
+                .into_iter()
+                .map(|(command, mut args)| {
+                    forward_commandline_arguments(&matches, &mut args);
+                    (command, args)
+                })
+                .collect();
+
+            call(commands, &mut out)
         },
         // Calling for example 'imag --versions' will lead here, as this option does not exit.
         // There's nothing to do in such a case
@@ -330,47 +329,79 @@ fn main() {
     }
 }
 
-fn fetch_aliases(config: Option<&Value>) -> Result<BTreeMap<String, String>, String> {
-    let cfg   = config.ok_or_else(|| String::from("No configuration found"))?;
-    let value = cfg
-        .read("imag.aliases")
-        .map_err(|_| String::from("Reading from config failed"));
-
-    match value? {
-        None                         => Ok(BTreeMap::new()),
-        Some(&Value::Table(ref tbl)) => {
-            let mut alias_mappings = BTreeMap::new();
-
-            for (k, v) in tbl {
-                match v {
-                    &Value::String(ref alias)      => {
-                        alias_mappings.insert(alias.clone(), k.clone());
+/// Call the commands, piping the output of each command into the next one
+///
+/// Exits if one of the commands cannot be called or exits with a non-zero exit code.
+fn call(commands: Vec<(String, Vec<String>)>, out: &mut Stdout) {
+    let last         = commands.len() - 1;
+    let mut children = vec![];
+    let mut stdin    = Stdio::inherit();
+
+    for (i, (subcommand, subcommand_args)) in commands.into_iter().enumerate() {
+        debug!("Calling 'imag-{}' with args: {:?}", subcommand, subcommand_args);
+
+        let stdout = if i == last { Stdio::inherit() } else { Stdio::piped() };
+
+        // Create a Command, and pass it the gathered arguments
+        match Command::new(format!("imag-{}", subcommand))
+            .stdin(stdin)
+            .stdout(stdout)
+            .stderr(Stdio::inherit())
+            .args(&subcommand_args[..])
+            .spawn()
+        {
+            Ok(mut child) => {
+                stdin = child.stdout.take().map(Stdio::from).unwrap_or_else(Stdio::inherit);
+                children.push((subcommand, child));
+            },
+
+            Err(e) => {
+                debug!("Error calling the subcommand");
+                match e.kind() {
+                    ErrorKind::NotFound => {
+                        let _ = writeln!(out, "No such command: 'imag-{}'", subcommand)
+                            .to_exit_code()
+                            .unwrap_or_exit();
+                        let _ = writeln!(out, "See 'imag --help' for available subcommands")
+                            .to_exit_code()
+                            .unwrap_or_exit();
+                        exit(1);
                     },
-                    &Value::Array(ref aliases) => {
-                        for alias in aliases {
-                            match alias {
-                                &Value::String(ref s) => {
-                                    alias_mappings.insert(s.clone(), k.clone());
-                                },
-                                _ => {
-                                    let e = format!("Not all values are a String in 'imag.aliases.{}'", k);
-                                    return Err(e);
-                                }
-                            }
-                        }
+                    ErrorKind::PermissionDenied => {
+                        let _ = writeln!(out, "No permission to execute: 'imag-{}'", subcommand)
+                            .to_exit_code()
+                            .unwrap_or_exit();
+                        exit(1);
                     },
-
                     _ => {
-                        let msg = format!("Type Error: 'imag.aliases.{}' is not a table or string", k);
-                        return Err(msg);
-                    },
+                        let _ = writeln!(out, "Error spawning: {:?}", e)
+                            .to_exit_code()
+                            .unwrap_or_exit();
+                        exit(1);
+                    }
                 }
             }
+        }
+    }
 
-            Ok(alias_mappings)
-        },
+    for (subcommand, mut child) in children {
+        match child.wait() {
+            Ok(exit_status) => {
+                if !exit_status.success() {
+                    debug!("imag-{} exited with non-zero exit code: {:?}", subcommand, exit_status);
+                    eprintln!("imag-{} exited with non-zero exit code", subcommand);
+                    exit(exit_status.code().unwrap_or(1));
+                }
+                debug!("Successful exit!");
+            },
 
-        Some(_) => Err(String::from("Type Error: 'imag.aliases' is not a table")),
+            Err(e) => {
+                let _ = writeln!(out, "Error waiting for imag-{}: {:?}", subcommand, e)
+                    .to_exit_code()
+                    .unwrap_or_exit();
+                exit(1);
+            },
+        }
     }
 }
 
@@ -410,6 +441,9 @@ fn forward_commandline_arguments(m: &ArgMatches, scmd: &mut Vec<String>) {
     push(Some("override-config"),
          Runtime::arg_config_override_name(), m , scmd);
 
+    push(Some("profile"),
+         Runtime::arg_profile_name(), m , scmd);
+
     push(Some("rtp"),
          Runtime::arg_runtimepath_name(), m , scmd);
 
@@ -421,5 +455,11 @@ fn forward_commandline_arguments(m: &ArgMatches, scmd: &mut Vec<String>) {
 
     push(Some("ignore-ids"),
          Runtime::arg_ignore_ids_name(), m , scmd);
+
+    push(Some("id-stream-format"),
+         Runtime::arg_id_stream_format_name(), m , scmd);
+
+    push(Some("dry-run"),
+         Runtime::arg_dry_run_name(), m , scmd);
 }
 
@@ -41,12 +41,15 @@ extern crate toml_query;
 #[macro_use] extern crate failure;
 
 extern crate libimagbookmark;
+extern crate libimagentrylink;
 #[macro_use] extern crate libimagrt;
 extern crate libimagerror;
 extern crate libimagutil;
 
 use std::io::Write;
 use std::process::exit;
+use std::str::FromStr;
+use std::time::Duration;
 
 use toml_query::read::TomlValueReadTypeExt;
 use failure::Error;
@@ -56,6 +59,9 @@ use libimagrt::setup::generate_runtime_setup;
 use libimagbookmark::collection::BookmarkCollection;
 use libimagbookmark::collection::BookmarkCollectionStore;
 use libimagbookmark::link::Link as BookmarkLink;
+use libimagentrylink::archive::ArchiveFormat;
+use libimagentrylink::archive::Archiver;
+use libimagentrylink::check::LinkChecker;
 use libimagerror::trace::{MapErrTrace, trace_error};
 use libimagerror::io::ToExitCode;
 use libimagerror::exit::ExitUnwrap;
@@ -78,6 +84,8 @@ fn main() {
             debug!("Call {}", name);
             match name {
                 "add"        => add(&rt),
+                "archive"    => archive(&rt),
+                "check"      => check(&rt),
                 "collection" => collection(&rt),
                 "list"       => list(&rt),
                 "remove"     => remove(&rt),
@@ -139,6 +147,65 @@ fn collection(rt: &Runtime) {
     }
 }
 
+fn archive(rt: &Runtime) {
+    let coll = get_collection_name(rt, "archive", "collection");
+    let scmd = rt.cli().subcommand_matches("archive").unwrap(); // safed by main()
+
+    let format = scmd
+        .value_of("format")
+        .map(|f| ArchiveFormat::from_str(f).map_err_trace_exit_unwrap())
+        .unwrap(); // safed by clap, there is a default value
+
+    let timeout = scmd
+        .value_of("timeout")
+        .map(|v| u64::from_str(v).map_err(Error::from).map_err_trace_exit_unwrap())
+        .unwrap(); // safed by clap, there is a default value
+
+    let archiver = Archiver::new()
+        .with_format(format)
+        .with_timeout(Duration::from_secs(timeout));
+
+    let collection = BookmarkCollectionStore::get(rt.store(), &coll)
+        .map_err_trace_exit_unwrap()
+        .ok_or_else(|| format_err!("No bookmark collection '{}' found", coll))
+        .map_err_trace_exit_unwrap();
+
+    let _ = rt.report_touched(collection.get_location()).unwrap_or_exit();
+
+    for id in collection.archive_links(rt.store(), &archiver).map_err_trace_exit_unwrap() {
+        writeln!(rt.stdout(), "{}", id).to_exit_code().unwrap_or_exit();
+        let _ = rt.report_touched(&id).unwrap_or_exit();
+    }
+}
+
+fn check(rt: &Runtime) {
+    let coll = get_collection_name(rt, "check", "collection");
+    let scmd = rt.cli().subcommand_matches("check").unwrap(); // safed by main()
+
+    let get_u64 = |name: &str| scmd
+        .value_of(name)
+        .map(|v| u64::from_str(v).map_err(Error::from).map_err_trace_exit_unwrap())
+        .unwrap(); // safed by clap, there are default values
+
+    let checker = LinkChecker::new()
+        .with_timeout(Duration::from_secs(get_u64("timeout")))
+        .with_delay(Duration::from_millis(get_u64("delay")));
+
+    let collection = BookmarkCollectionStore::get(rt.store(), &coll)
+        .map_err_trace_exit_unwrap()
+        .ok_or_else(|| format_err!("No bookmark collection '{}' found", coll))
+        .map_err_trace_exit_unwrap();
+
+    let _ = rt.report_touched(collection.get_location()).unwrap_or_exit();
+
+    let results = collection
+        .check_links(rt.store(), &checker)
+        .map_err_trace_exit_unwrap();
+
+    let broken = results.iter().filter(|&&(_, ref r)| r.is_broken()).count();
+    info!("Checked {} links, {} broken", results.len(), broken);
+}
+
 fn list(rt: &Runtime) {
     let coll = get_collection_name(rt, "list", "collection");
 
@@ -149,6 +216,22 @@ fn list(rt: &Runtime) {
 
     let _ = rt.report_touched(collection.get_location()).unwrap_or_exit();
 
+    if rt.cli().subcommand_matches("list").map(|scmd| scmd.is_present("broken")).unwrap_or(false) {
+        collection
+            .broken_links(rt.store())
+            .map_err_trace_exit_unwrap()
+            .into_iter()
+            .enumerate()
+            .for_each(|(i, (link, result))| {
+                let state = result.error
+                    .or_else(|| result.status.map(|s| s.to_string()))
+                    .unwrap_or_default();
+
+                writeln!(rt.stdout(), "{: >3}: {} ({})", i, link, state).to_exit_code().unwrap_or_exit()
+            });
+        return
+    }
+
     collection
         .links(rt.store())
         .map_dbg_str("Listing...")
@@ -99,6 +99,72 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                         .multiple(true)
                         .value_name("TAGS")
                         .help("Filter links to contain these tags. When multiple tags are specified, all of them must be set for the link to match."))
+                   .arg(Arg::with_name("broken")
+                        .long("broken")
+                        .takes_value(false)
+                        .required(false)
+                        .multiple(false)
+                        .help("Only list links which were found broken by the last 'check'"))
+                   )
+
+        .subcommand(SubCommand::with_name("archive")
+                   .about("Save an offline copy of the bookmarked pages")
+                   .version("0.1")
+                   .arg(Arg::with_name("collection")
+                        .long("collection")
+                        .short("c")
+                        .takes_value(true)
+                        .required(false)
+                        .multiple(false)
+                        .value_name("COLLECTION")
+                        .help("Archive this collection, if not specified default from config will be used"))
+                   .arg(Arg::with_name("format")
+                        .long("format")
+                        .short("f")
+                        .takes_value(true)
+                        .required(false)
+                        .multiple(false)
+                        .possible_values(&["text", "html"])
+                        .default_value("text")
+                        .value_name("FORMAT")
+                        .help("Save the text of the page or the page as HTML, with images and stylesheets inlined"))
+                   .arg(Arg::with_name("timeout")
+                        .long("timeout")
+                        .takes_value(true)
+                        .required(false)
+                        .multiple(false)
+                        .default_value("30")
+                        .value_name("SECONDS")
+                        .help("Timeout for a request in seconds"))
+                   )
+
+        .subcommand(SubCommand::with_name("check")
+                   .about("Check whether the bookmarks still resolve and record the results")
+                   .version("0.1")
+                   .arg(Arg::with_name("collection")
+                        .long("collection")
+                        .short("c")
+                        .takes_value(true)
+                        .required(false)
+                        .multiple(false)
+                        .value_name("COLLECTION")
+                        .help("Check this collection, if not specified default from config will be used"))
+                   .arg(Arg::with_name("timeout")
+                        .long("timeout")
+                        .takes_value(true)
+                        .required(false)
+                        .multiple(false)
+                        .default_value("10")
+                        .value_name("SECONDS")
+                        .help("Timeout for a request in seconds"))
+                   .arg(Arg::with_name("delay")
+                        .long("delay")
+                        .takes_value(true)
+                        .required(false)
+                        .multiple(false)
+                        .default_value("1000")
+                        .value_name("MILLISECONDS")
+                        .help("Time to wait between two requests to the same host, in milliseconds"))
                    )
 
         .subcommand(SubCommand::with_name("collection")
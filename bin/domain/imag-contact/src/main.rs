@@ -52,6 +52,7 @@ extern crate libimagerror;
 extern crate libimagutil;
 extern crate libimaginteraction;
 extern crate libimagentryedit;
+extern crate libimagentryquery;
 
 use std::process::exit;
 use std::path::PathBuf;
@@ -73,6 +74,8 @@ use libimagerror::iter::TraceIterator;
 use libimagcontact::store::ContactStore;
 use libimagcontact::contact::Contact;
 use libimagcontact::deser::DeserVcard;
+use libimagentryquery::cli::query_from_matches;
+use libimagentryquery::cli::entry_matches;
 
 mod ui;
 mod util;
@@ -114,6 +117,7 @@ fn main() {
 fn list(rt: &Runtime) {
     let scmd        = rt.cli().subcommand_matches("list").unwrap();
     let list_format = get_contact_print_format("contact.list_format", rt, &scmd);
+    let query       = query_from_matches(scmd).map_err_trace_exit_unwrap();
 
     let iterator = rt
         .store()
@@ -123,6 +127,7 @@ fn list(rt: &Runtime) {
         .trace_unwrap_exit()
         .map(|fle| fle.ok_or_else(|| Error::from(err_msg("StoreId not found".to_owned()))))
         .trace_unwrap_exit()
+        .filter(|fle| entry_matches(query.as_ref(), fle).map_err_trace_exit_unwrap())
         .map(|fle| {
             let _ = rt.report_touched(fle.get_location()).unwrap_or_exit();
             fle
@@ -44,6 +44,7 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                         .required(false)
                         .multiple(false)
                         .help("Print output as JSON"))
+                   .arg(::libimagentryquery::cli::query_arg())
                    )
 
         .subcommand(SubCommand::with_name("import")
@@ -34,24 +34,32 @@ use util::get_diary_name;
 
 pub fn delete(rt: &Runtime) {
     use libimaginteraction::ask::ask_bool;
+    use libimaginteraction::ui::pick_ids;
 
     let diaryname = get_diary_name(rt)
         .unwrap_or_else(|| warn_exit("No diary selected. Use either the configuration file or the commandline option", 1));
 
-    let to_del_location = rt
-        .cli()
-        .subcommand_matches("delete")
-        .unwrap()
-        .value_of("datetime")
-        .map(|dt| { debug!("DateTime = {:?}", dt); dt })
-        .and_then(DateTime::parse)
-        .map(Into::into)
-        .ok_or_else(|| warn_exit("Not deleting entries: missing date/time specification", 1))
-        .and_then(|dt: NDT| DiaryId::from_datetime(diaryname.clone(), dt).into_storeid())
-        .and_then(|id| rt.store().retrieve(id))
-        .map_err_trace_exit_unwrap()
-        .get_location()
-        .clone();
+    let scmd = rt.cli().subcommand_matches("delete").unwrap();
+
+    let to_del_location = if scmd.is_present("select") {
+        let collection = format!("diary/{}", diaryname);
+        pick_ids(rt.store(), Some(&collection), false)
+            .map_err_trace_exit_unwrap()
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| warn_exit("Not deleting entries: nothing selected", 1))
+    } else {
+        scmd.value_of("datetime")
+            .map(|dt| { debug!("DateTime = {:?}", dt); dt })
+            .and_then(DateTime::parse)
+            .map(Into::into)
+            .ok_or_else(|| warn_exit("Not deleting entries: missing date/time specification", 1))
+            .and_then(|dt: NDT| DiaryId::from_datetime(diaryname.clone(), dt).into_storeid())
+            .and_then(|id| rt.store().retrieve(id))
+            .map_err_trace_exit_unwrap()
+            .get_location()
+            .clone()
+    };
 
     let mut input = rt.stdin().unwrap_or_else(|| {
         error!("No input stream. Cannot ask for permission");
@@ -30,6 +30,8 @@ use libimagutil::debug_result::*;
 use libimagdiary::diaryid::DiaryId;
 use libimagdiary::diaryid::FromStoreId;
 use libimagstore::storeid::IntoStoreId;
+use libimagentryquery::cli::query_from_matches;
+use libimagentryquery::cli::entry_matches;
 
 use failure::Fallible as Result;
 
@@ -39,6 +41,11 @@ pub fn list(rt: &Runtime) {
     let diaryname = get_diary_name(rt)
         .unwrap_or_else(|| warn_exit("No diary selected. Use either the configuration file or the commandline option", 1));
 
+    let query = rt
+        .cli()
+        .subcommand_matches("list")
+        .and_then(|scmd| query_from_matches(scmd).map_err_trace_exit_unwrap());
+
     let mut ids = Diary::entries(rt.store(), &diaryname)
         .map_dbg_str("Ok")
         .map_err_trace_exit_unwrap()
@@ -54,6 +61,13 @@ pub fn list(rt: &Runtime) {
     ids.into_iter()
         .map(IntoStoreId::into_storeid)
         .trace_unwrap_exit()
+        .filter(|id| query.as_ref().map(|q| {
+            rt.store()
+                .get(id.clone())
+                .map_err_trace_exit_unwrap()
+                .map(|entry| entry_matches(Some(q), &entry).map_err_trace_exit_unwrap())
+                .unwrap_or(false)
+        }).unwrap_or(true))
         .for_each(|id| {
             let _ = rt.report_touched(&id).unwrap_or_exit();
 
@@ -44,12 +44,14 @@ extern crate itertools;
 
 extern crate libimagdiary;
 extern crate libimagentryedit;
+extern crate libimagentryquery;
 extern crate libimagentryview;
 extern crate libimagerror;
 extern crate libimaginteraction;
 #[macro_use] extern crate libimagrt;
 extern crate libimagstore;
 extern crate libimagtimeui;
+extern crate libimagtodo;
 extern crate libimagutil;
 
 use std::io::Write;
@@ -63,6 +65,7 @@ use itertools::Itertools;
 mod create;
 mod delete;
 mod list;
+mod tasks;
 mod ui;
 mod util;
 mod view;
@@ -70,6 +73,7 @@ mod view;
 use create::create;
 use delete::delete;
 use list::list;
+use tasks::sync_tasks;
 use view::view;
 
 fn main() {
@@ -89,6 +93,7 @@ fn main() {
                 "delete" => delete(&rt),
                 "list" => list(&rt),
                 "view" => view(&rt),
+                "sync-tasks" => sync_tasks(&rt),
                 other    => {
                     debug!("Unknown command");
                     let _ = rt.handle_unknown_subcommand("imag-diary", other, rt.cli())
@@ -0,0 +1,79 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2019 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+use std::io::Write;
+
+use libimagdiary::diary::Diary;
+use libimagrt::runtime::Runtime;
+use libimagerror::trace::MapErrTrace;
+use libimagerror::trace::trace_error;
+use libimagerror::iter::TraceIterator;
+use libimagerror::exit::ExitUnwrap;
+use libimagerror::io::ToExitCode;
+use libimagutil::warn_exit::warn_exit;
+use libimagstore::iter::get::StoreIdGetIteratorExtension;
+use libimagtodo::markdown::TaskProcessor;
+
+use util::get_diary_name;
+
+pub fn sync_tasks(rt: &Runtime) {
+    let diaryname = get_diary_name(rt).unwrap_or_else(|| warn_exit("No diary name", 1));
+    let scmd      = rt.cli().subcommand_matches("sync-tasks").unwrap();
+    let processor = TaskProcessor::default().create_todos(!scmd.is_present("no-create"));
+
+    let entries = Diary::entries(rt.store(), &diaryname)
+        .map_err_trace_exit_unwrap()
+        .into_get_iter(rt.store())
+        .trace_unwrap_exit()
+        .map(|e| e.unwrap_or_else(|| {
+            error!("Failed to fetch entry");
+            ::std::process::exit(1)
+        }));
+
+    let out         = rt.stdout();
+    let mut outlock = out.lock();
+
+    let mut failed = false;
+
+    for (id, result) in processor.sync_entries(rt.store(), entries) {
+        match result {
+            Ok(report) => {
+                let _ = writeln!(outlock, "{}: {}", id, report)
+                    .to_exit_code()
+                    .unwrap_or_exit();
+
+                for todo in report.touched() {
+                    let _ = rt.report_touched(todo).unwrap_or_exit();
+                }
+
+                let _ = rt.report_touched(&id).unwrap_or_exit();
+            },
+
+            Err(e) => {
+                error!("Failed to sync the task list items of '{}'", id);
+                trace_error(&e);
+                failed = true;
+            },
+        }
+    }
+
+    if failed {
+        ::std::process::exit(1)
+    }
+}
@@ -79,7 +79,8 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
 
         .subcommand(SubCommand::with_name("list")
                    .about("List diary entries")
-                   .version("0.1"))
+                   .version("0.1")
+                   .arg(::libimagentryquery::cli::query_arg()))
 
         .subcommand(SubCommand::with_name("delete")
                    .about("Delete a diary entry")
@@ -119,5 +120,16 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                         .help("Show the header when printing the entries"))
                 )
 
+        .subcommand(SubCommand::with_name("sync-tasks")
+                   .about("Create todo entries for the task list items (`- [ ] todo`) in the diary entries and sync their state")
+                   .version("0.1")
+
+                   .arg(Arg::with_name("no-create")
+                        .long("no-create")
+                        .takes_value(false)
+                        .required(false)
+                        .help("Do not create todo entries, only sync the items which already have one"))
+                )
+
 }
 
@@ -49,6 +49,7 @@ extern crate libimagstore;
 extern crate libimagerror;
 extern crate libimagutil;
 extern crate libimaginteraction;
+extern crate libimagentryquery;
 
 use std::io::Write;
 use std::process::exit;
@@ -71,6 +72,8 @@ use libimagstore::store::FileLockEntry;
 use libimagstore::store::Store;
 use libimagstore::storeid::StoreId;
 use libimaginteraction::ask::ask_bool;
+use libimagentryquery::cli::query_from_matches;
+use libimagentryquery::cli::entry_matches;
 use libimagutil::debug_result::DebugResult;
 
 mod ui;
@@ -427,6 +430,11 @@ fn list(rt: &Runtime) {
         .map(|s| Cell::new(s))
         .collect::<Vec<Cell>>();
 
+    let query = rt
+        .cli()
+        .subcommand_matches("list")
+        .and_then(|scmd| query_from_matches(scmd).map_err_trace_exit_unwrap());
+
     let mut empty = true;
     let mut table = Table::new();
     table.set_titles(Row::new(header));
@@ -447,6 +455,7 @@ fn list(rt: &Runtime) {
                 None
             },
         })
+        .filter(|h| entry_matches(query.as_ref(), h).map_err_trace_exit_unwrap())
         .enumerate()
         .for_each(|(i, e)| {
             let mut v = vec![format!("{}", i)];
@@ -114,6 +114,7 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                         .required(false)
                         .takes_value(false)
                         .help("List with details (how many instances)"))
+                   .arg(::libimagentryquery::cli::query_arg())
                    )
 
         .subcommand(SubCommand::with_name("show")
@@ -46,6 +46,7 @@ extern crate libimagerror;
 extern crate libimagstore;
 extern crate libimagutil;
 extern crate libimagentryref;
+extern crate libimagentryquery;
 
 use std::io::Write;
 use std::path::PathBuf;
@@ -62,8 +63,11 @@ use libimagmail::store::MailStore;
 use libimagmail::util;
 use libimagentryref::reference::{Ref, RefFassade};
 use libimagentryref::util::get_ref_config;
+use libimagentryquery::cli::query_from_matches;
+use libimagentryquery::cli::entry_matches;
 use libimagrt::runtime::Runtime;
 use libimagrt::setup::generate_runtime_setup;
+use libimagentryquery::search::SavedSearchResolver;
 use libimagutil::info_result::*;
 use libimagstore::store::FileLockEntry;
 use libimagstore::storeid::StoreIdIterator;
@@ -79,6 +83,7 @@ fn main() {
                                     &version,
                                     "Mail collection tool",
                                     build_ui);
+    rt.register_id_resolver(SavedSearchResolver);
 
     rt.cli()
         .subcommand_name()
@@ -129,6 +134,7 @@ fn list(rt: &Runtime) {
     let refconfig       = get_ref_config(rt, "imag-mail").map_err_trace_exit_unwrap();
     let scmd            = rt.cli().subcommand_matches("list").unwrap(); // safe via clap
     let print_content   = scmd.is_present("list-read");
+    let query           = query_from_matches(scmd).map_err_trace_exit_unwrap();
 
     if print_content {
         /// TODO: Check whether workaround with "{}" is still necessary when updating "indoc"
@@ -231,6 +237,7 @@ fn list(rt: &Runtime) {
     .into_get_iter(rt.store())
     .trace_unwrap_exit()
     .filter_map(|e| e)
+    .filter(|m| entry_matches(query.as_ref(), m).map_err_trace_exit_unwrap())
     .for_each(|m| list_mail(&rt, &refconfig, &m, print_content));
 }
 
@@ -66,6 +66,7 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                          .multiple(true)
                          .help("The ids of the mails to list information for"))
 
+                    .arg(::libimagentryquery::cli::query_arg())
                     )
 
         .subcommand(SubCommand::with_name("mail-store")
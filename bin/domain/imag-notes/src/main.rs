@@ -44,6 +44,9 @@ extern crate libimagentryedit;
 extern crate libimagerror;
 extern crate libimagutil;
 extern crate libimagstore;
+extern crate libimagentryquery;
+extern crate libimagtodo;
+extern crate libimaginteraction;
 
 use std::io::Write;
 use std::process::exit;
@@ -56,12 +59,17 @@ use libimagrt::setup::generate_runtime_setup;
 use libimagstore::iter::get::StoreIdGetIteratorExtension;
 use libimagnotes::note::Note;
 use libimagnotes::notestore::*;
+use libimagentryquery::cli::query_from_matches;
+use libimagentryquery::cli::entry_matches;
 use libimagerror::trace::MapErrTrace;
+use libimagerror::trace::trace_error;
 use libimagerror::exit::ExitUnwrap;
 use libimagerror::io::ToExitCode;
 use libimagerror::iter::TraceIterator;
 use libimagutil::info_result::*;
 use libimagutil::warn_result::WarnResult;
+use libimagtodo::markdown::TaskProcessor;
+use libimaginteraction::ui::pick_names;
 
 
 mod ui;
@@ -83,6 +91,7 @@ fn main() {
                 "delete" => delete(&rt),
                 "edit"   => edit(&rt),
                 "list"   => list(&rt),
+                "sync-tasks" => sync_tasks(&rt),
                 other    => {
                     debug!("Unknown command");
                     let _ = rt.handle_unknown_subcommand("imag-notes", other, rt.cli())
@@ -98,6 +107,21 @@ fn name_from_cli(rt: &Runtime, subcmd: &str) -> String {
     rt.cli().subcommand_matches(subcmd).unwrap().value_of("name").map(String::from).unwrap()
 }
 
+/// Get the name of the note from the commandline, or let the user pick one of the notes
+fn name_from_cli_or_pick(rt: &Runtime, subcmd: &str) -> String {
+    match rt.cli().subcommand_matches(subcmd).unwrap().value_of("name") {
+        Some(name) => String::from(name),
+        None       => pick_names(rt.store(), "notes", false)
+            .map_err_trace_exit_unwrap()
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| {
+                error!("No note selected");
+                exit(1)
+            }),
+    }
+}
+
 fn create(rt: &Runtime) {
     let name = name_from_cli(rt, "create");
     let mut note = rt
@@ -117,13 +141,13 @@ fn create(rt: &Runtime) {
 
 fn delete(rt: &Runtime) {
     let _ = rt.store()
-        .delete_note(name_from_cli(rt, "delete"))
+        .delete_note(name_from_cli_or_pick(rt, "delete"))
         .map_info_str("Ok")
         .map_err_trace_exit_unwrap();
 }
 
 fn edit(rt: &Runtime) {
-    let name = name_from_cli(rt, "edit");
+    let name = name_from_cli_or_pick(rt, "edit");
     let _ = rt
         .store()
         .get_note(name.clone())
@@ -144,6 +168,11 @@ fn edit(rt: &Runtime) {
 fn list(rt: &Runtime) {
     use std::cmp::Ordering;
 
+    let query = rt
+        .cli()
+        .subcommand_matches("list")
+        .and_then(|scmd| query_from_matches(scmd).map_err_trace_exit_unwrap());
+
     let _ = rt
         .store()
         .all_notes()
@@ -154,6 +183,7 @@ fn list(rt: &Runtime) {
             error!("Fatal: Nonexistent entry where entry should exist");
             exit(1)
         }))
+        .filter(|note| entry_matches(query.as_ref(), note).map_err_trace_exit_unwrap())
         .sorted_by(|note_a, note_b| if let (Ok(a), Ok(b)) = (note_a.get_name(), note_b.get_name()) {
             return a.cmp(&b)
         } else {
@@ -170,3 +200,64 @@ fn list(rt: &Runtime) {
         });
 }
 
+fn sync_tasks(rt: &Runtime) {
+    let scmd      = rt.cli().subcommand_matches("sync-tasks").unwrap();
+    let processor = TaskProcessor::default().create_todos(!scmd.is_present("no-create"));
+
+    let notes = match scmd.values_of("name") {
+        Some(names) => names
+            .map(|name| {
+                rt.store()
+                    .get_note(String::from(name))
+                    .map_err_trace_exit_unwrap()
+                    .unwrap_or_else(|| {
+                        error!("Cannot find note with name '{}'", name);
+                        exit(1)
+                    })
+            })
+            .collect::<Vec<_>>(),
+
+        None => rt
+            .store()
+            .all_notes()
+            .map_err_trace_exit_unwrap()
+            .into_get_iter(rt.store())
+            .trace_unwrap_exit()
+            .map(|opt| opt.unwrap_or_else(|| {
+                error!("Fatal: Nonexistent entry where entry should exist");
+                exit(1)
+            }))
+            .collect::<Vec<_>>(),
+    };
+
+    let out         = rt.stdout();
+    let mut outlock = out.lock();
+
+    let mut failed = false;
+
+    for (id, result) in processor.sync_entries(rt.store(), notes) {
+        match result {
+            Ok(report) => {
+                let _ = writeln!(outlock, "{}: {}", id, report)
+                    .to_exit_code()
+                    .unwrap_or_exit();
+
+                for todo in report.touched() {
+                    let _ = rt.report_touched(todo).unwrap_or_exit();
+                }
+
+                let _ = rt.report_touched(&id).unwrap_or_exit();
+            },
+
+            Err(e) => {
+                error!("Failed to sync the task list items of '{}'", id);
+                trace_error(&e);
+                failed = true;
+            },
+        }
+    }
+
+    if failed {
+        exit(1)
+    }
+}
@@ -44,8 +44,8 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                    .arg(Arg::with_name("name")
                         .index(1)
                         .takes_value(true)
-                        .required(true)
-                        .help("Delete Note with this name")
+                        .required(false)
+                        .help("Delete Note with this name. If not passed, the note can be picked interactively")
                         .value_name("NAME")))
 
         .subcommand(SubCommand::with_name("edit")
@@ -54,13 +54,30 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                    .arg(Arg::with_name("name")
                         .index(1)
                         .takes_value(true)
-                        .required(true)
-                        .help("Edit Note with this name")
+                        .required(false)
+                        .help("Edit Note with this name. If not passed, the note can be picked interactively")
                         .value_name("NAME"))
                    )
 
         .subcommand(SubCommand::with_name("list")
                    .about("List Notes")
-                   .version("0.1"))
+                   .version("0.1")
+                   .arg(::libimagentryquery::cli::query_arg()))
+
+        .subcommand(SubCommand::with_name("sync-tasks")
+                   .about("Create todo entries for the task list items (`- [ ] todo`) in notes and sync their state")
+                   .version("0.1")
+                   .arg(Arg::with_name("no-create")
+                        .long("no-create")
+                        .takes_value(false)
+                        .required(false)
+                        .help("Do not create todo entries, only sync the items which already have one"))
+                   .arg(Arg::with_name("name")
+                        .index(1)
+                        .takes_value(true)
+                        .required(false)
+                        .multiple(true)
+                        .help("Sync the notes with these names. If none is given, all notes are synced")
+                        .value_name("NAME")))
 
 }
@@ -37,6 +37,9 @@ use libimagerror::exit::ExitUnwrap;
 use libimagstore::store::FileLockEntry;
 use libimagtimetrack::timetrackingstore::TimeTrackStore;
 use libimagtimetrack::timetracking::TimeTracking;
+use libimagentryquery::filter::Query;
+use libimagentryquery::cli::query_from_matches;
+use libimagentryquery::cli::entry_matches;
 
 use libimagrt::runtime::Runtime;
 
@@ -78,14 +81,16 @@ pub fn list(rt: &Runtime) -> i32 {
     let end   = gettime(&cmd, "end-time");
 
     let list_not_ended = cmd.is_present("list-not-ended");
+    let query          = query_from_matches(cmd).map_err_trace_exit_unwrap();
 
-    list_impl(rt, start, end, list_not_ended)
+    list_impl(rt, start, end, list_not_ended, query.as_ref())
 }
 
 pub fn list_impl(rt: &Runtime,
                  start: Option<NaiveDateTime>,
                  end: Option<NaiveDateTime>,
-                 list_not_ended: bool)
+                 list_not_ended: bool,
+                 query: Option<&Query>)
     -> i32
 {
 
@@ -128,6 +133,7 @@ pub fn list_impl(rt: &Runtime,
         .map_err_trace_exit_unwrap()
         .trace_unwrap()
         .filter(|e| filter.filter(e))
+        .filter(|e| entry_matches(query, e).map_err_trace_exit_unwrap())
         .fold(Ok(table), |acc: Result<_>, e| {
             acc.and_then(|mut tab: Table| {
                 debug!("Processing {:?}", e.get_location());
@@ -50,6 +50,7 @@ extern crate libimagstore;
 #[macro_use] extern crate libimagrt;
 extern crate libimagtimetrack;
 extern crate libimagutil;
+extern crate libimagentryquery;
 
 mod cont;
 mod day;
@@ -107,7 +108,7 @@ fn main() {
     } else {
         let start = ::chrono::offset::Local::today().naive_local().and_hms(0, 0, 0);
         let end   = ::chrono::offset::Local::today().naive_local().and_hms(23, 59, 59);
-        list_impl(&rt, Some(start), Some(end), false)
+        list_impl(&rt, Some(start), Some(end), false, None)
     };
 
     ::std::process::exit(retval);
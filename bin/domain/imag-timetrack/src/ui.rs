@@ -46,6 +46,7 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                         .multiple(false)
                         .required(false)
                         .help("List not yet ended timetrackings even if after 'end-time'"))
+                   .arg(::libimagentryquery::cli::query_arg())
                    )
 
        .subcommand(SubCommand::with_name("start")
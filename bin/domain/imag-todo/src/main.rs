@@ -43,7 +43,9 @@ extern crate failure;
 
 #[macro_use] extern crate libimagrt;
 extern crate libimagerror;
+extern crate libimagstore;
 extern crate libimagtodo;
+extern crate libimagentryquery;
 
 use std::process::{Command, Stdio};
 use std::io::stdin;
@@ -53,10 +55,18 @@ use failure::Error;
 use libimagrt::runtime::Runtime;
 use libimagrt::setup::generate_runtime_setup;
 use libimagtodo::taskstore::TaskStore;
+use libimagtodo::markdown::MarkdownTodo;
+use libimagtodo::markdown::all_markdown_todos;
+use libimagtodo::markdown::set_todo_state;
 use libimagerror::trace::{MapErrTrace, trace_error};
 use libimagerror::iter::TraceIterator;
 use libimagerror::exit::ExitUnwrap;
 use libimagerror::io::ToExitCode;
+use libimagentryquery::cli::query_from_matches;
+use libimagentryquery::cli::entry_matches;
+use libimagentryquery::filter::Query;
+use libimagentryquery::search::SavedSearchResolver;
+use libimagstore::iter::get::StoreIdGetIteratorExtension;
 
 mod ui;
 
@@ -68,9 +78,13 @@ fn main() {
                                     "Interface with taskwarrior",
                                     build_ui);
 
+    rt.register_id_resolver(SavedSearchResolver);
+
     match rt.cli().subcommand_name() {
         Some("tw-hook") => tw_hook(&rt),
         Some("list") => list(&rt),
+        Some("done") => set_done(&rt, true),
+        Some("undone") => set_done(&rt, false),
         Some(other) => {
             debug!("Unknown command");
             let _ = rt.handle_unknown_subcommand("imag-todo", other, rt.cli())
@@ -118,6 +132,7 @@ fn list(rt: &Runtime) {
 
     let subcmd  = rt.cli().subcommand_matches("list").unwrap();
     let verbose = subcmd.is_present("verbose");
+    let query   = query_from_matches(subcmd).map_err_trace_exit_unwrap();
 
     // Helper for toml_query::read::TomlValueReadExt::read() return value, which does only
     // return Result<T> instead of Result<Option<T>>, which is a real inconvenience.
@@ -132,6 +147,10 @@ fn list(rt: &Runtime) {
             let uuids : Vec<_> = iter.trace_unwrap_exit().filter_map(|storeid| {
                 match rt.store().retrieve(storeid) {
                     Ok(fle) => {
+                        if !entry_matches(query.as_ref(), &fle).map_err_trace_exit_unwrap() {
+                            return None
+                        }
+
                         match fle.get_header().read_string("todo.uuid") {
                             Ok(Some(ref u)) => Some(u.clone()),
                             Ok(None) => {
@@ -178,5 +197,50 @@ fn list(rt: &Runtime) {
         });
 
     res.map_err_trace().ok();
+
+    list_markdown_todos(rt, query.as_ref());
 }
 
+/// List the todos for task list items in markdown content, as
+///
+///     <id> [x] <text> (<source entry>)
+///
+fn list_markdown_todos(rt: &Runtime, query: Option<&Query>) {
+    let out         = rt.stdout();
+    let mut outlock = out.lock();
+
+    all_markdown_todos(rt.store())
+        .map_err_trace_exit_unwrap()
+        .into_get_iter(rt.store())
+        .trace_unwrap_exit()
+        .filter_map(|e| e)
+        .filter(|todo| entry_matches(query, todo).map_err_trace_exit_unwrap())
+        .for_each(|todo| {
+            let mark   = if todo.is_todo_done().map_err_trace_exit_unwrap() { "x" } else { " " };
+            let text   = todo.get_todo_text().map_err_trace_exit_unwrap();
+            let source = todo.get_todo_source().map_err_trace_exit_unwrap();
+
+            let _ = writeln!(outlock, "{} [{}] {} ({})", todo.get_location(), mark, text, source)
+                .to_exit_code()
+                .unwrap_or_exit();
+        });
+}
+
+fn set_done(rt: &Runtime, done: bool) {
+    let ids = rt.ids::<::ui::PathProvider>().map_err_trace_exit_unwrap();
+    if ids.is_empty() {
+        error!("No todos passed");
+        ::std::process::exit(1)
+    }
+
+    for id in ids {
+        let (source, report) = set_todo_state(rt.store(), id.clone(), done)
+            .map_err_trace_exit_unwrap();
+
+        if report.source_changed() {
+            let _ = rt.report_touched(&source).unwrap_or_exit();
+        }
+
+        let _ = rt.report_touched(&id).unwrap_or_exit();
+    }
+}
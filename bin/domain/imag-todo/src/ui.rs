@@ -17,7 +17,14 @@
 // Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
 //
 
-use clap::{Arg, App, ArgGroup, SubCommand};
+use std::path::PathBuf;
+
+use clap::{Arg, ArgMatches, App, ArgGroup, SubCommand};
+
+use libimagstore::storeid::StoreId;
+use libimagstore::storeid::IntoStoreId;
+use libimagrt::runtime::IdPathProvider;
+use libimagerror::trace::MapErrTrace;
 
 pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
     app
@@ -47,7 +54,7 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                     )
 
         .subcommand(SubCommand::with_name("list")
-                    .about("List all tasks")
+                    .about("List all tasks, including the todos for task list items in markdown content")
                     .version("0.1")
 
                     .arg(Arg::with_name("verbose")
@@ -57,5 +64,48 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                          .required(false)
                          .help("Asks taskwarrior for all the details")
                         )
+                    .arg(::libimagentryquery::cli::query_arg())
+                   )
+        .subcommand(SubCommand::with_name("done")
+                    .about("Mark todos for task list items as done and check their checkboxes")
+                    .version("0.1")
+
+                    .arg(Arg::with_name("todo")
+                         .index(1)
+                         .takes_value(true)
+                         .required(false)
+                         .multiple(true)
+                         .value_name("ID")
+                         .help("The todos (todo/markdown/...) to mark as done"))
+                   )
+
+        .subcommand(SubCommand::with_name("undone")
+                    .about("Mark todos for task list items as not done and uncheck their checkboxes")
+                    .version("0.1")
+
+                    .arg(Arg::with_name("todo")
+                         .index(1)
+                         .takes_value(true)
+                         .required(false)
+                         .multiple(true)
+                         .value_name("ID")
+                         .help("The todos (todo/markdown/...) to mark as not done"))
                    )
 }
+
+pub struct PathProvider;
+impl IdPathProvider for PathProvider {
+    fn get_ids(matches: &ArgMatches) -> Vec<StoreId> {
+        match matches.subcommand() {
+            ("done", Some(subm)) | ("undone", Some(subm)) => subm
+                .values_of("todo")
+                .map(|vals| {
+                    vals.map(|s| PathBuf::from(s).into_storeid().map_err_trace_exit_unwrap())
+                        .collect()
+                })
+                .unwrap_or_else(Vec::new),
+
+            _ => vec![],
+        }
+    }
+}
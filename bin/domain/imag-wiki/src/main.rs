@@ -31,6 +31,8 @@ extern crate libimagwiki;
 extern crate libimagentryedit;
 extern crate libimagentrylink;
 extern crate libimagutil;
+extern crate libimagtodo;
+extern crate libimaginteraction;
 
 use std::io::Write;
 
@@ -38,10 +40,13 @@ use libimagrt::runtime::Runtime;
 use libimagrt::setup::generate_runtime_setup;
 use libimagerror::iter::TraceIterator;
 use libimagerror::trace::MapErrTrace;
+use libimagerror::trace::trace_error;
 use libimagerror::exit::ExitUnwrap;
 use libimagerror::io::ToExitCode;
 use libimagwiki::store::WikiStore;
 use libimagentryedit::edit::{Edit, EditHeader};
+use libimagtodo::markdown::TaskProcessor;
+use libimaginteraction::ui::pick_names;
 
 mod ui;
 use ui::build_ui;
@@ -62,6 +67,8 @@ fn main() {
         Some("create-wiki") => create_wiki(&rt),
         Some("show")        => show(&rt, wiki_name),
         Some("delete")      => delete(&rt, wiki_name),
+        Some("rename")      => rename(&rt, wiki_name),
+        Some("sync-tasks")  => sync_tasks(&rt, wiki_name),
         Some(other)         => {
             debug!("Unknown command");
             let _ = rt.handle_unknown_subcommand("imag-wiki", other, rt.cli())
@@ -211,12 +218,11 @@ fn show(rt: &Runtime, wiki_name: &str) {
                                 .values_of("show-name")
                                 .map(|v| v.map(String::from).collect::<Vec<String>>()));
 
-    let names = scmd
-        .values_of("show-name")
-        .unwrap() // safe by clap
-        .map(String::from)
-        .filter(|e| namefilter.filter(e))
-        .collect::<Vec<_>>();
+    let names = match scmd.values_of("show-name") {
+        Some(names) => names.map(String::from).filter(|e| namefilter.filter(e)).collect::<Vec<_>>(),
+        None        => pick_names(rt.store(), &format!("wiki/{}", wiki_name), true)
+            .map_err_trace_exit_unwrap(),
+    };
 
     let wiki = rt
         .store()
@@ -255,7 +261,17 @@ fn delete(rt: &Runtime, wiki_name: &str) {
     use libimagentrylink::internal::InternalLinker;
 
     let scmd   = rt.cli().subcommand_matches("delete").unwrap(); // safed by clap
-    let name   = String::from(scmd.value_of("delete-name").unwrap()); // safe by clap
+    let name   = match scmd.value_of("delete-name") {
+        Some(name) => String::from(name),
+        None       => pick_names(rt.store(), &format!("wiki/{}", wiki_name), false)
+            .map_err_trace_exit_unwrap()
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| {
+                error!("No wiki entry selected");
+                ::std::process::exit(1)
+            }),
+    };
     let unlink = !scmd.is_present("delete-no-remove-linkings");
 
     let wiki = rt
@@ -283,3 +299,100 @@ fn delete(rt: &Runtime, wiki_name: &str) {
         .map_err_trace_exit_unwrap();
 }
 
+fn rename(rt: &Runtime, wiki_name: &str) {
+    let scmd = rt.cli().subcommand_matches("rename").unwrap(); // safed by clap
+    let from = scmd.value_of("rename-from").unwrap(); // safe by clap
+    let to   = scmd.value_of("rename-to").unwrap(); // safe by clap
+
+    let wiki = rt
+            .store()
+            .get_wiki(&wiki_name)
+            .map_err_trace_exit_unwrap()
+            .unwrap_or_else(|| {
+                error!("No wiki '{}' found", wiki_name);
+                ::std::process::exit(1)
+            });
+
+    let report = wiki
+        .rename_entry(from, to)
+        .map_err_trace_exit_unwrap();
+
+    for &(_, ref new) in report.moved.iter() {
+        let _ = rt.report_touched(new).unwrap_or_exit();
+    }
+
+    for id in report.rewritten.iter() {
+        info!("Rewrote links in {}", id);
+    }
+}
+
+fn sync_tasks(rt: &Runtime, wiki_name: &str) {
+    use libimagstore::iter::get::StoreIdGetIteratorExtension;
+
+    let scmd      = rt.cli().subcommand_matches("sync-tasks").unwrap(); // safed by clap
+    let processor = TaskProcessor::default().create_todos(!scmd.is_present("no-create"));
+
+    let wiki = rt
+            .store()
+            .get_wiki(&wiki_name)
+            .map_err_trace_exit_unwrap()
+            .unwrap_or_else(|| {
+                error!("No wiki '{}' found", wiki_name);
+                ::std::process::exit(1)
+            });
+
+    let entries = match scmd.values_of("name") {
+        Some(names) => names
+            .map(|name| {
+                wiki.get_entry(name)
+                    .map_err_trace_exit_unwrap()
+                    .unwrap_or_else(|| {
+                        error!("No wiki entry '{}' found in wiki '{}'", name, wiki_name);
+                        ::std::process::exit(1)
+                    })
+            })
+            .collect::<Vec<_>>(),
+
+        None => wiki
+            .all_ids()
+            .map_err_trace_exit_unwrap()
+            .into_get_iter(rt.store())
+            .trace_unwrap_exit()
+            .map(|opt| opt.unwrap_or_else(|| {
+                error!("Fatal: Nonexistent entry where entry should exist");
+                ::std::process::exit(1)
+            }))
+            .collect::<Vec<_>>(),
+    };
+
+    let out         = rt.stdout();
+    let mut outlock = out.lock();
+
+    let mut failed = false;
+
+    for (id, result) in processor.sync_entries(rt.store(), entries) {
+        match result {
+            Ok(report) => {
+                let _ = writeln!(outlock, "{}: {}", id, report)
+                    .to_exit_code()
+                    .unwrap_or_exit();
+
+                for todo in report.touched() {
+                    let _ = rt.report_touched(todo).unwrap_or_exit();
+                }
+
+                let _ = rt.report_touched(&id).unwrap_or_exit();
+            },
+
+            Err(e) => {
+                error!("Failed to sync the task list items of '{}'", id);
+                trace_error(&e);
+                failed = true;
+            },
+        }
+    }
+
+    if failed {
+        ::std::process::exit(1)
+    }
+}
@@ -142,9 +142,9 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                    .arg(Arg::with_name("show-name")
                         .index(1)
                         .takes_value(true)
-                        .required(true)
+                        .required(false)
                         .multiple(true)
-                        .help("Name of the entry/entries to show (if not passed, all are shown)."))
+                        .help("Name of the entry/entries to show (if not passed, they can be picked interactively)."))
                    )
 
 
@@ -154,10 +154,10 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                    .arg(Arg::with_name("delete-name")
                         .index(1)
                         .takes_value(true)
-                        .required(true)
+                        .required(false)
                         .multiple(false)
                         .value_name("NAME")
-                        .help("Delete the entry under this name. The name must be unique, namespaces ('foo/bar') are allowed."))
+                        .help("Delete the entry under this name. The name must be unique, namespaces ('foo/bar') are allowed. If not passed, the entry can be picked interactively."))
 
                    .arg(Arg::with_name("delete-no-remove-linkings")
                         .long("no-remove-links")
@@ -167,4 +167,43 @@ pub fn build_ui<'a>(app: App<'a, 'a>) -> App<'a, 'a> {
                         .help("Do not remote links. WARNING: This leaves the store in an inconsistent state."))
                    )
 
+        .subcommand(SubCommand::with_name("rename")
+                   .about("Rename wiki entry, rewriting the links to it in the linked entries")
+                   .version("0.1")
+                   .arg(Arg::with_name("rename-from")
+                        .index(1)
+                        .takes_value(true)
+                        .required(true)
+                        .multiple(false)
+                        .value_name("NAME")
+                        .help("Rename the entry under this name. Namespaces ('foo/bar') are allowed."))
+
+                   .arg(Arg::with_name("rename-to")
+                        .index(2)
+                        .takes_value(true)
+                        .required(true)
+                        .multiple(false)
+                        .value_name("NEWNAME")
+                        .help("The new name of the entry. Namespaces ('foo/bar') are allowed."))
+                   )
+
+        .subcommand(SubCommand::with_name("sync-tasks")
+                   .about("Create todo entries for the task list items (`- [ ] todo`) in wiki entries and sync their state")
+                   .version("0.1")
+                   .arg(Arg::with_name("no-create")
+                        .long("no-create")
+                        .takes_value(false)
+                        .required(false)
+                        .multiple(false)
+                        .help("Do not create todo entries, only sync the items which already have one"))
+
+                   .arg(Arg::with_name("name")
+                        .index(1)
+                        .takes_value(true)
+                        .required(false)
+                        .multiple(true)
+                        .value_name("NAME")
+                        .help("Sync the entries with these names. If none is given, all entries of the wiki are synced. Namespaces ('foo/bar') are allowed."))
+                   )
+
 }
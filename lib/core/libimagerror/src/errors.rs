@@ -17,6 +17,8 @@
 // Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
 //
 
+use kind::ErrorKind;
+
 #[derive(Debug, Clone, Eq, PartialEq, Fail)]
 pub enum ErrorMsg {
     #[fail(display = "IO Error")]
@@ -95,6 +97,9 @@ pub enum ErrorMsg {
     EntryHeaderFieldMissing(&'static str),
 
 
+    #[fail(display = "Configuration type error at '{}', expected '{}'", _0, _1)]
+    ConfigTypeError(&'static str, &'static str),
+
     #[fail(display = "Toml deserialization error")]
     TomlDeserError,
 
@@ -103,3 +108,37 @@ pub enum ErrorMsg {
 
 }
 
+impl ErrorMsg {
+
+    /// The kind of error this message describes, if it describes a specific one
+    pub fn kind(&self) -> Option<ErrorKind> {
+        match *self {
+            ErrorMsg::IO                         |
+            ErrorMsg::FileError                  |
+            ErrorMsg::FileNotCopied              |
+            ErrorMsg::FileNotCreated             |
+            ErrorMsg::FileNotRemoved             |
+            ErrorMsg::FileNotRenamed             |
+            ErrorMsg::FileNotSeeked              |
+            ErrorMsg::FileNotWritten             |
+            ErrorMsg::DirNotCreated              => Some(ErrorKind::IO),
+
+            ErrorMsg::FileNotFound               |
+            ErrorMsg::EntryNotFound(_)           => Some(ErrorKind::NotFound),
+
+            ErrorMsg::EntryAlreadyExists(_)      => Some(ErrorKind::AlreadyExists),
+            ErrorMsg::IdLocked                   => Some(ErrorKind::Borrowed),
+
+            ErrorMsg::UTF8Error                  |
+            ErrorMsg::EntryHeaderFieldMissing(_) |
+            ErrorMsg::EntryHeaderTypeError       |
+            ErrorMsg::EntryHeaderTypeError2(..)  |
+            ErrorMsg::TomlDeserError             => Some(ErrorKind::Parse),
+
+            ErrorMsg::ConfigTypeError(..)        => Some(ErrorKind::Config),
+
+            _ => None,
+        }
+    }
+
+}
@@ -17,6 +17,14 @@
 // Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
 //
 
+use failure::Error;
+
+use kind::ErrorKind;
+
+/// The code a process exits with
+///
+/// Errors are converted to an exit code by their kind, see `ErrorKind::exit_code()` for the
+/// mapping.
 pub struct ExitCode(i32);
 
 impl From<i32> for ExitCode {
@@ -25,6 +33,24 @@ impl From<i32> for ExitCode {
     }
 }
 
+impl From<ErrorKind> for ExitCode {
+    fn from(kind: ErrorKind) -> ExitCode {
+        ExitCode(kind.exit_code())
+    }
+}
+
+impl<'a> From<&'a Error> for ExitCode {
+    fn from(e: &'a Error) -> ExitCode {
+        ExitCode::from(ErrorKind::of(e))
+    }
+}
+
+impl From<Error> for ExitCode {
+    fn from(e: Error) -> ExitCode {
+        ExitCode::from(&e)
+    }
+}
+
 impl ExitCode {
     pub fn code(self) -> i32 {
         self.0
@@ -20,6 +20,7 @@
 use std::io::ErrorKind;
 
 use exit::ExitCode;
+use kind::io_error_kind;
 
 pub enum Settings {
     Ignore(ErrorKind),
@@ -33,7 +34,8 @@ pub trait ToExitCode<T> {
 
 impl<T> ToExitCode<T> for Result<T, ::std::io::Error> {
 
-    /// Returns an exit code of 0 if the error was a broken pipe, else 1
+    /// Returns an exit code of 0 if the error was a broken pipe, else the exit code for the kind
+    /// of the error
     fn to_exit_code(self) -> Result<T, ExitCode> {
         self.to_exit_code_with(Settings::Ignore(ErrorKind::BrokenPipe))
     }
@@ -41,19 +43,19 @@ impl<T> ToExitCode<T> for Result<T, ::std::io::Error> {
     /// Returns an exit code depending on the settings
     ///
     /// Via the settings, errors can be ignores (translates to exit code zero). All other errors
-    /// are translated into exit code 1
+    /// are translated into the exit code for their kind (see `ErrorKind::exit_code()`)
     ///
     fn to_exit_code_with(self, settings: Settings) -> Result<T, ExitCode> {
         self.map_err(move |e| match settings {
             Settings::Ignore(kind) => if e.kind() == kind {
                 0
             } else {
-                1
+                io_error_kind(&e).exit_code()
             },
             Settings::IgnoreAny(v) => if v.iter().any(|el| e.kind() == *el) {
                 0
             } else {
-                1
+                io_error_kind(&e).exit_code()
             },
         })
         .map_err(ExitCode::from)
@@ -0,0 +1,235 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2019 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+//! Typed error kinds
+//!
+//! Errors in imag are `failure::Error`s, which are mostly built from strings. To be able to react
+//! on certain classes of errors, and to exit with a meaningful exit code, an error can carry a
+//! `KindError`, either as the error itself or as a context. `ErrorKind::of()` finds the kind of
+//! an error by inspecting its chain of causes.
+//!
+
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::io::Error as IOError;
+use std::io::ErrorKind as IOErrorKind;
+
+use failure::Context;
+use failure::Error;
+use failure::Fail;
+
+use errors::ErrorMsg;
+
+/// The kind of an error
+///
+/// Each kind maps to a stable process exit code, see `ErrorKind::exit_code()`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum ErrorKind {
+    /// Something (an entry, a file, a configuration key) was not found
+    NotFound,
+
+    /// Something which should be created exists already
+    AlreadyExists,
+
+    /// An entry is borrowed and cannot be used at the moment
+    Borrowed,
+
+    /// Data could not be parsed
+    Parse,
+
+    /// The configuration is missing or invalid
+    Config,
+
+    /// Reading or writing failed
+    IO,
+
+    /// An operation was not permitted
+    Permission,
+
+    /// Any other error
+    Other,
+}
+
+impl ErrorKind {
+
+    /// The exit code of a process which fails with an error of this kind
+    ///
+    /// The codes are taken from `sysexits.h`:
+    ///
+    /// | Kind          | Exit code |
+    /// | ------------- | --------- |
+    /// | Other         | 1         |
+    /// | Parse         | 65        |
+    /// | NotFound      | 66        |
+    /// | AlreadyExists | 73        |
+    /// | IO            | 74        |
+    /// | Borrowed      | 75        |
+    /// | Permission    | 77        |
+    /// | Config        | 78        |
+    ///
+    pub fn exit_code(self) -> i32 {
+        match self {
+            ErrorKind::Other         => 1,
+            ErrorKind::Parse         => 65,
+            ErrorKind::NotFound      => 66,
+            ErrorKind::AlreadyExists => 73,
+            ErrorKind::IO            => 74,
+            ErrorKind::Borrowed      => 75,
+            ErrorKind::Permission    => 77,
+            ErrorKind::Config        => 78,
+        }
+    }
+
+    /// Find the kind of an error
+    ///
+    /// A `KindError` in the chain of causes of `e` wins over kinds derived from other errors
+    /// (`ErrorMsg`s and IO errors). In both cases, the innermost cause wins.
+    pub fn of(e: &Error) -> ErrorKind {
+        let chain = e.iter_chain().collect::<Vec<_>>();
+
+        chain.iter()
+            .rev()
+            .filter_map(|f| explicit_kind(*f))
+            .next()
+            .or_else(|| chain.iter().rev().filter_map(|f| derived_kind(*f)).next())
+            .unwrap_or(ErrorKind::Other)
+    }
+
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, fmt: &mut Formatter) -> FmtResult {
+        let s = match *self {
+            ErrorKind::NotFound      => "not found",
+            ErrorKind::AlreadyExists => "already exists",
+            ErrorKind::Borrowed      => "borrowed",
+            ErrorKind::Parse         => "parse error",
+            ErrorKind::Config        => "configuration error",
+            ErrorKind::IO            => "IO error",
+            ErrorKind::Permission    => "permission denied",
+            ErrorKind::Other         => "error",
+        };
+        write!(fmt, "{}", s)
+    }
+}
+
+/// An error with a kind
+#[derive(Debug, Clone, Eq, PartialEq, Fail)]
+#[fail(display = "{}", msg)]
+pub struct KindError {
+    kind: ErrorKind,
+    msg: String,
+}
+
+impl KindError {
+    pub fn new<S: Into<String>>(kind: ErrorKind, msg: S) -> KindError {
+        KindError { kind, msg: msg.into() }
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+}
+
+/// Shortcut for `Error::from(KindError::new(kind, msg))`
+pub fn kind_err<S: Into<String>>(kind: ErrorKind, msg: S) -> Error {
+    Error::from(KindError::new(kind, msg))
+}
+
+fn explicit_kind(f: &Fail) -> Option<ErrorKind> {
+    f.downcast_ref::<KindError>()
+        .map(KindError::kind)
+        .or_else(|| f.downcast_ref::<Context<KindError>>().map(|c| c.get_context().kind()))
+}
+
+fn derived_kind(f: &Fail) -> Option<ErrorKind> {
+    if let Some(e) = f.downcast_ref::<IOError>() {
+        return Some(io_error_kind(e))
+    }
+
+    f.downcast_ref::<ErrorMsg>()
+        .or_else(|| f.downcast_ref::<Context<ErrorMsg>>().map(Context::get_context))
+        .and_then(ErrorMsg::kind)
+}
+
+pub(crate) fn io_error_kind(e: &IOError) -> ErrorKind {
+    match e.kind() {
+        IOErrorKind::NotFound         => ErrorKind::NotFound,
+        IOErrorKind::AlreadyExists    => ErrorKind::AlreadyExists,
+        IOErrorKind::PermissionDenied => ErrorKind::Permission,
+        IOErrorKind::InvalidData      => ErrorKind::Parse,
+        _                             => ErrorKind::IO,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use failure::ResultExt;
+    use failure::err_msg;
+
+    #[test]
+    fn test_kind_of_plain_error_is_other() {
+        assert_eq!(ErrorKind::Other, ErrorKind::of(&err_msg("something")));
+    }
+
+    #[test]
+    fn test_kind_of_kind_error() {
+        let e = kind_err(ErrorKind::Borrowed, "Entry already borrowed: foo");
+        assert_eq!(ErrorKind::Borrowed, ErrorKind::of(&e));
+        assert_eq!("Entry already borrowed: foo", format!("{}", e));
+    }
+
+    #[test]
+    fn test_kind_of_context() {
+        let e = Err::<(), _>(err_msg("inner"))
+            .context(KindError::new(ErrorKind::Config, "outer"))
+            .map_err(Error::from)
+            .unwrap_err();
+
+        assert_eq!(ErrorKind::Config, ErrorKind::of(&e));
+    }
+
+    #[test]
+    fn test_explicit_kind_wins() {
+        let e = Err::<(), _>(kind_err(ErrorKind::AlreadyExists, "exists"))
+            .context(ErrorMsg::IO)
+            .map_err(Error::from)
+            .unwrap_err();
+
+        assert_eq!(ErrorKind::AlreadyExists, ErrorKind::of(&e));
+    }
+
+    #[test]
+    fn test_kind_of_io_error() {
+        let e = Err::<(), _>(IOError::new(IOErrorKind::NotFound, "nope"))
+            .context(ErrorMsg::FileError)
+            .map_err(Error::from)
+            .unwrap_err();
+
+        assert_eq!(ErrorKind::NotFound, ErrorKind::of(&e));
+    }
+
+    #[test]
+    fn test_kind_of_error_msg() {
+        let e = Error::from(ErrorMsg::EntryNotFound(String::from("foo")));
+        assert_eq!(ErrorKind::NotFound, ErrorKind::of(&e));
+    }
+}
@@ -44,6 +44,7 @@ pub mod errors;
 pub mod exit;
 pub mod io;
 pub mod iter;
+pub mod kind;
 pub mod str;
 pub mod trace;
 
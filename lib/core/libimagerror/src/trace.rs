@@ -24,6 +24,8 @@ use std::fmt::Result as FmtResult;
 use failure::Error;
 use ansi_term::Colour::Red;
 
+use exit::ExitCode;
+
 struct ImagTrace<'a, T: 'a + ?Sized>(&'a T);
 
 impl<'a, T: 'a + ?Sized> ImagTrace<'a, T> {
@@ -87,8 +89,10 @@ impl<U> MapErrTrace for Result<U, Error> {
     }
 
     /// Trace the error and exit or unwrap the Ok(_).
+    ///
+    /// The exit code is derived from the kind of the error, see `ErrorKind::exit_code()`.
     fn map_err_trace_exit_unwrap(self) -> Self::Output {
-        self.map_err(|e| { trace_error(&e); exit(1) }).unwrap()
+        self.map_err(|e| { trace_error(&e); exit(ExitCode::from(&e).code()) }).unwrap()
     }
 
 }
@@ -18,6 +18,11 @@
 //
 
 use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::collections::btree_map::Iter as BTreeMapIter;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
 
 use toml::Value;
 use clap::App;
@@ -28,25 +33,270 @@ use failure::err_msg;
 
 use libimagerror::errors::ErrorMsg as EM;
 
-/// Get a new configuration object.
+use profile::Profile;
+
+/// The prefix for environment variables which override configuration values
 ///
-/// The passed runtimepath is used for searching the configuration file, whereas several file
-/// names are tested. If that does not work, the home directory and the XDG basedir are tested
-/// with all variants.
-pub fn fetch_config(searchpath: &PathBuf) -> Result<Option<Value>> {
-    use std::env;
+/// The rest of the variable name is the key in the configuration, where a double underscore
+/// (`__`) separates the path segments. `IMAG_CONFIG_IMAG__LOGGING__LEVEL=debug` therefor
+/// overrides `imag.logging.level`.
+///
+/// Environment variables are usually uppercase, so the path segments are matched against the keys
+/// of the configuration case-insensitively: `IMAG_CONFIG_DIARY__DIARIES__MYDIARY__TIMED` overrides
+/// `diary.diaries.MyDiary.timed` if that key exists. Segments which do not exist in the
+/// configuration are lowercased.
+pub const ENV_OVERRIDE_PREFIX : &'static str = "IMAG_CONFIG_";
+
+/// The environment variable which can be used to point to an alternative system-wide
+/// configuration directory
+pub const ENV_SYSTEM_CONFIG_DIR : &'static str = "IMAG_SYSTEM_CONFIG_DIR";
+
+/// The name of the per-store configuration file, which lives in the root of the store
+pub const STORE_CONFIG_FILE_NAME : &'static str = ".imagrc.toml";
+
+/// The source a configuration value originates from
+///
+/// The variants are ordered by precedence, so a value from a `Store` configuration file overrides
+/// a value from a `User` configuration file, but gets itself overridden by `Profile`,
+/// `Environment` and `CommandLine` values.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ConfigSource {
+    System(PathBuf),
+    User(PathBuf),
+    Store(PathBuf),
+    Profile(String),
+    Environment(String),
+    CommandLine,
+}
+
+impl Display for ConfigSource {
+    fn fmt(&self, fmt: &mut Formatter) -> FmtResult {
+        match *self {
+            ConfigSource::System(ref p)      => write!(fmt, "system configuration '{}'", p.display()),
+            ConfigSource::User(ref p)        => write!(fmt, "user configuration '{}'", p.display()),
+            ConfigSource::Store(ref p)       => write!(fmt, "store configuration '{}'", p.display()),
+            ConfigSource::Profile(ref n)     => write!(fmt, "profile '{}'", n),
+            ConfigSource::Environment(ref v) => write!(fmt, "environment variable '{}'", v),
+            ConfigSource::CommandLine        => write!(fmt, "commandline override"),
+        }
+    }
+}
+
+/// Mapping from configuration keys to the source which set them
+///
+/// Only "leaf" values (everything which is not a table) are tracked. Arrays are considered to be
+/// leafs, as they are replaced completely when overridden by a higher layer.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Provenance(BTreeMap<String, ConfigSource>);
+
+impl Provenance {
+
+    /// Get the source which set the value at `key`
+    ///
+    /// If `key` points to a table, the source of the table itself is not known, as the values
+    /// inside the table may originate from several sources. `None` is returned in this case, use
+    /// `Provenance::sources_below()` for tables.
+    pub fn source_of(&self, key: &str) -> Option<&ConfigSource> {
+        self.0.get(key)
+    }
+
+    /// Get all keys (and their sources) which are located below `prefix`
+    pub fn sources_below<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = (&'a String, &'a ConfigSource)> {
+        self.0
+            .iter()
+            .filter(move |&(k, _)| is_below(k, prefix))
+    }
+
+    pub fn iter(&self) -> BTreeMapIter<String, ConfigSource> {
+        self.0.iter()
+    }
+
+    fn record_leaves(&mut self, value: &Value, key: String, source: &ConfigSource) {
+        match *value {
+            Value::Table(ref t) => for (k, v) in t.iter() {
+                self.record_leaves(v, join_key(&key, k), source)
+            },
+            _ => {
+                let _ = self.0.insert(key, source.clone());
+            },
+        }
+    }
+
+    fn forget(&mut self, key: &str) {
+        let _ = self.0.remove(key);
+        let below : Vec<String> = self.0
+            .keys()
+            .filter(|k| is_below(k, key))
+            .cloned()
+            .collect();
+
+        for k in below {
+            let _ = self.0.remove(&k);
+        }
+    }
+}
+
+/// A configuration which was assembled from several layers
+///
+/// See `fetch_layered_config()`.
+#[derive(Debug, Clone)]
+pub struct LayeredConfig {
+    value: Value,
+    provenance: Provenance,
+    storepath: PathBuf,
+    profile: Option<Profile>,
+}
+
+impl LayeredConfig {
+
+    pub fn new() -> LayeredConfig {
+        LayeredConfig {
+            value: Value::Table(BTreeMap::new()),
+            provenance: Provenance::default(),
+            storepath: PathBuf::new(),
+            profile: None,
+        }
+    }
+
+    /// Merge `layer` on top of the configuration
+    ///
+    /// Tables are merged recursively, all other values of `layer` replace the values already
+    /// present.
+    pub fn merge(&mut self, layer: Value, source: ConfigSource) {
+        merge_value(&mut self.value, layer, String::new(), &source, &mut self.provenance)
+    }
+
+    /// Override the value at `key` with the string `raw`, see `override_config()`
+    pub fn apply_override(&mut self, key: &str, raw: String, source: ConfigSource) -> Result<()> {
+        let _ = override_value(&mut self.value, key, raw)?;
+        self.provenance.forget(key);
+        let _ = self.provenance.0.insert(String::from(key), source);
+        Ok(())
+    }
+
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+
+    pub fn provenance(&self) -> &Provenance {
+        &self.provenance
+    }
+
+    /// The path of the store this configuration was assembled for
+    pub fn storepath(&self) -> &PathBuf {
+        &self.storepath
+    }
+
+    /// The profile which was used to assemble this configuration
+    pub fn profile(&self) -> Option<&Profile> {
+        self.profile.as_ref()
+    }
+
+    pub fn into_parts(self) -> (Value, Provenance) {
+        (self.value, self.provenance)
+    }
+}
+
+impl Default for LayeredConfig {
+    fn default() -> LayeredConfig {
+        LayeredConfig::new()
+    }
+}
+
+fn join_key(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        String::from(key)
+    } else {
+        format!("{}.{}", prefix, key)
+    }
+}
+
+fn is_below(key: &str, prefix: &str) -> bool {
+    prefix.is_empty() || (key.starts_with(prefix) && key[prefix.len()..].starts_with("."))
+}
+
+fn merge_value(base: &mut Value, layer: Value, key: String, source: &ConfigSource, prov: &mut Provenance) {
+    match (base, layer) {
+        (&mut Value::Table(ref mut base_tab), Value::Table(layer_tab)) => {
+            for (k, v) in layer_tab {
+                let subkey = join_key(&key, &k);
+                match base_tab.get_mut(&k) {
+                    Some(base_value) => {
+                        merge_value(base_value, v, subkey, source, prov);
+                        continue
+                    },
+                    None => prov.record_leaves(&v, subkey, source),
+                }
+                let _ = base_tab.insert(k, v);
+            }
+        },
+        (base, layer) => {
+            prov.forget(&key);
+            prov.record_leaves(&layer, key, source);
+            *base = layer;
+        },
+    }
+}
+
+/// The variants of file names which are tested when searching a configuration file in a directory
+fn config_file_variants() -> Vec<&'static str> {
+    vec!["config", "config.toml", "imagrc", "imagrc.toml"]
+}
+
+fn read_config_file(path: &PathBuf) -> Option<Value> {
     use std::fs::File;
     use std::io::Read;
     use std::io::Write;
     use std::io::stderr;
 
+    use libimagerror::trace::trace_error;
+
+    let content = {
+        let f = File::open(path);
+        if f.is_err() {
+            let _ = writeln!(stderr(), "Error opening file: {:?}", f);
+            return None
+        }
+        let mut f = f.unwrap();
+
+        let mut s = String::new();
+        f.read_to_string(&mut s).ok();
+        s
+    };
+
+    ::toml::de::from_str::<::toml::Value>(&content[..])
+        .map(Some)
+        .unwrap_or_else(|e| {
+            let line_col = e
+                .line_col()
+                .map(|(line, col)| format!("Line {}, Column {}", line, col))
+                .unwrap_or_else(|| String::from("Line unknown, Column unknown"));
+
+            let _ = writeln!(stderr(), "Config file parser error in {} at {}", path.display(), line_col);
+            let e = Error::from(EM::TomlDeserError);
+            trace_error(&e);
+            None
+        })
+}
+
+/// Find the first configuration file in `candidates` which exists and can be parsed
+fn first_config_file(candidates: Vec<PathBuf>) -> Option<(PathBuf, Value)> {
+    candidates
+        .into_iter()
+        .filter(|path| path.exists() && path.is_file())
+        .filter_map(|path| read_config_file(&path).map(|v| (path, v)))
+        .nth(0)
+}
+
+fn user_config_candidates(searchpath: &PathBuf) -> Vec<PathBuf> {
+    use std::env;
+
     use xdg_basedir;
     use itertools::Itertools;
 
     use libimagutil::variants::generate_variants as gen_vars;
-    use libimagerror::trace::trace_error;
 
-    let variants : Vec<&'static str> = vec!["config", "config.toml", "imagrc", "imagrc.toml"];
+    let variants = config_file_variants();
     let modifier = |base: &PathBuf, v: &&str| {
         let mut base = base.clone();
         base.push(String::from(*v));
@@ -66,39 +316,191 @@ pub fn fetch_config(searchpath: &PathBuf) -> Result<Option<Value>> {
             .unwrap_or(vec![]),
     ];
 
-    let config = Itertools::flatten(vals.iter())
-        .filter(|path| path.exists() && path.is_file())
-        .filter_map(|path| {
-            let content = {
-                let f = File::open(path);
-                if f.is_err() {
-                    let _ = write!(stderr(), "Error opening file: {:?}", f);
-                    return None
-                }
-                let mut f = f.unwrap();
+    Itertools::flatten(vals.into_iter()).collect()
+}
+
+fn system_config_candidates() -> Vec<PathBuf> {
+    use std::env;
+    use libimagutil::variants::generate_variants as gen_vars;
+
+    let base = env::var(ENV_SYSTEM_CONFIG_DIR)
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from("/etc/imag"));
+
+    gen_vars(&base, config_file_variants().iter(), &|base: &PathBuf, v: &&str| {
+        let mut base = base.clone();
+        base.push(String::from(*v));
+        base
+    })
+}
 
-                let mut s = String::new();
-                f.read_to_string(&mut s).ok();
-                s
+/// Get a new configuration object.
+///
+/// The passed runtimepath is used for searching the configuration file, whereas several file
+/// names are tested. If that does not work, the home directory and the XDG basedir are tested
+/// with all variants.
+pub fn fetch_config(searchpath: &PathBuf) -> Result<Option<Value>> {
+    Ok(first_config_file(user_config_candidates(searchpath)).map(|(_, v)| v))
+}
+
+/// Get the configuration assembled from all configuration layers
+///
+/// The layers are, with increasing precedence:
+///
+/// 1. The system configuration, searched in `/etc/imag` (or the directory set in
+///    `$IMAG_SYSTEM_CONFIG_DIR`)
+/// 1. The user configuration, searched like `fetch_config()` does
+/// 1. The store configuration, a `.imagrc.toml` file in the root of the store
+/// 1. The `config` table of the selected `profile`, if any (see `Profile`)
+/// 1. Environment variables, see `ENV_OVERRIDE_PREFIX`
+/// 1. The `overrides` passed, which are `key=value` strings from the commandline
+///
+/// Tables are merged, so that a layer only has to specify the values it wants to change. All other
+/// values (including arrays) of a higher layer replace the values of the lower layers.
+///
+/// The store is located at `storepath`, if passed. Otherwise, the store path of the profile is
+/// used and if there is none, `default_storepath` is used. The resulting path is available via
+/// `LayeredConfig::storepath()`.
+///
+/// Environment and commandline overrides can only override values which exist in one of the
+/// configuration files, see `override_config()`. Overrides which cannot be applied are reported
+/// and ignored.
+///
+/// # Return value
+///
+/// If no configuration file was found in any of the layers, `None` is returned.
+///
+pub fn fetch_layered_config(searchpath: &PathBuf,
+                            storepath: Option<PathBuf>,
+                            default_storepath: PathBuf,
+                            profile: Option<&str>,
+                            overrides: Vec<String>)
+    -> Result<Option<LayeredConfig>>
+{
+    use std::env;
+    use libimagerror::trace::trace_error;
+
+    let mut config = LayeredConfig::new();
+    let mut found_file = false;
+
+    let files = vec![
+        first_config_file(system_config_candidates()).map(|(p, v)| (ConfigSource::System(p), v)),
+        first_config_file(user_config_candidates(searchpath)).map(|(p, v)| (ConfigSource::User(p), v)),
+    ];
+
+    for (source, value) in files.into_iter().filter_map(|x| x) {
+        debug!("Merging configuration layer: {}", source);
+        config.merge(value, source);
+        found_file = true;
+    }
+
+    let profile = match profile {
+        Some(name) => Some(Profile::from_config(config.value(), name)
+            .context(format_err!("Cannot load profile '{}'", name))?),
+        None => None,
+    };
+
+    config.storepath = storepath
+        .or_else(|| profile.as_ref().and_then(|p| p.store().cloned()))
+        .unwrap_or(default_storepath);
+
+    if let Some((path, value)) = first_config_file(vec![config.storepath.join(STORE_CONFIG_FILE_NAME)]) {
+        let source = ConfigSource::Store(path);
+        debug!("Merging configuration layer: {}", source);
+        config.merge(value, source);
+        found_file = true;
+    }
+
+    if !found_file {
+        return Ok(None)
+    }
+
+    if let Some(ref profile) = profile {
+        let source = ConfigSource::Profile(profile.name().clone());
+        debug!("Merging configuration layer: {}", source);
+        config.merge(profile.config_layer(), source);
+    }
+    config.profile = profile;
+
+    let overrides = env_overrides(config.value(), env::vars())
+        .into_iter()
+        .map(|(var, key, raw)| (key, raw, ConfigSource::Environment(var)))
+        .chain({
+            split_overrides(overrides)
+                .into_iter()
+                .map(|(key, raw)| (key, raw, ConfigSource::CommandLine))
+        });
+
+    for (key, raw, source) in overrides {
+        debug!("Overriding '{}' from {}", key, source);
+        if let Err(e) = config.apply_override(&key, raw, source) {
+            error!("Could not apply config override for '{}'", key);
+            trace_error(&e);
+
+            // TODO: continue question (interactive)
+        }
+    }
+
+    Ok(Some(config))
+}
+
+/// Filter and translate the configuration overrides from an iterator over environment variables
+///
+/// Returns `(variable name, config key, raw value)` tuples, sorted by variable name so that the
+/// result does not depend on the order of the environment. The keys are resolved against `config`
+/// as described for `ENV_OVERRIDE_PREFIX`.
+fn env_overrides<I>(config: &Value, vars: I) -> Vec<(String, String, String)>
+    where I: Iterator<Item = (String, String)>
+{
+    let mut overrides : Vec<(String, String, String)> = vars
+        .filter(|&(ref k, _)| k.starts_with(ENV_OVERRIDE_PREFIX) && k.len() > ENV_OVERRIDE_PREFIX.len())
+        .map(|(var, raw)| {
+            let key = env_override_key(config, &var[ENV_OVERRIDE_PREFIX.len()..]);
+            (var, key, raw)
+        })
+        .collect();
+
+    overrides.sort_by(|a, b| a.0.cmp(&b.0));
+    overrides
+}
+
+/// Translate the `__`-separated rest of an override variable name to a key in `config`
+fn env_override_key(config: &Value, name: &str) -> String {
+    let mut current = Some(config);
+
+    name.split("__")
+        .map(|segment| {
+            let segment = segment.to_lowercase();
+            let existing = match current {
+                Some(&Value::Table(ref t)) => t.iter().find(|&(k, _)| k.to_lowercase() == segment),
+                _                          => None,
             };
 
-            ::toml::de::from_str::<::toml::Value>(&content[..])
-                .map(Some)
-                .unwrap_or_else(|e| {
-                    let line_col = e
-                        .line_col()
-                        .map(|(line, col)| format!("Line {}, Column {}", line, col))
-                        .unwrap_or_else(|| String::from("Line unknown, Column unknown"));
-
-                    let _ = write!(stderr(), "Config file parser error at {}", line_col);
-                    let e = Error::from(EM::TomlDeserError);
-                    trace_error(&e);
-                    None
-                })
+            match existing {
+                Some((k, v)) => {
+                    current = Some(v);
+                    k.clone()
+                },
+                None => {
+                    current = None;
+                    segment
+                },
+            }
         })
-        .nth(0);
+        .collect::<Vec<String>>()
+        .join(".")
+}
 
-    Ok(config)
+fn split_overrides(v: Vec<String>) -> Vec<(String, String)> {
+    use libimagutil::key_value_split::*;
+
+    v.into_iter()
+        .map(|s| { debug!("Trying to process '{}'", s); s })
+        .filter_map(|s| s.into_kv().map(Into::into).or_else(|| {
+            warn!("Could split at '=' - will be ignore override");
+            None
+        }))
+        .collect()
 }
 
 /// Override the configuration.
@@ -110,29 +512,26 @@ pub fn fetch_config(searchpath: &PathBuf) -> Result<Option<Value>> {
 ///
 /// If `v` is empty, this is considered to be a successful `override_config()` call.
 pub fn override_config(val: &mut Value, v: Vec<String>) -> Result<()> {
-    use libimagutil::key_value_split::*;
+    split_overrides(v)
+        .into_iter()
+        .map(|(k, v)| override_value(val, &k, v))
+        .map(|elem: Result<()>| elem.context(err_msg("Config override error")).map_err(Error::from))
+        .collect::<Result<()>>()
+}
+
+fn override_value(val: &mut Value, k: &str, v: String) -> Result<()> {
     use toml_query::read::TomlValueReadExt;
 
-    v.into_iter()
-        .map(|s| { debug!("Trying to process '{}'", s); s })
-        .filter_map(|s| s.into_kv().map(Into::into).or_else(|| {
-            warn!("Could split at '=' - will be ignore override");
-            None
-        }))
-        .map(|(k, v)| {
-            let value = val.read_mut(&k)
-                .context(EM::TomlQueryError)?
-                .ok_or_else(|| Error::from(err_msg("No config value there, cannot override.")))?;
+    let value = val.read_mut(k)
+        .context(EM::TomlQueryError)?
+        .ok_or_else(|| Error::from(err_msg("No config value there, cannot override.")))?;
 
-            let new_value = into_value(value, v)
-                .ok_or_else(|| Error::from(err_msg("Config override type not matching")))?;
+    let new_value = into_value(value, v)
+        .ok_or_else(|| Error::from(err_msg("Config override type not matching")))?;
 
-            info!("Successfully overridden: {} = {}", k, new_value);
-            *value = new_value;
-            Ok(())
-        })
-        .map(|elem: Result<()>| elem.context(err_msg("Config override error")).map_err(Error::from))
-        .collect::<Result<()>>()
+    info!("Successfully overridden: {} = {}", k, new_value);
+    *value = new_value;
+    Ok(())
 }
 
 /// Tries to convert the String `s` into the same type as `value`.
@@ -169,3 +568,143 @@ pub trait InternalConfiguration {
 
 impl<'a> InternalConfiguration for App<'a, 'a> {}
 
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use toml::Value;
+    use toml::de::from_str as toml_from_str;
+    use toml_query::read::TomlValueReadExt;
+
+    use super::*;
+
+    fn layered(layers: Vec<(&str, ConfigSource)>) -> LayeredConfig {
+        let mut config = LayeredConfig::new();
+        for (s, source) in layers {
+            config.merge(toml_from_str(s).unwrap(), source);
+        }
+        config
+    }
+
+    fn user() -> ConfigSource {
+        ConfigSource::User(PathBuf::from("/home/user/.imag/imagrc.toml"))
+    }
+
+    fn store() -> ConfigSource {
+        ConfigSource::Store(PathBuf::from("/home/user/.imag/store/.imagrc.toml"))
+    }
+
+    #[test]
+    fn test_merge_tables_recursively() {
+        let config = layered(vec![
+            (r#"
+             [rt]
+             editor = "vim"
+             [diary]
+             default_diary = "private"
+             "#, user()),
+            (r#"
+             [diary]
+             default_diary = "work"
+             "#, store()),
+        ]);
+
+        let v = config.value();
+        assert_eq!(v.read("rt.editor").unwrap(), Some(&Value::String(String::from("vim"))));
+        assert_eq!(v.read("diary.default_diary").unwrap(), Some(&Value::String(String::from("work"))));
+
+        assert_eq!(config.provenance().source_of("rt.editor"), Some(&user()));
+        assert_eq!(config.provenance().source_of("diary.default_diary"), Some(&store()));
+        assert_eq!(config.provenance().source_of("diary"), None);
+    }
+
+    #[test]
+    fn test_merge_replaces_arrays() {
+        let config = layered(vec![
+            (r#"
+             [imag.logging]
+             destinations = [ "-", "/tmp/log" ]
+             "#, user()),
+            (r#"
+             [imag.logging]
+             destinations = [ "-" ]
+             "#, store()),
+        ]);
+
+        let dests = config.value().read("imag.logging.destinations").unwrap().unwrap();
+        assert_eq!(dests.as_array().unwrap().len(), 1);
+        assert_eq!(config.provenance().source_of("imag.logging.destinations"), Some(&store()));
+    }
+
+    #[test]
+    fn test_merge_table_replaces_value() {
+        let config = layered(vec![
+            (r#"
+             [a]
+             b = 1
+             "#, user()),
+            (r#"
+             [a.b]
+             c = 2
+             "#, store()),
+        ]);
+
+        assert_eq!(config.provenance().source_of("a.b"), None);
+        assert_eq!(config.provenance().source_of("a.b.c"), Some(&store()));
+        assert_eq!(config.provenance().sources_below("a").count(), 1);
+    }
+
+    #[test]
+    fn test_override_records_provenance() {
+        let mut config = layered(vec![
+            (r#"
+             [imag.logging]
+             level = "debug"
+             "#, user()),
+        ]);
+
+        let src = ConfigSource::Environment(String::from("IMAG_CONFIG_IMAG__LOGGING__LEVEL"));
+        config.apply_override("imag.logging.level", String::from("warn"), src.clone()).unwrap();
+
+        let level = config.value().read("imag.logging.level").unwrap();
+        assert_eq!(level, Some(&Value::String(String::from("warn"))));
+        assert_eq!(config.provenance().source_of("imag.logging.level"), Some(&src));
+    }
+
+    #[test]
+    fn test_override_nonexistent_key_fails() {
+        let mut config = layered(vec![("[a]\nb = 1", user())]);
+        let res = config.apply_override("a.c", String::from("2"), ConfigSource::CommandLine);
+        assert!(res.is_err());
+        assert_eq!(config.provenance().source_of("a.c"), None);
+    }
+
+    #[test]
+    fn test_env_overrides_translation() {
+        let vars = vec![
+            (String::from("IMAG_RTP"), String::from("/tmp")),
+            (String::from("IMAG_CONFIG_RT__EDITOR"), String::from("nano")),
+            (String::from("IMAG_CONFIG_IMAG__LOGGING__LEVEL"), String::from("warn")),
+            (String::from("IMAG_CONFIG_"), String::from("ignored")),
+        ];
+
+        let overrides = env_overrides(&Value::Table(Default::default()), vars.into_iter());
+        assert_eq!(overrides.len(), 2);
+        assert_eq!(overrides[0].1, "imag.logging.level");
+        assert_eq!(overrides[1].1, "rt.editor");
+    }
+
+    #[test]
+    fn test_env_overrides_keep_case_of_existing_keys() {
+        let config = toml_from_str::<Value>("[diary.diaries.MyDiary]\ntimed = \"hourly\"\n").unwrap();
+        let vars = vec![
+            (String::from("IMAG_CONFIG_DIARY__DIARIES__MYDIARY__TIMED"), String::from("minutely")),
+            (String::from("IMAG_CONFIG_DIARY__DIARIES__OTHER__TIMED"), String::from("daily")),
+        ];
+
+        let overrides = env_overrides(&config, vars.into_iter());
+        assert_eq!(overrides[0].1, "diary.diaries.MyDiary.timed");
+        assert_eq!(overrides[1].1, "diary.diaries.other.timed");
+    }
+}
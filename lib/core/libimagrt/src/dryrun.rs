@@ -0,0 +1,103 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2019 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+
+//! Reporting the changes of a dry run
+//!
+//! If `--dry-run` is passed, the store is opened with `Store::new_dry_run()`, so nothing is
+//! written to disk. When the runtime is dropped, the changes which would have been made are printed
+//! to stderr.
+
+use std::io::Write;
+
+use failure::Fallible as Result;
+use failure::ResultExt;
+
+use libimagerror::errors::ErrorMsg as EM;
+use libimagerror::trace::trace_error;
+use libimagstore::dryrun::Change;
+use libimagstore::dryrun::DiffLine;
+use libimagstore::dryrun::DryRun;
+
+/// Prints the changes of the dry run when dropped
+#[derive(Debug)]
+pub(crate) struct DryRunReporter(DryRun);
+
+impl DryRunReporter {
+
+    pub(crate) fn new(dry_run: DryRun) -> DryRunReporter {
+        DryRunReporter(dry_run)
+    }
+
+    fn report<W: Write>(&self, out: &mut W) -> Result<()> {
+        let changes = self.0.changes()?;
+
+        if changes.is_empty() {
+            return writeln!(out, "Dry run: No changes").context(EM::IO).map_err(From::from)
+        }
+
+        writeln!(out, "Dry run: {} changes, nothing was written", changes.len()).context(EM::IO)?;
+
+        for change in changes.iter() {
+            match *change {
+                Change::Created  { ref id, .. }           => writeln!(out, "\nCreated:  {}", id),
+                Change::Modified { ref id, .. }           => writeln!(out, "\nModified: {}", id),
+                Change::Moved    { ref from, ref to, .. } => writeln!(out, "\nMoved:    {} -> {}", from, to),
+                Change::Deleted  { ref id, .. }           => writeln!(out, "\nDeleted:  {}", id),
+            }.context(EM::IO)?;
+
+            write_diff(out, "Header", change.header_diff())?;
+            write_diff(out, "Content", change.content_diff())?;
+        }
+
+        Ok(())
+    }
+
+}
+
+impl Drop for DryRunReporter {
+    fn drop(&mut self) {
+        let err = ::std::io::stderr();
+        if let Err(e) = self.report(&mut err.lock()) {
+            trace_error(&e);
+        }
+    }
+}
+
+fn write_diff<W: Write>(out: &mut W, name: &str, diff: Vec<DiffLine>) -> Result<()> {
+    let is_changed = |l: &DiffLine| match *l {
+        DiffLine::Unchanged(_) => false,
+        _                      => true,
+    };
+
+    if !diff.iter().any(is_changed) {
+        return Ok(())
+    }
+
+    writeln!(out, "  {}:", name).context(EM::IO)?;
+    for line in diff {
+        match line {
+            DiffLine::Unchanged(l) => writeln!(out, "      {}", l),
+            DiffLine::Removed(l)   => writeln!(out, "    - {}", l),
+            DiffLine::Added(l)     => writeln!(out, "    + {}", l),
+        }.context(EM::IO)?;
+    }
+
+    Ok(())
+}
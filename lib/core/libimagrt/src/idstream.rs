@@ -0,0 +1,286 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2019 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+
+//! The protocol which is used to pass ids between imag commands
+//!
+//! If the output of an imag command is piped, the command prints the ids of the entries it
+//! touched. In the simplest form, this is one id per line:
+//!
+//! ```text
+//! diary/private/2019/01/01/12:00:00
+//! notes/shopping
+//! ```
+//!
+//! The structured form of the protocol starts with a header line containing the version of the
+//! protocol. Each following line contains an id, optionally followed by a tab character and a JSON
+//! object with attributes of the id:
+//!
+//! ```text
+//! #imag-id-stream v1
+//! notes/shopping	{"producer":"imag-grep","matches":[[0,4]]}
+//! notes/todo
+//! ```
+//!
+//! Readers accept both forms. Without the header line, each line is a plain id.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use failure::Fallible as Result;
+use failure::ResultExt;
+use failure::Error;
+use serde_json;
+use toml::Value;
+
+use libimagstore::storeid::StoreId;
+
+/// The prefix of the header line of the structured protocol
+pub const ID_STREAM_HEADER_PREFIX : &'static str = "#imag-id-stream v";
+
+/// The version of the structured protocol this implementation writes and can read
+pub const ID_STREAM_VERSION : u64 = 1;
+
+/// The format in which ids are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdStreamFormat {
+    /// One plain id per line
+    Plain,
+
+    /// The structured, versioned protocol
+    Structured,
+}
+
+impl FromStr for IdStreamFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<IdStreamFormat> {
+        match s {
+            "plain"      => Ok(IdStreamFormat::Plain),
+            "structured" => Ok(IdStreamFormat::Structured),
+            other        => Err(format_err!("Unknown id stream format: {}", other)),
+        }
+    }
+}
+
+/// Attributes attached to an id in the structured protocol
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct IdAttributes {
+    /// The command which produced the id
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub producer: Option<String>,
+
+    /// Header values, by their path in the header
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub header: BTreeMap<String, Value>,
+
+    /// Byte offsets (start, end) of matches in the content of the entry
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub matches: Vec<(usize, usize)>,
+}
+
+impl IdAttributes {
+
+    pub fn is_empty(&self) -> bool {
+        self.producer.is_none() && self.header.is_empty() && self.matches.is_empty()
+    }
+
+    /// Merge `other` into `self`, values from `other` win
+    pub fn merge(&mut self, other: IdAttributes) {
+        if other.producer.is_some() {
+            self.producer = other.producer;
+        }
+        self.header.extend(other.header);
+        if !other.matches.is_empty() {
+            self.matches = other.matches;
+        }
+    }
+
+}
+
+/// An id with its attributes
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdStreamItem {
+    id: StoreId,
+    attributes: IdAttributes,
+}
+
+impl IdStreamItem {
+
+    pub fn new(id: StoreId, attributes: IdAttributes) -> IdStreamItem {
+        IdStreamItem { id, attributes }
+    }
+
+    pub fn id(&self) -> &StoreId {
+        &self.id
+    }
+
+    pub fn attributes(&self) -> &IdAttributes {
+        &self.attributes
+    }
+
+    pub fn into_parts(self) -> (StoreId, IdAttributes) {
+        (self.id, self.attributes)
+    }
+
+}
+
+/// Get the header line for the structured protocol
+pub fn header_line() -> String {
+    format!("{}{}", ID_STREAM_HEADER_PREFIX, ID_STREAM_VERSION)
+}
+
+/// Format one line of the structured protocol
+pub fn format_item(id: &StoreId, attributes: &IdAttributes) -> Result<String> {
+    if attributes.is_empty() {
+        Ok(format!("{}", id))
+    } else {
+        let json = serde_json::to_string(attributes).context(err_msg_id_stream())?;
+        Ok(format!("{}\t{}", id, json))
+    }
+}
+
+/// Parse the ids from `buf`
+///
+/// Returns the items and the format the buffer was written in.
+pub fn parse(buf: &str) -> Result<(IdStreamFormat, Vec<IdStreamItem>)> {
+    let mut lines = buf.lines().filter(|l| !l.trim().is_empty()).peekable();
+
+    let format = match lines.peek() {
+        Some(first) if first.starts_with(ID_STREAM_HEADER_PREFIX) => {
+            let version = u64::from_str(&first[ID_STREAM_HEADER_PREFIX.len()..].trim())
+                .context(format_err!("Invalid id stream header: {}", first))?;
+
+            if version > ID_STREAM_VERSION {
+                return Err(format_err!("Unsupported id stream version {}, supported: {}",
+                                       version, ID_STREAM_VERSION))
+            }
+
+            IdStreamFormat::Structured
+        },
+        _ => IdStreamFormat::Plain,
+    };
+
+    let items = lines
+        .filter(|l| format == IdStreamFormat::Plain || !l.starts_with("#"))
+        .map(|line| match format {
+            IdStreamFormat::Plain      => parse_plain_line(line),
+            IdStreamFormat::Structured => parse_structured_line(line),
+        })
+        .collect::<Result<Vec<IdStreamItem>>>()?;
+
+    Ok((format, items))
+}
+
+fn parse_plain_line(line: &str) -> Result<IdStreamItem> {
+    StoreId::new(PathBuf::from(line)).map(|id| IdStreamItem::new(id, IdAttributes::default()))
+}
+
+fn parse_structured_line(line: &str) -> Result<IdStreamItem> {
+    let mut split = line.splitn(2, '\t');
+    let id = split.next().unwrap_or(""); // splitn() yields at least one element
+    let id = StoreId::new(PathBuf::from(id))?;
+
+    let attributes = match split.next() {
+        Some(json) => serde_json::from_str(json)
+            .context(format_err!("Invalid attributes for '{}'", id))
+            .context(err_msg_id_stream())?,
+        None => IdAttributes::default(),
+    };
+
+    Ok(IdStreamItem::new(id, attributes))
+}
+
+fn err_msg_id_stream() -> Error {
+    format_err!("Id stream error")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use toml::Value;
+
+    use libimagstore::storeid::StoreId;
+
+    use super::*;
+
+    fn id(s: &str) -> StoreId {
+        StoreId::new(PathBuf::from(s)).unwrap()
+    }
+
+    #[test]
+    fn test_parse_plain() {
+        let (format, items) = parse("a/b\nc\n\n").unwrap();
+        assert_eq!(format, IdStreamFormat::Plain);
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].id(), &id("a/b"));
+        assert!(items[1].attributes().is_empty());
+    }
+
+    #[test]
+    fn test_parse_structured() {
+        let buf = "#imag-id-stream v1\na/b\t{\"producer\":\"imag-grep\",\"matches\":[[0,4]]}\nc\n";
+        let (format, items) = parse(buf).unwrap();
+
+        assert_eq!(format, IdStreamFormat::Structured);
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].attributes().producer, Some(String::from("imag-grep")));
+        assert_eq!(items[0].attributes().matches, vec![(0, 4)]);
+        assert!(items[1].attributes().is_empty());
+    }
+
+    #[test]
+    fn test_parse_unsupported_version() {
+        assert!(parse("#imag-id-stream v99\na").is_err());
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let mut attrs = IdAttributes::default();
+        attrs.producer = Some(String::from("imag-ids"));
+        let _ = attrs.header.insert(String::from("todo.status"), Value::String(String::from("open")));
+
+        let buf = format!("{}\n{}\n{}\n",
+                          header_line(),
+                          format_item(&id("todo/1"), &attrs).unwrap(),
+                          format_item(&id("todo/2"), &IdAttributes::default()).unwrap());
+
+        let (_, items) = parse(&buf).unwrap();
+        assert_eq!(items, vec![
+            IdStreamItem::new(id("todo/1"), attrs),
+            IdStreamItem::new(id("todo/2"), IdAttributes::default()),
+        ]);
+    }
+
+    #[test]
+    fn test_merge_attributes() {
+        let mut a = IdAttributes::default();
+        a.producer = Some(String::from("imag-grep"));
+        a.matches  = vec![(1, 2)];
+
+        let mut b = IdAttributes::default();
+        b.producer = Some(String::from("imag-tag"));
+
+        a.merge(b);
+        assert_eq!(a.producer, Some(String::from("imag-tag")));
+        assert_eq!(a.matches, vec![(1, 2)]);
+    }
+}
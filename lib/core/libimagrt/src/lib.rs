@@ -49,6 +49,11 @@ extern crate clap;
 extern crate toml;
 extern crate toml_query;
 extern crate atty;
+extern crate serde;
+#[macro_use] extern crate serde_derive;
+extern crate serde_json;
+extern crate chrono;
+#[cfg(test)] extern crate tempdir;
 
 extern crate libimagstore;
 extern crate libimagutil;
@@ -56,10 +61,15 @@ extern crate libimagerror;
 extern crate libimaginteraction;
 
 pub mod configuration;
+mod dryrun;
+pub mod idstream;
 pub mod logger;
+pub mod profile;
+pub mod resolver;
 pub mod io;
 pub mod runtime;
 pub mod setup;
 pub mod spec;
+pub mod undo;
 pub mod version;
 
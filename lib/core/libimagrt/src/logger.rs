@@ -23,6 +23,11 @@ use std::collections::BTreeMap;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::ops::Deref;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::path::PathBuf;
+#[cfg(unix)]
+use std::os::unix::net::UnixDatagram;
 
 use runtime::Runtime;
 
@@ -36,15 +41,25 @@ use toml::Value;
 use toml_query::read::TomlValueReadExt;
 use toml_query::read::TomlValueReadTypeExt;
 use handlebars::Handlebars;
+use chrono::DateTime;
+use chrono::Local;
+use serde_json;
 
 use libimagerror::errors::ErrorMsg as EM;
 
 type ModuleName = String;
 
+/// The path of the local syslog socket, which is also served by journald
+#[cfg(unix)]
+const SYSLOG_SOCKET : &'static str = "/dev/log";
+
 #[derive(Debug)]
 enum LogDestination {
     Stderr,
-    File(Arc<Mutex<::std::fs::File>>),
+    File(Arc<Mutex<LogFile>>),
+
+    #[cfg(unix)]
+    Syslog(UnixDatagram),
 }
 
 impl Default for LogDestination {
@@ -53,6 +68,118 @@ impl Default for LogDestination {
     }
 }
 
+/// When files are rotated, configured in `imag.logging.rotation`
+#[derive(Debug, Clone)]
+struct Rotation {
+    max_size: Option<u64>,
+    interval: Option<Interval>,
+    keep: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Interval {
+    Hourly,
+    Daily,
+    Weekly,
+}
+
+impl Interval {
+    fn period(&self, t: &DateTime<Local>) -> String {
+        match *self {
+            Interval::Hourly => t.format("%Y-%m-%d %H").to_string(),
+            Interval::Daily  => t.format("%Y-%m-%d").to_string(),
+            Interval::Weekly => t.format("%G-%V").to_string(),
+        }
+    }
+}
+
+/// A log file, which is rotated if a rotation is configured
+#[derive(Debug)]
+struct LogFile {
+    path: PathBuf,
+    file: File,
+    rotation: Option<Rotation>,
+
+    /// The period of the interval in which the file was started
+    period: Option<String>,
+}
+
+impl LogFile {
+
+    fn open(path: PathBuf, rotation: Option<Rotation>) -> ::std::io::Result<LogFile> {
+        let file   = OpenOptions::new().append(true).create(true).open(&path)?;
+        let period = match rotation.as_ref().and_then(|r| r.interval) {
+            Some(interval) => {
+                let modified = file.metadata()?.modified()?;
+                Some(interval.period(&DateTime::<Local>::from(modified)))
+            },
+            None => None,
+        };
+
+        Ok(LogFile { path, file, rotation, period })
+    }
+
+    fn write_line(&mut self, line: &str) -> ::std::io::Result<()> {
+        if self.needs_rotation(line.len() as u64 + 1)? {
+            self.rotate()?;
+        }
+
+        writeln!(self.file, "{}", line)
+    }
+
+    fn needs_rotation(&self, additional: u64) -> ::std::io::Result<bool> {
+        let rotation = match self.rotation {
+            Some(ref r) => r,
+            None        => return Ok(false),
+        };
+
+        if let Some(max) = rotation.max_size {
+            let size = self.file.metadata()?.len();
+            if size > 0 && size + additional > max {
+                return Ok(true)
+            }
+        }
+
+        Ok(match (rotation.interval, self.period.as_ref()) {
+            (Some(interval), Some(period)) => &interval.period(&Local::now()) != period,
+            _ => false,
+        })
+    }
+
+    /// Rotate the files: `<path>` becomes `<path>.1`, `<path>.1` becomes `<path>.2` and so on.
+    /// The oldest file is removed.
+    fn rotate(&mut self) -> ::std::io::Result<()> {
+        let keep     = self.rotation.as_ref().map(|r| r.keep).unwrap_or(0);
+        let path     = self.path.clone();
+        let numbered = |n: usize| PathBuf::from(format!("{}.{}", path.display(), n));
+
+        if keep == 0 {
+            ::std::fs::remove_file(&path)?;
+        } else {
+            if numbered(keep).exists() {
+                ::std::fs::remove_file(numbered(keep))?;
+            }
+
+            for n in (1..keep).rev() {
+                if numbered(n).exists() {
+                    ::std::fs::rename(numbered(n), numbered(n + 1))?;
+                }
+            }
+
+            ::std::fs::rename(&path, numbered(1))?;
+        }
+
+        let new = LogFile::open(path.clone(), self.rotation.clone())?;
+        self.file   = new.file;
+        self.period = self.rotation
+            .as_ref()
+            .and_then(|r| r.interval)
+            .map(|i| i.period(&Local::now()));
+        Ok(())
+    }
+
+}
+
 #[derive(Debug)]
 struct ModuleSettings {
     enabled:        bool,
@@ -77,6 +204,15 @@ pub struct ImagLogger {
     module_settings     : BTreeMap<ModuleName, ModuleSettings>,
 
     handlebars: Handlebars,
+
+    /// Whether log lines are JSON objects rather than rendered templates
+    json: bool,
+
+    /// The name of the running command
+    command: String,
+
+    /// The path of the store, if known
+    store: Option<PathBuf>,
 }
 
 impl ImagLogger {
@@ -90,7 +226,16 @@ impl ImagLogger {
         ::libimaginteraction::format::register_all_color_helpers(&mut handlebars);
         ::libimaginteraction::format::register_all_format_helpers(&mut handlebars);
 
-        {
+        let json = match config {
+            Some(cfg) => cfg
+                .read_bool("imag.logging.json")
+                .map_err(Error::from)
+                .context(EM::TomlQueryError)?
+                .unwrap_or(false),
+            None => false,
+        };
+
+        if !json {
             use self::log_lvl_aggregate::*;
             let _ = aggregate::<Trace>(&mut handlebars, config, "TRACE")?;
             let _ = aggregate::<Debug>(&mut handlebars, config, "DEBUG")?;
@@ -99,14 +244,63 @@ impl ImagLogger {
             let _ = aggregate::<Error>(&mut handlebars, config, "ERROR")?;
         }
 
+        let command = ::std::env::args()
+            .next()
+            .and_then(|a| PathBuf::from(a).file_name().and_then(|n| n.to_str()).map(String::from))
+            .unwrap_or_else(|| String::from("imag"));
+
+        let rotation = aggregate_rotation(config)?;
+
         Ok(ImagLogger {
             global_loglevel     : aggregate_global_loglevel(matches, config)?,
-            global_destinations : aggregate_global_destinations(config)?,
-            module_settings     : aggregate_module_settings(matches, config)?,
+            global_destinations : aggregate_global_destinations(config, rotation.as_ref())?,
+            module_settings     : aggregate_module_settings(matches, config, rotation.as_ref())?,
             handlebars          : handlebars,
+            json                : json,
+            command             : command,
+            store               : None,
         })
     }
 
+    /// Set the store path which is added to JSON log lines
+    pub fn with_store_path(mut self, store: PathBuf) -> ImagLogger {
+        self.store = Some(store);
+        self
+    }
+
+    fn render(&self, record: &Record) -> String {
+        if self.json {
+            let mut data = BTreeMap::new();
+            data.insert("time",        serde_json::Value::from(Local::now().to_rfc3339()));
+            data.insert("level",       serde_json::Value::from(format!("{}", record.level())));
+            data.insert("module_path", serde_json::Value::from(record.module_path()));
+            data.insert("file",        serde_json::Value::from(record.file()));
+            data.insert("line",        serde_json::Value::from(record.line()));
+            data.insert("target",      serde_json::Value::from(record.target()));
+            data.insert("message",     serde_json::Value::from(format!("{}", record.args())));
+            data.insert("command",     serde_json::Value::from(self.command.clone()));
+            data.insert("store",       serde_json::Value::from(self.store.as_ref().map(|p| p.display().to_string())));
+
+            serde_json::to_string(&data)
+                .unwrap_or_else(|e| format!("Failed rendering logging data: {:?}", e))
+        } else {
+            let mut data = BTreeMap::new();
+
+            {
+                data.insert("level",        format!("{}", record.level()));
+                data.insert("module_path",  String::from(record.module_path().unwrap_or("<modulepath unknown>")));
+                data.insert("file",         String::from(record.file().unwrap_or("<file unknown>")));
+                data.insert("line",         format!("{}", record.line().unwrap_or(0)));
+                data.insert("target",       String::from(record.target()));
+                data.insert("message",      format!("{}", record.args()));
+            }
+
+            self.handlebars
+                .render(&format!("{}", record.level()), &data)
+                .unwrap_or_else(|e| format!("Failed rendering logging data: {:?}\n", e))
+        }
+    }
+
     pub fn global_loglevel(&self) -> Level {
         self.global_loglevel
     }
@@ -124,21 +318,7 @@ impl Log for ImagLogger {
     }
 
     fn log(&self, record: &Record) {
-        let mut data = BTreeMap::new();
-
-        {
-            data.insert("level",        format!("{}", record.level()));
-            data.insert("module_path",  String::from(record.module_path().unwrap_or("<modulepath unknown>")));
-            data.insert("file",         String::from(record.file().unwrap_or("<file unknown>")));
-            data.insert("line",         format!("{}", record.line().unwrap_or(0)));
-            data.insert("target",       String::from(record.target()));
-            data.insert("message",      format!("{}", record.args()));
-        }
-
-        let logtext = self
-            .handlebars
-            .render(&format!("{}", record.level()), &data)
-            .unwrap_or_else(|e| format!("Failed rendering logging data: {:?}\n", e));
+        let logtext = self.render(record);
 
         let log_to_destination = |d: &LogDestination| match d {
             &LogDestination::Stderr => {
@@ -149,10 +329,17 @@ impl Log for ImagLogger {
                 let _ = arc_mutex_logdest
                     .deref()
                     .lock()
-                    .map(|mut logdest| {
-                        write!(logdest, "{}\n", logtext)
-                    });
-            }
+                    .map(|mut logdest| logdest.write_line(&logtext));
+            },
+            #[cfg(unix)]
+            &LogDestination::Syslog(ref socket) => {
+                let msg = format!("<{}>{}[{}]: {}",
+                                  syslog_priority(record.level()),
+                                  self.command,
+                                  ::std::process::id(),
+                                  logtext);
+                let _ = socket.send(msg.as_bytes());
+            },
         };
 
         // hack to get the right target configuration.
@@ -243,16 +430,45 @@ fn aggregate_global_loglevel(matches: &ArgMatches, config: Option<&Value>) -> Re
     }
 }
 
-fn translate_destination(raw: &str) -> Result<LogDestination> {
-    use std::fs::OpenOptions;
+/// The syslog priority for messages of `level`, with the facility "user"
+#[cfg(unix)]
+fn syslog_priority(level: Level) -> u8 {
+    let severity = match level {
+        Level::Error => 3,
+        Level::Warn  => 4,
+        Level::Info  => 6,
+        Level::Debug => 7,
+        Level::Trace => 7,
+    };
+
+    8 + severity
+}
+
+#[cfg(unix)]
+fn syslog_destination(path: &str) -> Result<LogDestination> {
+    UnixDatagram::unbound()
+        .and_then(|socket| socket.connect(path).map(|_| socket))
+        .map(LogDestination::Syslog)
+        .context(format!("Cannot connect to syslog socket: {}", path))
+        .map_err(Error::from)
+}
+
+#[cfg(not(unix))]
+fn syslog_destination(_: &str) -> Result<LogDestination> {
+    Err(err_msg("Logging to syslog is only supported on unix systems"))
+}
 
+fn translate_destination(raw: &str, rotation: Option<&Rotation>) -> Result<LogDestination> {
     match raw {
         "-" => Ok(LogDestination::Stderr),
-        other => {
-            OpenOptions::new()
-                .append(true)
-                .create(true)
-                .open(other)
+        #[cfg(unix)]
+        "syslog" => syslog_destination(SYSLOG_SOCKET),
+        #[cfg(not(unix))]
+        "syslog" => syslog_destination(""),
+        other => if other.starts_with("syslog:") {
+            syslog_destination(&other["syslog:".len()..])
+        } else {
+            LogFile::open(PathBuf::from(other), rotation.cloned())
                 .map(Mutex::new)
                 .map(Arc::new)
                 .map(LogDestination::File)
@@ -264,19 +480,60 @@ fn translate_destination(raw: &str) -> Result<LogDestination> {
 }
 
 
-fn translate_destinations(raw: &Vec<Value>) -> Result<Vec<LogDestination>> {
+fn translate_destinations(raw: &Vec<Value>, rotation: Option<&Rotation>) -> Result<Vec<LogDestination>> {
     raw.iter()
         .map(|val| {
             val.as_str()
                 .ok_or_else(|| "Type error at 'imag.logging.modules.<mod>.destinations', expected Array<String>")
                 .map_err(err_msg)
                 .map_err(Error::from)
-                .and_then(|s| translate_destination(s))
+                .and_then(|s| translate_destination(s, rotation))
         })
         .collect()
 }
 
-fn aggregate_global_destinations(config: Option<&Value>)
+fn aggregate_rotation(config: Option<&Value>) -> Result<Option<Rotation>> {
+    let cfg = match config {
+        Some(cfg) => cfg,
+        None      => return Ok(None),
+    };
+
+    let rotation = match cfg.read("imag.logging.rotation").map_err(Error::from).context(EM::TomlQueryError)? {
+        Some(r) => r,
+        None    => return Ok(None),
+    };
+
+    let max_size = rotation
+        .read_int("max_size")
+        .map_err(Error::from)
+        .context(EM::TomlQueryError)?
+        .map(|i| i as u64);
+
+    let interval = match rotation
+        .read_string("interval")
+        .map_err(Error::from)
+        .context(EM::TomlQueryError)?
+    {
+        None => None,
+        Some(s) => Some(match s.as_ref() {
+            "hourly" => Interval::Hourly,
+            "daily"  => Interval::Daily,
+            "weekly" => Interval::Weekly,
+            other    => return Err(format_err!("Invalid rotation interval at 'imag.logging.rotation.interval': {}", other)),
+        }),
+    };
+
+    let keep = rotation
+        .read_int("keep")
+        .map_err(Error::from)
+        .context(EM::TomlQueryError)?
+        .map(|i| i as usize)
+        .unwrap_or(5);
+
+    Ok(Some(Rotation { max_size, interval, keep }))
+}
+
+fn aggregate_global_destinations(config: Option<&Value>, rotation: Option<&Rotation>)
     -> Result<Vec<LogDestination>>
 {
     match config {
@@ -291,7 +548,7 @@ fn aggregate_global_destinations(config: Option<&Value>)
                 let msg = "Type error at 'imag.logging.destinations', expected 'Array'";
                 Error::from(err_msg(msg))
             })
-            .and_then(translate_destinations),
+            .and_then(|dests| translate_destinations(dests, rotation)),
     }
 }
 
@@ -346,7 +603,9 @@ mod log_lvl_aggregate {
 
 }
 
-fn aggregate_module_settings(_matches: &ArgMatches, config: Option<&Value>)
+fn aggregate_module_settings(_matches: &ArgMatches,
+                             config: Option<&Value>,
+                             rotation: Option<&Rotation>)
     -> Result<BTreeMap<ModuleName, ModuleSettings>>
 {
     // Helper macro to return the error from Some(Err(_)) and map everything else to an
@@ -378,7 +637,7 @@ fn aggregate_module_settings(_matches: &ArgMatches, config: Option<&Value>)
                                         let msg = "Type error at 'imag.logging.modules.<mod>.destinations', expected 'Array'";
                                         Error::from(err_msg(msg))
                                     })
-                                    .and_then(translate_destinations)
+                                    .and_then(|dests| translate_destinations(dests, rotation))
                             })
                     };
 
@@ -430,3 +689,53 @@ fn aggregate_module_settings(_matches: &ArgMatches, config: Option<&Value>)
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+
+    fn read(path: &PathBuf) -> String {
+        ::std::fs::read_to_string(path).unwrap()
+    }
+
+    #[test]
+    fn test_log_file_rotates_on_size() {
+        let dir  = TempDir::new("imag-logger").unwrap();
+        let path = dir.path().join("imag.log");
+        let rotation = Rotation { max_size: Some(10), interval: None, keep: 2 };
+
+        let mut file = LogFile::open(path.clone(), Some(rotation)).unwrap();
+        file.write_line("first").unwrap();
+        file.write_line("second").unwrap();
+        file.write_line("third").unwrap();
+        file.write_line("fourth").unwrap();
+
+        assert_eq!("fourth\n", read(&path));
+        assert_eq!("third\n", read(&dir.path().join("imag.log.1")));
+        assert_eq!("second\n", read(&dir.path().join("imag.log.2")));
+        assert!(!dir.path().join("imag.log.3").exists());
+    }
+
+    #[test]
+    fn test_log_file_without_rotation_appends() {
+        let dir  = TempDir::new("imag-logger").unwrap();
+        let path = dir.path().join("imag.log");
+
+        let mut file = LogFile::open(path.clone(), None).unwrap();
+        file.write_line("first").unwrap();
+        file.write_line("second").unwrap();
+
+        assert_eq!("first\nsecond\n", read(&path));
+        assert!(!dir.path().join("imag.log.1").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_syslog_priority() {
+        assert_eq!(11, syslog_priority(Level::Error));
+        assert_eq!(12, syslog_priority(Level::Warn));
+        assert_eq!(14, syslog_priority(Level::Info));
+        assert_eq!(15, syslog_priority(Level::Trace));
+    }
+}
@@ -0,0 +1,213 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2019 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+
+use std::path::PathBuf;
+use std::collections::BTreeMap;
+
+use toml::Value;
+use toml_query::read::TomlValueReadExt;
+use failure::Fallible as Result;
+use failure::ResultExt;
+use failure::Error;
+use failure::err_msg;
+
+use libimagerror::errors::ErrorMsg as EM;
+
+/// The environment variable which can be used to select a profile
+pub const ENV_PROFILE : &'static str = "IMAG_PROFILE";
+
+/// A named profile from the configuration
+///
+/// Profiles live in the `profiles` table of the configuration:
+///
+/// ```toml
+/// [profiles.work]
+/// store  = "/home/user/work/store"
+/// editor = "nano"
+///
+/// [profiles.work.config.diary]
+/// default_diary = "work"
+///
+/// [profiles.work.config.bookmark]
+/// default_collection = "work"
+///
+/// [profiles.work.config.imag.logging]
+/// level = "warn"
+/// ```
+///
+/// `store` and `editor` are optional. The `config` table is optional as well. It is merged on top
+/// of the configuration files, so every setting of every module can be overridden per profile.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Profile {
+    name: String,
+    store: Option<PathBuf>,
+    editor: Option<String>,
+    config: Option<Value>,
+}
+
+impl Profile {
+
+    /// Read the profile `name` from the configuration `config`
+    ///
+    /// Fails if there is no such profile or the profile is malformed.
+    pub fn from_config(config: &Value, name: &str) -> Result<Profile> {
+        let key = format!("profiles.{}", name);
+
+        let table = config
+            .read(&key)
+            .context(EM::TomlQueryError)?
+            .ok_or_else(|| format_err!("No such profile: '{}'", name))?;
+
+        if !table.is_table() {
+            return Err(format_err!("Type error at '{}', expected 'Table'", key))
+        }
+
+        let store = read_string(table, &key, "store")?.map(PathBuf::from);
+        let editor = read_string(table, &key, "editor")?;
+        let config = match table.read("config").context(EM::TomlQueryError)? {
+            Some(t @ &Value::Table(_)) => Some(t.clone()),
+            Some(_) => return Err(format_err!("Type error at '{}.config', expected 'Table'", key)),
+            None    => None,
+        };
+
+        Ok(Profile {
+            name: String::from(name),
+            store,
+            editor,
+            config,
+        })
+    }
+
+    /// Get the names of all profiles defined in the configuration
+    pub fn names(config: &Value) -> Result<Vec<String>> {
+        match config.read("profiles").context(EM::TomlQueryError)? {
+            Some(&Value::Table(ref t)) => Ok(t.keys().cloned().collect()),
+            Some(_) => Err(Error::from(err_msg("Type error at 'profiles', expected 'Table'"))),
+            None    => Ok(vec![]),
+        }
+    }
+
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    /// The store path of the profile, if any
+    pub fn store(&self) -> Option<&PathBuf> {
+        self.store.as_ref()
+    }
+
+    /// The editor of the profile, if any
+    pub fn editor(&self) -> Option<&String> {
+        self.editor.as_ref()
+    }
+
+    /// The configuration layer of the profile
+    ///
+    /// This contains the `config` table of the profile, with the `editor` of the profile set as
+    /// `rt.editor`.
+    pub fn config_layer(&self) -> Value {
+        let mut layer = self.config.clone().unwrap_or_else(|| Value::Table(BTreeMap::new()));
+
+        if let Some(ref editor) = self.editor {
+            if let Value::Table(ref mut t) = layer {
+                let rt = t
+                    .entry(String::from("rt"))
+                    .or_insert_with(|| Value::Table(BTreeMap::new()));
+
+                if let Value::Table(ref mut rt) = *rt {
+                    let _ = rt.insert(String::from("editor"), Value::String(editor.clone()));
+                } else {
+                    warn!("Profile '{}' sets 'rt' to a non-table value, ignoring editor", self.name);
+                }
+            }
+        }
+
+        layer
+    }
+
+}
+
+fn read_string(table: &Value, key: &str, name: &str) -> Result<Option<String>> {
+    match table.read(name).context(EM::TomlQueryError)? {
+        Some(&Value::String(ref s)) => Ok(Some(s.clone())),
+        Some(_) => Err(format_err!("Type error at '{}.{}', expected 'String'", key, name)),
+        None    => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use toml::Value;
+    use toml::de::from_str as toml_from_str;
+    use toml_query::read::TomlValueReadExt;
+
+    use super::Profile;
+
+    static CONFIG : &'static str = r#"
+    [rt]
+    editor = "vim"
+
+    [profiles.work]
+    store  = "/tmp/work"
+    editor = "nano"
+
+    [profiles.work.config.diary]
+    default_diary = "work"
+
+    [profiles.private]
+    "#;
+
+    #[test]
+    fn test_profile_from_config() {
+        let config : Value = toml_from_str(CONFIG).unwrap();
+        let profile = Profile::from_config(&config, "work").unwrap();
+
+        assert_eq!(profile.name(), "work");
+        assert_eq!(profile.store().map(|p| p.display().to_string()), Some(String::from("/tmp/work")));
+        assert_eq!(profile.editor(), Some(&String::from("nano")));
+
+        let layer = profile.config_layer();
+        assert_eq!(layer.read("rt.editor").unwrap(), Some(&Value::String(String::from("nano"))));
+        assert_eq!(layer.read("diary.default_diary").unwrap(), Some(&Value::String(String::from("work"))));
+    }
+
+    #[test]
+    fn test_empty_profile() {
+        let config : Value = toml_from_str(CONFIG).unwrap();
+        let profile = Profile::from_config(&config, "private").unwrap();
+
+        assert!(profile.store().is_none());
+        assert!(profile.editor().is_none());
+        assert_eq!(profile.config_layer(), Value::Table(Default::default()));
+    }
+
+    #[test]
+    fn test_missing_profile() {
+        let config : Value = toml_from_str(CONFIG).unwrap();
+        assert!(Profile::from_config(&config, "nonexistent").is_err());
+    }
+
+    #[test]
+    fn test_profile_names() {
+        let config : Value = toml_from_str(CONFIG).unwrap();
+        let names = Profile::names(&config).unwrap();
+        assert_eq!(names, vec![String::from("private"), String::from("work")]);
+    }
+}
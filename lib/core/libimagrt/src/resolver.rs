@@ -0,0 +1,40 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2019 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+//! Hooks for resolving ids which do not name an entry directly
+//!
+//! Libraries can provide references which stand for a number of entries, for example saved
+//! searches (`@name`). The runtime cannot know about them, so binaries register an `IdResolver`
+//! with `Runtime::register_id_resolver()`, and the runtime passes all ids it gets through the
+//! registered resolvers.
+
+use std::fmt::Debug;
+
+use failure::Fallible as Result;
+
+use libimagstore::store::Store;
+use libimagstore::storeid::StoreId;
+
+pub trait IdResolver : Debug {
+
+    /// Resolve `id` to the ids it stands for
+    ///
+    /// Returns `None` if `id` is not a reference this resolver knows about.
+    fn resolve(&self, store: &Store, id: &StoreId) -> Result<Option<Vec<StoreId>>>;
+}
@@ -25,11 +25,16 @@ use std::io::Stdin;
 use std::io::StdoutLock;
 use std::borrow::Borrow;
 use std::result::Result as RResult;
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::str::FromStr;
 
 pub use clap::App;
 use clap::AppSettings;
 use toml::Value;
 use toml_query::read::TomlValueReadExt;
+use toml_query::read::TomlValueReadTypeExt;
 
 use clap::{Arg, ArgMatches};
 use failure::ResultExt;
@@ -37,9 +42,18 @@ use failure::Fallible as Result;
 use failure::Error;
 use failure::err_msg;
 
-use configuration::{fetch_config, override_config, InternalConfiguration};
+use configuration::{fetch_layered_config, ConfigSource, Provenance, InternalConfiguration};
+use profile::Profile;
+use profile::ENV_PROFILE;
 use logger::ImagLogger;
 use io::OutputProxy;
+use dryrun::DryRunReporter;
+use undo::UndoRecorder;
+use undo::DEFAULT_UNDO_KEEP;
+use idstream::IdAttributes;
+use idstream::IdStreamFormat;
+use idstream::IdStreamItem;
+use resolver::IdResolver;
 
 use libimagerror::exit::ExitCode;
 use libimagerror::errors::ErrorMsg as EM;
@@ -58,13 +72,28 @@ use atty;
 pub struct Runtime<'a> {
     rtp: PathBuf,
     configuration: Option<Value>,
+    config_provenance: Provenance,
+    profile: Option<Profile>,
     cli_matches: ArgMatches<'a>,
     store: Store,
 
+    /// Reports the changes of a dry run. Declared after `store`, so that it is dropped after it.
+    dry_run: Option<DryRunReporter>,
+
+    /// Records the changes for `imag undo`. Declared after `store`, so that it is dropped after it.
+    undo: Option<UndoRecorder>,
+
+    appname: String,
+
     has_output_pipe: bool,
     has_input_pipe: bool,
 
-    ignore_ids: bool
+    ignore_ids: bool,
+
+    id_stream_format: Cell<IdStreamFormat>,
+    id_stream_header_written: Cell<bool>,
+    id_attributes: RefCell<BTreeMap<StoreId, IdAttributes>>,
+    id_resolvers: RefCell<Vec<Box<IdResolver>>>,
 }
 
 impl<'a> Runtime<'a> {
@@ -73,22 +102,31 @@ impl<'a> Runtime<'a> {
     /// in $HOME/.imag/config, $XDG_CONFIG_DIR/imag/config or from env("$IMAG_CONFIG")
     /// and builds the Runtime object with it.
     ///
+    /// The configuration is assembled from several layers, see
+    /// `configuration::fetch_layered_config()`.
+    ///
     /// The cli_app object should be initially build with the ::get_default_cli_builder() function.
     pub fn new<C>(cli_app: C) -> Result<Runtime<'a>>
         where C: Clone + CliSpec<'a> + InternalConfiguration
     {
-        use libimagerror::trace::trace_error;
-
         let matches = cli_app.clone().matches();
 
-        let rtp = get_rtp_match(&matches);
+        let rtp     = get_rtp_match(&matches);
+        let profile = get_profile_match(&matches);
 
         let configpath = matches.value_of(Runtime::arg_config_name())
                                 .map_or_else(|| rtp.clone(), PathBuf::from);
 
         debug!("Config path = {:?}", configpath);
+        debug!("Profile     = {:?}", profile);
+
+        let config = fetch_layered_config(&configpath,
+                                          get_explicit_storepath_match(&matches),
+                                          get_default_storepath(&rtp),
+                                          profile.as_ref().map(String::as_str),
+                                          get_override_specs(&matches))?;
 
-        let config = match fetch_config(&configpath)? {
+        let config = match config {
             None => {
                 return Err(err_msg("No configuration file found"))
                     .context(err_msg("Maybe try to use 'imag-init' to initialize imag?"))
@@ -96,73 +134,117 @@ impl<'a> Runtime<'a> {
                     .context(err_msg("Cannot instantiate runtime"))
                     .map_err(Error::from);
             },
-            Some(mut config) => {
-                if let Err(e) = override_config(&mut config, get_override_specs(&matches)) {
-                    error!("Could not apply config overrides");
-                    trace_error(&e);
-
-                    // TODO: continue question (interactive)
-                }
-
-                Some(config)
-            }
+            Some(config) => config,
         };
 
-        Runtime::_new(cli_app, matches, config)
+        let storepath = config.storepath().clone();
+        let profile   = config.profile().cloned();
+        let (config, provenance) = config.into_parts();
+
+        Runtime::_new(cli_app, matches, storepath, Some(config), provenance, profile)
     }
 
     /// Builds the Runtime object using the given `config`.
+    ///
+    /// Profiles are not supported by this constructor.
     pub fn with_configuration<C>(cli_app: C, config: Option<Value>) -> Result<Runtime<'a>>
         where C: Clone + CliSpec<'a> + InternalConfiguration
     {
-        let matches = cli_app.clone().matches();
-        Runtime::_new(cli_app, matches, config)
-    }
-
-    fn _new<C>(cli_app: C, matches: ArgMatches<'a>, config: Option<Value>) -> Result<Runtime<'a>>
+        let matches   = cli_app.clone().matches();
+        let storepath = get_storepath_match(&matches, &get_rtp_match(&matches));
+        Runtime::_new(cli_app, matches, storepath, config, Provenance::default(), None)
+    }
+
+    fn _new<C>(cli_app: C,
+               matches: ArgMatches<'a>,
+               storepath: PathBuf,
+               config: Option<Value>,
+               provenance: Provenance,
+               profile: Option<Profile>)
+        -> Result<Runtime<'a>>
     where C: Clone + CliSpec<'a> + InternalConfiguration
     {
         if cli_app.enable_logging() {
-            Runtime::init_logger(&matches, config.as_ref())
+            Runtime::_init_logger(&matches, config.as_ref(), Some(&storepath))
         }
 
         let rtp = get_rtp_match(&matches);
 
-        let storepath = matches.value_of(Runtime::arg_storepath_name())
-                                .map_or_else(|| {
-                                    let mut spath = rtp.clone();
-                                    spath.push("store");
-                                    spath
-                                }, PathBuf::from);
-
         debug!("RTP path    = {:?}", rtp);
         debug!("Store path  = {:?}", storepath);
         debug!("CLI         = {:?}", matches);
         trace!("Config      = {:#?}", config);
+        trace!("Config provenance = {:#?}", provenance);
+
+        let is_dry_run = matches.is_present(Runtime::arg_dry_run_name());
+
+        let is_undo_enabled = config
+            .as_ref()
+            .map(|c| c.read_bool("rt.undo.enabled"))
+            .unwrap_or(Ok(None))
+            .map_err(Error::from)
+            .context(EM::TomlQueryError)?
+            .unwrap_or(false);
 
         let store_result = if cli_app.use_inmemory_fs() {
-            Store::new_inmemory(storepath, &config)
+            Store::new_inmemory(storepath, &config).map(|store| (store, None, None))
+        } else if is_dry_run {
+            Store::new_dry_run(storepath, &config)
+                .map(|(store, dry_run)| (store, Some(DryRunReporter::new(dry_run)), None))
+        } else if is_undo_enabled {
+            let keep = config
+                .as_ref()
+                .map(|c| c.read_int("rt.undo.keep"))
+                .unwrap_or(Ok(None))
+                .map_err(Error::from)
+                .context(EM::TomlQueryError)?
+                .map(|i| i as usize)
+                .unwrap_or(DEFAULT_UNDO_KEEP);
+
+            Store::new_journaled(storepath.clone(), &config)
+                .map(|(store, journal)| {
+                    (store, None, Some(UndoRecorder::new(journal, &storepath, keep)))
+                })
         } else {
-            Store::new(storepath, &config)
+            Store::new(storepath, &config).map(|store| (store, None, None))
         };
 
         let has_output_pipe = !atty::is(atty::Stream::Stdout);
         let has_input_pipe  = !atty::is(atty::Stream::Stdin);
         let ignore_ids      = matches.is_present(Runtime::arg_ignore_ids_name());
+        let appname         = String::from(cli_app.name());
+
+        let id_stream_format = matches
+            .value_of(Runtime::arg_id_stream_format_name())
+            .map(IdStreamFormat::from_str)
+            .unwrap_or(Ok(IdStreamFormat::Plain))?;
 
         debug!("has output pipe = {}", has_output_pipe);
         debug!("has input pipe  = {}", has_input_pipe);
         debug!("ignore ids      = {}", ignore_ids);
+        debug!("dry run         = {}", is_dry_run);
+        debug!("undo            = {}", is_undo_enabled);
+        debug!("id stream       = {:?}", id_stream_format);
 
-        store_result.map(|store| Runtime {
+        store_result.map(|(store, dry_run, undo)| Runtime {
             cli_matches: matches,
             configuration: config,
+            config_provenance: provenance,
+            profile: profile,
             rtp: rtp,
             store: store,
+            dry_run,
+            undo,
+            appname,
 
             has_output_pipe,
             has_input_pipe,
             ignore_ids,
+
+            id_stream_format: Cell::new(id_stream_format),
+            id_stream_header_written: Cell::new(false),
+            id_attributes: RefCell::new(BTreeMap::new()),
+            id_resolvers: RefCell::new(Vec::new()),
         })
         .context(err_msg("Cannot instantiate runtime"))
         .map_err(Error::from)
@@ -225,6 +307,13 @@ impl<'a> Runtime<'a> {
                  .required(false)
                  .takes_value(true))
 
+            .arg(Arg::with_name(Runtime::arg_profile_name())
+                .long("profile")
+                .help("Use a profile from the configuration. Overrides the IMAG_PROFILE environment variable")
+                .required(false)
+                .takes_value(true)
+                .value_name("PROFILE"))
+
             .arg(Arg::with_name(Runtime::arg_runtimepath_name())
                 .long("rtp")
                 .help("Alternative runtimepath")
@@ -252,6 +341,20 @@ impl<'a> Runtime<'a> {
                 .required(false)
                 .takes_value(false))
 
+            .arg(Arg::with_name(Runtime::arg_dry_run_name())
+                .long(Runtime::arg_dry_run_name())
+                .help("Do not write anything to the store. The changes which would have been made are printed when the command exits.")
+                .required(false)
+                .takes_value(false))
+
+            .arg(Arg::with_name(Runtime::arg_id_stream_format_name())
+                .long(Runtime::arg_id_stream_format_name())
+                .help("Format in which the IDs of the touched entries are printed if stdout is a pipe. 'structured' adds attributes like header values or match offsets to the IDs. If the IDs on stdin are structured, the output is structured as well.")
+                .required(false)
+                .takes_value(true)
+                .possible_values(&["plain", "structured"])
+                .value_name("FORMAT"))
+
     }
 
     /// Get the argument names of the Runtime which are available
@@ -262,10 +365,13 @@ impl<'a> Runtime<'a> {
             Runtime::arg_no_color_output_name(),
             Runtime::arg_config_name(),
             Runtime::arg_config_override_name(),
+            Runtime::arg_profile_name(),
             Runtime::arg_runtimepath_name(),
             Runtime::arg_storepath_name(),
             Runtime::arg_editor_name(),
             Runtime::arg_ignore_ids_name(),
+            Runtime::arg_id_stream_format_name(),
+            Runtime::arg_dry_run_name(),
         ]
     }
 
@@ -274,6 +380,16 @@ impl<'a> Runtime<'a> {
         "ignore-ids"
     }
 
+    /// Get the id-stream-format argument name for the Runtime
+    pub fn arg_id_stream_format_name() -> &'static str {
+        "id-stream-format"
+    }
+
+    /// Get the dry-run argument name for the Runtime
+    pub fn arg_dry_run_name() -> &'static str {
+        "dry-run"
+    }
+
     /// Get the verbosity argument name for the Runtime
     pub fn arg_verbosity_name() -> &'static str {
         "verbosity"
@@ -299,6 +415,11 @@ impl<'a> Runtime<'a> {
         "config-override"
     }
 
+    /// Get the profile argument name for the Runtime
+    pub fn arg_profile_name() -> &'static str {
+        "profile"
+    }
+
     /// Get the runtime argument name for the Runtime
     pub fn arg_runtimepath_name() -> &'static str {
         "runtimepath"
@@ -339,11 +460,7 @@ impl<'a> Runtime<'a> {
 
     #[cfg(feature = "pub_logging_initialization")]
     pub fn init_logger(matches: &ArgMatches, config: Option<&Value>) {
-        Self::_init_logger(matches, config)
-    }
-    #[cfg(not(feature = "pub_logging_initialization"))]
-    fn init_logger(matches: &ArgMatches, config: Option<&Value>) {
-        Self::_init_logger(matches, config)
+        Self::_init_logger(matches, config, None)
     }
 
     /// Initialize the internal logger
@@ -352,7 +469,9 @@ impl<'a> Runtime<'a> {
     /// initializes a env-logger instance. Errors are ignored in this case.
     /// If the environment variable is not set, this initializes the internal imag logger. On
     /// error, this exits (as there is nothing we can do about that)
-    fn _init_logger(matches: &ArgMatches, config: Option<&Value>) {
+    ///
+    /// The store path, if passed, is added to each line of JSON formatted logging output.
+    fn _init_logger(matches: &ArgMatches, config: Option<&Value>, storepath: Option<&PathBuf>) {
         use log::set_max_level;
         use log::set_boxed_logger;
         use std::env::var as env_var;
@@ -365,6 +484,11 @@ impl<'a> Runtime<'a> {
                 .map_err_trace()
                 .unwrap_or_else(|_| exit(1));
 
+            let logger = match storepath {
+                Some(path) => logger.with_store_path(path.clone()),
+                None       => logger,
+            };
+
             set_max_level(logger.global_loglevel().to_level_filter());
 
             // safe debug output for later, after the instance itself
@@ -394,6 +518,11 @@ impl<'a> Runtime<'a> {
         &self.rtp
     }
 
+    /// Check whether the runtime runs in dry-run mode, where nothing is written to the store
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run.is_some()
+    }
+
     /// Get the commandline interface matches
     pub fn cli(&self) -> &ArgMatches {
         &self.cli_matches
@@ -404,6 +533,41 @@ impl<'a> Runtime<'a> {
     }
 
     pub fn ids<T: IdPathProvider>(&self) -> Result<Vec<StoreId>> {
+        self.ids_with_attributes::<T>()
+            .map(|items| items.into_iter().map(|item| item.into_parts().0).collect())
+    }
+
+    /// Get the ids to operate on, or let the user pick them if none were passed
+    ///
+    /// If neither the commandline nor stdin provide ids, the fuzzy finder from
+    /// `libimaginteraction::picker` is shown on the terminal, offering all entries. Selecting
+    /// nothing is an error.
+    pub fn ids_or_pick<T: IdPathProvider>(&self) -> Result<Vec<StoreId>> {
+        let ids = self.ids::<T>()?;
+        if !ids.is_empty() || self.has_input_pipe {
+            return Ok(ids)
+        }
+
+        debug!("No ids passed, asking the user to pick");
+        let ids = ::libimaginteraction::ui::pick_ids(self.store(), None, true)?;
+        if ids.is_empty() {
+            Err(err_msg("No entries selected"))
+        } else {
+            Ok(ids)
+        }
+    }
+
+    /// Get the ids to operate on, with the attributes which were passed with them
+    ///
+    /// If the ids are read from stdin, they are parsed with the id stream protocol (see
+    /// `idstream`). The attributes are remembered and passed on when the id is reported as touched
+    /// later. If the ids on stdin are in the structured format, the output is structured as well.
+    ///
+    /// Ids from the commandline never carry attributes.
+    ///
+    /// Ids which one of the registered `IdResolver`s knows about (for example references to saved
+    /// searches) are replaced by the ids they resolve to.
+    pub fn ids_with_attributes<T: IdPathProvider>(&self) -> Result<Vec<IdStreamItem>> {
         use std::io::Read;
 
         if self.has_input_pipe {
@@ -412,25 +576,92 @@ impl<'a> Runtime<'a> {
             let mut lock = stdin.lock();
 
             let mut buf = String::new();
-            lock.read_to_string(&mut buf)
-                .map_err(Error::from)
-                .and_then(|_| {
-                    trace!("Got IDs = {}", buf);
-                    buf.lines()
-                        .map(PathBuf::from)
-                        .map(|id| StoreId::new(id).map_err(Error::from))
-                        .collect()
-                })
+            lock.read_to_string(&mut buf).context(EM::IO)?;
+            trace!("Got IDs = {}", buf);
+
+            let (format, items) = ::idstream::parse(&buf)?;
+            if format == IdStreamFormat::Structured {
+                self.id_stream_format.set(IdStreamFormat::Structured);
+            }
+
+            let items = self.resolve_ids(items)?;
+
+            {
+                let mut attributes = self.id_attributes.borrow_mut();
+                for item in items.iter().filter(|i| !i.attributes().is_empty()) {
+                    let _ = attributes.insert(item.id().clone(), item.attributes().clone());
+                }
+            }
+
+            Ok(items)
         } else {
-            Ok(T::get_ids(self.cli()))
+            let items = T::get_ids(self.cli())
+               .into_iter()
+               .map(|id| IdStreamItem::new(id, IdAttributes::default()))
+               .collect();
+
+            self.resolve_ids(items)
         }
     }
 
+    /// Register a resolver for ids which do not name an entry directly
+    ///
+    /// See `resolver::IdResolver`. Has to be called before the ids are fetched.
+    pub fn register_id_resolver<R: IdResolver + 'static>(&self, resolver: R) {
+        self.id_resolvers.borrow_mut().push(Box::new(resolver));
+    }
+
+    /// Replace ids by the ids the registered resolvers resolve them to
+    ///
+    /// The attributes of a resolved id are passed on to all ids it resolves to.
+    fn resolve_ids(&self, items: Vec<IdStreamItem>) -> Result<Vec<IdStreamItem>> {
+        let resolvers = self.id_resolvers.borrow();
+        if resolvers.is_empty() {
+            return Ok(items)
+        }
+
+        let mut resolved = Vec::with_capacity(items.len());
+
+        'items: for item in items {
+            for resolver in resolvers.iter() {
+                if let Some(ids) = resolver.resolve(self.store(), item.id())? {
+                    debug!("Resolved '{}' with {:?}", item.id(), resolver);
+                    for id in ids {
+                        resolved.push(IdStreamItem::new(id, item.attributes().clone()));
+                    }
+                    continue 'items;
+                }
+            }
+
+            resolved.push(item);
+        }
+
+        Ok(resolved)
+    }
+
     /// Get the configuration object
     pub fn config(&self) -> Option<&Value> {
         self.configuration.as_ref()
     }
 
+    /// Get the source of the configuration value at `key`
+    ///
+    /// Returns `None` if the key is not set, points to a table or the configuration was not
+    /// loaded from configuration files (see `Runtime::with_configuration()`).
+    pub fn config_source(&self, key: &str) -> Option<&ConfigSource> {
+        self.config_provenance.source_of(key)
+    }
+
+    /// Get the provenance information for all configuration values
+    pub fn config_provenance(&self) -> &Provenance {
+        &self.config_provenance
+    }
+
+    /// Get the profile the runtime was set up with, if any
+    pub fn profile(&self) -> Option<&Profile> {
+        self.profile.as_ref()
+    }
+
     /// Get the store object
     pub fn store(&self) -> &Store {
         &self.store
@@ -578,10 +809,25 @@ impl<'a> Runtime<'a> {
     }
 
     pub fn report_touched(&self, id: &StoreId) -> RResult<(), ExitCode> {
+        self.report_touched_with(id, IdAttributes::default())
+    }
+
+    /// Report `id` as touched, attaching `attributes` to it
+    ///
+    /// The attributes are merged with the ones the id was passed in with. They are only printed
+    /// if the id stream is structured.
+    pub fn report_touched_with(&self, id: &StoreId, attributes: IdAttributes)
+        -> RResult<(), ExitCode>
+    {
         let out      = ::std::io::stdout();
         let mut lock = out.lock();
 
-        self.report_touched_id(id, &mut lock)
+        self.report_touched_id_with(id, attributes, &mut lock)
+    }
+
+    /// Get the format in which touched ids are reported
+    pub fn id_stream_format(&self) -> IdStreamFormat {
+        self.id_stream_format.get()
     }
 
     pub fn report_all_touched<ID, I>(&self, ids: I) -> RResult<(), ExitCode>
@@ -600,13 +846,44 @@ impl<'a> Runtime<'a> {
 
     #[inline]
     fn report_touched_id(&self, id: &StoreId, output: &mut StdoutLock) -> RResult<(), ExitCode> {
+        self.report_touched_id_with(id, IdAttributes::default(), output)
+    }
+
+    fn report_touched_id_with(&self,
+                              id: &StoreId,
+                              attributes: IdAttributes,
+                              output: &mut StdoutLock)
+        -> RResult<(), ExitCode>
+    {
         use std::io::Write;
 
-        if self.output_is_pipe() && !self.ignore_ids {
-            trace!("Reporting: {} to {:?}", id, output);
-            writeln!(output, "{}", id).to_exit_code()
-        } else {
-            Ok(())
+        if !self.output_is_pipe() || self.ignore_ids {
+            return Ok(())
+        }
+
+        trace!("Reporting: {} to {:?}", id, output);
+        match self.id_stream_format() {
+            IdStreamFormat::Plain      => writeln!(output, "{}", id).to_exit_code(),
+            IdStreamFormat::Structured => {
+                if !self.id_stream_header_written.get() {
+                    writeln!(output, "{}", ::idstream::header_line()).to_exit_code()?;
+                    self.id_stream_header_written.set(true);
+                }
+
+                let mut attrs = self.id_attributes
+                    .borrow()
+                    .get(id)
+                    .cloned()
+                    .unwrap_or_default();
+                attrs.merge(attributes);
+                attrs.producer = Some(self.appname.clone());
+
+                let line = ::idstream::format_item(id, &attrs).map_err(|e| {
+                    trace_error(&e);
+                    ExitCode::from(1)
+                })?;
+                writeln!(output, "{}", line).to_exit_code()
+            },
         }
     }
 }
@@ -666,7 +943,33 @@ pub fn get_rtp_match<'a>(matches: &ArgMatches<'a>) -> PathBuf {
         }, PathBuf::from)
 }
 
-fn get_override_specs(matches: &ArgMatches) -> Vec<String> {
+/// Exported for the `imag` command, you probably do not want to use that.
+pub fn get_storepath_match<'a>(matches: &ArgMatches<'a>, rtp: &PathBuf) -> PathBuf {
+    get_explicit_storepath_match(matches).unwrap_or_else(|| get_default_storepath(rtp))
+}
+
+/// Exported for the `imag` command, you probably do not want to use that.
+pub fn get_explicit_storepath_match<'a>(matches: &ArgMatches<'a>) -> Option<PathBuf> {
+    matches.value_of(Runtime::arg_storepath_name()).map(PathBuf::from)
+}
+
+/// Exported for the `imag` command, you probably do not want to use that.
+pub fn get_default_storepath(rtp: &PathBuf) -> PathBuf {
+    let mut spath = rtp.clone();
+    spath.push("store");
+    spath
+}
+
+/// Exported for the `imag` command, you probably do not want to use that.
+pub fn get_profile_match<'a>(matches: &ArgMatches<'a>) -> Option<String> {
+    matches.value_of(Runtime::arg_profile_name())
+        .map(String::from)
+        .or_else(|| env::var(ENV_PROFILE).ok())
+        .filter(|s| !s.is_empty())
+}
+
+/// Exported for the `imag` command, you probably do not want to use that.
+pub fn get_override_specs(matches: &ArgMatches) -> Vec<String> {
     matches
         .values_of("config-override")
         .map(|values| {
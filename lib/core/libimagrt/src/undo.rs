@@ -0,0 +1,333 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2019 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+
+//! The undo journal
+//!
+//! For each invocation of an imag command, the runtime records the content the altered entries had
+//! before (the pre-image) and after (the post-image) the command ran, together with the
+//! commandline and a timestamp. The records live in the `.imag-undo` directory in the store.
+//!
+//! Reverting a record restores the pre-images, but only if the entries were not altered since,
+//! that is if their content still equals the post-image. Otherwise the conflicting entries are
+//! reported and nothing is reverted.
+
+use std::path::PathBuf;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use chrono::Local;
+use failure::Fallible as Result;
+use failure::ResultExt;
+use failure::Error;
+use serde_json;
+
+use libimagerror::errors::ErrorMsg as EM;
+use libimagerror::trace::trace_error;
+use libimagstore::journal::Journal;
+
+/// The name of the directory in the store where the undo records are kept
+pub const UNDO_DIR_NAME : &'static str = ".imag-undo";
+
+/// The default number of records which are kept
+pub const DEFAULT_UNDO_KEEP : usize = 100;
+
+/// The change of one entry by one command
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UndoChange {
+    pub id: String,
+    pub pre: Option<String>,
+    pub post: Option<String>,
+}
+
+/// The changes made by one invocation of an imag command
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UndoRecord {
+    pub timestamp: String,
+    pub command: Vec<String>,
+    pub changes: Vec<UndoChange>,
+}
+
+/// The result of reverting a record
+#[derive(Debug)]
+pub enum Revert {
+    /// The record was reverted
+    Reverted(UndoRecord),
+
+    /// The record was not reverted, because the listed entries were altered since
+    Conflict(UndoRecord, Vec<String>),
+}
+
+/// The undo records of a store
+#[derive(Debug)]
+pub struct UndoLog {
+    store: PathBuf,
+}
+
+impl UndoLog {
+
+    pub fn new(storepath: &PathBuf) -> UndoLog {
+        UndoLog { store: storepath.clone() }
+    }
+
+    fn dir(&self) -> PathBuf {
+        self.store.join(UNDO_DIR_NAME)
+    }
+
+    /// Get all records with their path, the most recent one first
+    pub fn records(&self) -> Result<Vec<(PathBuf, UndoRecord)>> {
+        let dir = self.dir();
+        if !dir.exists() {
+            return Ok(vec![])
+        }
+
+        let mut paths = ::std::fs::read_dir(&dir)
+            .context(EM::IO)?
+            .map(|entry| entry.map(|e| e.path()).context(EM::IO).map_err(Error::from))
+            .collect::<Result<Vec<PathBuf>>>()?;
+
+        paths.retain(|p| p.extension().map(|e| e == "json").unwrap_or(false));
+        paths.sort();
+        paths.reverse();
+
+        paths.into_iter()
+            .map(|path| {
+                let file = OpenOptions::new().read(true).open(&path).context(EM::IO)?;
+                let rec  = serde_json::from_reader(file)
+                    .context(format_err!("Invalid undo record: {}", path.display()))?;
+                Ok((path, rec))
+            })
+            .collect()
+    }
+
+    /// Append a record, removing the oldest records so that at most `keep` records remain
+    pub fn push(&self, record: &UndoRecord, keep: usize) -> Result<()> {
+        let dir = self.dir();
+        let _   = ::std::fs::create_dir_all(&dir).context(EM::DirNotCreated)?;
+
+        let time = SystemTime::now().duration_since(UNIX_EPOCH).context(EM::IO)?;
+        let name = format!("{:020}{:09}-{}.json",
+                           time.as_secs(),
+                           time.subsec_nanos(),
+                           ::std::process::id());
+
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(dir.join(name))
+            .context(EM::FileNotCreated)?;
+        let _ = serde_json::to_writer(file, record).context(EM::FileNotWritten)?;
+
+        for (path, _) in self.records()?.into_iter().skip(keep) {
+            let _ = ::std::fs::remove_file(path).context(EM::FileNotRemoved)?;
+        }
+
+        Ok(())
+    }
+
+    /// Revert the `n` most recent records, the most recent one first
+    ///
+    /// Stops at the first record which conflicts with the current state of the store.
+    pub fn revert_last(&self, n: usize) -> Result<Vec<Revert>> {
+        let mut result = vec![];
+
+        for (path, record) in self.records()?.into_iter().take(n) {
+            let conflicts = record
+                .changes
+                .iter()
+                .map(|change| {
+                    self.current(&change.id)
+                        .map(|current| if current != change.post { Some(change.id.clone()) } else { None })
+                })
+                .collect::<Result<Vec<Option<String>>>>()?
+                .into_iter()
+                .filter_map(|x| x)
+                .collect::<Vec<String>>();
+
+            if !conflicts.is_empty() {
+                result.push(Revert::Conflict(record, conflicts));
+                break
+            }
+
+            for change in record.changes.iter() {
+                self.restore(&change.id, change.pre.as_ref())?;
+            }
+
+            let _ = ::std::fs::remove_file(path).context(EM::FileNotRemoved)?;
+            result.push(Revert::Reverted(record));
+        }
+
+        Ok(result)
+    }
+
+    fn current(&self, id: &str) -> Result<Option<String>> {
+        match ::std::fs::read_to_string(self.store.join(id)) {
+            Ok(s)  => Ok(Some(s)),
+            Err(e) => match e.kind() {
+                ::std::io::ErrorKind::NotFound => Ok(None),
+                _ => Err(e).context(EM::IO).map_err(Error::from),
+            },
+        }
+    }
+
+    fn restore(&self, id: &str, content: Option<&String>) -> Result<()> {
+        let path = self.store.join(id);
+        debug!("Restoring {}", path.display());
+
+        match content {
+            None => if path.exists() {
+                ::std::fs::remove_file(&path)
+                    .context(EM::FileNotRemoved)
+                    .map_err(Error::from)
+            } else {
+                Ok(())
+            },
+
+            Some(content) => {
+                if let Some(parent) = path.parent() {
+                    let _ = ::std::fs::create_dir_all(parent).context(EM::DirNotCreated)?;
+                }
+
+                let mut file = OpenOptions::new()
+                    .write(true)
+                    .create(true)
+                    .truncate(true)
+                    .open(&path)
+                    .context(EM::FileNotCreated)?;
+
+                file.write_all(content.as_bytes())
+                    .context(EM::FileNotWritten)
+                    .map_err(Error::from)
+            },
+        }
+    }
+
+}
+
+/// Writes an undo record for the changes of the store when dropped
+#[derive(Debug)]
+pub(crate) struct UndoRecorder {
+    journal: Journal,
+    log: UndoLog,
+    keep: usize,
+}
+
+impl UndoRecorder {
+
+    pub(crate) fn new(journal: Journal, storepath: &PathBuf, keep: usize) -> UndoRecorder {
+        UndoRecorder { journal, log: UndoLog::new(storepath), keep }
+    }
+
+    fn record(&self) -> Result<()> {
+        let changes = self.journal
+            .changes()?
+            .into_iter()
+            .map(|change| UndoChange {
+                id: change.id.local_display_string(),
+                pre: change.pre,
+                post: change.post,
+            })
+            .collect::<Vec<_>>();
+
+        if changes.is_empty() {
+            return Ok(())
+        }
+
+        let record = UndoRecord {
+            timestamp: Local::now().format("%Y-%m-%dT%H:%M:%S").to_string(),
+            command: ::std::env::args().collect(),
+            changes,
+        };
+
+        self.log.push(&record, self.keep)
+    }
+
+}
+
+impl Drop for UndoRecorder {
+    fn drop(&mut self) {
+        if let Err(e) = self.record() {
+            trace_error(&e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempdir::TempDir;
+
+    use super::*;
+
+    fn record(id: &str, pre: Option<&str>, post: Option<&str>) -> UndoRecord {
+        UndoRecord {
+            timestamp: String::from("2019-01-01T00:00:00"),
+            command: vec![String::from("imag-tag")],
+            changes: vec![UndoChange {
+                id: String::from(id),
+                pre: pre.map(String::from),
+                post: post.map(String::from),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_revert() {
+        let dir = TempDir::new("imag-undo").unwrap();
+        let log = UndoLog::new(&dir.path().to_path_buf());
+
+        ::std::fs::write(dir.path().join("a"), "new").unwrap();
+        log.push(&record("a", Some("old"), Some("new")), 10).unwrap();
+        log.push(&record("b", None, None), 10).unwrap();
+        assert_eq!(log.records().unwrap().len(), 2);
+
+        let reverted = log.revert_last(2).unwrap();
+        assert_eq!(reverted.len(), 2);
+        assert_eq!(::std::fs::read_to_string(dir.path().join("a")).unwrap(), "old");
+        assert!(log.records().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_revert_conflict() {
+        let dir = TempDir::new("imag-undo").unwrap();
+        let log = UndoLog::new(&dir.path().to_path_buf());
+
+        ::std::fs::write(dir.path().join("a"), "altered since").unwrap();
+        log.push(&record("a", None, Some("new")), 10).unwrap();
+
+        match log.revert_last(1).unwrap().pop() {
+            Some(Revert::Conflict(_, ids)) => assert_eq!(ids, vec![String::from("a")]),
+            other => panic!("Expected conflict, got {:?}", other),
+        }
+        assert!(dir.path().join("a").exists());
+        assert_eq!(log.records().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_push_keeps_limit() {
+        let dir = TempDir::new("imag-undo").unwrap();
+        let log = UndoLog::new(&dir.path().to_path_buf());
+
+        for _ in 0..5 {
+            log.push(&record("a", None, None), 3).unwrap();
+        }
+        assert_eq!(log.records().unwrap().len(), 3);
+    }
+}
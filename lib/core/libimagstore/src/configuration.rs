@@ -24,6 +24,8 @@ use failure::ResultExt;
 use failure::Error;
 
 use libimagerror::errors::ErrorMsg as EM;
+use libimagerror::kind::ErrorKind;
+use libimagerror::kind::kind_err;
 
 /// Checks whether the store configuration has a key "implicit-create" which maps to a boolean
 /// value. If that key is present, the boolean is returned, otherwise false is returned.
@@ -36,7 +38,7 @@ pub fn config_implicit_store_create_allowed(config: &Option<Value>) -> Result<bo
         t.read_bool(key)
             .map_err(Error::from)
             .context(EM::TomlQueryError)?
-            .ok_or_else(|| format_err!("Config key missing: {}", key))
+            .ok_or_else(|| kind_err(ErrorKind::Config, format!("Config key missing: {}", key)))
     } else {
         Ok(false)
     }
@@ -0,0 +1,267 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2019 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+
+//! Inspecting the changes of a store which was opened in dry-run mode
+//!
+//! A store created with `Store::new_dry_run()` never writes to disk. The `DryRun` object which is
+//! returned alongside the store can be used to get the changes which would have been made.
+
+use std::path::PathBuf;
+
+use toml::Value;
+use failure::Fallible as Result;
+use failure::Error;
+
+use libimagerror::errors::ErrorMsg as EM;
+
+use file_abstraction::overlay::OverlayBackend;
+use file_abstraction::fs::read_file;
+use storeid::StoreId;
+use storeid::StoreIdWithBase;
+
+/// A handle to the changes of a store in dry-run mode
+#[derive(Debug, Clone)]
+pub struct DryRun {
+    base: PathBuf,
+    backend: OverlayBackend,
+}
+
+/// A change which would have been made to the store
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Created  { id: StoreId, new: String },
+    Modified { id: StoreId, old: String, new: String },
+
+    /// An entry was moved. It might have been modified as well, in which case `old` and `new`
+    /// differ.
+    Moved    { from: StoreId, to: StoreId, old: String, new: String },
+    Deleted  { id: StoreId, old: String },
+}
+
+/// A line in a diff
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffLine {
+    Unchanged(String),
+    Removed(String),
+    Added(String),
+}
+
+impl DryRun {
+
+    pub(crate) fn new(base: PathBuf, backend: OverlayBackend) -> DryRun {
+        DryRun { base, backend }
+    }
+
+    /// Get the changes which would have been written to disk, ordered by id
+    ///
+    /// Entries which were written without being altered are not reported.
+    pub fn changes(&self) -> Result<Vec<Change>> {
+        let state = self.backend.lock().map_err(|_| Error::from(EM::LockError))?;
+
+        // Follow chains of renames, so that a -> b -> c is reported as a -> c
+        let mut moves : Vec<(PathBuf, PathBuf)> = vec![];
+        for &(ref from, ref to) in state.renames.iter() {
+            match moves.iter_mut().find(|mv| &mv.1 == from) {
+                Some(mv) => mv.1 = to.clone(),
+                None     => moves.push((from.clone(), to.clone())),
+            }
+        }
+
+        let mut handled = vec![];
+        let mut changes = vec![];
+
+        for (from, to) in moves {
+            let old = read_file(&from)?;
+            let new = state.upper.get(&to).cloned().and_then(|c| c);
+
+            if let (Some(old), Some(new)) = (old, new) {
+                if read_file(&to)?.is_none() && state.upper.get(&from) == Some(&None) {
+                    changes.push(Change::Moved {
+                        from: self.storeid(&from)?,
+                        to: self.storeid(&to)?,
+                        old,
+                        new,
+                    });
+                    handled.push(from);
+                    handled.push(to);
+                }
+            }
+        }
+
+        let mut paths = state.upper.keys().filter(|p| !handled.contains(*p)).collect::<Vec<_>>();
+        paths.sort();
+
+        for path in paths {
+            let old = read_file(path)?;
+            let new = state.upper.get(path).cloned().and_then(|c| c);
+
+            let change = match (old, new) {
+                (None, Some(new))      => Change::Created { id: self.storeid(path)?, new },
+                (Some(old), None)      => Change::Deleted { id: self.storeid(path)?, old },
+                (Some(old), Some(new)) => if old != new {
+                    Change::Modified { id: self.storeid(path)?, old, new }
+                } else {
+                    continue
+                },
+                (None, None)           => continue,
+            };
+
+            changes.push(change);
+        }
+
+        Ok(changes)
+    }
+
+    fn storeid(&self, path: &PathBuf) -> Result<StoreId> {
+        StoreIdWithBase::from_full_path(&self.base, path.as_path()).map(Into::into)
+    }
+
+}
+
+impl Change {
+
+    /// Diff of the headers of the old and new version of the entry
+    pub fn header_diff(&self) -> Vec<DiffLine> {
+        let (old, new) = self.versions();
+        diff(&split_entry(old).0, &split_entry(new).0)
+    }
+
+    /// Diff of the contents of the old and new version of the entry
+    pub fn content_diff(&self) -> Vec<DiffLine> {
+        let (old, new) = self.versions();
+        diff(&split_entry(old).1, &split_entry(new).1)
+    }
+
+    fn versions(&self) -> (&str, &str) {
+        match *self {
+            Change::Created  { ref new, .. }          => ("", new),
+            Change::Modified { ref old, ref new, .. } => (old, new),
+            Change::Moved    { ref old, ref new, .. } => (old, new),
+            Change::Deleted  { ref old, .. }          => (old, ""),
+        }
+    }
+
+}
+
+/// Split an entry into the (normalized) header and the content
+///
+/// If the entry cannot be parsed, the complete text is treated as content.
+fn split_entry(text: &str) -> (String, String) {
+    use util::entry_buffer_to_header_content;
+
+    if text.is_empty() {
+        return (String::new(), String::new())
+    }
+
+    entry_buffer_to_header_content(text)
+        .ok()
+        .and_then(|(header, content): (Value, String)| {
+            ::toml::ser::to_string_pretty(&header).ok().map(|h| (h, content))
+        })
+        .unwrap_or_else(|| (String::new(), String::from(text)))
+}
+
+/// Compute a line based diff between `old` and `new`
+pub fn diff(old: &str, new: &str) -> Vec<DiffLine> {
+    let old = old.lines().collect::<Vec<_>>();
+    let new = new.lines().collect::<Vec<_>>();
+
+    // lcs[i][j] is the length of the longest common subsequence of old[i..] and new[j..]
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                ::std::cmp::max(lcs[i + 1][j], lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut result = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            result.push(DiffLine::Unchanged(String::from(old[i])));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            result.push(DiffLine::Removed(String::from(old[i])));
+            i += 1;
+        } else {
+            result.push(DiffLine::Added(String::from(new[j])));
+            j += 1;
+        }
+    }
+
+    result.extend(old[i..].iter().map(|l| DiffLine::Removed(String::from(*l))));
+    result.extend(new[j..].iter().map(|l| DiffLine::Added(String::from(*l))));
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use tempdir::TempDir;
+
+    use super::*;
+    use store::Store;
+
+    #[test]
+    fn test_diff() {
+        let d = diff("a\nb\nc", "a\nc\nd");
+        assert_eq!(d, vec![
+            DiffLine::Unchanged(String::from("a")),
+            DiffLine::Removed(String::from("b")),
+            DiffLine::Unchanged(String::from("c")),
+            DiffLine::Added(String::from("d")),
+        ]);
+    }
+
+    #[test]
+    fn test_dry_run_changes() {
+        let dir = TempDir::new("imag-dry-run").unwrap();
+        let (store, dry) = Store::new_dry_run(dir.path().to_path_buf(), &None).unwrap();
+
+        let a = StoreId::new(PathBuf::from("a")).unwrap();
+        let b = StoreId::new(PathBuf::from("b")).unwrap();
+        let c = StoreId::new(PathBuf::from("c")).unwrap();
+
+        {
+            let _ = store.create(a.clone()).unwrap();
+            let _ = store.create(b.clone()).unwrap();
+        }
+        store.move_by_id(b.clone(), c.clone()).unwrap();
+
+        assert!(!dir.path().join("a").exists());
+
+        let changes = dry.changes().unwrap();
+        assert_eq!(changes.len(), 2);
+        match changes[0] {
+            Change::Created { ref id, .. } => assert_eq!(id, &a),
+            ref other => panic!("Unexpected change: {:?}", other),
+        }
+        match changes[1] {
+            Change::Created { ref id, .. } => assert_eq!(id, &c),
+            ref other => panic!("Unexpected change: {:?}", other),
+        }
+    }
+}
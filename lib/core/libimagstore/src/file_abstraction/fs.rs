@@ -33,6 +33,7 @@ use file_abstraction::iter::PathIterator;
 use file_abstraction::iter::PathIterBuilder;
 
 use walkdir::WalkDir;
+use walkdir::DirEntry;
 use failure::ResultExt;
 use failure::Fallible as Result;
 use failure::Error;
@@ -169,6 +170,12 @@ pub struct WalkDirPathIterBuilder {
     basepath: PathBuf
 }
 
+impl WalkDirPathIterBuilder {
+    pub fn new(basepath: PathBuf) -> WalkDirPathIterBuilder {
+        WalkDirPathIterBuilder { basepath }
+    }
+}
+
 impl PathIterBuilder for WalkDirPathIterBuilder {
     fn build_iter(&self) -> Box<Iterator<Item = Result<PathBuf>>> {
         trace!("Building iterator for {}", self.basepath.display());
@@ -176,6 +183,7 @@ impl PathIterBuilder for WalkDirPathIterBuilder {
             .min_depth(1)
             .max_open(100)
             .into_iter()
+            .filter_entry(|e| e.depth() == 0 || !is_hidden(e))
             .map(|r| {
                 trace!("Working in PathIterator with {:?}", r);
                 r.map(|e| PathBuf::from(e.path()))
@@ -191,6 +199,26 @@ impl PathIterBuilder for WalkDirPathIterBuilder {
     }
 }
 
+/// Hidden files and directories (like the per-store configuration file or a `.git` directory) are
+/// not entries and therefor not yielded when iterating the store
+fn is_hidden(e: &DirEntry) -> bool {
+    e.file_name()
+        .to_str()
+        .map(|s| s.starts_with("."))
+        .unwrap_or(false)
+}
+
+/// Read a file to a string, `None` if it does not exist
+pub(crate) fn read_file(path: &PathBuf) -> Result<Option<String>> {
+    match ::std::fs::read_to_string(path) {
+        Ok(s)  => Ok(Some(s)),
+        Err(e) => match e.kind() {
+            ::std::io::ErrorKind::NotFound => Ok(None),
+            _ => Err(e).context(EM::IO).map_err(Error::from),
+        },
+    }
+}
+
 fn open_file<A: AsRef<Path>>(p: A) -> ::std::io::Result<Option<File>> {
     match OpenOptions::new().write(true).read(true).open(p) {
         Err(e) => match e.kind() {
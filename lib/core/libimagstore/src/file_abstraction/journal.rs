@@ -0,0 +1,147 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2019 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+
+//! A filesystem backend which remembers the pre-images of all files it alters
+//!
+//! Before a file is written, removed or renamed for the first time, its content is recorded. This
+//! is used to undo imag commands.
+
+use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use libimagerror::errors::ErrorMsg as EM;
+
+use failure::Fallible as Result;
+use failure::Error;
+
+use super::FileAbstraction;
+use super::FileAbstractionInstance;
+use super::Drain;
+use super::fs::FSFileAbstraction;
+use super::fs::read_file;
+use store::Entry;
+use storeid::StoreIdWithBase;
+use file_abstraction::iter::PathIterator;
+
+/// The pre-images of the altered files, `None` if the file did not exist
+pub(crate) type JournalBackend = Arc<Mutex<BTreeMap<PathBuf, Option<String>>>>;
+
+fn record(backend: &JournalBackend, path: &PathBuf) -> Result<()> {
+    let mut pre_images = backend.lock().map_err(|_| Error::from(EM::LockError))?;
+    if !pre_images.contains_key(path) {
+        trace!("Recording pre-image of {:?}", path);
+        let _ = pre_images.insert(path.clone(), read_file(path)?);
+    }
+    Ok(())
+}
+
+#[derive(Debug)]
+pub struct JournalFileAbstractionInstance {
+    inner: Box<FileAbstractionInstance>,
+    path: PathBuf,
+    backend: JournalBackend,
+}
+
+impl FileAbstractionInstance for JournalFileAbstractionInstance {
+
+    fn get_file_content<'a>(&mut self, id: StoreIdWithBase<'a>) -> Result<Option<Entry>> {
+        self.inner.get_file_content(id)
+    }
+
+    fn write_file_content(&mut self, buf: &Entry) -> Result<()> {
+        record(&self.backend, &self.path)?;
+        self.inner.write_file_content(buf)
+    }
+}
+
+/// `FileAbstraction` which writes to the filesystem and records the pre-images of altered files
+#[derive(Debug, Default)]
+pub struct JournalFileAbstraction {
+    inner: FSFileAbstraction,
+    backend: JournalBackend,
+}
+
+impl JournalFileAbstraction {
+
+    pub(crate) fn backend(&self) -> &JournalBackend {
+        &self.backend
+    }
+
+}
+
+impl FileAbstraction for JournalFileAbstraction {
+
+    fn remove_file(&self, path: &PathBuf) -> Result<()> {
+        record(&self.backend, path)?;
+        self.inner.remove_file(path)
+    }
+
+    fn copy(&self, from: &PathBuf, to: &PathBuf) -> Result<()> {
+        record(&self.backend, to)?;
+        self.inner.copy(from, to)
+    }
+
+    fn rename(&self, from: &PathBuf, to: &PathBuf) -> Result<()> {
+        record(&self.backend, from)?;
+        record(&self.backend, to)?;
+        self.inner.rename(from, to)
+    }
+
+    fn create_dir_all(&self, path: &PathBuf) -> Result<()> {
+        self.inner.create_dir_all(path)
+    }
+
+    fn exists(&self, path: &PathBuf) -> Result<bool> {
+        self.inner.exists(path)
+    }
+
+    fn is_file(&self, path: &PathBuf) -> Result<bool> {
+        self.inner.is_file(path)
+    }
+
+    fn new_instance(&self, p: PathBuf) -> Box<FileAbstractionInstance> {
+        Box::new(JournalFileAbstractionInstance {
+            inner: self.inner.new_instance(p.clone()),
+            path: p,
+            backend: self.backend.clone(),
+        })
+    }
+
+    fn drain(&self) -> Result<Drain> {
+        self.inner.drain()
+    }
+
+    fn fill(&mut self, mut d: Drain) -> Result<()> {
+        d.iter().fold(Ok(()), |acc, (path, element)| {
+            acc.and_then(|_| self.new_instance(path).write_file_content(&element))
+        })
+    }
+
+    fn pathes_recursively<'a>(&self,
+                              basepath: PathBuf,
+                              storepath: &'a PathBuf,
+                              backend: Arc<FileAbstraction>)
+        -> Result<PathIterator<'a>>
+    {
+        self.inner.pathes_recursively(basepath, storepath, backend)
+    }
+}
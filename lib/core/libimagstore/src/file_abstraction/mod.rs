@@ -30,6 +30,8 @@ use storeid::StoreIdWithBase;
 pub mod fs;
 pub mod inmemory;
 pub mod iter;
+pub mod journal;
+pub mod overlay;
 
 use self::iter::PathIterator;
 
@@ -0,0 +1,284 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2019 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+
+//! A copy-on-write overlay over the filesystem
+//!
+//! All reading operations are served from the filesystem until the file was written. All writing
+//! operations only alter the in-memory layer, so nothing is ever written to disk.
+
+use std::path::PathBuf;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+
+use libimagerror::errors::ErrorMsg as EM;
+
+use failure::Fallible as Result;
+use failure::Error;
+
+use super::FileAbstraction;
+use super::FileAbstractionInstance;
+use super::Drain;
+use super::fs::FSFileAbstraction;
+use super::fs::WalkDirPathIterBuilder;
+use super::fs::read_file;
+use store::Entry;
+use storeid::StoreIdWithBase;
+use file_abstraction::iter::PathIterator;
+use file_abstraction::iter::PathIterBuilder;
+
+/// The state of the overlay
+///
+/// A path which maps to `None` was removed, a path which maps to `Some(_)` was written with the
+/// string as content.
+#[derive(Debug, Default)]
+pub(crate) struct OverlayState {
+    pub(crate) upper: HashMap<PathBuf, Option<String>>,
+
+    /// Renames, in the order they happened
+    pub(crate) renames: Vec<(PathBuf, PathBuf)>,
+}
+
+pub(crate) type OverlayBackend = Arc<Mutex<OverlayState>>;
+
+fn lock(backend: &OverlayBackend) -> Result<MutexGuard<OverlayState>> {
+    backend.lock().map_err(|_| Error::from(EM::LockError))
+}
+
+/// Read a file from the overlay, falling back to the filesystem
+fn read(backend: &OverlayBackend, path: &PathBuf) -> Result<Option<String>> {
+    if let Some(upper) = lock(backend)?.upper.get(path) {
+        return Ok(upper.clone())
+    }
+
+    read_file(path)
+}
+
+#[derive(Debug)]
+pub struct OverlayFileAbstractionInstance {
+    backend: OverlayBackend,
+    path: PathBuf,
+}
+
+impl FileAbstractionInstance for OverlayFileAbstractionInstance {
+
+    fn get_file_content<'a>(&mut self, id: StoreIdWithBase<'a>) -> Result<Option<Entry>> {
+        debug!("Getting overlay file: {:?}", self);
+        match read(&self.backend, &self.path)? {
+            Some(s) => Entry::from_str(id, &s).map(Some),
+            None    => Ok(None),
+        }
+    }
+
+    fn write_file_content(&mut self, buf: &Entry) -> Result<()> {
+        debug!("Writing overlay file: {:?}", self);
+        let buf = buf.to_str()?;
+        let _   = lock(&self.backend)?.upper.insert(self.path.clone(), Some(buf));
+        Ok(())
+    }
+}
+
+/// `FileAbstraction` which reads from the filesystem but keeps all writes in memory
+#[derive(Debug, Default)]
+pub struct OverlayFileAbstraction {
+    lower: FSFileAbstraction,
+    backend: OverlayBackend,
+}
+
+impl OverlayFileAbstraction {
+
+    pub(crate) fn backend(&self) -> &OverlayBackend {
+        &self.backend
+    }
+
+}
+
+impl FileAbstraction for OverlayFileAbstraction {
+
+    fn remove_file(&self, path: &PathBuf) -> Result<()> {
+        debug!("Removing (overlay): {:?}", path);
+        if !self.exists(path)? {
+            return Err(Error::from(EM::FileNotFound))
+        }
+
+        let _ = lock(&self.backend)?.upper.insert(path.clone(), None);
+        Ok(())
+    }
+
+    fn copy(&self, from: &PathBuf, to: &PathBuf) -> Result<()> {
+        debug!("Copying (overlay): {:?} -> {:?}", from, to);
+        let content = read(&self.backend, from)?.ok_or_else(|| EM::FileNotFound)?;
+        let _ = lock(&self.backend)?.upper.insert(to.clone(), Some(content));
+        Ok(())
+    }
+
+    fn rename(&self, from: &PathBuf, to: &PathBuf) -> Result<()> {
+        debug!("Renaming (overlay): {:?} -> {:?}", from, to);
+        let content = read(&self.backend, from)?.ok_or_else(|| EM::FileNotFound)?;
+        let mut state = lock(&self.backend)?;
+        let _ = state.upper.insert(from.clone(), None);
+        let _ = state.upper.insert(to.clone(), Some(content));
+        state.renames.push((from.clone(), to.clone()));
+        Ok(())
+    }
+
+    fn create_dir_all(&self, _: &PathBuf) -> Result<()> {
+        Ok(())
+    }
+
+    fn exists(&self, path: &PathBuf) -> Result<bool> {
+        match lock(&self.backend)?.upper.get(path) {
+            Some(upper) => Ok(upper.is_some()),
+            None        => self.lower.exists(path),
+        }
+    }
+
+    fn is_file(&self, path: &PathBuf) -> Result<bool> {
+        match lock(&self.backend)?.upper.get(path) {
+            Some(upper) => Ok(upper.is_some()),
+            None        => self.lower.is_file(path),
+        }
+    }
+
+    fn new_instance(&self, p: PathBuf) -> Box<FileAbstractionInstance> {
+        Box::new(OverlayFileAbstractionInstance {
+            backend: self.backend.clone(),
+            path: p,
+        })
+    }
+
+    /// We return nothing from the overlay here, as the filesystem backend does.
+    fn drain(&self) -> Result<Drain> {
+        Ok(Drain::empty())
+    }
+
+    fn fill(&mut self, mut d: Drain) -> Result<()> {
+        d.iter().fold(Ok(()), |acc, (path, element)| {
+            acc.and_then(|_| self.new_instance(path).write_file_content(&element))
+        })
+    }
+
+    fn pathes_recursively<'a>(&self,
+                              basepath: PathBuf,
+                              storepath: &'a PathBuf,
+                              backend: Arc<FileAbstraction>)
+        -> Result<PathIterator<'a>>
+    {
+        trace!("Building PathIterator object (overlay implementation)");
+        let builder = OverlayPathIterBuilder {
+            lower: WalkDirPathIterBuilder::new(basepath.clone()),
+            basepath,
+            backend: self.backend.clone(),
+        };
+
+        // Removed files are filtered by the PathIterator, as it checks `is_file()` on the overlay
+        Ok(PathIterator::new(Box::new(builder), storepath, backend))
+    }
+}
+
+#[derive(Debug)]
+pub struct OverlayPathIterBuilder {
+    lower: WalkDirPathIterBuilder,
+    basepath: PathBuf,
+    backend: OverlayBackend,
+}
+
+impl PathIterBuilder for OverlayPathIterBuilder {
+    fn build_iter(&self) -> Box<Iterator<Item = Result<PathBuf>>> {
+        let created = match lock(&self.backend) {
+            Err(e)    => return Box::new(::std::iter::once(Err(e))),
+            Ok(state) => state
+                .upper
+                .iter()
+                .filter(|&(path, content)| {
+                    content.is_some() && path.starts_with(&self.basepath) && !path.exists()
+                })
+                .map(|(path, _)| Ok(path.clone()))
+                .collect::<Vec<_>>(),
+        };
+
+        Box::new(self.lower.build_iter().chain(created.into_iter()))
+    }
+
+    fn in_collection(&mut self, c: &str) {
+        debug!("Altering PathIterBuilder path with: {:?}", c);
+        self.lower.in_collection(c);
+        self.basepath.push(c);
+        debug!(" -> path : {:?}", self.basepath);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use tempdir::TempDir;
+
+    use super::*;
+
+    fn entry_text(content: &str) -> String {
+        format!("---\n[imag]\nversion = \"{}\"\n---\n{}", env!("CARGO_PKG_VERSION"), content)
+    }
+
+    #[test]
+    fn test_write_does_not_touch_disk() {
+        let dir  = TempDir::new("imag-overlay").unwrap();
+        let path = dir.path().join("entry");
+        ::std::fs::write(&path, entry_text("old")).unwrap();
+
+        let base    = dir.path().to_path_buf();
+        let overlay = OverlayFileAbstraction::default();
+        let id      = StoreIdWithBase::new(&base, PathBuf::from("entry"));
+
+        let mut instance = overlay.new_instance(path.clone());
+        let mut entry    = instance.get_file_content(id.clone()).unwrap().unwrap();
+        assert_eq!(entry.get_content(), "old");
+
+        *entry.get_content_mut() = String::from("new");
+        instance.write_file_content(&entry).unwrap();
+
+        let entry = instance.get_file_content(id).unwrap().unwrap();
+        assert_eq!(entry.get_content(), "new");
+        assert_eq!(::std::fs::read_to_string(&path).unwrap(), entry_text("old"));
+    }
+
+    #[test]
+    fn test_remove_and_rename() {
+        let dir = TempDir::new("imag-overlay").unwrap();
+        let a   = dir.path().join("a");
+        let b   = dir.path().join("b");
+        let c   = dir.path().join("c");
+        ::std::fs::write(&a, entry_text("a")).unwrap();
+        ::std::fs::write(&b, entry_text("b")).unwrap();
+
+        let overlay = OverlayFileAbstraction::default();
+        overlay.remove_file(&a).unwrap();
+        overlay.rename(&b, &c).unwrap();
+
+        assert!(!overlay.exists(&a).unwrap());
+        assert!(!overlay.exists(&b).unwrap());
+        assert!(overlay.is_file(&c).unwrap());
+        assert!(a.exists());
+        assert!(b.exists());
+        assert!(!c.exists());
+        assert!(overlay.remove_file(&a).is_err());
+    }
+}
@@ -0,0 +1,125 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2019 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+
+//! Recording the changes a store makes on the filesystem
+//!
+//! A store created with `Store::new_journaled()` writes to disk as usual, but remembers the
+//! content of each file before it was altered for the first time. The `Journal` object which is
+//! returned alongside the store can be used to get these pre-images and the current content of the
+//! files, for example to undo a command.
+
+use std::path::PathBuf;
+
+use failure::Fallible as Result;
+use failure::Error;
+
+use libimagerror::errors::ErrorMsg as EM;
+
+use file_abstraction::journal::JournalBackend;
+use file_abstraction::fs::read_file;
+use storeid::StoreId;
+use storeid::StoreIdWithBase;
+
+/// A handle to the pre-images recorded by a journaled store
+#[derive(Debug, Clone)]
+pub struct Journal {
+    base: PathBuf,
+    backend: JournalBackend,
+}
+
+/// A file which was altered by the store
+///
+/// `None` means that the file did not exist (before or after the change).
+#[derive(Debug, Clone, PartialEq)]
+pub struct JournalChange {
+    pub id: StoreId,
+    pub pre: Option<String>,
+    pub post: Option<String>,
+}
+
+impl Journal {
+
+    pub(crate) fn new(base: PathBuf, backend: JournalBackend) -> Journal {
+        Journal { base, backend }
+    }
+
+    /// Get the files which were altered, ordered by id
+    ///
+    /// Files which were written without being altered are not reported.
+    pub fn changes(&self) -> Result<Vec<JournalChange>> {
+        let pre_images = self.backend.lock().map_err(|_| Error::from(EM::LockError))?;
+
+        pre_images
+            .iter()
+            .filter_map(|(path, pre)| match read_file(path) {
+                Err(e)                      => Some(Err(e)),
+                Ok(ref post) if post == pre => None,
+                Ok(post)                    => Some({
+                    StoreIdWithBase::from_full_path(&self.base, path.as_path())
+                        .map(|id| JournalChange { id: id.into(), pre: pre.clone(), post })
+                }),
+            })
+            .collect()
+    }
+
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use tempdir::TempDir;
+
+    use store::Store;
+    use storeid::StoreId;
+
+    #[test]
+    fn test_journal_records_pre_images() {
+        let dir = TempDir::new("imag-journal").unwrap();
+        let original = format!("---\n[imag]\nversion = \"{}\"\n---\noriginal", env!("CARGO_PKG_VERSION"));
+        ::std::fs::write(dir.path().join("a"), &original).unwrap();
+
+        let (store, journal) = Store::new_journaled(dir.path().to_path_buf(), &None).unwrap();
+
+        let a = StoreId::new(PathBuf::from("a")).unwrap();
+        let b = StoreId::new(PathBuf::from("b")).unwrap();
+        let c = StoreId::new(PathBuf::from("c")).unwrap();
+
+        {
+            let mut entry = store.retrieve(a.clone()).unwrap();
+            *entry.get_content_mut() = String::from("altered");
+        }
+
+        {
+            let _ = store.create(b.clone()).unwrap();
+            let _ = store.retrieve(c.clone()).unwrap();
+        }
+        store.delete(c).unwrap(); // created and deleted, so not reported
+
+        let changes = journal.changes().unwrap();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].id, a);
+        assert_eq!(changes[0].pre, Some(original));
+        assert!(changes[0].post.as_ref().map(|s| s.ends_with("altered")).unwrap_or(false));
+        assert_eq!(changes[1].id, b);
+        assert_eq!(changes[1].pre, None);
+        assert!(changes[1].post.is_some());
+    }
+}
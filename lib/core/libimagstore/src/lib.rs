@@ -45,6 +45,7 @@ extern crate toml;
 extern crate semver;
 extern crate walkdir;
 #[macro_use] extern crate is_match;
+extern crate serde;
 extern crate serde_json;
 #[macro_use] extern crate failure;
 extern crate toml_query;
@@ -57,6 +58,8 @@ extern crate libimagutil;
 pub mod storeid;
 pub mod iter;
 pub mod store;
+pub mod dryrun;
+pub mod journal;
 mod configuration;
 mod file_abstraction;
 
@@ -32,14 +32,20 @@ use std::fmt::Debug;
 use std::fmt::Error as FMTError;
 
 use libimagerror::errors::ErrorMsg as EM;
+use libimagerror::kind::ErrorKind;
+use libimagerror::kind::kind_err;
+use libimagerror::kind::KindError;
 
 use toml::Value;
+use toml_query::insert::TomlValueInsertExt;
 use toml_query::read::TomlValueReadExt;
 use toml_query::read::TomlValueReadTypeExt;
 use failure::Fallible as Result;
 use failure::ResultExt;
 use failure::err_msg;
 use failure::Error;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
 
 use storeid::{IntoStoreId, StoreId};
 use iter::Entries;
@@ -47,6 +53,10 @@ use file_abstraction::FileAbstraction;
 use file_abstraction::FileAbstractionInstance;
 use file_abstraction::fs::FSFileAbstraction;
 use file_abstraction::inmemory::InMemoryFileAbstraction;
+use file_abstraction::overlay::OverlayFileAbstraction;
+use dryrun::DryRun;
+use file_abstraction::journal::JournalFileAbstraction;
+use journal::Journal;
 
 use libimagutil::debug_result::*;
 
@@ -100,7 +110,7 @@ impl StoreEntry {
                 None       => Ok(Entry::new(self.id.clone()))
             }
         } else {
-            Err(format_err!("EntryAlreadyBorrowed: {}", self.id))
+            Err(kind_err(ErrorKind::Borrowed, format!("EntryAlreadyBorrowed: {}", self.id)))
         }
     }
 
@@ -181,6 +191,27 @@ impl Store {
         Self::new_with_backend(location, store_config, backend)
     }
 
+    /// Create the store with a copy-on-write overlay over the filesystem
+    ///
+    /// Entries are read from the filesystem, but nothing is ever written to disk. The returned
+    /// `DryRun` object can be used to inspect the changes which would have been made.
+    pub fn new_dry_run(location: PathBuf, store_config: &Option<Value>) -> Result<(Store, DryRun)> {
+        let overlay = OverlayFileAbstraction::default();
+        let dry_run = DryRun::new(location.clone(), overlay.backend().clone());
+        let backend = Arc::new(overlay);
+        Self::new_with_backend(location, store_config, backend).map(|store| (store, dry_run))
+    }
+
+    /// Create the store with a backend which records the pre-images of all altered files
+    ///
+    /// The store writes to disk as `Store::new()` does. The returned `Journal` object can be used
+    /// to inspect which files were altered and what they looked like before.
+    pub fn new_journaled(location: PathBuf, store_config: &Option<Value>) -> Result<(Store, Journal)> {
+        let backend = JournalFileAbstraction::default();
+        let journal = Journal::new(location.clone(), backend.backend().clone());
+        Self::new_with_backend(location, store_config, Arc::new(backend)).map(|store| (store, journal))
+    }
+
     /// Create a Store object as descripbed in `Store::new()` documentation, but with an alternative
     /// backend implementation.
     ///
@@ -193,7 +224,7 @@ impl Store {
         debug!("Building new Store object");
         if !location.exists() {
             if !config_implicit_store_create_allowed(store_config)? {
-                return Err(format_err!("CreateStoreDirDenied"))
+                return Err(kind_err(ErrorKind::Config, "CreateStoreDirDenied"))
                     .context(EM::FileError)
                     .context(EM::IO)
                     .map_err(Error::from)
@@ -205,7 +236,8 @@ impl Store {
                 .map_dbg_err_str("Failed")?;
         } else if location.is_file() {
             debug!("Store path exists as file");
-            return Err(format_err!("StorePathExists: {}", location.display()));
+            return Err(kind_err(ErrorKind::AlreadyExists,
+                                format!("StorePathExists: {}", location.display())));
         }
 
         let store = Store {
@@ -237,7 +269,7 @@ impl Store {
 
         if exists {
             debug!("Entry exists: {:?}", id);
-            return Err(format_err!("EntryAlreadyExists: {}", id));
+            return Err(kind_err(ErrorKind::AlreadyExists, format!("EntryAlreadyExists: {}", id)));
         }
 
         {
@@ -249,7 +281,7 @@ impl Store {
 
             if hsmap.contains_key(&id) {
                 debug!("Cannot create, internal cache already contains: '{}'", id);
-                return Err(format_err!("EntryAlreadyExists: {}", id))
+                return Err(kind_err(ErrorKind::AlreadyExists, format!("EntryAlreadyExists: {}", id)))
                            .context(format_err!("CreateCallError: {}", id))
                            .map_err(Error::from)
             }
@@ -462,7 +494,7 @@ impl Store {
 
             let do_remove = match entries.get(&id) {
                 Some(e) => if e.is_borrowed() { // entry is currently borrowed, we cannot delete it
-                    return Err(Error::from(EM::LockError))
+                    return Err(kind_err(ErrorKind::Borrowed, format!("EntryAlreadyBorrowed: {}", id)))
                         .context(format_err!("DeleteCallError: {}", id))
                         .map_err(Error::from)
                     // false
@@ -525,7 +557,7 @@ impl Store {
             .context(format_err!("MoveCallError: {} -> {}", entry.get_location(), new_id))?;
 
         if hsmap.contains_key(&new_id) {
-            return Err(format_err!("Entry exists already: {}", new_id.clone()))
+            return Err(kind_err(ErrorKind::AlreadyExists, format!("Entry exists already: {}", new_id)))
                 .context(format_err!("MoveCallError: {} -> {}", entry.get_location(), new_id))
                 .map_err(Error::from)
         }
@@ -588,7 +620,7 @@ impl Store {
                 .map_err(|_| Error::from(EM::LockError))?;
 
             if hsmap.contains_key(&new_id) {
-                return Err(format_err!("Entry already exists: {}", new_id));
+                return Err(kind_err(ErrorKind::AlreadyExists, format!("Entry already exists: {}", new_id)));
             }
             debug!("New id does not exist in cache");
 
@@ -596,7 +628,7 @@ impl Store {
             // if we have one, but it is borrowed, we really should not rename it, as this might
             // lead to strange errors
             if hsmap.get(&old_id).map(|e| e.is_borrowed()).unwrap_or(false) {
-                return Err(format_err!("Entry already borrowed: {}", old_id));
+                return Err(kind_err(ErrorKind::Borrowed, format!("Entry already borrowed: {}", old_id)));
             }
 
             debug!("Old id is not yet borrowed");
@@ -605,7 +637,7 @@ impl Store {
             let new_id_pb = new_id.clone().with_base(self.path()).into_pathbuf()?;
 
             if self.backend.exists(&new_id_pb)? {
-                return Err(format_err!("Entry already exists: {}", new_id));
+                return Err(kind_err(ErrorKind::AlreadyExists, format!("Entry already exists: {}", new_id)));
             }
             debug!("New entry does not yet exist on filesystem. Good.");
 
@@ -851,6 +883,97 @@ impl Entry {
         &mut self.header
     }
 
+    /// Deserialize the header section at `path` into a `T`
+    ///
+    /// `path` is a `toml-query` path, like `"habit.template"`. Returns `Ok(None)` if there is no
+    /// value at `path`. If the value cannot be deserialized, the error names the entry and the
+    /// path.
+    pub fn get_section<T: DeserializeOwned>(&self, path: &str) -> Result<Option<T>> {
+        let value = match self.header.read(path).map_err(Error::from).context(EM::TomlQueryError)? {
+            Some(value) => value.clone(),
+            None        => return Ok(None),
+        };
+
+        value.try_into::<T>()
+            .map(Some)
+            .map_err(|e| self.section_error(Error::from(e), "read", path))
+    }
+
+    /// Same as `Entry::get_section()`, but fails with a `NotFound` error if there is no value at
+    /// `path`
+    pub fn require_section<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        self.get_section(path)?.ok_or_else(|| {
+            let msg = format!("Header section '{}' missing in entry {}", path, self.location);
+            kind_err(ErrorKind::NotFound, msg)
+        })
+    }
+
+    /// Serialize `section` and write it to the header at `path`
+    ///
+    /// Tables on the way to `path` are created if necessary. A value which is already at `path`
+    /// is replaced, so fields of `T` which are not serialized (like `None` values) are removed
+    /// from the header. Use `Entry::merge_section()` to keep keys which are not part of `T`.
+    pub fn set_section<T: Serialize + ?Sized>(&mut self, path: &str, section: &T) -> Result<()> {
+        let value = self.section_value(path, section)?;
+
+        self.header
+            .insert(path, value)
+            .map(|_| ())
+            .map_err(Error::from)
+            .context(EM::TomlQueryError)
+            .map_err(Error::from)
+    }
+
+    /// Same as `Entry::set_section()`, but if there is a table at `path` already and `section`
+    /// serializes to a table, the keys of `section` are merged into it
+    ///
+    /// Keys which are not part of `T` (for example kind flags) are kept, as are keys of `T` which
+    /// are not serialized.
+    pub fn merge_section<T: Serialize + ?Sized>(&mut self, path: &str, section: &T) -> Result<()> {
+        let value = self.section_value(path, section)?;
+
+        if let Value::Table(ref new) = value {
+            let old = self.header.read_mut(path).map_err(Error::from).context(EM::TomlQueryError)?;
+            if let Some(&mut Value::Table(ref mut old)) = old {
+                for (k, v) in new {
+                    let _ = old.insert(k.clone(), v.clone());
+                }
+                return Ok(())
+            }
+        }
+
+        self.set_section(path, &value)
+    }
+
+    /// Remove the header section at `path`, including all values in it
+    ///
+    /// Returns the removed value, if there was one.
+    pub fn delete_section(&mut self, path: &str) -> Result<Option<Value>> {
+        let (parent, key) = match path.rfind('.') {
+            Some(i) => (Some(&path[..i]), &path[i + 1..]),
+            None    => (None, path),
+        };
+
+        let parent = match parent {
+            Some(p) => self.header.read_mut(p).map_err(Error::from).context(EM::TomlQueryError)?,
+            None    => Some(&mut self.header),
+        };
+
+        match parent {
+            Some(&mut Value::Table(ref mut t)) => Ok(t.remove(key)),
+            _                                  => Ok(None),
+        }
+    }
+
+    fn section_value<T: Serialize + ?Sized>(&self, path: &str, section: &T) -> Result<Value> {
+        Value::try_from(section).map_err(|e| self.section_error(Error::from(e), "write", path))
+    }
+
+    fn section_error(&self, e: Error, what: &str, path: &str) -> Error {
+        let msg = format!("Failed to {} header section '{}' of entry {}", what, path, self.location);
+        Error::from(e.context(KindError::new(ErrorKind::Parse, msg)))
+    }
+
     /// Get the content of the Entry
     pub fn get_content(&self) -> &EntryContent {
         &self.content
@@ -876,12 +999,12 @@ impl Entry {
     /// Currently, this only verifies the header. This might change in the future.
     pub fn verify(&self) -> Result<()> {
         if !has_main_section(&self.header)? {
-            Err(format_err!("MissingMainSection"))
+            Err(kind_err(ErrorKind::Parse, "MissingMainSection"))
         } else if !has_imag_version_in_main_section(&self.header)? {
-            Err(format_err!("MissingVersionInfo"))
+            Err(kind_err(ErrorKind::Parse, "MissingVersionInfo"))
         } else if !has_only_tables(&self.header)? {
             debug!("Could not verify that it only has tables in its base table");
-            Err(format_err!("NonTableInBaseTable"))
+            Err(kind_err(ErrorKind::Parse, "NonTableInBaseTable"))
         } else {
             Ok(())
         }
@@ -903,7 +1026,7 @@ fn has_only_tables(t: &Value) -> Result<bool> {
     debug!("Verifying that table has only tables");
     match *t {
         Value::Table(ref tab) => Ok(tab.iter().all(|(_, x)| is_match!(*x, Value::Table(_)))),
-        _ => Err(format_err!("HeaderTypeFailure")),
+        _ => Err(kind_err(ErrorKind::Parse, "HeaderTypeFailure")),
     }
 }
 
@@ -911,7 +1034,7 @@ fn has_main_section(t: &Value) -> Result<bool> {
     t.read("imag")
         .map_err(Error::from)
         .context(EM::TomlQueryError)?
-        .ok_or_else(|| format_err!("ConfigKeyMissingError('imag')"))
+        .ok_or_else(|| kind_err(ErrorKind::Parse, "ConfigKeyMissingError('imag')"))
         .map(Value::is_table)
 }
 
@@ -919,7 +1042,7 @@ fn has_imag_version_in_main_section(t: &Value) -> Result<bool> {
     t.read_string("imag.version")
         .map_err(Error::from)
         .context(EM::TomlQueryError)?
-        .ok_or_else(|| format_err!("ConfigKeyMissingError('imag.version')"))
+        .ok_or_else(|| kind_err(ErrorKind::Parse, "ConfigKeyMissingError('imag.version')"))
         .map_err(Error::from)
         .map(String::from)
         .map(|s: String| ::semver::Version::parse(&s).is_ok())
@@ -999,6 +1122,101 @@ Hai
 
 ";
 
+    static TEST_SECTION_ENTRY : &'static str = "---
+[imag]
+version = '0.0.3'
+
+[test.section]
+a = 1
+b = 2
+is_test = true
+---
+";
+
+    #[test]
+    fn test_entry_get_section() {
+        use super::Entry;
+        use std::path::PathBuf;
+
+        let entry = Entry::from_str(StoreId::new(PathBuf::from("test/section")).unwrap(),
+                                    TEST_SECTION_ENTRY).unwrap();
+
+        let section : Option<BTreeMap<String, Value>> = entry.get_section("test.section").unwrap();
+        assert_eq!(3, section.unwrap().len());
+
+        let a : i64 = entry.require_section("test.section.a").unwrap();
+        assert_eq!(1, a);
+
+        let missing : Option<i64> = entry.get_section("test.missing").unwrap();
+        assert!(missing.is_none());
+        assert!(entry.require_section::<i64>("test.missing").is_err());
+
+        let err = entry.get_section::<BTreeMap<String, i64>>("test.section").unwrap_err();
+        let msg = format!("{}", err);
+        assert!(msg.contains("test.section"), "Error message should name the path: {}", msg);
+        assert!(msg.contains("test/section"), "Error message should name the entry: {}", msg);
+    }
+
+    #[test]
+    fn test_entry_set_section_replaces_tables() {
+        use super::Entry;
+        use std::path::PathBuf;
+
+        let mut entry = Entry::from_str(StoreId::new(PathBuf::from("test/section")).unwrap(),
+                                        TEST_SECTION_ENTRY).unwrap();
+
+        let mut section = BTreeMap::new();
+        section.insert(String::from("a"), 3);
+        section.insert(String::from("c"), 4);
+        entry.set_section("test.section", &section).unwrap();
+
+        let section : BTreeMap<String, Value> = entry.require_section("test.section").unwrap();
+        assert_eq!(2, section.len());
+        assert_eq!(Some(&Value::Integer(3)), section.get("a"));
+        assert_eq!(Some(&Value::Integer(4)), section.get("c"));
+
+        entry.set_section("other.value", &String::from("foo")).unwrap();
+        assert_eq!("foo", entry.require_section::<String>("other.value").unwrap());
+    }
+
+    #[test]
+    fn test_entry_merge_section_merges_tables() {
+        use super::Entry;
+        use std::path::PathBuf;
+
+        let mut entry = Entry::from_str(StoreId::new(PathBuf::from("test/section")).unwrap(),
+                                        TEST_SECTION_ENTRY).unwrap();
+
+        let mut section = BTreeMap::new();
+        section.insert(String::from("a"), 3);
+        section.insert(String::from("c"), 4);
+        entry.merge_section("test.section", &section).unwrap();
+
+        let section : BTreeMap<String, Value> = entry.require_section("test.section").unwrap();
+        assert_eq!(Some(&Value::Integer(3)), section.get("a"));
+        assert_eq!(Some(&Value::Integer(2)), section.get("b"));
+        assert_eq!(Some(&Value::Integer(4)), section.get("c"));
+        assert_eq!(Some(&Value::Boolean(true)), section.get("is_test"));
+
+        entry.merge_section("new.section", &section).unwrap();
+        assert_eq!(section, entry.require_section::<BTreeMap<String, Value>>("new.section").unwrap());
+    }
+
+    #[test]
+    fn test_entry_delete_section() {
+        use super::Entry;
+        use std::path::PathBuf;
+
+        let mut entry = Entry::from_str(StoreId::new(PathBuf::from("test/section")).unwrap(),
+                                        TEST_SECTION_ENTRY).unwrap();
+
+        assert_eq!(Some(Value::Integer(1)), entry.delete_section("test.section.a").unwrap());
+        assert!(entry.delete_section("test.section").unwrap().is_some());
+        assert!(entry.get_section::<Value>("test.section").unwrap().is_none());
+        assert!(entry.delete_section("test.section").unwrap().is_none());
+        assert!(entry.delete_section("nonexistent.section").unwrap().is_none());
+    }
+
     #[test]
     fn test_entry_from_str() {
         use super::Entry;
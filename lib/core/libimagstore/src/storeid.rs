@@ -31,6 +31,9 @@ use failure::Fallible as Result;
 use failure::err_msg;
 use failure::Error;
 
+use libimagerror::kind::ErrorKind;
+use libimagerror::kind::kind_err;
+
 use store::Store;
 
 use iter::create::StoreCreateIterator;
@@ -52,7 +55,7 @@ impl StoreId {
         debug!("Trying to get a new baseless id from: {:?}", id);
         if id.is_absolute() {
             debug!("Error: Id is absolute!");
-            Err(format_err!("Store Id local part is absolute: {}", id.display()))
+            Err(kind_err(ErrorKind::Parse, format!("Store Id local part is absolute: {}", id.display())))
         } else {
             debug!("Building Storeid object baseless");
             Ok(StoreId(id))
@@ -133,6 +136,12 @@ impl Display for StoreId {
 
 }
 
+impl AsRef<StoreId> for StoreId {
+    fn as_ref(&self) -> &StoreId {
+        self
+    }
+}
+
 /// This Trait allows you to convert various representations to a single one
 /// suitable for usage in the Store
 pub trait IntoStoreId {
@@ -25,6 +25,7 @@
 //! The BookmarkCollection type offers helper functions to get all links or such things.
 
 use regex::Regex;
+use url::Url;
 
 use failure::Fallible as Result;
 use failure::Error;
@@ -39,6 +40,9 @@ use libimagentrylink::external::ExternalLinker;
 use libimagentrylink::external::iter::UrlIter;
 use libimagentrylink::internal::InternalLinker;
 use libimagentrylink::internal::Link as StoreLink;
+use libimagentrylink::check::LinkChecker;
+use libimagentrylink::check::LinkCheckResult;
+use libimagentrylink::archive::Archiver;
 
 use link::Link;
 
@@ -81,6 +85,9 @@ pub trait BookmarkCollection : Sized + InternalLinker + ExternalLinker {
     fn add_link(&mut self, store: &Store, l: Link)               -> Result<Vec<StoreId>>;
     fn get_links_matching<'a>(&self, store: &'a Store, r: Regex) -> Result<LinksMatchingRegexIter<'a>>;
     fn remove_link(&mut self, store: &Store, l: Link)            -> Result<Vec<StoreId>>;
+    fn check_links(&self, store: &Store, c: &LinkChecker)        -> Result<Vec<(StoreId, LinkCheckResult)>>;
+    fn broken_links(&self, store: &Store)                        -> Result<Vec<(Url, LinkCheckResult)>>;
+    fn archive_links(&self, store: &Store, a: &Archiver)         -> Result<Vec<StoreId>>;
 }
 
 impl BookmarkCollection for Entry {
@@ -109,6 +116,43 @@ impl BookmarkCollection for Entry {
         l.into_url().and_then(|url| self.remove_external_link(store, url))
     }
 
+    fn check_links(&self, store: &Store, c: &LinkChecker) -> Result<Vec<(StoreId, LinkCheckResult)>> {
+        let ids = self.link_entries()?
+            .into_iter()
+            .map(|l| l.get_store_id().clone())
+            .collect();
+
+        c.check_entries(store, ids)
+    }
+
+    fn broken_links(&self, store: &Store) -> Result<Vec<(Url, LinkCheckResult)>> {
+        use libimagentrylink::external::Link as ExternalLink;
+        use libimagentrylink::check::get_check_result;
+
+        let mut broken = Vec::new();
+        for link in self.link_entries()? {
+            let entry = match store.get(link.get_store_id().clone())? {
+                Some(entry) => entry,
+                None        => continue,
+            };
+
+            if let (Some(url), Some(result)) = (entry.get_url()?, get_check_result(&entry)?) {
+                if result.is_broken() {
+                    broken.push((url, result));
+                }
+            }
+        }
+
+        Ok(broken)
+    }
+
+    fn archive_links(&self, store: &Store, a: &Archiver) -> Result<Vec<StoreId>> {
+        self.link_entries()?
+            .into_iter()
+            .map(|l| a.archive(store, l.get_store_id()).map(|e| e.get_location().clone()))
+            .collect()
+    }
+
 }
 
 pub mod iter {
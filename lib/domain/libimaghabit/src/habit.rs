@@ -17,9 +17,6 @@
 // Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
 //
 
-use toml::Value;
-use toml_query::read::TomlValueReadTypeExt;
-use toml_query::insert::TomlValueInsertExt;
 use chrono::NaiveDateTime;
 use chrono::Local;
 use chrono::NaiveDate;
@@ -29,8 +26,9 @@ use failure::err_msg;
 
 use iter::HabitInstanceStoreIdIterator;
 use util::IsHabitCheck;
-use util::get_string_header_from_entry;
 use instance::IsHabitInstance;
+use instance::HabitInstanceHeader;
+use instance::HABIT_INSTANCE_HEADER_PATH;
 
 use libimagentrylink::internal::InternalLinker;
 use libimagstore::store::Store;
@@ -81,6 +79,9 @@ pub trait HabitTemplate : Sized {
     /// Check whether the instance is a habit by checking its headers for the habit data
     fn is_habit_template(&self) -> Result<bool>;
 
+    /// Get the `habit.template` header section
+    fn habit_template_header(&self) -> Result<HabitTemplateHeader>;
+
     fn habit_name(&self) -> Result<String>;
     fn habit_basedate(&self) -> Result<String>;
     fn habit_recur_spec(&self) -> Result<String>;
@@ -95,6 +96,22 @@ pub trait HabitTemplate : Sized {
 
 provide_kindflag_path!(pub IsHabitTemplate, "habit.template.is_habit_template");
 
+pub const HABIT_TEMPLATE_HEADER_PATH : &'static str = "habit.template";
+
+/// The `habit.template` header section of a habit template
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HabitTemplateHeader {
+    pub name: String,
+    pub basedate: String,
+    pub recurspec: String,
+
+    #[serde(default)]
+    pub comment: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub until: Option<String>,
+}
+
 impl HabitTemplate for Entry {
 
     fn create_instance_with_date<'a>(&mut self, store: &'a Store, date: &NaiveDate) -> Result<FileLockEntry<'a>> {
@@ -212,27 +229,28 @@ impl HabitTemplate for Entry {
         self.is::<IsHabitTemplate>().map_err(From::from)
     }
 
+    fn habit_template_header(&self) -> Result<HabitTemplateHeader> {
+        self.require_section(HABIT_TEMPLATE_HEADER_PATH)
+    }
+
     fn habit_name(&self) -> Result<String> {
-        get_string_header_from_entry(self, "habit.template.name")
+        self.habit_template_header().map(|h| h.name)
     }
 
     fn habit_basedate(&self) -> Result<String> {
-        get_string_header_from_entry(self, "habit.template.basedate")
+        self.habit_template_header().map(|h| h.basedate)
     }
 
     fn habit_recur_spec(&self) -> Result<String> {
-        get_string_header_from_entry(self, "habit.template.recurspec")
+        self.habit_template_header().map(|h| h.recurspec)
     }
 
     fn habit_comment(&self) -> Result<String> {
-        get_string_header_from_entry(self, "habit.template.comment")
+        self.habit_template_header().map(|h| h.comment)
     }
 
     fn habit_until_date(&self) -> Result<Option<String>> {
-        self.get_header()
-            .read_string("habit.template.until")
-            .map_err(From::from)
-            .map(|os| os.map(String::from))
+        self.habit_template_header().map(|h| h.until)
     }
 
     fn instance_exists_for_date(&self, date: &NaiveDate) -> Result<bool> {
@@ -266,8 +284,6 @@ fn instance_id_for_name_and_datestr(habit_name: &String, habit_date: &String) ->
 }
 
 pub mod builder {
-    use toml::Value;
-    use toml_query::insert::TomlValueInsertExt;
     use chrono::NaiveDate;
 
     use libimagstore::store::Store;
@@ -283,6 +299,8 @@ pub mod builder {
 
     use libimagutil::date::date_to_string;
     use habit::IsHabitTemplate;
+    use habit::HabitTemplateHeader;
+    use habit::HABIT_TEMPLATE_HEADER_PATH;
 
     #[derive(Debug)]
     pub struct HabitBuilder {
@@ -360,18 +378,15 @@ pub mod builder {
             let mut entry = try!(store.create(sid));
 
             let _ = entry.set_isflag::<IsHabitTemplate>()?;
-            {
-                let h = entry.get_header_mut();
-                let _ = h.insert("habit.template.name", Value::String(name))?;
-                let _ = h.insert("habit.template.basedate", Value::String(date))?;
-                let _ = h.insert("habit.template.recurspec", Value::String(recur))?;
-                let _ = h.insert("habit.template.comment", Value::String(comment))?;
-            }
 
-            if let Some(until) = self.untildate {
-                let until = date_to_string(&until);
-                try!(entry.get_header_mut().insert("habit.template.until", Value::String(until)));
-            }
+            let header = HabitTemplateHeader {
+                name,
+                basedate: date,
+                recurspec: recur,
+                comment,
+                until: self.untildate.as_ref().map(date_to_string),
+            };
+            let _ = entry.merge_section(HABIT_TEMPLATE_HEADER_PATH, &header)?;
 
             debug!("Success: Created entry in store and set headers");
             Ok(entry)
@@ -406,13 +421,8 @@ fn postprocess_instance<'a>(mut entry: FileLockEntry<'a>,
                             template: &mut Entry)
     -> Result<FileLockEntry<'a>>
 {
-    {
-        let _   = entry.set_isflag::<IsHabitInstance>()?;
-        let hdr = entry.get_header_mut();
-        let _   = hdr.insert("habit.instance.name",    Value::String(name))?;
-        let _   = hdr.insert("habit.instance.date",    Value::String(date))?;
-        let _   = hdr.insert("habit.instance.comment", Value::String(comment))?;
-    }
+    let _ = entry.set_isflag::<IsHabitInstance>()?;
+    let _ = entry.merge_section(HABIT_INSTANCE_HEADER_PATH, &HabitInstanceHeader { name, date, comment })?;
 
     entry.add_internal_link(template)?;
 
@@ -18,12 +18,8 @@
 //
 
 use chrono::NaiveDate;
-use toml::Value;
-use toml_query::set::TomlValueSetExt;
 use failure::Fallible as Result;
 
-use util::*;
-
 use libimagstore::store::Entry;
 use libimagentryutil::isa::Is;
 use libimagentryutil::isa::IsKindHeaderPathProvider;
@@ -39,6 +35,9 @@ pub trait HabitInstance {
     /// data
     fn is_habit_instance(&self) -> Result<bool>;
 
+    /// Get the `habit.instance` header section
+    fn get_instance_header(&self) -> Result<HabitInstanceHeader>;
+
     fn get_date(&self) -> Result<NaiveDate>;
     fn set_date(&mut self, n: &NaiveDate) -> Result<()>;
     fn get_comment(&self) -> Result<String>;
@@ -48,40 +47,49 @@ pub trait HabitInstance {
 
 provide_kindflag_path!(pub IsHabitInstance, "habit.instance.is_habit_instance");
 
+pub const HABIT_INSTANCE_HEADER_PATH : &'static str = "habit.instance";
+
+/// The `habit.instance` header section of a habit instance
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HabitInstanceHeader {
+    /// The name of the template this is an instance of
+    pub name: String,
+    pub date: String,
+
+    #[serde(default)]
+    pub comment: String,
+}
+
 impl HabitInstance for Entry {
     fn is_habit_instance(&self) -> Result<bool> {
         self.is::<IsHabitInstance>().map_err(From::from)
     }
 
+    fn get_instance_header(&self) -> Result<HabitInstanceHeader> {
+        self.require_section(HABIT_INSTANCE_HEADER_PATH)
+    }
+
     fn get_date(&self) -> Result<NaiveDate> {
-        use libimagutil::date::date_from_string as dts;
-        let date_from_string = |d| dts(d).map_err(From::from);
-        get_string_header_from_entry(self, "habit.instance.date").and_then(date_from_string)
+        use libimagutil::date::date_from_string;
+        self.get_instance_header()
+            .and_then(|h| date_from_string(h.date).map_err(From::from))
     }
 
     fn set_date(&mut self, n: &NaiveDate) -> Result<()> {
         use libimagutil::date::date_to_string;
-        // Using `set` here because when creating the entry, these headers should be made present.
-        self.get_header_mut()
-            .set("habit.instance.date", Value::String(date_to_string(n)))
-            .map_err(From::from)
-            .map(|_| ())
+        self.set_section("habit.instance.date", &date_to_string(n))
     }
 
     fn get_comment(&self) -> Result<String> {
-        get_string_header_from_entry(self, "habit.instance.comment")
+        self.get_instance_header().map(|h| h.comment)
     }
 
     fn set_comment(&mut self, c: String) -> Result<()> {
-        // Using `set` here because when creating the entry, these headers should be made present.
-        self.get_header_mut()
-            .set("habit.instance.comment", Value::String(c))
-            .map_err(From::from)
-            .map(|_| ())
+        self.set_section("habit.instance.comment", &c)
     }
 
     fn get_template_name(&self) -> Result<String> {
-        get_string_header_from_entry(self, "habit.instance.name")
+        self.get_instance_header().map(|h| h.name)
     }
 
 }
@@ -36,8 +36,7 @@
 )]
 
 extern crate chrono;
-extern crate toml;
-extern crate toml_query;
+#[macro_use] extern crate serde_derive;
 extern crate kairos;
 #[macro_use] extern crate log;
 #[macro_use] extern crate failure;
@@ -19,7 +19,6 @@
 
 use std::ops::BitXor;
 
-use failure::Error;
 use failure::Fallible as Result;
 
 use habit::HabitTemplate;
@@ -27,7 +26,6 @@ use instance::HabitInstance;
 
 use libimagstore::storeid::StoreId;
 use libimagstore::store::Entry;
-use libimagerror::errors::ErrorMsg as EM;
 
 /// Helper trait to check whether a object which can be a habit instance and a habit template is
 /// actually a valid object, whereas "valid" is defined that it is _either_ an instance or a
@@ -83,11 +81,6 @@ impl IsHabitCheck for Entry {
 
 #[inline]
 pub fn get_string_header_from_entry(e: &Entry, path: &'static str) -> Result<String> {
-    use toml_query::read::TomlValueReadTypeExt;
-
-    e.get_header()
-        .read_string(path)?
-        .ok_or_else(|| EM::EntryHeaderFieldMissing(path))
-        .map_err(Error::from)
+    e.require_section(path)
 }
 
@@ -17,8 +17,6 @@
 // Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
 //
 
-use toml::Value;
-use toml_query::insert::TomlValueInsertExt;
 use chrono::naive::NaiveDateTime as NDT;
 use failure::Fallible as Result;
 use failure::Error;
@@ -62,8 +60,8 @@ impl<'a> Iterator for CreateTimeTrackIter<'a>
                         .create(id)
                         .map_err(Error::from)
                         .and_then(|mut entry| {
-                            let v = Value::String(starttime.format(DATE_TIME_FORMAT).to_string());
-                            let _ = entry.get_header_mut().insert(DATE_TIME_START_HEADER_PATH, v)?;
+                            let v = starttime.format(DATE_TIME_FORMAT).to_string();
+                            let _ = entry.set_section(DATE_TIME_START_HEADER_PATH, &v)?;
                             Ok(entry)
                         })
                 })
@@ -17,8 +17,6 @@
 // Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
 //
 
-use toml::Value;
-use toml_query::insert::TomlValueInsertExt;
 use chrono::naive::NaiveDateTime as NDT;
 use failure::Fallible as Result;
 
@@ -45,8 +43,8 @@ impl<'a> Iterator for SetEndTimeIter<'a> {
         self.inner
             .next()
             .map(|res| res.and_then(|mut fle| {
-                let v = Value::String(self.datetime.format(DATE_TIME_FORMAT).to_string());
-                let _ = fle.get_header_mut().insert(DATE_TIME_END_HEADER_PATH, v)?;
+                let v = self.datetime.format(DATE_TIME_FORMAT).to_string();
+                let _ = fle.set_section(DATE_TIME_END_HEADER_PATH, &v)?;
                 Ok(fle)
             }))
     }
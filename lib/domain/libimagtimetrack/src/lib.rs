@@ -39,8 +39,6 @@
 
 extern crate filters;
 extern crate chrono;
-extern crate toml;
-extern crate toml_query;
 #[macro_use]
 extern crate lazy_static;
 #[macro_use]
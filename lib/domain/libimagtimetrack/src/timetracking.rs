@@ -27,15 +27,10 @@
 use chrono::naive::NaiveDateTime;
 
 use libimagstore::store::Entry;
-use libimagerror::errors::ErrorMsg as EM;
 
 use tag::TimeTrackingTag as TTT;
 use constants::*;
 
-use toml::Value;
-use toml_query::delete::TomlValueDeleteExt;
-use toml_query::insert::TomlValueInsertExt;
-use toml_query::read::TomlValueReadTypeExt;
 use failure::Fallible as Result;
 use failure::Error;
 
@@ -62,56 +57,37 @@ pub trait TimeTracking {
 impl TimeTracking for Entry {
 
     fn get_timetrack_tag(&self) -> Result<TTT> {
-        self.get_header()
-            .read_string(DATE_TIME_TAG_HEADER_PATH)
-            .map_err(Error::from)?
-            .ok_or_else(|| Error::from(EM::EntryHeaderReadError))
+        self.require_section::<String>(DATE_TIME_TAG_HEADER_PATH)
             .map(Into::into)
     }
 
     fn set_start_datetime(&mut self, dt: NaiveDateTime) -> Result<()> {
         let s = dt.format(DATE_TIME_FORMAT).to_string();
-
-        self.get_header_mut()
-            .insert(DATE_TIME_START_HEADER_PATH, Value::String(s))
-            .map_err(Error::from)
-            .map(|_| ())
+        self.set_section(DATE_TIME_START_HEADER_PATH, &s)
     }
 
     fn get_start_datetime(&self) -> Result<Option<NaiveDateTime>> {
-        self.get_header()
-            .read_string(DATE_TIME_START_HEADER_PATH)
-            .map_err(Error::from)
+        self.get_section(DATE_TIME_START_HEADER_PATH)
             .and_then(header_value_to_dt)
     }
 
     fn delete_start_datetime(&mut self) -> Result<()> {
-        self.get_header_mut()
-            .delete(DATE_TIME_START_HEADER_PATH)
-            .map_err(Error::from)
+        self.delete_section(DATE_TIME_START_HEADER_PATH)
             .map(|_| ())
     }
 
     fn set_end_datetime(&mut self, dt: NaiveDateTime) -> Result<()> {
         let s = dt.format(DATE_TIME_FORMAT).to_string();
-
-        self.get_header_mut()
-            .insert(DATE_TIME_END_HEADER_PATH, Value::String(s))
-            .map_err(Error::from)
-            .map(|_| ())
+        self.set_section(DATE_TIME_END_HEADER_PATH, &s)
     }
 
     fn get_end_datetime(&self) -> Result<Option<NaiveDateTime>> {
-        self.get_header()
-            .read_string(DATE_TIME_END_HEADER_PATH)
-            .map_err(Error::from)
+        self.get_section(DATE_TIME_END_HEADER_PATH)
             .and_then(header_value_to_dt)
     }
 
     fn delete_end_datetime(&mut self) -> Result<()> {
-        self.get_header_mut()
-            .delete(DATE_TIME_END_HEADER_PATH)
-            .map_err(Error::from)
+        self.delete_section(DATE_TIME_END_HEADER_PATH)
             .map(|_| ())
     }
 
@@ -23,8 +23,6 @@
 //! create, get and delete events.
 
 use chrono::NaiveDateTime as NDT;
-use toml::Value;
-use toml_query::insert::TomlValueInsertExt;
 use failure::Fallible as Result;
 use failure::Error;
 
@@ -77,17 +75,12 @@ impl<'a> TimeTrackStore<'a> for Store {
             })
             .and_then(|id| self.create(id))
             .and_then(|mut fle| {
-                let v = Value::String(ts.as_str().to_owned());
-                fle.get_header_mut()
-                    .insert(DATE_TIME_TAG_HEADER_PATH, v)
-                    .map_err(Error::from)
+                fle.set_section(DATE_TIME_TAG_HEADER_PATH, ts.as_str())
                     .map(|_| fle)
             })
             .and_then(|mut fle| {
-                let v = Value::String(start.format(DATE_TIME_FORMAT).to_string());
-                fle.get_header_mut()
-                    .insert(DATE_TIME_START_HEADER_PATH, v)
-                    .map_err(Error::from)
+                let v = start.format(DATE_TIME_FORMAT).to_string();
+                fle.set_section(DATE_TIME_START_HEADER_PATH, &v)
                     .map(|_| fle)
             })
     }
@@ -95,10 +88,8 @@ impl<'a> TimeTrackStore<'a> for Store {
     fn create_timetracking(&'a self, start: &NDT, end: &NDT, ts: &TTT) -> Result<FileLockEntry<'a>> {
         self.create_timetracking_at(start, ts)
             .and_then(|mut fle| {
-                let v = Value::String(end.format(DATE_TIME_FORMAT).to_string());
-                fle.get_header_mut()
-                    .insert(DATE_TIME_END_HEADER_PATH, v)
-                    .map_err(Error::from)
+                let v = end.format(DATE_TIME_FORMAT).to_string();
+                fle.set_section(DATE_TIME_END_HEADER_PATH, &v)
                     .map(|_| fle)
             })
     }
@@ -43,9 +43,13 @@ extern crate toml_query;
 #[macro_use] extern crate log;
 extern crate serde_json;
 extern crate failure;
+extern crate regex;
+extern crate sha1;
 
 #[macro_use] extern crate libimagstore;
 extern crate libimagerror;
+extern crate libimagentrylink;
+extern crate libimagentrymarkdown;
 extern crate task_hookrs;
 
 module_entry_path_mod!("todo");
@@ -53,4 +57,5 @@ module_entry_path_mod!("todo");
 pub mod task;
 pub mod taskstore;
 pub mod iter;
+pub mod markdown;
 
@@ -0,0 +1,699 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2019 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+//! Todo entries for the task list items in markdown content
+//!
+//! Notes, diary entries and wiki pages often contain task list items (`- [ ] call plumber`). The
+//! `TaskProcessor` creates a todo entry in `todo/markdown/` for each of these items, links it to
+//! the entry the item was found in and syncs the state of the checkbox with the todo entry, in
+//! both directions.
+//!
+//! A todo entry is identified by the id of its source entry, the text of the item and, if several
+//! items in the entry have the same text, the position of the item among them. Editing the text of
+//! an item creates a new todo entry. The todo entries of items which were removed or edited are
+//! closed on the next sync.
+//!
+//! The `MarkdownTodo` extension gives access to the state of such a todo entry, `set_todo_state()`
+//! changes it and writes it back to the checkbox of its item right away.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::path::PathBuf;
+
+use failure::Fallible as Result;
+use regex::Regex;
+use toml::Value;
+use toml_query::read::TomlValueReadExt;
+use toml_query::read::TomlValueReadTypeExt;
+use toml_query::set::TomlValueSetExt;
+
+use libimagentrylink::internal::InternalLinker;
+use libimagentrymarkdown::outline::Outline;
+use libimagerror::kind::ErrorKind;
+use libimagerror::kind::kind_err;
+use libimagstore::store::Entry;
+use libimagstore::store::FileLockEntry;
+use libimagstore::store::Store;
+use libimagstore::storeid::IntoStoreId;
+use libimagstore::storeid::StoreId;
+use libimagstore::storeid::StoreIdIterator;
+use module_path::ModuleEntryPath;
+
+/// The collection the todo entries for task list items are stored in
+pub const MARKDOWN_TODO_COLLECTION : &'static str = "todo/markdown";
+
+/// What a `TaskProcessor::process()` call did
+#[derive(Debug, Default)]
+pub struct TaskSyncReport {
+    /// The todo entries which were created
+    pub created: Vec<StoreId>,
+
+    /// The todo entries whose state was changed because the checkbox was changed
+    pub updated: Vec<StoreId>,
+
+    /// The todo entries whose state was written back to the checkbox in the processed entry
+    pub written_back: Vec<StoreId>,
+
+    /// The todo entries whose task list item is gone, which were closed and unlinked
+    pub closed: Vec<StoreId>,
+}
+
+impl TaskSyncReport {
+
+    /// Whether the processed entry was changed
+    pub fn source_changed(&self) -> bool {
+        !self.written_back.is_empty()
+    }
+
+    /// Whether nothing was created or changed
+    pub fn is_empty(&self) -> bool {
+        self.created.is_empty()
+            && self.updated.is_empty()
+            && self.written_back.is_empty()
+            && self.closed.is_empty()
+    }
+
+    /// The todo entries which were created or changed
+    pub fn touched(&self) -> impl Iterator<Item = &StoreId> {
+        self.created
+            .iter()
+            .chain(self.updated.iter())
+            .chain(self.written_back.iter())
+            .chain(self.closed.iter())
+    }
+
+}
+
+impl Display for TaskSyncReport {
+
+    fn fmt(&self, fmt: &mut Formatter) -> FmtResult {
+        write!(fmt, "{} created, {} updated, {} written back, {} closed",
+               self.created.len(),
+               self.updated.len(),
+               self.written_back.len(),
+               self.closed.len())
+    }
+
+}
+
+/// A processor which syncs the task list items in the content of an entry with todo entries
+///
+/// The processor can be configured to
+///
+///  * Create todo entries for task list items which do not have one yet (default: true)
+///  * Write the state of todo entries back to the checkboxes in the processed entry
+///    (default: true)
+///
+///  # Note
+///
+///  There's no TaskProcessor::new() function, please use `TaskProcessor::default()`.
+///
+pub struct TaskProcessor {
+    create_todos: bool,
+    write_back: bool,
+}
+
+impl TaskProcessor {
+
+    /// Switch todo entry creation on/off
+    ///
+    /// With creation switched off, only the task list items which already have a todo entry are
+    /// synced.
+    pub fn create_todos(mut self, b: bool) -> Self {
+        self.create_todos = b;
+        self
+    }
+
+    /// Switch writing the state of todo entries back to the processed entry on/off
+    pub fn write_back(mut self, b: bool) -> Self {
+        self.write_back = b;
+        self
+    }
+
+    /// Process an Entry for its task list items
+    ///
+    /// The todo entries remember the state of the checkbox at the last sync (`todo.source_done`),
+    /// which tells which side was changed since then:
+    ///
+    /// * If the checkbox was changed, the state of the todo entry is set to the state of the
+    ///   checkbox
+    /// * If the todo entry was changed (`todo.done`), the checkbox is checked or unchecked in the
+    ///   content of the processed entry
+    ///
+    /// The processed entry is linked to all todo entries of its items. Todo entries which are
+    /// linked to the processed entry but whose item is gone, because it was removed or its text
+    /// was edited, are marked as done and unlinked.
+    ///
+    /// If an item is removed while there are other items with the same text, the remaining items
+    /// keep the todo entries whose state matches their checkbox, so they do not take over the
+    /// state of the removed one.
+    pub fn process<'a>(&self, entry: &mut Entry, store: &'a Store) -> Result<TaskSyncReport> {
+        let source      = entry.get_location().clone();
+        let source_name = source.local_display_string();
+        let items       = Outline::parse(entry.get_content()).tasks;
+        let mut report  = TaskSyncReport::default();
+        let mut changes = vec![];
+        let mut seen    = HashMap::new();
+
+        let mut counts = HashMap::new();
+        for item in items.iter() {
+            *counts.entry(item.text.clone()).or_insert(0usize) += 1;
+        }
+
+        let mut linked = linked_todos(entry, store)?;
+
+        for item in items {
+            let n = {
+                let count = seen.entry(item.text.clone()).or_insert(0);
+                *count += 1;
+                *count - 1
+            };
+
+            let id = todo_id(&source_name, &item.text, n)?;
+            trace!("Processing task item {:?} -> {}", item.text, id);
+
+            let candidates = linked.entry(item.text.clone()).or_insert_with(Vec::new);
+            let shrinking  = counts[&item.text] - n < candidates.len();
+
+            let mut todo = match pick_todo(candidates, &id, item.done, shrinking)? {
+                Some(todo) => todo,
+                None       => {
+                    if !self.create_todos {
+                        continue
+                    }
+
+                    let id = free_todo_id(store, &source_name, &item.text, n)?;
+                    let mut todo = store.create(id.clone())?;
+                    {
+                        let hdr = todo.get_header_mut();
+                        if hdr.read("todo")?.is_none() {
+                            hdr.set("todo", Value::Table(BTreeMap::new()))?;
+                        }
+
+                        hdr.set("todo.text", Value::String(item.text.clone()))?;
+                        hdr.set("todo.source", Value::String(source_name.clone()))?;
+                        hdr.set("todo.done", Value::Boolean(item.done))?;
+                        hdr.set("todo.source_done", Value::Boolean(item.done))?;
+                    }
+
+                    report.created.push(id);
+                    todo
+                },
+            };
+
+            let id     = todo.get_location().clone();
+            let done   = todo.get_header().read_bool("todo.done")?.unwrap_or(false);
+            let synced = todo.get_header().read_bool("todo.source_done")?.unwrap_or(done);
+
+            if item.done != synced {
+                debug!("Checkbox of {} changed, setting done = {}", id, item.done);
+                todo.get_header_mut().set("todo.done", Value::Boolean(item.done))?;
+                todo.get_header_mut().set("todo.source_done", Value::Boolean(item.done))?;
+                report.updated.push(id.clone());
+            } else if done != synced && self.write_back {
+                debug!("Todo {} changed, setting checkbox to {}", id, done);
+                todo.get_header_mut().set("todo.source_done", Value::Boolean(done))?;
+                changes.push((item.line, done));
+                report.written_back.push(id.clone());
+            }
+
+            let _ = entry.add_internal_link(&mut todo)?;
+        }
+
+        for (_, todos) in linked {
+            for mut todo in todos {
+                debug!("Task item of {} is gone, closing it", todo.get_location());
+                todo.set_todo_done(true)?;
+                entry.remove_internal_link(&mut todo)?;
+                report.closed.push(todo.get_location().clone());
+            }
+        }
+
+        if !changes.is_empty() {
+            let content = set_checkboxes(entry.get_content(), &changes)?;
+            *entry.get_content_mut() = content;
+        }
+
+        Ok(report)
+    }
+
+    /// Process all `entries`
+    ///
+    /// An error while processing an entry does not stop the sync, the result for every entry is
+    /// returned, in the order of `entries`. Entries for which nothing was created or changed are
+    /// left out.
+    pub fn sync_entries<'a, I>(&self, store: &'a Store, entries: I) -> Vec<(StoreId, Result<TaskSyncReport>)>
+        where I: IntoIterator<Item = FileLockEntry<'a>>
+    {
+        entries
+            .into_iter()
+            .filter_map(|mut entry| {
+                let id = entry.get_location().clone();
+                match self.process(&mut entry, store) {
+                    Ok(ref report) if report.is_empty() => None,
+                    result => Some((id, result)),
+                }
+            })
+            .collect()
+    }
+
+}
+
+impl Default for TaskProcessor {
+    fn default() -> Self {
+        TaskProcessor {
+            create_todos: true,
+            write_back: true,
+        }
+    }
+}
+
+/// Get the id of the todo entry for the `n`th (starting at 0) task list item with the text `text`
+/// in the entry `source`
+fn todo_id(source: &str, text: &str, n: usize) -> Result<StoreId> {
+    use sha1::{Sha1, Digest};
+
+    let key = if n == 0 {
+        format!("{}\n{}", source, text)
+    } else {
+        format!("{}\n{}\n{}", source, text, n)
+    };
+
+    let digest = Sha1::digest(key.as_bytes());
+    ModuleEntryPath::new(format!("markdown/{:x}", digest)).into_storeid()
+}
+
+/// Get the id for a new todo entry for the `n`th task list item with the text `text` in `source`
+///
+/// If there is a todo entry with that id already, one of a removed item, the next free position
+/// is used.
+fn free_todo_id(store: &Store, source: &str, text: &str, n: usize) -> Result<StoreId> {
+    let mut n = n;
+    loop {
+        let id = todo_id(source, text, n)?;
+        if !store.exists(id.clone())? {
+            return Ok(id)
+        }
+        n += 1;
+    }
+}
+
+/// Get the todo entries of the task list items of `entry` linked to it, by the text of their item
+fn linked_todos<'a>(entry: &Entry, store: &'a Store) -> Result<HashMap<String, Vec<FileLockEntry<'a>>>> {
+    let mut ids = entry
+        .get_internal_links()?
+        .map(|link| link.get_store_id().clone())
+        .filter(|id| id.is_in_collection(&["todo", "markdown"]))
+        .collect::<Vec<_>>();
+    ids.sort();
+    ids.dedup();
+
+    let mut todos = HashMap::new();
+    for id in ids {
+        if let Some(todo) = store.get(id)? {
+            if todo.get_todo_source()? != *entry.get_location() {
+                continue
+            }
+
+            let text = todo.get_todo_text()?;
+            todos.entry(text).or_insert_with(Vec::new).push(todo);
+        }
+    }
+
+    Ok(todos)
+}
+
+/// Take the todo entry for a task list item with the id `id` and the state `done` from `todos`
+///
+/// The todo entry with the id of the item is taken if there is one. If items with this text were
+/// removed (`shrinking`), a todo entry whose last synced state is the state of the item is
+/// preferred, as the todo entry with the id of the item may be the one of a removed item.
+fn pick_todo<'a>(todos: &mut Vec<FileLockEntry<'a>>, id: &StoreId, done: bool, shrinking: bool)
+    -> Result<Option<FileLockEntry<'a>>>
+{
+    let mut candidates = vec![];
+    for (i, todo) in todos.iter().enumerate() {
+        let hdr    = todo.get_header();
+        let synced = match hdr.read_bool("todo.source_done")? {
+            Some(b) => b,
+            None    => hdr.read_bool("todo.done")?.unwrap_or(false),
+        };
+        candidates.push((i, todo.get_location() == id, synced == done));
+    }
+
+    let find = |by_id: bool, by_state: bool| {
+        candidates
+            .iter()
+            .find(|&&(_, is_id, is_state)| (!by_id || is_id) && (!by_state || is_state))
+            .map(|&(i, _, _)| i)
+    };
+
+    let preferred = if shrinking {
+        find(true, true).or_else(|| find(false, true)).or_else(|| find(true, false))
+    } else {
+        find(true, false)
+    };
+    let index = preferred.or_else(|| find(false, false));
+
+    Ok(index.map(|i| todos.remove(i)))
+}
+
+/// Get the ids of all todo entries for task list items
+pub fn all_markdown_todos(store: &Store) -> Result<StoreIdIterator> {
+    store.entries().map(|es| es.in_collection(MARKDOWN_TODO_COLLECTION).into_storeid_iter())
+}
+
+/// Set the state of the todo entry `id` and check or uncheck the checkbox of its item
+///
+/// The source entry of the todo is processed right away (without creating todo entries), so the
+/// checkbox does not have to wait for the next sync. Returns the id of the source entry together
+/// with what was done to it.
+pub fn set_todo_state(store: &Store, id: StoreId, done: bool) -> Result<(StoreId, TaskSyncReport)> {
+    let source = {
+        let mut todo = store.get(id.clone())?
+            .ok_or_else(|| kind_err(ErrorKind::NotFound, format!("No todo '{}'", id)))?;
+
+        if !todo.is_markdown_todo() {
+            let msg = format!("'{}' is not a todo for a task list item", id);
+            return Err(kind_err(ErrorKind::Other, msg))
+        }
+
+        todo.set_todo_done(done)?;
+        todo.get_todo_source()?
+    };
+
+    match store.get(source.clone())? {
+        Some(mut entry) => TaskProcessor::default()
+            .create_todos(false)
+            .process(&mut entry, store)
+            .map(|report| (source, report)),
+
+        None => {
+            warn!("Source entry '{}' of '{}' does not exist anymore", source, id);
+            Ok((source, TaskSyncReport::default()))
+        },
+    }
+}
+
+/// Extension for the todo entries of task list items
+pub trait MarkdownTodo {
+
+    /// Whether the entry is a todo entry for a task list item
+    fn is_markdown_todo(&self) -> bool;
+
+    /// The text of the task list item
+    fn get_todo_text(&self) -> Result<String>;
+
+    /// The id of the entry the task list item was found in
+    fn get_todo_source(&self) -> Result<StoreId>;
+
+    /// Whether the todo is done
+    fn is_todo_done(&self) -> Result<bool>;
+
+    /// Mark the todo as done or not done
+    ///
+    /// The checkbox of the task list item is changed on the next sync of its source entry.
+    fn set_todo_done(&mut self, done: bool) -> Result<()>;
+
+}
+
+impl MarkdownTodo for Entry {
+
+    fn is_markdown_todo(&self) -> bool {
+        self.get_location().is_in_collection(&["todo", "markdown"])
+    }
+
+    fn get_todo_text(&self) -> Result<String> {
+        self.get_header()
+            .read_string("todo.text")?
+            .ok_or_else(|| kind_err(ErrorKind::Parse, "Header field 'todo.text' missing"))
+    }
+
+    fn get_todo_source(&self) -> Result<StoreId> {
+        self.get_header()
+            .read_string("todo.source")?
+            .ok_or_else(|| kind_err(ErrorKind::Parse, "Header field 'todo.source' missing"))
+            .and_then(|s| PathBuf::from(s).into_storeid())
+    }
+
+    fn is_todo_done(&self) -> Result<bool> {
+        self.get_header().read_bool("todo.done").map(|b| b.unwrap_or(false)).map_err(From::from)
+    }
+
+    fn set_todo_done(&mut self, done: bool) -> Result<()> {
+        self.get_header_mut()
+            .set("todo.done", Value::Boolean(done))
+            .map(|_| ())
+            .map_err(From::from)
+    }
+
+}
+
+/// Check or uncheck the checkboxes of the task list items on the lines `changes` (starting at 1)
+fn set_checkboxes(content: &str, changes: &[(usize, bool)]) -> Result<String> {
+    let checkbox = Regex::new(r"^(\s*(?:[-*+]|\d+[.)])\s+\[)[ xX](\])")?;
+
+    let content = content
+        .split('\n')
+        .enumerate()
+        .map(|(i, line)| match changes.iter().find(|&&(n, _)| n == i + 1) {
+            Some(&(_, done)) => {
+                let mark = if done { "x" } else { " " };
+                checkbox.replace(line, |caps: &::regex::Captures| {
+                    format!("{}{}{}", &caps[1], mark, &caps[2])
+                }).into_owned()
+            },
+            None => String::from(line),
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    Ok(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_store() -> Store {
+        Store::new_inmemory(PathBuf::from("/"), &None).unwrap()
+    }
+
+    #[test]
+    fn test_set_checkboxes() {
+        let content = "# Todo\n- [ ] one\n  * [x] two\n1. [X] three\n";
+        let result  = set_checkboxes(content, &[(2, true), (3, false), (4, false)]).unwrap();
+        assert_eq!("# Todo\n- [x] one\n  * [ ] two\n1. [ ] three\n", result);
+    }
+
+    #[test]
+    fn test_process_creates_and_syncs_todos() {
+        let store = get_store();
+        let mut note = store.create(PathBuf::from("notes/plumbing")).unwrap();
+        *note.get_content_mut() = String::from("# Plumbing\n\n- [ ] call plumber\n- [x] buy pipes\n");
+
+        let report = TaskProcessor::default().process(&mut note, &store).unwrap();
+        assert_eq!(2, report.created.len());
+        assert!(report.updated.is_empty());
+        assert!(!report.source_changed());
+        assert_eq!(2, note.get_internal_links().unwrap().count());
+
+        let call_id = report.created[0].clone();
+        {
+            let todo = store.get(call_id.clone()).unwrap().unwrap();
+            let hdr  = todo.get_header();
+            assert_eq!(Some(String::from("call plumber")), hdr.read_string("todo.text").unwrap());
+            assert_eq!(Some(String::from("notes/plumbing")), hdr.read_string("todo.source").unwrap());
+            assert_eq!(Some(false), hdr.read_bool("todo.done").unwrap());
+        }
+
+        // checking the checkbox marks the todo as done
+        *note.get_content_mut() = String::from("# Plumbing\n\n- [x] call plumber\n- [x] buy pipes\n");
+        let report = TaskProcessor::default().process(&mut note, &store).unwrap();
+        assert!(report.created.is_empty());
+        assert_eq!(vec![call_id.clone()], report.updated);
+        {
+            let todo = store.get(call_id.clone()).unwrap().unwrap();
+            assert_eq!(Some(true), todo.get_header().read_bool("todo.done").unwrap());
+        }
+
+        // reopening the todo unchecks the checkbox
+        {
+            let mut todo = store.get(call_id.clone()).unwrap().unwrap();
+            todo.get_header_mut().set("todo.done", Value::Boolean(false)).unwrap();
+        }
+        let report = TaskProcessor::default().process(&mut note, &store).unwrap();
+        assert_eq!(vec![call_id.clone()], report.written_back);
+        assert_eq!("# Plumbing\n\n- [ ] call plumber\n- [x] buy pipes\n", note.get_content());
+
+        // nothing changed since the last sync
+        let report = TaskProcessor::default().process(&mut note, &store).unwrap();
+        assert!(report.is_empty());
+    }
+
+    #[test]
+    fn test_process_without_creating_todos() {
+        let store = get_store();
+        let mut note = store.create(PathBuf::from("notes/plumbing")).unwrap();
+        *note.get_content_mut() = String::from("- [ ] call plumber\n");
+
+        let report = TaskProcessor::default()
+            .create_todos(false)
+            .process(&mut note, &store)
+            .unwrap();
+
+        assert!(report.created.is_empty());
+        assert_eq!(0, note.get_internal_links().unwrap().count());
+    }
+
+    #[test]
+    fn test_process_identical_items() {
+        let store = get_store();
+        let mut note = store.create(PathBuf::from("notes/plumbing")).unwrap();
+        *note.get_content_mut() = String::from("# Monday\n- [ ] call plumber\n# Tuesday\n- [ ] call plumber\n");
+
+        let report = TaskProcessor::default().process(&mut note, &store).unwrap();
+        assert_eq!(2, report.created.len());
+        assert!(report.created[0] != report.created[1]);
+
+        // checking the second checkbox only marks the second todo as done
+        *note.get_content_mut() = String::from("# Monday\n- [ ] call plumber\n# Tuesday\n- [x] call plumber\n");
+        let report2 = TaskProcessor::default().process(&mut note, &store).unwrap();
+        assert_eq!(vec![report.created[1].clone()], report2.updated);
+
+        let first = store.get(report.created[0].clone()).unwrap().unwrap();
+        assert!(!first.is_todo_done().unwrap());
+    }
+
+    #[test]
+    fn test_process_closes_orphaned_todos() {
+        let store = get_store();
+        let mut note = store.create(PathBuf::from("notes/plumbing")).unwrap();
+        *note.get_content_mut() = String::from("- [ ] call plumber\n- [ ] buy pipes\n");
+
+        let report = TaskProcessor::default().process(&mut note, &store).unwrap();
+        let call_id  = report.created[0].clone();
+        let pipes_id = report.created[1].clone();
+
+        // removing an item and editing the text of the other one closes both todos
+        *note.get_content_mut() = String::from("- [ ] call the plumber\n");
+        let report = TaskProcessor::default().process(&mut note, &store).unwrap();
+        assert_eq!(1, report.created.len());
+
+        let mut closed = report.closed.clone();
+        closed.sort();
+        let mut expected = vec![call_id.clone(), pipes_id.clone()];
+        expected.sort();
+        assert_eq!(expected, closed);
+
+        assert!(store.get(call_id).unwrap().unwrap().is_todo_done().unwrap());
+        assert!(store.get(pipes_id).unwrap().unwrap().is_todo_done().unwrap());
+        assert_eq!(1, note.get_internal_links().unwrap().count());
+
+        // nothing to close on the next sync
+        let report = TaskProcessor::default().process(&mut note, &store).unwrap();
+        assert!(report.is_empty());
+    }
+
+    #[test]
+    fn test_process_removed_identical_item() {
+        let store = get_store();
+        let mut note = store.create(PathBuf::from("notes/plumbing")).unwrap();
+        *note.get_content_mut() = String::from("- [x] call plumber\n- [ ] call plumber\n");
+
+        let report = TaskProcessor::default().process(&mut note, &store).unwrap();
+        let first  = report.created[0].clone();
+        let second = report.created[1].clone();
+
+        // the remaining item keeps its todo and does not take over the state of the first one
+        *note.get_content_mut() = String::from("- [ ] call plumber\n");
+        let report = TaskProcessor::default().process(&mut note, &store).unwrap();
+        assert!(report.created.is_empty());
+        assert!(report.updated.is_empty());
+        assert_eq!(vec![first], report.closed);
+        assert!(!store.get(second).unwrap().unwrap().is_todo_done().unwrap());
+        assert_eq!("- [ ] call plumber\n", note.get_content());
+
+        // re-adding the item creates a new todo
+        *note.get_content_mut() = String::from("- [ ] call plumber\n- [ ] call plumber\n");
+        let report = TaskProcessor::default().process(&mut note, &store).unwrap();
+        assert_eq!(1, report.created.len());
+        assert!(report.closed.is_empty());
+    }
+
+    #[test]
+    fn test_sync_entries_continues_after_errors() {
+        let store = get_store();
+        {
+            let mut note = store.create(PathBuf::from("notes/broken")).unwrap();
+            *note.get_content_mut() = String::from("- [ ] call plumber\n");
+        }
+        {
+            let mut note = store.create(PathBuf::from("notes/plumbing")).unwrap();
+            *note.get_content_mut() = String::from("- [ ] call plumber\n");
+        }
+
+        // A todo entry with the id of the item of notes/broken but without a text
+        let id = todo_id("notes/broken", "call plumber", 0).unwrap();
+        {
+            let mut todo = store.create(id.clone()).unwrap();
+            todo.get_header_mut().set("todo", Value::Table(BTreeMap::new())).unwrap();
+            todo.get_header_mut().set("todo.source", Value::String(String::from("notes/broken"))).unwrap();
+            let mut broken = store.get(PathBuf::from("notes/broken").into_storeid().unwrap()).unwrap().unwrap();
+            broken.add_internal_link(&mut todo).unwrap();
+        }
+
+        let entries = vec!["notes/broken", "notes/plumbing"]
+            .into_iter()
+            .map(|id| store.get(PathBuf::from(id).into_storeid().unwrap()).unwrap().unwrap());
+
+        let results = TaskProcessor::default().sync_entries(&store, entries);
+        assert_eq!(2, results.len());
+        assert!(results[0].1.is_err());
+        assert_eq!("notes/plumbing", results[1].0.local_display_string());
+        assert_eq!(1, results[1].1.as_ref().unwrap().created.len());
+    }
+
+    #[test]
+    fn test_set_todo_state() {
+        let store = get_store();
+        let id = {
+            let mut note = store.create(PathBuf::from("notes/plumbing")).unwrap();
+            *note.get_content_mut() = String::from("- [ ] call plumber\n");
+            TaskProcessor::default().process(&mut note, &store).unwrap().created[0].clone()
+        };
+
+        {
+            let todo = store.get(id.clone()).unwrap().unwrap();
+            assert!(todo.is_markdown_todo());
+            assert_eq!("call plumber", todo.get_todo_text().unwrap());
+            assert_eq!("notes/plumbing", todo.get_todo_source().unwrap().local_display_string());
+        }
+
+        let (source, report) = set_todo_state(&store, id.clone(), true).unwrap();
+        assert_eq!("notes/plumbing", source.local_display_string());
+        assert_eq!(vec![id.clone()], report.written_back);
+
+        let note = store.get(PathBuf::from("notes/plumbing").into_storeid().unwrap()).unwrap().unwrap();
+        assert_eq!("- [x] call plumber\n", note.get_content());
+        assert!(store.get(id).unwrap().unwrap().is_todo_done().unwrap());
+    }
+
+}
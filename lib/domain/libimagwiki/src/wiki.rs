@@ -24,6 +24,8 @@ use libimagstore::store::FileLockEntry;
 use libimagstore::storeid::IntoStoreId;
 use libimagstore::iter::Entries;
 use libimagentrylink::internal::InternalLinker;
+use libimagentrymarkdown::rewrite::MoveReport;
+use libimagentrymarkdown::rewrite::move_entry;
 
 use failure::Fallible as Result;
 use failure::Error;
@@ -100,5 +102,20 @@ impl<'a, 'b> Wiki<'a, 'b> {
         let sid   = ::module_path::ModuleEntryPath::new(path).into_storeid()?;
         self.0.delete(sid)
     }
+
+    /// Rename the entry `from_name` to `to_name`
+    ///
+    /// The links of the entry are kept and markdown links to it in the content of the linked
+    /// entries are rewritten, see `libimagentrymarkdown::rewrite::move_entry()`.
+    pub fn rename_entry<EF, ET>(&self, from_name: EF, to_name: ET) -> Result<MoveReport>
+        where EF: AsRef<str>,
+              ET: AsRef<str>
+    {
+        let from = PathBuf::from(format!("{}/{}", self.1, from_name.as_ref()));
+        let from = ::module_path::ModuleEntryPath::new(from).into_storeid()?;
+        let to   = PathBuf::from(format!("{}/{}", self.1, to_name.as_ref()));
+        let to   = ::module_path::ModuleEntryPath::new(to).into_storeid()?;
+        move_entry(self.0, from, to)
+    }
 }
 
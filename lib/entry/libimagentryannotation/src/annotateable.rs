@@ -36,6 +36,9 @@ use failure::ResultExt;
 use failure::Error;
 use failure::err_msg;
 
+use libimagerror::kind::ErrorKind;
+use libimagerror::kind::kind_err;
+
 use module_path::ModuleEntryPath;
 
 pub trait Annotateable {
@@ -81,7 +84,7 @@ impl Annotateable for Entry {
             Ok(Some(annotation))
         } else {
             // error: annotation does not exist
-            Err(format_err!("Annotation '{}' does not exist", ann_name)).map_err(Error::from)
+            Err(kind_err(ErrorKind::NotFound, format!("Annotation '{}' does not exist", ann_name)))
         }
     }
 
@@ -39,7 +39,7 @@
 
 extern crate toml;
 extern crate toml_query;
-#[macro_use] extern crate failure;
+extern crate failure;
 #[macro_use] extern crate log;
 extern crate uuid;
 
@@ -26,9 +26,11 @@ use libimagentrylink::internal::InternalLinker;
 
 use toml_query::read::TomlValueReadTypeExt;
 
+use libimagerror::kind::ErrorKind;
+use libimagerror::kind::kind_err;
+
 use failure::Fallible as Result;
 use failure::Error;
-use failure::err_msg;
 use store::CATEGORY_REGISTER_NAME_FIELD_PATH;
 use iter::CategoryEntryIterator;
 
@@ -50,7 +52,7 @@ impl Category for Entry {
         self.get_header()
             .read_string(CATEGORY_REGISTER_NAME_FIELD_PATH)
             .map_err(Error::from)?
-            .ok_or_else(|| Error::from(err_msg("Category name missing")))
+            .ok_or_else(|| kind_err(ErrorKind::Parse, "Category name missing"))
     }
 
     fn get_entries<'a>(&self, store: &'a Store) -> Result<CategoryEntryIterator<'a>> {
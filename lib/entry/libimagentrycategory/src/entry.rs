@@ -25,11 +25,12 @@ use toml::Value;
 use libimagstore::store::Entry;
 use libimagentrylink::internal::InternalLinker;
 use libimagerror::errors::ErrorMsg as EM;
+use libimagerror::kind::ErrorKind;
+use libimagerror::kind::kind_err;
 
 use failure::Fallible as Result;
 use failure::ResultExt;
 use failure::Error;
-use failure::err_msg;
 use store::CategoryStore;
 
 pub trait EntryCategory {
@@ -65,7 +66,7 @@ impl EntryCategory for Entry {
         trace!("Setting category '{}' checked", s);
         let mut category = register
             .get_category_by_name(s)?
-            .ok_or_else(|| Error::from(err_msg("Category does not exist")))?;
+            .ok_or_else(|| kind_err(ErrorKind::NotFound, format!("Category does not exist: {}", s)))?;
 
         let _ = self.set_category(s)?;
         let _ = self.add_internal_link(&mut category)?;
@@ -77,7 +78,7 @@ impl EntryCategory for Entry {
         trace!("Getting category from '{}'", self.get_location());
         self.get_header()
             .read_string("category.value")?
-            .ok_or_else(|| Error::from(err_msg("Category name missing")))
+            .ok_or_else(|| kind_err(ErrorKind::Parse, "Category name missing"))
     }
 
     fn has_category(&self) -> Result<bool> {
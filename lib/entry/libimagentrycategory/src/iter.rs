@@ -21,13 +21,14 @@ use libimagstore::storeid::StoreIdIterator;
 use libimagstore::store::Store;
 use libimagstore::store::FileLockEntry;
 use libimagerror::errors::ErrorMsg as EM;
+use libimagerror::kind::ErrorKind;
+use libimagerror::kind::kind_err;
 
 use toml_query::read::TomlValueReadTypeExt;
 
 use failure::Fallible as Result;
 use failure::ResultExt;
 use failure::Error;
-use failure::err_msg;
 use store::CATEGORY_REGISTER_NAME_FIELD_PATH;
 use entry::EntryCategory;
 
@@ -68,12 +69,12 @@ impl<'a> Iterator for CategoryNameIter<'a> {
                         let func = |store: &Store| { // hack for returning Some(Result<_, _>)
                             store
                                 .get(sid)?
-                                .ok_or_else(|| Error::from(err_msg("Store read error")))?
+                                .ok_or_else(|| kind_err(ErrorKind::NotFound, "Store read error"))?
                                 .get_header()
                                 .read_string(query)
                                 .map_err(Error::from)
                                 .context(EM::EntryHeaderReadError)?
-                                .ok_or_else(|| Error::from(err_msg("Store read error")))
+                                .ok_or_else(|| kind_err(ErrorKind::NotFound, "Store read error"))
                         };
 
                     return Some(func(&self.0))
@@ -105,7 +106,7 @@ impl<'a> Iterator for CategoryEntryIterator<'a> {
                     let getter = |next| -> Result<(String, FileLockEntry<'a>)> {
                         let entry = self.0
                             .get(next)?
-                            .ok_or_else(|| Error::from(err_msg("Store read error")))?;
+                            .ok_or_else(|| kind_err(ErrorKind::NotFound, "Store read error"))?;
                         Ok((entry.get_category()?, entry))
                     };
 
@@ -28,6 +28,8 @@ use libimagstore::store::FileLockEntry;
 use libimagstore::storeid::StoreId;
 use libimagentryutil::isa::Is;
 use libimagerror::errors::ErrorMsg as EM;
+use libimagerror::kind::ErrorKind;
+use libimagerror::kind::kind_err;
 
 use failure::Fallible as Result;
 use failure::ResultExt;
@@ -94,7 +96,7 @@ impl CategoryStore for Store {
 
         {
             let mut category = self.get(sid.clone())?
-                .ok_or_else(|| Error::from(err_msg("Category does not exist")))
+                .ok_or_else(|| kind_err(ErrorKind::NotFound, format!("Category does not exist: {}", name)))
                 .map_err(Error::from)?;
 
             for entry in category.get_entries(self)? {
@@ -18,9 +18,6 @@
 //
 
 use chrono::naive::NaiveDateTime;
-use toml_query::delete::TomlValueDeleteExt;
-use toml_query::insert::TomlValueInsertExt;
-use toml_query::read::TomlValueReadTypeExt;
 use toml::Value;
 
 use libimagstore::store::Entry;
@@ -29,7 +26,6 @@ use libimagerror::errors::ErrorMsg as EM;
 use failure::Error;
 use failure::Fallible as Result;
 use failure::ResultExt;
-use failure::err_msg;
 use range::DateTimeRange;
 
 pub trait EntryDate {
@@ -45,27 +41,30 @@ pub trait EntryDate {
 }
 
 lazy_static! {
-    static ref DATE_HEADER_LOCATION : &'static str              = "datetime.value";
-    static ref DATE_RANGE_START_HEADER_LOCATION : &'static str  = "datetime.range.start";
-    static ref DATE_RANGE_END_HEADER_LOCATION : &'static str    = "datetime.range.end";
-    static ref DATE_FMT : &'static str                          = "%Y-%m-%dT%H:%M:%S";
+    static ref DATE_HEADER_LOCATION : &'static str       = "datetime.value";
+    static ref DATE_RANGE_HEADER_LOCATION : &'static str = "datetime.range";
+    static ref DATE_FMT : &'static str                   = "%Y-%m-%dT%H:%M:%S";
+}
+
+/// The header section of a date range
+#[derive(Debug, Serialize, Deserialize)]
+struct DateTimeRangeHeader {
+    start: String,
+    end: String,
 }
 
 impl EntryDate for Entry {
 
     fn delete_date(&mut self) -> Result<()> {
-        self.get_header_mut()
-            .delete(&DATE_HEADER_LOCATION)
+        self.delete_section(&DATE_HEADER_LOCATION)
             .map(|_| ())
             .context("Delete date error")
             .map_err(Error::from)
     }
 
     fn read_date(&self) -> Result<NaiveDateTime> {
-        self.get_header()
-            .read_string(&DATE_HEADER_LOCATION)
+        self.require_section::<String>(&DATE_HEADER_LOCATION)
             .context("Error while reading date")?
-            .ok_or_else(|| err_msg("Error reading date"))?
             .parse::<NaiveDateTime>()
             .context("Datetime parse error")
             .map_err(Error::from)
@@ -89,57 +88,31 @@ impl EntryDate for Entry {
     ///
     fn set_date(&mut self, d: NaiveDateTime) -> Result<Option<Result<NaiveDateTime>>> {
         let date = d.format(&DATE_FMT).to_string();
+        let old  = self.get_section::<Value>(&DATE_HEADER_LOCATION)
+            .context("Error setting date")?;
 
-        self.get_header_mut()
-            .insert(&DATE_HEADER_LOCATION, Value::String(date))
-            .map_err(Error::from)
-            .map(|opt| opt.map(|stri| {
-                stri.as_str()
-                    .ok_or_else(|| Error::from(EM::EntryHeaderTypeError))?
-                    .parse::<NaiveDateTime>()
-                    .context("Datetime parse error")
-                    .map_err(Error::from)
-            }))
-            .context("Error setting date")
-            .map_err(Error::from)
+        self.set_section(&DATE_HEADER_LOCATION, &date)
+            .context("Error setting date")?;
+
+        Ok(old.as_ref().map(val_to_ndt))
     }
 
 
     /// Deletes the date range
-    ///
-    /// # Warning
-    ///
-    /// First deletes the start, then the end. If the first operation fails, this might leave the
-    /// header in an inconsistent state.
-    ///
     fn delete_date_range(&mut self) -> Result<()> {
-        let _ = self
-             .get_header_mut()
-            .delete(&DATE_RANGE_START_HEADER_LOCATION)
-            .map(|_| ())
-            .context("Delete Datetime range error")?;
-
-        self.get_header_mut()
-            .delete(&DATE_RANGE_END_HEADER_LOCATION)
+        self.delete_section(&DATE_RANGE_HEADER_LOCATION)
             .map(|_| ())
             .context("Delete Datetime range error")
             .map_err(Error::from)
     }
 
     fn read_date_range(&self) -> Result<DateTimeRange> {
-        let start = self
-            .get_header()
-            .read_string(&DATE_RANGE_START_HEADER_LOCATION)
-            .context("Error while reading Datetime range")?
-            .ok_or_else(|| err_msg("Error reading date"))
-            .and_then(str_to_ndt)?;
-
-        let end = self
-            .get_header()
-            .read_string(&DATE_RANGE_START_HEADER_LOCATION)
-            .context("Error reading Datetime range")?
-            .ok_or_else(|| err_msg("Error reading date"))
-            .and_then(str_to_ndt)?;
+        let header = self
+            .require_section::<DateTimeRangeHeader>(&DATE_RANGE_HEADER_LOCATION)
+            .context("Error while reading Datetime range")?;
+
+        let start = str_to_ndt(header.start)?;
+        let end   = str_to_ndt(header.end)?;
 
         DateTimeRange::new(start, end)
             .context("Datetime Range error")
@@ -148,43 +121,33 @@ impl EntryDate for Entry {
 
     /// Set the date range
     ///
-    /// # Warning
+    /// # Return value
     ///
-    /// This first sets the start, then the end. If the first operation fails, this might leave the
-    /// header in an inconsistent state.
+    /// Like `EntryDate::set_date()`, the replaced range (if there was one) is returned. If the
+    /// old range could not be read, an error is returned, though the new range is set.
     ///
     fn set_date_range(&mut self, start: NaiveDateTime, end: NaiveDateTime)
         -> Result<Option<Result<DateTimeRange>>>
     {
-        let start = start.format(&DATE_FMT).to_string();
-        let end   = end.format(&DATE_FMT).to_string();
+        let old = self.get_section::<DateTimeRangeHeader>(&DATE_RANGE_HEADER_LOCATION);
 
-        let opt_old_start = self
-            .get_header_mut()
-            .insert(&DATE_RANGE_START_HEADER_LOCATION, Value::String(start))
-            .map(|opt| opt.as_ref().map(val_to_ndt))
-            .context("Error setting Datetime range")?;
+        let header = DateTimeRangeHeader {
+            start: start.format(&DATE_FMT).to_string(),
+            end:   end.format(&DATE_FMT).to_string(),
+        };
 
-        let opt_old_end = self
-            .get_header_mut()
-            .insert(&DATE_RANGE_END_HEADER_LOCATION, Value::String(end))
-            .map(|opt| opt.as_ref().map(val_to_ndt))
+        self.set_section(&DATE_RANGE_HEADER_LOCATION, &header)
             .context("Error setting Datetime range")?;
 
-        match (opt_old_start, opt_old_end) {
-            (Some(Ok(old_start)), Some(Ok(old_end))) => {
-                let dr = DateTimeRange::new(old_start, old_end)
+        match old.context("Error processing Datetime range")? {
+            Some(old) => {
+                let dr = DateTimeRange::new(str_to_ndt(old.start)?, str_to_ndt(old.end)?)
                     .context("Error processing Datetime range")
                     .map_err(Error::from);
 
                 Ok(Some(dr))
             },
-
-            (Some(Err(e)), _) => Err(e),
-            (_, Some(Err(e))) => Err(e),
-            _ => {
-                Ok(None)
-            },
+            None => Ok(None),
         }
     }
 
@@ -322,5 +285,26 @@ mod tests {
 
         assert!(hdr_field.is_none());
     }
-}
 
+    #[test]
+    fn test_set_read_date_range() {
+        let store = get_store();
+
+        let start = NaiveDateTime::new(NaiveDate::from_ymd(2000, 01, 02), NaiveTime::from_hms(03, 04, 05));
+        let end   = NaiveDateTime::new(NaiveDate::from_ymd(2000, 01, 03), NaiveTime::from_hms(03, 04, 05));
+
+        let mut entry = store.create(PathBuf::from("test")).unwrap();
+        assert!(entry.set_date_range(start, end).unwrap().is_none());
+
+        let range = entry.read_date_range().unwrap();
+        assert_eq!(DateTimeRange::new(start, end).unwrap(), range);
+
+        let later = NaiveDateTime::new(NaiveDate::from_ymd(2000, 01, 04), NaiveTime::from_hms(03, 04, 05));
+        let old   = entry.set_date_range(start, later).unwrap();
+        assert_eq!(Some(range), old.map(|r| r.unwrap()));
+
+        assert!(entry.delete_date_range().is_ok());
+        assert!(entry.get_header().read("datetime.range").unwrap().is_none());
+        assert!(entry.read_date_range().is_err());
+    }
+}
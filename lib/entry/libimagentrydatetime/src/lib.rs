@@ -40,6 +40,7 @@
 #[macro_use] extern crate lazy_static;
 extern crate chrono;
 extern crate toml_query;
+#[macro_use] extern crate serde_derive;
 extern crate toml;
 extern crate failure;
 
@@ -20,7 +20,9 @@
 use chrono::naive::NaiveDateTime;
 
 use failure::Fallible as Result;
-use failure::err_msg;
+
+use libimagerror::kind::ErrorKind;
+use libimagerror::kind::kind_err;
 
 /// A Range between two dates
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -39,7 +41,7 @@ impl DateTimeRange {
         if start < end {
             Ok(DateTimeRange(start, end))
         } else {
-            Err(err_msg("End date before start date"))
+            Err(kind_err(ErrorKind::Parse, "End date before start date"))
         }
     }
 
@@ -52,6 +54,8 @@ mod tests {
     use chrono::naive::NaiveDate;
     use chrono::naive::NaiveTime;
 
+    use libimagerror::kind::ErrorKind;
+
     use super::DateTimeRange;
 
     #[test]
@@ -69,6 +73,7 @@ mod tests {
         let res = DateTimeRange::new(start, end);
 
         assert!(res.is_err());
+        assert_eq!(ErrorKind::Parse, ErrorKind::of(&res.unwrap_err()));
     }
 
     #[test]
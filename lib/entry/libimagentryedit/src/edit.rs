@@ -26,6 +26,8 @@ use failure::ResultExt;
 use failure::err_msg;
 
 use libimagerror::errors::ErrorMsg as EM;
+use libimagerror::kind::ErrorKind;
+use libimagerror::kind::kind_err;
 
 pub trait Edit {
     fn edit_content(&mut self, rt: &Runtime) -> Result<()>;
@@ -77,7 +79,7 @@ pub fn edit_in_tmpfile(rt: &Runtime, s: &mut String) -> Result<()> {
     let editor = rt
         .editor()
         .context(err_msg("No editor"))?
-        .ok_or_else(|| Error::from(err_msg("No editor")))?;
+        .ok_or_else(|| kind_err(ErrorKind::Config, "No editor"))?;
 
     edit_in_tmpfile_with_command(editor, s)
         .context(EM::IO)
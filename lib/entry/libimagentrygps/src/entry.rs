@@ -21,10 +21,7 @@ use types::*;
 
 use libimagstore::store::Entry;
 
-use toml_query::read::TomlValueReadExt;
-use toml_query::insert::TomlValueInsertExt;
-use toml_query::delete::TomlValueDeleteExt;
-use failure::ResultExt;
+use toml::Value;
 use failure::Fallible as Result;
 use failure::Error;
 
@@ -57,47 +54,24 @@ pub trait GPSEntry {
 impl GPSEntry for Entry {
 
     fn set_coordinates(&mut self, c: Coordinates) -> Result<()> {
-        self.get_header_mut()
-            .insert("gps.coordinates", c.into())
-            .map(|_| ())
-            .map_err(Error::from)
+        self.set_section("gps.coordinates", &c)
     }
 
     fn get_coordinates(&self) -> Result<Option<Coordinates>> {
-        match self.get_header().read("gps.coordinates").map_err(Error::from)?  {
-            Some(hdr) => Coordinates::from_value(hdr).map(Some),
-            None      => Ok(None),
-        }
+        self.get_section("gps.coordinates")
     }
 
     fn remove_coordinates(&mut self) -> Result<Option<Result<Coordinates>>> {
-        let coordinates = self.get_coordinates();
-
-        let patterns = [
-            "gps.coordinates.latitude.degree",
-            "gps.coordinates.latitude.minutes",
-            "gps.coordinates.latitude.seconds",
-            "gps.coordinates.longitude.degree",
-            "gps.coordinates.longitude.minutes",
-            "gps.coordinates.longitude.seconds",
-            "gps.coordinates.latitude",
-            "gps.coordinates.longitude",
-            "gps.coordinates",
-            "gps",
-        ];
-
-        let hdr = self.get_header_mut();
-        for pattern in patterns.iter() {
-            let _ = hdr.delete(pattern)
-                .map_err(Error::from)
-                .context("Error writing header")?;
-        }
+        let coordinates = self.delete_section("gps.coordinates")?;
 
-        match coordinates {
-            Ok(None)       => Ok(None),
-            Ok(Some(some)) => Ok(Some(Ok(some))),
-            Err(e)         => Ok(Some(Err(e))),
+        let gps_is_empty = self.get_section::<Value>("gps")?
+            .map(|gps| gps.as_table().map(|t| t.is_empty()).unwrap_or(false))
+            .unwrap_or(false);
+        if gps_is_empty {
+            let _ = self.delete_section("gps")?;
         }
+
+        Ok(coordinates.map(|c| c.try_into::<Coordinates>().map_err(Error::from)))
     }
 }
 
@@ -166,5 +140,27 @@ mod tests {
         assert_eq!(0, coordinates.latitude.minutes);
         assert_eq!(0, coordinates.latitude.seconds);
     }
-}
 
+    #[test]
+    fn test_remove_gps() {
+        setup_logging();
+
+        let store = get_store();
+
+        let mut entry = store.create(PathBuf::from("test_remove_gps")).unwrap();
+
+        let coordinates = Coordinates {
+            latitude: GPSValue::new(1, 2, 3),
+            longitude: GPSValue::new(4, 5, 6),
+        };
+
+        assert!(entry.set_coordinates(coordinates.clone()).is_ok());
+
+        let removed = entry.remove_coordinates().unwrap();
+        assert_eq!(Some(coordinates), removed.map(|r| r.unwrap()));
+
+        assert!(entry.get_coordinates().unwrap().is_none());
+        assert!(entry.get_header().as_table().unwrap().get("gps").is_none());
+        assert!(entry.remove_coordinates().unwrap().is_none());
+    }
+}
@@ -36,7 +36,6 @@
 )]
 
 extern crate toml;
-extern crate toml_query;
 #[macro_use] extern crate serde_derive;
 extern crate failure;
 
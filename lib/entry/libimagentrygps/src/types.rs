@@ -24,10 +24,11 @@ use std::fmt::Result as FmtResult;
 
 use toml::Value;
 use failure::Fallible as Result;
-use failure::err_msg;
 use failure::Error;
 
 use libimagerror::errors::ErrorMsg as EM;
+use libimagerror::kind::ErrorKind;
+use libimagerror::kind::kind_err;
 
 pub trait FromValue : Sized {
     fn from_value(v: &Value) -> Result<Self>;
@@ -88,17 +89,17 @@ impl FromValue for GPSValue {
             Value::Table(ref map) => {
                 Ok(GPSValue::new(
                     map.get("degree")
-                        .ok_or_else(|| Error::from(err_msg("Degree missing")))
+                        .ok_or_else(|| kind_err(ErrorKind::Parse, "Degree missing"))
                         .and_then(&int_to_appropriate_width)?,
 
                     map
                         .get("minutes")
-                        .ok_or_else(|| Error::from(err_msg("Minutes missing")))
+                        .ok_or_else(|| kind_err(ErrorKind::Parse, "Minutes missing"))
                         .and_then(&int_to_appropriate_width)?,
 
                     map
                         .get("seconds")
-                        .ok_or_else(|| Error::from(err_msg("Seconds missing")))
+                        .ok_or_else(|| kind_err(ErrorKind::Parse, "Seconds missing"))
                         .and_then(&int_to_appropriate_width)?
                 ))
             }
@@ -156,7 +157,7 @@ impl FromValue for Coordinates {
             .and_then(|t| {
                 let get = |m: &BTreeMap<_, _>, what: &'static str, ek| -> Result<GPSValue> {
                     m.get(what)
-                        .ok_or_else(|| Error::from(err_msg(ek)))
+                        .ok_or_else(|| kind_err(ErrorKind::Parse, ek))
                         .and_then(GPSValue::from_value)
                 };
 
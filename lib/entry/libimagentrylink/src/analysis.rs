@@ -0,0 +1,347 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2019 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+//! Queries on the link network of the store
+//!
+//! `LinkAnalysis` is built from the link network aggregated by
+//! `StoreLinkConsistentExt::aggregate_link_network()`. Links are treated as undirected, relations
+//! are ignored. External link entries are not part of the analysis.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::VecDeque;
+
+use failure::Fallible as Result;
+
+use libimagstore::store::Store;
+use libimagstore::storeid::StoreId;
+
+use external::is_external_link_storeid;
+use internal::store_check::LinkNetwork;
+use internal::store_check::StoreLinkConsistentExt;
+
+#[derive(Debug, Default)]
+pub struct LinkAnalysis {
+    adjacency: BTreeMap<StoreId, BTreeSet<StoreId>>,
+}
+
+impl LinkAnalysis {
+
+    /// Aggregate the link network of `store` and build the analysis from it
+    pub fn from_store(store: &Store) -> Result<LinkAnalysis> {
+        store.aggregate_link_network().map(|network| LinkAnalysis::from_network(&network))
+    }
+
+    /// Build the analysis from a link network
+    ///
+    /// Links to entries which are not in the network and links of an entry to itself are ignored.
+    pub fn from_network(network: &LinkNetwork) -> LinkAnalysis {
+        let mut adjacency = BTreeMap::new();
+
+        for id in network.keys().filter(|id| !is_external_link_storeid(id)) {
+            let _ = adjacency.insert(id.clone(), BTreeSet::new());
+        }
+
+        for (src, linking) in network.iter() {
+            for target in linking.outgoing.iter() {
+                if src == target || !adjacency.contains_key(src) || !adjacency.contains_key(target) {
+                    continue
+                }
+
+                let _ = adjacency.get_mut(src).map(|s| s.insert(target.clone()));
+                let _ = adjacency.get_mut(target).map(|s| s.insert(src.clone()));
+            }
+        }
+
+        LinkAnalysis { adjacency }
+    }
+
+    pub fn contains(&self, id: &StoreId) -> bool {
+        self.adjacency.contains_key(id)
+    }
+
+    /// Get all entries within `depth` links of `id`, with their distance from `id`
+    ///
+    /// The result is ordered by distance. `id` itself is not contained.
+    pub fn neighbourhood(&self, id: &StoreId, depth: usize) -> Vec<(StoreId, usize)> {
+        let mut distances = BTreeMap::new();
+        let mut queue     = VecDeque::new();
+        let mut result    = Vec::new();
+
+        let _ = distances.insert(id, 0);
+        queue.push_back(id);
+
+        while let Some(current) = queue.pop_front() {
+            let distance = distances[current];
+            if distance >= depth {
+                continue
+            }
+
+            for next in self.links_of(current) {
+                if !distances.contains_key(next) {
+                    let _ = distances.insert(next, distance + 1);
+                    result.push((next.clone(), distance + 1));
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Get the shortest link path from `from` to `to`, including both ends
+    ///
+    /// Returns `None` if there is no path between the entries.
+    pub fn shortest_path(&self, from: &StoreId, to: &StoreId) -> Option<Vec<StoreId>> {
+        if !self.contains(from) || !self.contains(to) {
+            return None
+        }
+
+        let mut predecessors : BTreeMap<&StoreId, Option<&StoreId>> = BTreeMap::new();
+        let mut queue = VecDeque::new();
+
+        let _ = predecessors.insert(from, None);
+        queue.push_back(from);
+
+        while let Some(current) = queue.pop_front() {
+            if current == to {
+                let mut path = vec![current.clone()];
+                let mut step = predecessors[current];
+                while let Some(prev) = step {
+                    path.push(prev.clone());
+                    step = predecessors[prev];
+                }
+                path.reverse();
+                return Some(path)
+            }
+
+            for next in self.links_of(current) {
+                if !predecessors.contains_key(next) {
+                    let _ = predecessors.insert(next, Some(current));
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Get the connected components of the network, largest first
+    pub fn components(&self) -> Vec<Vec<StoreId>> {
+        let mut seen       = BTreeSet::new();
+        let mut components = Vec::new();
+
+        for start in self.adjacency.keys() {
+            if seen.contains(start) {
+                continue
+            }
+
+            let mut component = Vec::new();
+            let mut queue     = VecDeque::new();
+            let _ = seen.insert(start);
+            queue.push_back(start);
+
+            while let Some(current) = queue.pop_front() {
+                component.push(current.clone());
+                for next in self.links_of(current) {
+                    if seen.insert(next) {
+                        queue.push_back(next);
+                    }
+                }
+            }
+
+            component.sort();
+            components.push(component);
+        }
+
+        components.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        components
+    }
+
+    /// Get all entries which neither link to nor are linked from other entries
+    pub fn isolated(&self) -> Vec<StoreId> {
+        self.adjacency
+            .iter()
+            .filter(|&(_, links)| links.is_empty())
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+
+    /// Get the number of links of each entry, highest first
+    pub fn degrees(&self) -> Vec<(StoreId, usize)> {
+        let mut degrees = self.adjacency
+            .iter()
+            .map(|(id, links)| (id.clone(), links.len()))
+            .collect::<Vec<_>>();
+
+        degrees.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        degrees
+    }
+
+    /// Get the PageRank of each entry, highest first
+    ///
+    /// `damping` is the damping factor (usually 0.85), the ranks are computed with `iterations`
+    /// power iterations. The ranks sum up to 1.
+    pub fn pagerank(&self, damping: f64, iterations: usize) -> Vec<(StoreId, f64)> {
+        let n = self.adjacency.len();
+        if n == 0 {
+            return Vec::new()
+        }
+
+        let ids   = self.adjacency.keys().collect::<Vec<_>>();
+        let index = ids.iter().enumerate().map(|(i, id)| (*id, i)).collect::<BTreeMap<_, _>>();
+        let base  = (1.0 - damping) / n as f64;
+
+        let mut ranks = vec![1.0 / n as f64; n];
+
+        for _ in 0..iterations {
+            // Entries without links distribute their rank over all entries
+            let dangling = ids
+                .iter()
+                .zip(ranks.iter())
+                .filter(|&(id, _)| self.adjacency[*id].is_empty())
+                .map(|(_, rank)| rank)
+                .sum::<f64>();
+
+            let mut next = vec![base + damping * dangling / n as f64; n];
+            for (i, id) in ids.iter().enumerate() {
+                let links = &self.adjacency[*id];
+                if links.is_empty() {
+                    continue
+                }
+
+                let share = damping * ranks[i] / links.len() as f64;
+                for link in links.iter() {
+                    next[index[link]] += share;
+                }
+            }
+
+            ranks = next;
+        }
+
+        let mut result = ids.into_iter().cloned().zip(ranks.into_iter()).collect::<Vec<_>>();
+        result.sort_by(|a, b| {
+            b.1.partial_cmp(&a.1)
+                .unwrap_or(::std::cmp::Ordering::Equal)
+                .then_with(|| a.0.cmp(&b.0))
+        });
+        result
+    }
+
+    fn links_of<'a>(&'a self, id: &StoreId) -> impl Iterator<Item = &'a StoreId> {
+        self.adjacency.get(id).into_iter().flat_map(|links| links.iter())
+    }
+
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use libimagstore::store::Store;
+    use libimagstore::storeid::StoreId;
+
+    use super::*;
+    use internal::InternalLinker;
+
+    fn get_store() -> Store {
+        Store::new_inmemory(PathBuf::from("/"), &None).unwrap()
+    }
+
+    fn id(s: &str) -> StoreId {
+        StoreId::new(PathBuf::from(s)).unwrap()
+    }
+
+    // a - b - c - d    e - f    g
+    fn setup(store: &Store) {
+        let mut a = store.create(PathBuf::from("a")).unwrap();
+        let mut b = store.create(PathBuf::from("b")).unwrap();
+        let mut c = store.create(PathBuf::from("c")).unwrap();
+        let mut d = store.create(PathBuf::from("d")).unwrap();
+        let mut e = store.create(PathBuf::from("e")).unwrap();
+        let mut f = store.create(PathBuf::from("f")).unwrap();
+        let _     = store.create(PathBuf::from("g")).unwrap();
+
+        a.add_internal_link(&mut b).unwrap();
+        b.add_internal_link(&mut c).unwrap();
+        c.add_internal_link(&mut d).unwrap();
+        e.add_internal_link(&mut f).unwrap();
+    }
+
+    #[test]
+    fn test_neighbourhood() {
+        let store = get_store();
+        setup(&store);
+        let analysis = LinkAnalysis::from_store(&store).unwrap();
+
+        let n = analysis.neighbourhood(&id("a"), 2);
+        assert_eq!(vec![(id("b"), 1), (id("c"), 2)], n);
+
+        assert!(analysis.neighbourhood(&id("g"), 5).is_empty());
+    }
+
+    #[test]
+    fn test_shortest_path() {
+        let store = get_store();
+        setup(&store);
+        let analysis = LinkAnalysis::from_store(&store).unwrap();
+
+        let path = analysis.shortest_path(&id("a"), &id("d")).unwrap();
+        assert_eq!(vec![id("a"), id("b"), id("c"), id("d")], path);
+
+        assert_eq!(Some(vec![id("a")]), analysis.shortest_path(&id("a"), &id("a")));
+        assert!(analysis.shortest_path(&id("a"), &id("e")).is_none());
+        assert!(analysis.shortest_path(&id("a"), &id("nonexistent")).is_none());
+    }
+
+    #[test]
+    fn test_components_and_isolated() {
+        let store = get_store();
+        setup(&store);
+        let analysis = LinkAnalysis::from_store(&store).unwrap();
+
+        let components = analysis.components();
+        assert_eq!(vec![
+            vec![id("a"), id("b"), id("c"), id("d")],
+            vec![id("e"), id("f")],
+            vec![id("g")],
+        ], components);
+
+        assert_eq!(vec![id("g")], analysis.isolated());
+    }
+
+    #[test]
+    fn test_ranking() {
+        let store = get_store();
+        setup(&store);
+        let analysis = LinkAnalysis::from_store(&store).unwrap();
+
+        let degrees = analysis.degrees();
+        assert_eq!((id("b"), 2), degrees[0]);
+        assert_eq!((id("c"), 2), degrees[1]);
+        assert_eq!((id("g"), 0), degrees[6]);
+
+        let ranks = analysis.pagerank(0.85, 50);
+        assert_eq!(7, ranks.len());
+        assert!(ranks[0].0 == id("b") || ranks[0].0 == id("c"));
+
+        let sum = ranks.iter().map(|r| r.1).sum::<f64>();
+        assert!((sum - 1.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,419 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2019 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+//! Offline copies of web pages
+//!
+//! The `Archiver` fetches the page an external link points to and stores a readable copy of it
+//! as a new entry in the `links/archive` collection. The content of the archive entry is either
+//! the text of the page or the page as HTML, with its images and stylesheets inlined and its
+//! scripts removed. The archive entry is linked to the external link entry, so all copies of a
+//! page can be found from there. Because the copy is the content of an entry, it can be searched
+//! like every other entry.
+
+use std::io::Read;
+use std::str::FromStr;
+use std::time::Duration;
+
+use chrono::Local;
+use regex::Captures;
+use regex::Regex;
+use reqwest::Client;
+use reqwest::header::CONTENT_TYPE;
+use reqwest::header::USER_AGENT;
+use url::Url;
+use sha1::{Sha1, Digest};
+use hex;
+use base64;
+use failure::Fallible as Result;
+use failure::Error;
+
+use libimagstore::store::Entry;
+use libimagstore::store::FileLockEntry;
+use libimagstore::store::Store;
+use libimagstore::storeid::StoreId;
+use libimagstore::storeid::IntoStoreId;
+use libimagerror::kind::ErrorKind;
+use libimagerror::kind::kind_err;
+use libimagutil::date::datetime_to_string;
+
+use check::IMAG_USER_AGENT;
+use check::build_client;
+use check::decode_entities;
+use check::extract_title;
+use external::Link as ExternalLink;
+use external::is_external_link_storeid;
+use internal::InternalLinker;
+use module_path::ModuleEntryPath;
+
+/// The header path the metadata of an archived copy is stored at
+pub const ARCHIVE_HEADER_PATH : &str = "archive";
+
+/// Pages and resources larger than this are cut off
+const MAX_DOWNLOAD_SIZE : u64 = 10 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    /// The text of the page, without markup
+    Text,
+
+    /// The HTML of the page, with images and stylesheets inlined and scripts removed
+    Html,
+}
+
+impl ArchiveFormat {
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            ArchiveFormat::Text => "text",
+            ArchiveFormat::Html => "html",
+        }
+    }
+}
+
+impl FromStr for ArchiveFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "text" => Ok(ArchiveFormat::Text),
+            "html" => Ok(ArchiveFormat::Html),
+            other  => Err(kind_err(ErrorKind::Parse, format!("Unknown archive format: '{}'", other))),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveHeader {
+    /// The URL of the archived page
+    pub url: String,
+
+    /// When the page was archived
+    pub captured: String,
+
+    /// The format of the content, "text" or "html"
+    pub format: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+}
+
+/// Check whether `id` is the id of an archived copy
+pub fn is_archive_storeid<A: AsRef<StoreId>>(id: A) -> bool {
+    id.as_ref().is_in_collection(&["links", "archive"])
+}
+
+pub fn get_archive_header(entry: &Entry) -> Result<Option<ArchiveHeader>> {
+    entry.get_section(ARCHIVE_HEADER_PATH)
+}
+
+/// Get the ids of the archived copies linked to `entry`
+pub fn get_archives(entry: &Entry) -> Result<Vec<StoreId>> {
+    Ok(entry
+       .get_internal_links()?
+       .map(|l| l.get_store_id().clone())
+       .filter(is_archive_storeid)
+       .collect())
+}
+
+/// Creates archived copies of web pages
+#[derive(Debug, Clone)]
+pub struct Archiver {
+    timeout: Duration,
+    format: ArchiveFormat,
+}
+
+impl Default for Archiver {
+    fn default() -> Self {
+        Archiver {
+            timeout: Duration::from_secs(30),
+            format: ArchiveFormat::Text,
+        }
+    }
+}
+
+impl Archiver {
+
+    pub fn new() -> Self {
+        Archiver::default()
+    }
+
+    /// Fail a request if there is no response after `timeout`
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_format(mut self, format: ArchiveFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Fetch `url` and return the title of the page, if any, and the archived content
+    ///
+    /// Pages which are not HTML but text are archived as they are, other content is refused.
+    pub fn fetch(&self, url: &Url) -> Result<(Option<String>, String)> {
+        let client = build_client(self.timeout, 10)?;
+        let (final_url, content_type, body) = download(&client, url.as_str())?;
+
+        if !content_type.starts_with("text/") && !content_type.contains("html") {
+            let msg = format!("Cannot archive content of type '{}': {}", content_type, url);
+            return Err(kind_err(ErrorKind::Other, msg))
+        }
+
+        let page = String::from_utf8_lossy(&body).into_owned();
+        if !content_type.contains("html") {
+            return Ok((None, page))
+        }
+
+        let title   = extract_title(&page);
+        let content = match self.format {
+            ArchiveFormat::Text => html_to_text(&page),
+            ArchiveFormat::Html => inline_resources(&client, &final_url, &page),
+        };
+
+        Ok((title, content))
+    }
+
+    /// Archive the page of the external link entry `id`
+    ///
+    /// Each call creates a new archive entry, which is linked to the external link entry.
+    pub fn archive<'a>(&self, store: &'a Store, id: &StoreId) -> Result<FileLockEntry<'a>> {
+        if !is_external_link_storeid(id) {
+            return Err(kind_err(ErrorKind::Other, format!("Not an external link: {}", id)))
+        }
+
+        let mut link = store.get(id.clone())?
+            .ok_or_else(|| kind_err(ErrorKind::NotFound, format!("Entry does not exist: {}", id)))?;
+
+        let url = link.get_url()?
+            .ok_or_else(|| kind_err(ErrorKind::NotFound, format!("No URL in external link entry {}", id)))?;
+
+        let (title, content) = self.fetch(&url)?;
+
+        let now        = Local::now().naive_local();
+        let hash       = hex::encode(Sha1::digest(url.as_str().as_bytes()));
+        let archive_id = ModuleEntryPath::new(format!("archive/{}-{}", hash, now.format("%Y%m%d%H%M%S")))
+            .into_storeid()?;
+
+        let mut archive = store.create(archive_id)?;
+        archive.set_section(ARCHIVE_HEADER_PATH, &ArchiveHeader {
+            url: url.into_string(),
+            captured: datetime_to_string(&now),
+            format: self.format.as_str().to_owned(),
+            title,
+        })?;
+        *archive.get_content_mut() = content;
+
+        link.add_internal_link(&mut archive)?;
+        Ok(archive)
+    }
+
+}
+
+/// Download `url`, returning the URL after redirects, the content type and the body
+fn download(client: &Client, url: &str) -> Result<(Url, String, Vec<u8>)> {
+    let mut response = client
+        .get(url)
+        .header(USER_AGENT, IMAG_USER_AGENT)
+        .send()?;
+
+    if !response.status().is_success() {
+        let msg = format!("Fetching {} failed: {}", url, response.status());
+        return Err(kind_err(ErrorKind::Other, msg))
+    }
+
+    let final_url    = Url::parse(response.url().as_str())?;
+    let content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("")
+        .to_owned();
+
+    let mut body = Vec::new();
+    let _ = response.by_ref().take(MAX_DOWNLOAD_SIZE).read_to_end(&mut body)?;
+
+    Ok((final_url, content_type, body))
+}
+
+/// Extract the readable text of a HTML page
+fn html_to_text(html: &str) -> String {
+    let invisible = Regex::new(r"(?is)<(head|script|style|noscript)\b[^>]*>.*?</(head|script|style|noscript)\s*>").unwrap();
+    let comments  = Regex::new(r"(?s)<!--.*?-->").unwrap();
+    let blocks    = Regex::new(r"(?i)</?(p|div|br|hr|li|ul|ol|dl|dt|dd|h[1-6]|tr|table|section|article|header|footer|blockquote|pre)\b[^>]*>").unwrap();
+    let tags      = Regex::new(r"(?s)<[^>]*>").unwrap();
+
+    let text = invisible.replace_all(html, "");
+    let text = comments.replace_all(&text, "");
+    let text = blocks.replace_all(&text, "\n");
+    let text = tags.replace_all(&text, "");
+
+    let mut out   = String::new();
+    let mut blank = true;
+    for line in text.lines() {
+        let line = decode_entities(&line.split_whitespace().collect::<Vec<_>>().join(" "));
+        if line.is_empty() {
+            if !blank {
+                out.push('\n');
+            }
+            blank = true;
+        } else {
+            out.push_str(&line);
+            out.push('\n');
+            blank = false;
+        }
+    }
+
+    out.trim_end().to_owned() + "\n"
+}
+
+/// Remove the scripts of a HTML page and inline its images and stylesheets
+///
+/// Resources which cannot be fetched are left untouched.
+fn inline_resources(client: &Client, base: &Url, html: &str) -> String {
+    let scripts = Regex::new(r"(?is)<script\b[^>]*>.*?</script\s*>").unwrap();
+    let images  = Regex::new(r#"(?i)(<img\b[^>]*?\bsrc\s*=\s*)["']([^"']+)["']"#).unwrap();
+    let styles  = Regex::new(r#"(?i)<link\b[^>]*\brel\s*=\s*["']?stylesheet\b[^>]*>"#).unwrap();
+    let href    = Regex::new(r#"(?i)\bhref\s*=\s*["']([^"']+)["']"#).unwrap();
+
+    let fetch = |src: &str| -> Option<(String, Vec<u8>)> {
+        let url = base.join(src).ok()?;
+        match download(client, url.as_str()) {
+            Ok((_, content_type, body)) => Some((content_type, body)),
+            Err(e) => {
+                debug!("Failed to fetch {} for inlining: {:?}", url, e);
+                None
+            },
+        }
+    };
+
+    let html = scripts.replace_all(html, "");
+
+    let html = images.replace_all(&html, |caps: &Captures| {
+        if caps[2].starts_with("data:") {
+            return caps[0].to_owned()
+        }
+
+        match fetch(&caps[2]) {
+            Some((content_type, body)) =>
+                format!("{}\"data:{};base64,{}\"", &caps[1], content_type, base64::encode(&body)),
+            None => caps[0].to_owned(),
+        }
+    });
+
+    let html = styles.replace_all(&html, |caps: &Captures| {
+        href.captures(&caps[0])
+            .and_then(|h| fetch(&h[1]))
+            .map(|(_, body)| format!("<style>{}</style>", String::from_utf8_lossy(&body)))
+            .unwrap_or_else(|| caps[0].to_owned())
+    });
+
+    html.into_owned()
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use url::Url;
+
+    use libimagstore::store::Store;
+
+    use super::*;
+    use external::ExternalLinker;
+    use test_server;
+
+    const PAGE : &str = "<html><head><title>The page</title><script>var x = 1;</script></head>\
+        <body><!-- comment --><h1>Heading</h1><p>Some   <b>bold</b> text &amp; more.</p>\
+        <img src=\"/img.gif\"><link rel=\"stylesheet\" href=\"style.css\">\
+        <script src=\"/app.js\"></script></body></html>";
+
+    fn serve() -> String {
+        test_server::serve(vec![
+            ("/page",      test_server::ok("text/html; charset=utf-8", PAGE)),
+            ("/img.gif",   test_server::ok("image/gif", "GIF89a")),
+            ("/style.css", test_server::ok("text/css", "body { color: red; }")),
+            ("/file.txt",  test_server::ok("text/plain", "Just text")),
+            ("/file.bin",  test_server::ok("application/octet-stream", "binary")),
+        ])
+    }
+
+    #[test]
+    fn test_html_to_text() {
+        assert_eq!("Heading\n\nSome bold text & more.\n", html_to_text(PAGE));
+    }
+
+    #[test]
+    fn test_fetch() {
+        let base    = serve();
+        let page    = Url::parse(&format!("{}/page", base)).unwrap();
+        let archive = Archiver::new().with_format(ArchiveFormat::Html);
+
+        let (title, html) = archive.fetch(&page).unwrap();
+        assert_eq!(Some(String::from("The page")), title);
+        assert!(!html.contains("<script"));
+        assert!(html.contains(&format!("src=\"data:image/gif;base64,{}\"", base64::encode("GIF89a"))));
+        assert!(html.contains("<style>body { color: red; }</style>"));
+
+        let (title, text) = Archiver::new().fetch(&page).unwrap();
+        assert_eq!(Some(String::from("The page")), title);
+        assert!(text.starts_with("Heading"));
+
+        let txt = Url::parse(&format!("{}/file.txt", base)).unwrap();
+        assert_eq!((None, String::from("Just text")), Archiver::new().fetch(&txt).unwrap());
+
+        let bin = Url::parse(&format!("{}/file.bin", base)).unwrap();
+        assert!(Archiver::new().fetch(&bin).is_err());
+
+        let missing = Url::parse(&format!("{}/missing", base)).unwrap();
+        assert!(Archiver::new().fetch(&missing).is_err());
+    }
+
+    #[test]
+    fn test_archive() {
+        let base  = serve();
+        let store = Store::new_inmemory(PathBuf::from("/"), &None).unwrap();
+
+        {
+            let mut entry = store.create(PathBuf::from("bookmarks")).unwrap();
+            let url = Url::parse(&format!("{}/page", base)).unwrap();
+            let _   = entry.add_external_link(&store, url).unwrap();
+        }
+
+        let link_id = ::check::external_link_ids(&store).unwrap().remove(0);
+
+        let archive_id = {
+            let archive = Archiver::new().archive(&store, &link_id).unwrap();
+            assert!(is_archive_storeid(archive.get_location()));
+            assert!(archive.get_content().contains("Some bold text & more."));
+
+            let header = get_archive_header(&archive).unwrap().unwrap();
+            assert_eq!(format!("{}/page", base), header.url);
+            assert_eq!("text", header.format);
+            assert_eq!(Some(String::from("The page")), header.title);
+
+            archive.get_location().clone()
+        };
+
+        let link = store.get(link_id).unwrap().unwrap();
+        assert_eq!(vec![archive_id], get_archives(&link).unwrap());
+
+        let not_a_link = StoreId::new(PathBuf::from("bookmarks")).unwrap();
+        assert!(Archiver::new().archive(&store, &not_a_link).is_err());
+    }
+}
@@ -0,0 +1,463 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2019 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+//! Health checks for external links
+//!
+//! The `LinkChecker` fetches the URLs of external link entries and records the HTTP status code,
+//! the URL redirected to, the page title and the time of the check in the header of the external
+//! link entry, at `links.external.check`.
+
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::mpsc::channel;
+use std::thread;
+use std::time::Duration;
+
+use chrono::Local;
+use reqwest::Client;
+use reqwest::RedirectPolicy;
+use reqwest::header::CONTENT_TYPE;
+use reqwest::header::USER_AGENT;
+use url::Url;
+use failure::Fallible as Result;
+use failure::Error;
+
+use libimagstore::store::Entry;
+use libimagstore::store::FileLockEntry;
+use libimagstore::store::Store;
+use libimagstore::storeid::StoreId;
+use libimagerror::kind::ErrorKind;
+use libimagerror::kind::kind_err;
+use libimagutil::date::datetime_to_string;
+
+use external::Link as ExternalLink;
+use external::is_external_link_storeid;
+
+/// The header path the result of a check is stored at
+pub const CHECK_HEADER_PATH : &str = "links.external.check";
+
+/// The user agent sent with all requests
+pub(crate) const IMAG_USER_AGENT : &str = concat!("imag/", env!("CARGO_PKG_VERSION"));
+
+/// Only this many bytes of a page are searched for its title
+const TITLE_SEARCH_LIMIT : u64 = 64 * 1024;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct LinkCheckResult {
+    /// The HTTP status code, if a response was received
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+
+    /// The URL the request was redirected to, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub final_url: Option<String>,
+
+    /// The title of the page, if it is a HTML page with a title
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+
+    /// Why the request failed, if it failed (for example because of a timeout)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+
+    /// When the link was checked
+    pub checked: String,
+}
+
+impl LinkCheckResult {
+
+    /// A link is broken if the request failed or the server answered with an error status code
+    pub fn is_broken(&self) -> bool {
+        self.error.is_some() || self.status.map(|s| s >= 400).unwrap_or(true)
+    }
+
+}
+
+/// Get the result of the last check of an external link entry, if it was checked
+pub fn get_check_result(entry: &Entry) -> Result<Option<LinkCheckResult>> {
+    entry.get_section(CHECK_HEADER_PATH)
+}
+
+fn set_check_result(entry: &mut Entry, result: &LinkCheckResult) -> Result<()> {
+    entry.set_section(CHECK_HEADER_PATH, result)
+}
+
+/// Get the ids of all external link entries in the store
+pub fn external_link_ids(store: &Store) -> Result<Vec<StoreId>> {
+    store.entries()?
+        .in_collection("links")
+        .into_storeid_iter()
+        .filter(|id| id.as_ref().map(is_external_link_storeid).unwrap_or(true))
+        .collect()
+}
+
+/// Get all external link entries whose last check found them broken, with their URL and the
+/// result of the check
+pub fn broken_links(store: &Store) -> Result<Vec<(StoreId, Url, LinkCheckResult)>> {
+    let mut broken = Vec::new();
+
+    for id in external_link_ids(store)? {
+        let entry = get_existing(store, &id)?;
+
+        if let (Some(url), Some(result)) = (entry.get_url()?, get_check_result(&entry)?) {
+            if result.is_broken() {
+                broken.push((id, url, result));
+            }
+        }
+    }
+
+    Ok(broken)
+}
+
+/// Checks URLs by requesting them
+///
+/// URLs on the same host are requested one after another, waiting for the politeness delay
+/// between two requests. Up to `concurrency` hosts are checked at the same time.
+#[derive(Debug, Clone)]
+pub struct LinkChecker {
+    timeout: Duration,
+    delay: Duration,
+    concurrency: usize,
+    max_redirects: usize,
+}
+
+impl Default for LinkChecker {
+    fn default() -> Self {
+        LinkChecker {
+            timeout: Duration::from_secs(10),
+            delay: Duration::from_secs(1),
+            concurrency: 4,
+            max_redirects: 10,
+        }
+    }
+}
+
+impl LinkChecker {
+
+    pub fn new() -> Self {
+        LinkChecker::default()
+    }
+
+    /// Fail a request if there is no response after `timeout`
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Wait `delay` between two requests to the same host
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Check up to `concurrency` hosts at the same time
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = ::std::cmp::max(concurrency, 1);
+        self
+    }
+
+    /// Check `urls`, without touching the store
+    ///
+    /// The results are not in the order of `urls`.
+    pub fn check_urls(&self, urls: Vec<Url>) -> Result<Vec<(Url, LinkCheckResult)>> {
+        let client = build_client(self.timeout, self.max_redirects)?;
+
+        let mut by_host : BTreeMap<String, Vec<Url>> = BTreeMap::new();
+        for url in urls {
+            let host = url.host_str().unwrap_or("").to_owned();
+            by_host.entry(host).or_insert_with(Vec::new).push(url);
+        }
+
+        let workers = ::std::cmp::min(self.concurrency, by_host.len());
+        let queue   = Arc::new(Mutex::new(by_host.into_iter().map(|(_, urls)| urls).collect::<Vec<_>>()));
+        let (sender, receiver) = channel();
+
+        let handles = (0..workers)
+            .map(|_| {
+                let queue  = queue.clone();
+                let sender = sender.clone();
+                let client = client.clone();
+                let delay  = self.delay;
+
+                thread::spawn(move || loop {
+                    let urls = match queue.lock().ok().and_then(|mut q| q.pop()) {
+                        Some(urls) => urls,
+                        None       => break,
+                    };
+
+                    for (i, url) in urls.into_iter().enumerate() {
+                        if i != 0 {
+                            thread::sleep(delay);
+                        }
+
+                        let result = check_url(&client, &url);
+                        if sender.send((url, result)).is_err() {
+                            return
+                        }
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        drop(sender);
+        let results = receiver.iter().collect();
+
+        for handle in handles {
+            handle.join().map_err(|_| kind_err(ErrorKind::Other, "Link checker thread panicked"))?;
+        }
+
+        Ok(results)
+    }
+
+    /// Check the external link entries `ids` and record the results in their headers
+    ///
+    /// Ids which are not external link entries are ignored. The results are ordered by id.
+    pub fn check_entries(&self, store: &Store, ids: Vec<StoreId>) -> Result<Vec<(StoreId, LinkCheckResult)>> {
+        let mut ids_by_url : BTreeMap<String, Vec<StoreId>> = BTreeMap::new();
+
+        for id in ids {
+            if !is_external_link_storeid(&id) {
+                debug!("Not an external link, not checking: {}", id);
+                continue
+            }
+
+            match get_existing(store, &id)?.get_url()? {
+                Some(url) => ids_by_url.entry(url.into_string()).or_insert_with(Vec::new).push(id),
+                None      => warn!("No URL in external link entry {}", id),
+            }
+        }
+
+        let urls = ids_by_url
+            .keys()
+            .map(|s| Url::parse(s).map_err(Error::from))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut results = Vec::new();
+        for (url, result) in self.check_urls(urls)? {
+            for id in ids_by_url.get(url.as_str()).into_iter().flat_map(|ids| ids.iter()) {
+                let mut entry = get_existing(store, id)?;
+                set_check_result(&mut entry, &result)?;
+                results.push((id.clone(), result.clone()));
+            }
+        }
+
+        results.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(results)
+    }
+
+}
+
+fn check_url(client: &Client, url: &Url) -> LinkCheckResult {
+    debug!("Checking {}", url);
+    let checked = datetime_to_string(&Local::now().naive_local());
+
+    let request = client
+        .get(url.as_str())
+        .header(USER_AGENT, IMAG_USER_AGENT);
+
+    let mut response = match request.send() {
+        Ok(response) => response,
+        Err(e)       => return LinkCheckResult {
+            status: None,
+            final_url: None,
+            title: None,
+            error: Some(e.to_string()),
+            checked,
+        },
+    };
+
+    let status    = response.status().as_u16();
+    let final_url = if response.url().as_str() != url.as_str() {
+        Some(response.url().as_str().to_owned())
+    } else {
+        None
+    };
+
+    let is_html = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.contains("html"))
+        .unwrap_or(false);
+
+    let title = if is_html {
+        let mut buf = Vec::new();
+        match response.by_ref().take(TITLE_SEARCH_LIMIT).read_to_end(&mut buf) {
+            Ok(_)  => extract_title(&String::from_utf8_lossy(&buf)),
+            Err(e) => {
+                debug!("Failed to read the body of {}: {:?}", url, e);
+                None
+            },
+        }
+    } else {
+        None
+    };
+
+    LinkCheckResult {
+        status: Some(status),
+        final_url,
+        title,
+        error: None,
+        checked,
+    }
+}
+
+pub(crate) fn build_client(timeout: Duration, max_redirects: usize) -> Result<Client> {
+    Client::builder()
+        .timeout(timeout)
+        .redirect(RedirectPolicy::limited(max_redirects))
+        .build()
+        .map_err(Error::from)
+}
+
+/// Find the content of the `<title>` element in a HTML document
+pub(crate) fn extract_title(html: &str) -> Option<String> {
+    // ASCII lowercasing keeps the byte offsets intact
+    let lower = html.to_ascii_lowercase();
+    let start = lower.find("<title").and_then(|i| lower[i..].find('>').map(|j| i + j + 1))?;
+    let end   = lower[start..].find("</title").map(|i| start + i)?;
+
+    let title = decode_entities(&html[start..end].split_whitespace().collect::<Vec<_>>().join(" "));
+
+    if title.is_empty() {
+        None
+    } else {
+        Some(title)
+    }
+}
+
+/// Decode the most common HTML entities
+pub(crate) fn decode_entities(s: &str) -> String {
+    s.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+fn get_existing<'a>(store: &'a Store, id: &StoreId) -> Result<FileLockEntry<'a>> {
+    store.get(id.clone())?
+        .ok_or_else(|| kind_err(ErrorKind::NotFound, format!("Entry does not exist: {}", id)))
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::TcpListener;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    use url::Url;
+
+    use libimagstore::store::Store;
+
+    use super::*;
+    use external::ExternalLinker;
+    use test_server;
+
+    fn serve() -> String {
+        let page = "<html><head><TITLE>\n  Hello &amp; welcome </TITLE></head></html>";
+        test_server::serve(vec![
+            ("/ok",       test_server::ok("text/html", page)),
+            ("/redirect", test_server::redirect("/ok")),
+        ])
+    }
+
+    fn checker() -> LinkChecker {
+        LinkChecker::new()
+            .with_timeout(Duration::from_secs(5))
+            .with_delay(Duration::from_millis(0))
+    }
+
+    #[test]
+    fn test_check_urls() {
+        let base = serve();
+        let ok   = Url::parse(&format!("{}/ok", base)).unwrap();
+        let redir = Url::parse(&format!("{}/redirect", base)).unwrap();
+        let gone = Url::parse(&format!("{}/gone", base)).unwrap();
+
+        let results = checker().check_urls(vec![ok.clone(), redir.clone(), gone.clone()]).unwrap();
+        assert_eq!(3, results.len());
+
+        let get = |u: &Url| results.iter().find(|r| r.0 == *u).unwrap().1.clone();
+
+        let r = get(&ok);
+        assert_eq!(Some(200), r.status);
+        assert_eq!(Some(String::from("Hello & welcome")), r.title);
+        assert!(r.final_url.is_none());
+        assert!(!r.is_broken());
+
+        let r = get(&redir);
+        assert_eq!(Some(200), r.status);
+        assert_eq!(Some(ok.as_str().to_owned()), r.final_url);
+
+        let r = get(&gone);
+        assert_eq!(Some(404), r.status);
+        assert!(r.is_broken());
+    }
+
+    #[test]
+    fn test_check_unreachable() {
+        // Bind and drop a listener to get a port nobody listens on
+        let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let url  = Url::parse(&format!("http://127.0.0.1:{}/", port)).unwrap();
+
+        let results = checker().check_urls(vec![url]).unwrap();
+        assert_eq!(1, results.len());
+        assert!(results[0].1.error.is_some());
+        assert!(results[0].1.is_broken());
+    }
+
+    #[test]
+    fn test_check_entries() {
+        let base  = serve();
+        let store = Store::new_inmemory(PathBuf::from("/"), &None).unwrap();
+
+        {
+            let mut entry = store.create(PathBuf::from("bookmarks")).unwrap();
+            let _ = entry.add_external_link(&store, Url::parse(&format!("{}/ok", base)).unwrap()).unwrap();
+            let _ = entry.add_external_link(&store, Url::parse(&format!("{}/gone", base)).unwrap()).unwrap();
+        }
+
+        let ids = external_link_ids(&store).unwrap();
+        assert_eq!(2, ids.len());
+
+        let results = checker().check_entries(&store, ids).unwrap();
+        assert_eq!(2, results.len());
+
+        for (id, result) in results {
+            let entry = store.get(id).unwrap().unwrap();
+            assert_eq!(Some(result), get_check_result(&entry).unwrap());
+        }
+
+        let broken = broken_links(&store).unwrap();
+        assert_eq!(1, broken.len());
+        assert_eq!(format!("{}/gone", base), broken[0].1.as_str());
+        assert_eq!(Some(404), broken[0].2.status);
+    }
+
+    #[test]
+    fn test_extract_title() {
+        assert_eq!(Some(String::from("A title")), extract_title("<html><title lang=\"en\">A   title</title>"));
+        assert_eq!(None, extract_title("<html><title></title>"));
+        assert_eq!(None, extract_title("<html>no title</html>"));
+    }
+}
@@ -40,6 +40,8 @@ use libimagstore::storeid::StoreId;
 use libimagstore::storeid::IntoStoreId;
 use libimagutil::debug_result::*;
 use libimagerror::errors::ErrorMsg as EM;
+use libimagerror::kind::ErrorKind;
+use libimagerror::kind::KindError;
 
 use toml_query::read::TomlValueReadExt;
 use toml_query::read::TomlValueReadTypeExt;
@@ -47,7 +49,6 @@ use toml_query::insert::TomlValueInsertExt;
 use failure::Error;
 use failure::Fallible as Result;
 use failure::ResultExt;
-use failure::err_msg;
 
 use internal::InternalLinker;
 use module_path::ModuleEntryPath;
@@ -81,7 +82,7 @@ impl Link for Entry {
                     debug!("Found url, parsing: {:?}", s);
                     Url::parse(&s[..])
                         .map_err(Error::from)
-                        .context(err_msg("Invalid URI"))
+                        .context(KindError::new(ErrorKind::Parse, "Invalid URI"))
                         .map_err(Error::from)
                         .map(Some)
                 },
@@ -90,7 +91,7 @@ impl Link for Entry {
     }
 
     fn get_url(&self) -> Result<Option<Url>> {
-        match self.get_header().read_string("links.external.url")? {
+        match self.get_header().read_string("links.external.content.url")? {
             None        => Ok(None),
             Some(ref s) => Url::parse(&s[..])
                 .map(Some)
@@ -0,0 +1,513 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2019 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+//! The link graph of the store
+//!
+//! `GraphBuilder` walks the internal (and optionally the external) links of entries, starting at a
+//! set of root entries or at all entries in the store, and collects them in a `LinkGraph`. The
+//! graph can be exported as Graphviz DOT, GraphML or JSON.
+//!
+//! Links are stored on both ends, so each link is contained only once in the graph: plain links
+//! are undirected edges, related links are directed edges which are labeled with the relation
+//! from the point of view of the source node.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::VecDeque;
+use std::path::Component;
+
+use toml::Value;
+use toml_query::read::TomlValueReadExt;
+use failure::Fallible as Result;
+use failure::Error;
+
+use libimagstore::store::Entry;
+use libimagstore::store::Store;
+use libimagstore::storeid::StoreId;
+
+use external::Link as ExternalLink;
+use external::is_external_link_storeid;
+use internal::InternalLinker;
+use relation::Relation;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    /// An entry in the store
+    Entry,
+
+    /// An external link (an URL)
+    External,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Node {
+    /// The store id of the entry or the URL of an external link
+    pub id: String,
+    pub label: String,
+
+    /// The collection of the entry (the first component of its id), if any
+    pub collection: Option<String>,
+    pub kind: NodeKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Edge {
+    pub from: String,
+    pub to: String,
+    pub relation: Option<Relation>,
+}
+
+impl Edge {
+
+    /// Create an edge
+    ///
+    /// Edges are normalized so that `from` is smaller than `to` (the relation is inverted if the
+    /// edge is flipped), so that a link and the link back result in the same edge.
+    pub fn new(from: String, to: String, relation: Option<Relation>) -> Edge {
+        if from <= to {
+            Edge { from, to, relation }
+        } else {
+            Edge { from: to, to: from, relation: relation.map(|r| r.inverse()) }
+        }
+    }
+
+}
+
+#[derive(Debug, Default)]
+pub struct LinkGraph {
+    nodes: BTreeMap<String, Node>,
+    edges: BTreeSet<Edge>,
+}
+
+impl LinkGraph {
+
+    pub fn nodes(&self) -> impl Iterator<Item = &Node> {
+        self.nodes.values()
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = &Edge> {
+        self.edges.iter()
+    }
+
+    pub fn node(&self, id: &str) -> Option<&Node> {
+        self.nodes.get(id)
+    }
+
+    /// Export the graph in the Graphviz DOT format
+    ///
+    /// If `cluster` is true, the entries of each collection are put in a cluster subgraph.
+    pub fn to_dot(&self, cluster: bool) -> String {
+        let mut out = String::from("digraph imag {\n");
+
+        let node_line = |node: &Node| {
+            let shape = match node.kind {
+                NodeKind::Entry    => "box",
+                NodeKind::External => "ellipse",
+            };
+            format!("\"{}\" [label=\"{}\", shape={}];\n", dot_escape(&node.id), dot_escape(&node.label), shape)
+        };
+
+        if cluster {
+            let mut clusters : BTreeMap<Option<&String>, Vec<&Node>> = BTreeMap::new();
+            for node in self.nodes.values() {
+                clusters.entry(node.collection.as_ref()).or_insert_with(Vec::new).push(node);
+            }
+
+            for (collection, nodes) in clusters {
+                match collection {
+                    Some(collection) => {
+                        out.push_str(&format!("  subgraph \"cluster_{}\" {{\n", dot_escape(collection)));
+                        out.push_str(&format!("    label=\"{}\";\n", dot_escape(collection)));
+                        for node in nodes {
+                            out.push_str("    ");
+                            out.push_str(&node_line(node));
+                        }
+                        out.push_str("  }\n");
+                    },
+                    None => for node in nodes {
+                        out.push_str("  ");
+                        out.push_str(&node_line(node));
+                    },
+                }
+            }
+        } else {
+            for node in self.nodes.values() {
+                out.push_str("  ");
+                out.push_str(&node_line(node));
+            }
+        }
+
+        for edge in self.edges.iter() {
+            let attrs = match edge.relation {
+                Some(ref rel) => format!("label=\"{}\"", dot_escape(rel.as_str())),
+                None          => String::from("dir=none"),
+            };
+            out.push_str(&format!("  \"{}\" -> \"{}\" [{}];\n", dot_escape(&edge.from), dot_escape(&edge.to), attrs));
+        }
+
+        out.push_str("}\n");
+        out
+    }
+
+    /// Export the graph in the GraphML format
+    ///
+    /// The label, the collection and the kind of a node and the relation of an edge are exported
+    /// as data attributes.
+    pub fn to_graphml(&self) -> String {
+        let mut out = String::new();
+        out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        out.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+        out.push_str("  <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>\n");
+        out.push_str("  <key id=\"collection\" for=\"node\" attr.name=\"collection\" attr.type=\"string\"/>\n");
+        out.push_str("  <key id=\"kind\" for=\"node\" attr.name=\"kind\" attr.type=\"string\"/>\n");
+        out.push_str("  <key id=\"relation\" for=\"edge\" attr.name=\"relation\" attr.type=\"string\"/>\n");
+        out.push_str("  <graph id=\"imag\" edgedefault=\"undirected\">\n");
+
+        for node in self.nodes.values() {
+            out.push_str(&format!("    <node id=\"{}\">\n", xml_escape(&node.id)));
+            out.push_str(&format!("      <data key=\"label\">{}</data>\n", xml_escape(&node.label)));
+            if let Some(ref collection) = node.collection {
+                out.push_str(&format!("      <data key=\"collection\">{}</data>\n", xml_escape(collection)));
+            }
+            out.push_str(&format!("      <data key=\"kind\">{}</data>\n", kind_name(node.kind)));
+            out.push_str("    </node>\n");
+        }
+
+        for edge in self.edges.iter() {
+            match edge.relation {
+                Some(ref rel) => {
+                    out.push_str(&format!("    <edge source=\"{}\" target=\"{}\" directed=\"true\">\n",
+                                          xml_escape(&edge.from), xml_escape(&edge.to)));
+                    out.push_str(&format!("      <data key=\"relation\">{}</data>\n", xml_escape(rel.as_str())));
+                    out.push_str("    </edge>\n");
+                },
+                None => {
+                    out.push_str(&format!("    <edge source=\"{}\" target=\"{}\"/>\n",
+                                          xml_escape(&edge.from), xml_escape(&edge.to)));
+                },
+            }
+        }
+
+        out.push_str("  </graph>\n");
+        out.push_str("</graphml>\n");
+        out
+    }
+
+    /// Export the graph as JSON
+    ///
+    /// The result is an object with a `nodes` and an `edges` array.
+    pub fn to_json(&self) -> Result<String> {
+        let nodes = self.nodes
+            .values()
+            .map(|node| json!({
+                "id":         node.id,
+                "label":      node.label,
+                "collection": node.collection,
+                "kind":       kind_name(node.kind),
+            }))
+            .collect::<Vec<_>>();
+
+        let edges = self.edges
+            .iter()
+            .map(|edge| json!({
+                "from":     edge.from,
+                "to":       edge.to,
+                "relation": edge.relation.as_ref().map(Relation::as_str),
+            }))
+            .collect::<Vec<_>>();
+
+        ::serde_json::to_string_pretty(&json!({ "nodes": nodes, "edges": edges }))
+            .map_err(Error::from)
+    }
+
+}
+
+/// Builder for a `LinkGraph`
+pub struct GraphBuilder<'a> {
+    store: &'a Store,
+    roots: Option<Vec<StoreId>>,
+    depth: Option<usize>,
+    externals: bool,
+    label_fields: Vec<String>,
+}
+
+impl<'a> GraphBuilder<'a> {
+
+    /// Create a builder which walks all entries of `store`, without external links
+    pub fn new(store: &'a Store) -> GraphBuilder<'a> {
+        GraphBuilder {
+            store,
+            roots: None,
+            depth: None,
+            externals: false,
+            label_fields: Vec::new(),
+        }
+    }
+
+    /// Only walk the links starting at `roots`
+    pub fn with_roots(mut self, roots: Vec<StoreId>) -> Self {
+        self.roots = Some(roots);
+        self
+    }
+
+    /// Only follow links up to `depth` hops away from the roots
+    pub fn with_depth(mut self, depth: usize) -> Self {
+        self.depth = Some(depth);
+        self
+    }
+
+    /// Include external links in the graph
+    pub fn with_externals(mut self, externals: bool) -> Self {
+        self.externals = externals;
+        self
+    }
+
+    /// Label nodes with the value of the first of these header fields which is present in the
+    /// entry. Nodes are labeled with their id if none is present.
+    pub fn with_label_fields(mut self, fields: Vec<String>) -> Self {
+        self.label_fields = fields;
+        self
+    }
+
+    pub fn build(self) -> Result<LinkGraph> {
+        let mut graph = LinkGraph::default();
+        let mut seen  = BTreeSet::new();
+        let mut edges = Vec::new();
+
+        let mut queue = match self.roots {
+            Some(ref roots) => roots.iter().cloned().map(|id| (id, 0)).collect::<VecDeque<_>>(),
+            None => self.store
+                .entries()?
+                .into_storeid_iter()
+                .map(|r| r.map(|id| (id, 0)))
+                .filter(|r| r.as_ref().map(|&(ref id, _)| !is_external_link_storeid(id)).unwrap_or(true))
+                .collect::<Result<VecDeque<_>>>()?,
+        };
+
+        while let Some((id, depth)) = queue.pop_front() {
+            if !seen.insert(id.clone()) {
+                continue
+            }
+
+            let entry = match self.store.get(id.clone())? {
+                Some(entry) => entry,
+                None        => {
+                    debug!("Entry does not exist, not adding it to the graph: {}", id);
+                    continue
+                },
+            };
+
+            let node = self.entry_node(&entry)?;
+            let node_id = node.id.clone();
+            let _ = graph.nodes.insert(node.id.clone(), node);
+
+            for link in entry.get_internal_links()? {
+                let target = link.get_store_id().clone();
+
+                if is_external_link_storeid(&target) {
+                    if self.externals {
+                        if let Some(url) = self.external_url(target)? {
+                            let _ = graph.nodes.entry(url.clone()).or_insert_with(|| Node {
+                                id: url.clone(),
+                                label: url.clone(),
+                                collection: None,
+                                kind: NodeKind::External,
+                            });
+                            edges.push(Edge::new(node_id.clone(), url, None));
+                        }
+                    }
+                    continue
+                }
+
+                edges.push(Edge::new(node_id.clone(), target.local_display_string(), link.relation().cloned()));
+
+                if self.depth.map(|max| depth < max).unwrap_or(true) {
+                    queue.push_back((target, depth + 1));
+                }
+            }
+        }
+
+        // Only keep edges between nodes which made it into the graph
+        graph.edges = edges
+            .into_iter()
+            .filter(|e| graph.nodes.contains_key(&e.from) && graph.nodes.contains_key(&e.to))
+            .collect();
+
+        Ok(graph)
+    }
+
+    fn entry_node(&self, entry: &Entry) -> Result<Node> {
+        let id = entry.get_location().local_display_string();
+
+        let mut label = None;
+        for field in self.label_fields.iter() {
+            label = match entry.get_header().read(field)? {
+                Some(&Value::String(ref s)) => Some(s.clone()),
+                Some(&Value::Table(_))      => None,
+                Some(&Value::Array(_))      => None,
+                Some(other)                 => Some(other.to_string()),
+                None                        => None,
+            };
+
+            if label.is_some() {
+                break
+            }
+        }
+
+        let collection = {
+            let mut components = entry.get_location().components();
+            match (components.next(), components.next()) {
+                (Some(Component::Normal(c)), Some(_)) => c.to_str().map(String::from),
+                _                                     => None,
+            }
+        };
+
+        Ok(Node {
+            label: label.unwrap_or_else(|| id.clone()),
+            id,
+            collection,
+            kind: NodeKind::Entry,
+        })
+    }
+
+    fn external_url(&self, id: StoreId) -> Result<Option<String>> {
+        match self.store.get(id)? {
+            Some(entry) => Ok(entry.get_url()?.map(|url| url.into_string())),
+            None        => Ok(None),
+        }
+    }
+
+}
+
+fn kind_name(kind: NodeKind) -> &'static str {
+    match kind {
+        NodeKind::Entry    => "entry",
+        NodeKind::External => "external",
+    }
+}
+
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use toml::Value;
+    use toml_query::insert::TomlValueInsertExt;
+
+    use libimagstore::store::Store;
+    use libimagstore::storeid::StoreId;
+
+    use super::*;
+    use internal::InternalLinker;
+    use relation::Relation;
+
+    fn get_store() -> Store {
+        Store::new_inmemory(PathBuf::from("/"), &None).unwrap()
+    }
+
+    fn setup(store: &Store) {
+        let mut a = store.create(PathBuf::from("notes/a")).unwrap();
+        let mut b = store.create(PathBuf::from("notes/b")).unwrap();
+        let mut c = store.create(PathBuf::from("todo/c")).unwrap();
+        let mut d = store.create(PathBuf::from("todo/d")).unwrap();
+        let _     = store.create(PathBuf::from("lonely")).unwrap();
+
+        let _ = a.get_header_mut().insert("note.name", Value::String(String::from("Note A"))).unwrap();
+
+        a.add_internal_link(&mut b).unwrap();
+        b.add_internal_related_link(&mut c, Relation::Blocks).unwrap();
+        c.add_internal_link(&mut d).unwrap();
+    }
+
+    #[test]
+    fn test_whole_store() {
+        let store = get_store();
+        setup(&store);
+
+        let graph = GraphBuilder::new(&store).build().unwrap();
+        assert_eq!(5, graph.nodes().count());
+        assert_eq!(3, graph.edges().count());
+
+        assert!(graph.edges().any(|e| *e == Edge::new("notes/b".into(), "todo/c".into(), Some(Relation::Blocks))));
+        assert_eq!(Some(&String::from("todo")), graph.node("todo/c").unwrap().collection.as_ref());
+        assert!(graph.node("lonely").unwrap().collection.is_none());
+    }
+
+    #[test]
+    fn test_roots_and_depth() {
+        let store = get_store();
+        setup(&store);
+
+        let root  = StoreId::new(PathBuf::from("notes/a")).unwrap();
+        let graph = GraphBuilder::new(&store)
+            .with_roots(vec![root])
+            .with_depth(1)
+            .build()
+            .unwrap();
+
+        let mut nodes = graph.nodes().map(|n| n.id.clone()).collect::<Vec<_>>();
+        nodes.sort();
+        assert_eq!(vec!["notes/a", "notes/b"], nodes);
+        assert_eq!(1, graph.edges().count());
+    }
+
+    #[test]
+    fn test_labels_and_export() {
+        let store = get_store();
+        setup(&store);
+
+        let graph = GraphBuilder::new(&store)
+            .with_label_fields(vec![String::from("note.name")])
+            .build()
+            .unwrap();
+
+        assert_eq!("Note A", graph.node("notes/a").unwrap().label);
+        assert_eq!("notes/b", graph.node("notes/b").unwrap().label);
+
+        let dot = graph.to_dot(true);
+        assert!(dot.contains("subgraph \"cluster_notes\""));
+        assert!(dot.contains("\"notes/b\" -> \"todo/c\" [label=\"blocks\"];"));
+        assert!(dot.contains("\"notes/a\" -> \"notes/b\" [dir=none];"));
+
+        let graphml = graph.to_graphml();
+        assert!(graphml.contains("<data key=\"label\">Note A</data>"));
+
+        let json : ::serde_json::Value = ::serde_json::from_str(&graph.to_json().unwrap()).unwrap();
+        assert_eq!(5, json["nodes"].as_array().unwrap().len());
+        assert_eq!(3, json["edges"].as_array().unwrap().len());
+    }
+
+    #[test]
+    fn test_edge_normalization() {
+        let e1 = Edge::new("b".into(), "a".into(), Some(Relation::Parent));
+        let e2 = Edge::new("a".into(), "b".into(), Some(Relation::Child));
+        assert_eq!(e1, e2);
+    }
+}
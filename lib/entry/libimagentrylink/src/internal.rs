@@ -18,22 +18,25 @@
 //
 
 use std::collections::BTreeMap;
+use std::str::FromStr;
 
 use libimagstore::storeid::StoreId;
 use libimagstore::storeid::IntoStoreId;
 use libimagstore::store::Entry;
 use libimagstore::store::Store;
 use libimagerror::errors::ErrorMsg as EM;
+use libimagerror::kind::ErrorKind;
+use libimagerror::kind::kind_err;
 
 use toml_query::read::TomlValueReadExt;
 use toml_query::insert::TomlValueInsertExt;
 use failure::ResultExt;
 use failure::Fallible as Result;
 use failure::Error;
-use failure::err_msg;
 
 use self::iter::LinkIter;
 use self::iter::IntoValues;
+use relation::Relation;
 
 use toml::Value;
 
@@ -41,6 +44,7 @@ use toml::Value;
 pub enum Link {
     Id          { link: StoreId },
     Annotated   { link: StoreId, annotation: String },
+    Related     { link: StoreId, relation: Relation },
 }
 
 impl Link {
@@ -49,6 +53,7 @@ impl Link {
         match *self {
             Link::Id { ref link }             => store.exists(link.clone()),
             Link::Annotated { ref link, .. }  => store.exists(link.clone()),
+            Link::Related { ref link, .. }    => store.exists(link.clone()),
         }
         .map_err(From::from)
     }
@@ -57,6 +62,7 @@ impl Link {
         match *self {
             Link::Id { ref link }             => link.to_str(),
             Link::Annotated { ref link, .. }  => link.to_str(),
+            Link::Related { ref link, .. }    => link.to_str(),
         }
         .map_err(From::from)
     }
@@ -66,6 +72,7 @@ impl Link {
         match self {
             &Link::Id { link: ref s }             => s.eq(id),
             &Link::Annotated { link: ref s, .. }  => s.eq(id),
+            &Link::Related { link: ref s, .. }    => s.eq(id),
         }
     }
 
@@ -74,6 +81,15 @@ impl Link {
         match self {
             &Link::Id { link: ref s }             => s,
             &Link::Annotated { link: ref s, .. }  => s,
+            &Link::Related { link: ref s, .. }    => s,
+        }
+    }
+
+    /// Get the relation of the Link, if it is a `Link::Related`
+    pub fn relation(&self) -> Option<&Relation> {
+        match self {
+            &Link::Related { ref relation, .. } => Some(relation),
+            _                                   => None,
         }
     }
 
@@ -83,6 +99,8 @@ impl Link {
             Link::Id { link: s } => Link::Id { link: s },
             Link::Annotated { link: s, annotation: ann } =>
                 Link::Annotated { link: s, annotation: ann },
+            Link::Related { link: s, relation: rel } =>
+                Link::Related { link: s, relation: rel },
         }
     }
 
@@ -105,7 +123,20 @@ impl Link {
                         tab.insert("annotation".to_owned(), Value::String(anno.clone()));
                         Value::Table(tab)
                     })
-            }
+            },
+            &Link::Related { ref link, ref relation } => {
+                link.to_str()
+                    .map(Value::String)
+                    .context(EM::ConversionError)
+                    .map_err(Error::from)
+                    .map(|link| {
+                        let mut tab = BTreeMap::new();
+
+                        tab.insert("link".to_owned(),     link);
+                        tab.insert("relation".to_owned(), Value::String(relation.as_str().to_owned()));
+                        Value::Table(tab)
+                    })
+            },
         }
     }
 
@@ -118,6 +149,9 @@ impl ::std::cmp::PartialEq for Link {
             (&Link::Annotated { link: ref a, annotation: ref ann1 },
              &Link::Annotated { link: ref b, annotation: ref ann2 }) =>
                 (a, ann1).eq(&(b, ann2)),
+            (&Link::Related { link: ref a, relation: ref rel1 },
+             &Link::Related { link: ref b, relation: ref rel2 }) =>
+                (a, rel1).eq(&(b, rel2)),
             _ => false,
         }
     }
@@ -135,6 +169,7 @@ impl Into<StoreId> for Link {
         match self {
             Link::Id { link }            => link,
             Link::Annotated { link, .. } => link,
+            Link::Related { link, .. }   => link,
         }
     }
 }
@@ -144,6 +179,7 @@ impl IntoStoreId for Link {
         match self {
             Link::Id { link }            => Ok(link),
             Link::Annotated { link, .. } => Ok(link),
+            Link::Related { link, .. }   => Ok(link),
         }
     }
 }
@@ -153,6 +189,7 @@ impl AsRef<StoreId> for Link {
         match self {
             &Link::Id { ref link }            => &link,
             &Link::Annotated { ref link, .. } => &link,
+            &Link::Related { ref link, .. }   => &link,
         }
     }
 }
@@ -176,6 +213,21 @@ pub trait InternalLinker {
 
     /// Add internal annotated link
     fn add_internal_annotated_link(&mut self, link: &mut Entry, annotation: String) -> Result<()>;
+
+    /// Add an internal link with the relation `relation`
+    ///
+    /// The linked entry links back with the inverse relation. A plain link between the two
+    /// entries is replaced by the related one.
+    fn add_internal_related_link(&mut self, link: &mut Entry, relation: Relation) -> Result<()>;
+
+    /// Get the internal links with the relation `relation`
+    fn get_internal_related_links(&self, relation: &Relation) -> Result<LinkIter>;
+
+    /// Remove the internal link with the relation `relation`, and the link back with the inverse
+    /// relation
+    ///
+    /// Other links between the two entries are kept.
+    fn remove_internal_related_link(&mut self, link: &mut Entry, relation: &Relation) -> Result<()>;
 }
 
 pub mod iter {
@@ -460,7 +512,7 @@ impl InternalLinker for Entry {
         for id in self.get_internal_links()?.map(|l| l.get_store_id().clone()) {
             match store.get(id).map_err(Error::from)? {
                 Some(mut entry) => self.remove_internal_link(&mut entry)?,
-                None            => return Err(err_msg("Link target does not exist")),
+                None            => return Err(kind_err(ErrorKind::NotFound, "Link target does not exist")),
             }
         }
 
@@ -476,19 +528,75 @@ impl InternalLinker for Entry {
         add_internal_link_with_instance(self, link, new_link)
     }
 
+    fn add_internal_related_link(&mut self, link: &mut Entry, relation: Relation) -> Result<()> {
+        debug!("Adding internal link from {:?} to {:?} with relation {}",
+               self.get_location(), link.get_location(), relation);
+
+        let back = Link::Related {
+            link: self.get_location().clone(),
+            relation: relation.inverse(),
+        };
+        let new_link = Link::Related {
+            link: link.get_location().clone(),
+            relation: relation,
+        };
+
+        let _ = add_link_deduplicated(link, back)?;
+        add_link_deduplicated(self, new_link)
+    }
+
+    fn get_internal_related_links(&self, relation: &Relation) -> Result<LinkIter> {
+        let links = self.get_internal_links()?
+            .filter(|l| l.relation() == Some(relation))
+            .collect();
+
+        Ok(LinkIter::new(links))
+    }
+
+    fn remove_internal_related_link(&mut self, link: &mut Entry, relation: &Relation) -> Result<()> {
+        let own_loc   = self.get_location().clone();
+        let other_loc = link.get_location().clone();
+        let inverse   = relation.inverse();
+
+        debug!("Removing internal link from {:?} to {:?} with relation {}", own_loc, other_loc, relation);
+
+        let links = link.get_internal_links()?
+            .filter(|l| !(l.eq_store_id(&own_loc) && l.relation() == Some(&inverse)));
+        let _ = rewrite_links(link.get_header_mut(), links)?;
+
+        let links = self.get_internal_links()?
+            .filter(|l| !(l.eq_store_id(&other_loc) && l.relation() == Some(relation)));
+        rewrite_links(self.get_header_mut(), links)
+    }
+
+}
+
+/// Add `instance` to the links of `this`, without linking to the same entry twice
+///
+/// A plain link is not added if `this` links to the entry already. An annotated or related link
+/// replaces a plain link to the same entry.
+fn add_link_deduplicated(this: &mut Entry, instance: Link) -> Result<()> {
+    let target = instance.get_store_id().clone();
+    let links  = this.get_internal_links()?.collect::<Vec<_>>();
+
+    if is_match!(instance, Link::Id { .. }) && links.iter().any(|l| l.eq_store_id(&target)) {
+        debug!("{:?} links to {:?} already", this.get_location(), target);
+        return Ok(())
+    }
+
+    let links = links
+        .into_iter()
+        .filter(|l| !(is_match!(*l, Link::Id { .. }) && l.eq_store_id(&target)))
+        .chain(LinkIter::new(vec![instance]));
+
+    rewrite_links(this.get_header_mut(), links)
 }
 
 fn add_internal_link_with_instance(this: &mut Entry, link: &mut Entry, instance: Link) -> Result<()> {
     debug!("Adding internal link from {:?} to {:?}", this.get_location(), instance);
 
     add_foreign_link(link, this.get_location().clone())
-        .and_then(|_| {
-            this.get_internal_links()
-                .and_then(|links| {
-                    let links = links.chain(LinkIter::new(vec![instance]));
-                    rewrite_links(this.get_header_mut(), links)
-                })
-        })
+        .and_then(|_| add_link_deduplicated(this, instance))
 }
 
 fn rewrite_links<I: Iterator<Item = Link>>(header: &mut Value, links: I) -> Result<()> {
@@ -514,29 +622,7 @@ fn rewrite_links<I: Iterator<Item = Link>>(header: &mut Value, links: I) -> Resu
 /// This is a helper function which does this.
 fn add_foreign_link(target: &mut Entry, from: StoreId) -> Result<()> {
     debug!("Linking back from {:?} to {:?}", target.get_location(), from);
-    target.get_internal_links()
-        .and_then(|links| {
-            let links = links
-                             .chain(LinkIter::new(vec![from.into()]))
-                             .into_values()
-                             .into_iter()
-                             .fold(Ok(vec![]), |acc: Result<Vec<_>>, elem| {
-                                acc.and_then(move |mut v| {
-                                    v.push(elem.context(EM::ConversionError)?);
-                                    Ok(v)
-                                })
-                             })?;
-            debug!("Setting links in {:?}: {:?}", target.get_location(), links);
-
-            let res = target
-                .get_header_mut()
-                .insert("links.internal", Value::Array(links))
-                .map_err(Error::from)
-                .context(EM::EntryHeaderReadError)
-                .map_err(Error::from);
-
-            process_rw_result(res).map(|_| ())
-        })
+    add_link_deduplicated(target, from.into())
 }
 
 fn process_rw_result(links: Result<Option<Value>>) -> Result<LinkIter> {
@@ -554,14 +640,14 @@ fn process_rw_result(links: Result<Option<Value>>) -> Result<LinkIter> {
         Ok(Some(Value::Array(l))) => l,
         Ok(Some(_)) => {
             debug!("We expected an Array for the links, but there was a non-Array!");
-            return Err(err_msg("Link type error"));
+            return Err(kind_err(ErrorKind::Parse, "Link type error"));
         }
     };
 
     if !links.iter().all(|l| is_match!(*l, Value::String(_)) || is_match!(*l, Value::Table(_))) {
         debug!("At least one of the Values which were expected in the Array of links is not a String or a Table!");
         debug!("Generating LinkError");
-        return Err(err_msg("Existing Link type error"));
+        return Err(kind_err(ErrorKind::Parse, "Existing Link type error"));
     }
 
     let links : Vec<Link> = links.into_iter()
@@ -574,31 +660,33 @@ fn process_rw_result(links: Result<Option<Value>>) -> Result<LinkIter> {
                     ,
                 Value::Table(mut tab) => {
                     debug!("Destructuring table");
-                    if !tab.contains_key("link")
-                    || !tab.contains_key("annotation") {
-                        debug!("Things missing... returning Error instance");
-                        Err(err_msg("Link parser error"))
-                    } else {
-                        let link = tab.remove("link")
-                            .ok_or(err_msg("Link parser: field missing"))?;
-
-                        let anno = tab.remove("annotation")
-                            .ok_or(err_msg("Link parser: Field missing"))?;
-
-                        debug!("Ok, here we go with building a Link::Annotated");
-                        match (link, anno) {
-                            (Value::String(link), Value::String(anno)) => {
-                                StoreId::new(PathBuf::from(link))
-                                    .map_err(From::from)
-                                    .map(|link| {
-                                        Link::Annotated {
-                                            link: link,
-                                            annotation: anno,
-                                        }
-                                    })
-                            },
-                            _ => Err(err_msg("Link parser: Field type error")),
-                        }
+                    let link = tab.remove("link")
+                        .ok_or_else(|| kind_err(ErrorKind::Parse, "Link parser: field missing"))?;
+
+                    match (link, tab.remove("annotation"), tab.remove("relation")) {
+                        (Value::String(link), Some(Value::String(anno)), None) => {
+                            debug!("Ok, here we go with building a Link::Annotated");
+                            StoreId::new(PathBuf::from(link))
+                                .map_err(From::from)
+                                .map(|link| {
+                                    Link::Annotated {
+                                        link: link,
+                                        annotation: anno,
+                                    }
+                                })
+                        },
+                        (Value::String(link), None, Some(Value::String(rel))) => {
+                            debug!("Ok, here we go with building a Link::Related");
+                            let relation = Relation::from_str(&rel)?;
+                            StoreId::new(PathBuf::from(link))
+                                .map_err(From::from)
+                                .map(|link| Link::Related { link, relation })
+                        },
+                        (_, None, None) => {
+                            debug!("Things missing... returning Error instance");
+                            Err(kind_err(ErrorKind::Parse, "Link parser error"))
+                        },
+                        _ => Err(kind_err(ErrorKind::Parse, "Link parser: Field type error")),
                     }
                 }
                 _ => unreachable!(),
@@ -611,159 +699,370 @@ fn process_rw_result(links: Result<Option<Value>>) -> Result<LinkIter> {
 }
 
 pub mod store_check {
+    use std::collections::BTreeMap;
+    use std::collections::HashMap;
+    use std::fmt::{Display, Formatter, Result as FmtResult};
+    use std::io::Read;
+    use std::io::Write;
+
+    use libimagstore::store::Entry;
+    use libimagstore::store::FileLockEntry;
     use libimagstore::store::Store;
+    use libimagstore::storeid::StoreId;
+    use libimagerror::kind::ErrorKind;
+    use libimagerror::kind::kind_err;
+    use libimaginteraction::ask::ask_bool;
 
-    use failure::ResultExt;
+    use toml::Value;
+    use toml_query::read::TomlValueReadExt;
     use failure::Fallible as Result;
     use failure::Error;
-    use failure::err_msg;
+
+    use super::InternalLinker;
+    use super::Link;
+    use super::iter::LinkIter;
+    use super::rewrite_links;
+    use relation::Relation;
+
+    /// The incoming and outgoing links of an entry
+    #[derive(Debug, Default, Clone)]
+    pub struct Linking {
+        pub outgoing: Vec<StoreId>,
+        pub incoming: Vec<StoreId>,
+    }
+
+    /// The link network of the store, mapping each StoreId onto its links
+    pub type LinkNetwork = HashMap<StoreId, Linking>;
+
+    /// A problem with the links in the store
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum LinkIssue {
+        /// `from` links to `to`, which does not exist
+        DeadLink { from: StoreId, to: StoreId },
+
+        /// `from` links to `to`, but `to` does not link back
+        ///
+        /// `relations` are the relations of the links from `from` to `to`, the link back has to
+        /// have the inverse relations. If it is empty, the link is a plain one.
+        MissingBackLink { from: StoreId, to: StoreId, relations: Vec<Relation> },
+
+        /// `entry` contains a link more than once, or a plain link next to a related link to the
+        /// same entry
+        DuplicateLinks { entry: StoreId },
+
+        /// The links of `entry` are not stored sorted or not in the format they are written in
+        UnnormalizedLinks { entry: StoreId },
+    }
+
+    impl LinkIssue {
+
+        /// A description of what `StoreLinkConsistentExt::repair_link_issue()` does to fix the
+        /// issue
+        pub fn fix_description(&self) -> String {
+            match *self {
+                LinkIssue::DeadLink { ref from, ref to } =>
+                    format!("Remove the link from {} to {}", from, to),
+                LinkIssue::MissingBackLink { ref from, ref to, .. } =>
+                    format!("Add the link back from {} to {}", to, from),
+                LinkIssue::DuplicateLinks { ref entry } =>
+                    format!("Remove the duplicate links of {}", entry),
+                LinkIssue::UnnormalizedLinks { ref entry } =>
+                    format!("Rewrite the links of {}", entry),
+            }
+        }
+
+    }
+
+    impl Display for LinkIssue {
+        fn fmt(&self, fmt: &mut Formatter) -> FmtResult {
+            match *self {
+                LinkIssue::DeadLink { ref from, ref to } =>
+                    write!(fmt, "Dead link: {} -> {}", from, to),
+                LinkIssue::MissingBackLink { ref from, ref to, .. } =>
+                    write!(fmt, "One-directional link: {} -> {}", from, to),
+                LinkIssue::DuplicateLinks { ref entry } =>
+                    write!(fmt, "Duplicate links in {}", entry),
+                LinkIssue::UnnormalizedLinks { ref entry } =>
+                    write!(fmt, "Links not stored normalized in {}", entry),
+            }
+        }
+    }
+
+    /// The result of `StoreLinkConsistentExt::repair_link_issues()`
+    #[derive(Debug, Default)]
+    pub struct LinkRepairReport {
+        pub fixed: Vec<LinkIssue>,
+        pub skipped: Vec<LinkIssue>,
+        pub failed: Vec<(LinkIssue, Error)>,
+    }
+
+    impl LinkRepairReport {
+        pub fn is_success(&self) -> bool {
+            self.failed.is_empty()
+        }
+
+        /// Whether all issues were repaired, so none of them are left in the store
+        pub fn is_complete(&self) -> bool {
+            self.failed.is_empty() && self.skipped.is_empty()
+        }
+    }
+
+    impl Display for LinkRepairReport {
+        fn fmt(&self, fmt: &mut Formatter) -> FmtResult {
+            write!(fmt, "{} issues fixed, {} skipped, {} failed",
+                   self.fixed.len(), self.skipped.len(), self.failed.len())
+        }
+    }
 
     pub trait StoreLinkConsistentExt {
-        fn check_link_consistency(&self) -> Result<()>;
+
+        /// Aggregate the link network of the store
+        ///
+        /// Each entry in the store is mapped onto a `Linking` object, which contains the list of
+        /// StoreIds the entry links to and the list of StoreIds of entries which link to it.
+        /// Links to entries which do not exist are contained in the outgoing list only.
+        fn aggregate_link_network(&self) -> Result<LinkNetwork>;
+
+        /// Find all link issues in the store
+        ///
+        /// Dead and one-directional links are listed first, then the issues with the link
+        /// storage of single entries.
+        fn find_link_issues(&self) -> Result<Vec<LinkIssue>>;
+
+        /// Repair a single link issue
+        ///
+        /// Dead links are removed, missing links back are added (with the inverse relation, if
+        /// any) and the links of entries with duplicate or unnormalized links are rewritten.
+        fn repair_link_issue(&self, issue: &LinkIssue) -> Result<()>;
+
+        /// Repair all `issues` for which `confirm` returns true
+        ///
+        /// Failing repairs do not abort, they are collected in the report. An error returned by
+        /// `confirm` aborts.
+        fn repair_link_issues<F>(&self, issues: Vec<LinkIssue>, confirm: F) -> Result<LinkRepairReport>
+            where F: FnMut(&LinkIssue) -> Result<bool>;
     }
 
     impl StoreLinkConsistentExt for Store {
-        fn check_link_consistency(&self) -> Result<()> {
-            use std::collections::HashMap;
+        fn aggregate_link_network(&self) -> Result<LinkNetwork> {
+            let mut network = self
+                .entries()?
+                .into_get_iter()
+                .fold(Ok(HashMap::new()), |map, element| {
+                    map.and_then(|mut map| {
+                        debug!("Checking element = {:?}", element);
+                        let entry = element?.ok_or_else(|| kind_err(ErrorKind::NotFound, "TODO: Not yet handled"))?;
+
+                        debug!("Checking entry = {:?}", entry.get_location());
+
+                        let mut linking = Linking::default();
+                        for internal_link in entry.get_internal_links()? {
+                            debug!("internal link = {:?}", internal_link);
+                            linking.outgoing.push(internal_link.get_store_id().clone());
+                        }
 
-            use internal::InternalLinker;
+                        map.insert(entry.get_location().clone(), linking);
+                        Ok(map)
+                    })
+                })?;
 
-            use libimagstore::storeid::StoreId;
-            use libimagutil::debug_result::DebugResult;
+            // Fill in the incoming links from the outgoing links of all other entries
+            let outgoing = network
+                .iter()
+                .flat_map(|(src, linking)| {
+                    linking.outgoing.iter().map(move |target| (src.clone(), target.clone()))
+                })
+                .collect::<Vec<_>>();
 
-            // Helper data structure to collect incoming and outgoing links for each StoreId
-            #[derive(Debug, Default)]
-            struct Linking {
-                outgoing: Vec<StoreId>,
-                incoming: Vec<StoreId>,
+            for (src, target) in outgoing {
+                if let Some(linking) = network.get_mut(&target) {
+                    linking.incoming.push(src);
+                }
             }
 
-            // Helper function to aggregate the Link network
-            //
-            // This function aggregates a HashMap which maps each StoreId object in the store onto
-            // a Linking object, which contains a list of StoreIds which this entry links to and a
-            // list of StoreIds which link to the current one.
-            //
-            // The lambda returns an error if something fails
-            let aggregate_link_network = |store: &Store| -> Result<HashMap<StoreId, Linking>> {
-                store
-                    .entries()?
-                    .into_get_iter()
-                    .fold(Ok(HashMap::new()), |map, element| {
-                        map.and_then(|mut map| {
-                            debug!("Checking element = {:?}", element);
-                            let entry = element?.ok_or_else(|| err_msg("TODO: Not yet handled"))?;
-
-                            debug!("Checking entry = {:?}", entry.get_location());
-
-                            let internal_links = entry
-                                .get_internal_links()?
-                                .into_getter(store); // get the FLEs from the Store
-
-                            let mut linking = Linking::default();
-                            for internal_link in internal_links {
-                                debug!("internal link = {:?}", internal_link);
-
-                                linking.outgoing.push(internal_link?.get_location().clone());
-                                linking.incoming.push(entry.get_location().clone());
-                            }
+            Ok(network)
+        }
 
-                            map.insert(entry.get_location().clone(), linking);
-                            Ok(map)
-                        })
-                    })
-            };
-
-            // Helper to check whethre all StoreIds in the network actually exists
-            //
-            // Because why not?
-            let all_collected_storeids_exist = |network: &HashMap<StoreId, Linking>| -> Result<()> {
-                for (id, _) in network.iter() {
-                    if is_match!(self.get(id.clone()), Ok(Some(_))) {
-                        debug!("Exists in store: {:?}", id);
-
-                        if !self.exists(id.clone())? {
-                            warn!("Does exist in store but not on FS: {:?}", id);
-                            return Err(err_msg("Link target does not exist"))
-                        }
-                    } else {
-                        warn!("Does not exist in store: {:?}", id);
-                        return Err(err_msg("Link target does not exist"))
-                    }
+        fn find_link_issues(&self) -> Result<Vec<LinkIssue>> {
+            let mut links_of       = BTreeMap::new();
+            let mut storage_issues = Vec::new();
+
+            for element in self.entries()?.into_get_iter() {
+                let entry = element?.ok_or_else(|| kind_err(ErrorKind::NotFound, "TODO: Not yet handled"))?;
+                let id    = entry.get_location().clone();
+                debug!("Checking links of {}", id);
+
+                let links      = entry.get_internal_links()?.collect::<Vec<_>>();
+                let normalized = normalize_links(links.clone());
+
+                if normalized.len() != links.len() {
+                    storage_issues.push(LinkIssue::DuplicateLinks { entry: id.clone() });
+                } else if !is_stored_normalized(&entry, &normalized)? {
+                    storage_issues.push(LinkIssue::UnnormalizedLinks { entry: id.clone() });
                 }
 
-                Ok(())
-            };
-
-            // Helper function to create a SLCECD::OneDirectionalLink error object
-            let mk_one_directional_link_err = |src: StoreId, target: StoreId| -> Error {
-                Error::from(format_err!("Dead link: {} -> {}",
-                                        src.local_display_string(),
-                                        target.local_display_string()))
-            };
-
-            // Helper lambda to check whether the _incoming_ links of each entry actually also
-            // appear in the _outgoing_ list of the linked entry
-            let incoming_links_exists_as_outgoing_links =
-                |src: &StoreId, linking: &Linking, network: &HashMap<StoreId, Linking>| -> Result<()> {
-                    for link in linking.incoming.iter() {
-                        // Check whether the links which are _incoming_ on _src_ are outgoing
-                        // in each of the links in the incoming list.
-                        let incoming_consistent = network.get(link)
-                            .map(|l| l.outgoing.contains(src))
-                            .unwrap_or(false);
-
-                        if !incoming_consistent {
-                            return Err(mk_one_directional_link_err(src.clone(), link.clone()))
-                        }
+                let _ = links_of.insert(id, normalized);
+            }
+
+            let mut issues = Vec::new();
+            for (src, links) in links_of.iter() {
+                // Group the links by target, so that multiple relations to one entry are handled
+                // as one link
+                let mut targets : BTreeMap<&StoreId, Vec<Relation>> = BTreeMap::new();
+                for link in links.iter() {
+                    let relations = targets.entry(link.get_store_id()).or_insert_with(Vec::new);
+                    if let Some(relation) = link.relation() {
+                        relations.push(relation.clone());
                     }
+                }
 
-                    Ok(())
-                };
-
-            // Helper lambda to check whether the _outgoing links of each entry actually also
-            // appear in the _incoming_ list of the linked entry
-            let outgoing_links_exist_as_incoming_links =
-                |src: &StoreId, linking: &Linking, network: &HashMap<StoreId, Linking>| -> Result<()> {
-                    for link in linking.outgoing.iter() {
-                        // Check whether the links which are _outgoing_ on _src_ are incoming
-                        // in each of the links in the outgoing list.
-                        let outgoing_consistent = network.get(link)
-                            .map(|l| l.incoming.contains(src))
-                            .unwrap_or(false);
-
-                        if !outgoing_consistent {
-                            return Err(mk_one_directional_link_err(link.clone(), src.clone()))
-                        }
+                for (target, relations) in targets {
+                    match links_of.get(target) {
+                        None => issues.push(LinkIssue::DeadLink {
+                            from: src.clone(),
+                            to: target.clone(),
+                        }),
+                        Some(back) => if !back.iter().any(|l| l.eq_store_id(src)) {
+                            issues.push(LinkIssue::MissingBackLink {
+                                from: src.clone(),
+                                to: target.clone(),
+                                relations,
+                            })
+                        },
                     }
+                }
+            }
 
-                    Ok(())
-                };
+            issues.append(&mut storage_issues);
+            Ok(issues)
+        }
 
-            aggregate_link_network(&self)
-                .map_dbg_str("Aggregated")
-                .map_dbg(|nw| {
-                    let mut s = String::new();
-                    for (k, v) in nw {
-                        s.push_str(&format!("{}\n in: {:?}\n out: {:?}", k, v.incoming, v.outgoing));
-                    }
-                    s
-                })
-                .and_then(|nw| {
-                    all_collected_storeids_exist(&nw)
-                        .map(|_| nw)
-                        .context(err_msg("Link handling error"))
-                        .map_err(Error::from)
-                })
-                .and_then(|nw| {
-                    for (id, linking) in nw.iter() {
-                        incoming_links_exists_as_outgoing_links(id, linking, &nw)?;
-                        outgoing_links_exist_as_incoming_links(id, linking, &nw)?;
-                    }
-                    Ok(())
-                })
-                .map(|_| ())
+        fn repair_link_issue(&self, issue: &LinkIssue) -> Result<()> {
+            debug!("Repairing: {}", issue);
+
+            match *issue {
+                LinkIssue::DeadLink { ref from, ref to } => {
+                    let mut entry = get_existing(self, from)?;
+                    let links     = entry.get_internal_links()?.filter(|l| !l.eq_store_id(to));
+                    rewrite_links(entry.get_header_mut(), links)
+                },
+
+                LinkIssue::MissingBackLink { ref from, ref to, ref relations } => {
+                    let mut entry = get_existing(self, to)?;
+                    let back = if relations.is_empty() {
+                        vec![Link::Id { link: from.clone() }]
+                    } else {
+                        relations
+                            .iter()
+                            .map(|r| Link::Related { link: from.clone(), relation: r.inverse() })
+                            .collect()
+                    };
+
+                    let links = entry.get_internal_links()?.chain(back.into_iter()).collect();
+                    let links = normalize_links(links);
+                    rewrite_links(entry.get_header_mut(), LinkIter::new(links))
+                },
+
+                LinkIssue::DuplicateLinks { ref entry } | LinkIssue::UnnormalizedLinks { ref entry } => {
+                    let mut entry = get_existing(self, entry)?;
+                    let links     = normalize_links(entry.get_internal_links()?.collect());
+                    rewrite_links(entry.get_header_mut(), LinkIter::new(links))
+                },
+            }
+        }
+
+        fn repair_link_issues<F>(&self, issues: Vec<LinkIssue>, mut confirm: F) -> Result<LinkRepairReport>
+            where F: FnMut(&LinkIssue) -> Result<bool>
+        {
+            let mut report = LinkRepairReport::default();
+
+            for issue in issues {
+                if !confirm(&issue)? {
+                    debug!("Skipping: {}", issue);
+                    report.skipped.push(issue);
+                    continue
+                }
+
+                match self.repair_link_issue(&issue) {
+                    Ok(())  => report.fixed.push(issue),
+                    Err(e)  => report.failed.push((issue, e)),
+                }
+            }
+
+            Ok(report)
+        }
+    }
+
+    /// Repair `issues`, asking for each one on `input` and `output` whether it should be repaired
+    ///
+    /// With `yes`, all issues are repaired without asking. Otherwise an `input` to ask on is
+    /// required. As repairs can remove links, an empty answer skips the issue. Failed repairs are
+    /// logged and collected in the report.
+    pub fn repair_link_issues_interactive<R: Read>(store: &Store,
+                                                   issues: Vec<LinkIssue>,
+                                                   yes: bool,
+                                                   input: Option<R>,
+                                                   output: &mut Write)
+        -> Result<LinkRepairReport>
+    {
+        let mut input = match (yes, input) {
+            (true, _)        => None,
+            (false, Some(i)) => Some(i),
+            (false, None)    => {
+                let msg = "No input stream. Cannot ask for permission, pass --yes to repair without asking";
+                return Err(kind_err(ErrorKind::Other, msg))
+            },
+        };
+
+        let report = store.repair_link_issues(issues, |issue| match input {
+            None            => Ok(true),
+            Some(ref mut i) => {
+                let question = format!("{}. {}", issue, issue.fix_description());
+                ask_bool(&question, Some(false), i, &mut *output)
+            },
+        })?;
+
+        for &(ref issue, ref e) in report.failed.iter() {
+            error!("Failed to repair: {}: {}", issue, e);
         }
+
+        Ok(report)
+    }
+
+    /// Remove duplicate links and plain links which are shadowed by a related link to the same
+    /// entry, and sort the links
+    fn normalize_links(links: Vec<Link>) -> Vec<Link> {
+        let related = links
+            .iter()
+            .filter(|l| l.relation().is_some())
+            .map(|l| l.get_store_id().clone())
+            .collect::<Vec<_>>();
+
+        let mut links = links
+            .into_iter()
+            .filter(|l| !(is_match!(*l, Link::Id { .. }) && related.contains(l.get_store_id())))
+            .collect::<Vec<_>>();
+
+        links.sort();
+        links.dedup();
+        links
+    }
+
+    /// Check whether the links are stored in the header of `entry` exactly as `rewrite_links()`
+    /// would store `links`
+    fn is_stored_normalized(entry: &Entry, links: &[Link]) -> Result<bool> {
+        let expected = links.iter().map(Link::to_value).collect::<Result<Vec<_>>>()?;
+
+        match entry.get_header().read("links.internal")? {
+            None                        => Ok(expected.is_empty()),
+            Some(&Value::Array(ref v))  => Ok(*v == expected),
+            Some(_)                     => Ok(false),
+        }
+    }
+
+    fn get_existing<'a>(store: &'a Store, id: &StoreId) -> Result<FileLockEntry<'a>> {
+        store.get(id.clone())?
+            .ok_or_else(|| kind_err(ErrorKind::NotFound, format!("Entry does not exist: {}", id)))
     }
 
 }
@@ -776,6 +1075,7 @@ mod test {
 
     use super::InternalLinker;
     use super::Link;
+    use relation::Relation;
 
     fn setup_logging() {
         let _ = ::env_logger::try_init();
@@ -1004,10 +1304,222 @@ mod test {
                 match link  {
                     Link::Id {..}        => {},
                     Link::Annotated {..} => assert!(false, "Annotated link found"),
+                    Link::Related {..}   => assert!(false, "Related link found"),
                 }
             }
         }
     }
 
-}
+    #[test]
+    fn test_link_related() {
+        setup_logging();
+        let store  = get_store();
+        let mut e1 = store.create(PathBuf::from("test_link_related-1")).unwrap();
+        let mut e2 = store.create(PathBuf::from("test_link_related-2")).unwrap();
+        let mut e3 = store.create(PathBuf::from("test_link_related-3")).unwrap();
+
+        assert!(e1.add_internal_link(&mut e2).is_ok());
+        assert!(e1.add_internal_related_link(&mut e2, Relation::Blocks).is_ok());
+        assert!(e1.add_internal_related_link(&mut e3, Relation::Parent).is_ok());
+
+        // The plain link was replaced
+        let e1_links = e1.get_internal_links().unwrap().collect::<Vec<_>>();
+        assert_eq!(e1_links.len(), 2, "Expected two links, got: {:?}", e1_links);
+        assert!(e1_links.iter().all(|l| l.relation().is_some()));
+
+        let blocked = e1.get_internal_related_links(&Relation::Blocks).unwrap().collect::<Vec<_>>();
+        assert_eq!(blocked.len(), 1);
+        assert!(blocked[0].eq_store_id(e2.get_location()));
 
+        let blocking = e2.get_internal_related_links(&Relation::BlockedBy).unwrap().collect::<Vec<_>>();
+        assert_eq!(blocking.len(), 1);
+        assert!(blocking[0].eq_store_id(e1.get_location()));
+
+        let parents = e3.get_internal_related_links(&Relation::Child).unwrap().collect::<Vec<_>>();
+        assert_eq!(parents.len(), 1);
+        assert!(parents[0].eq_store_id(e1.get_location()));
+
+        // A second relation between the same entries is kept when removing the first one
+        assert!(e1.add_internal_related_link(&mut e2, Relation::References).is_ok());
+        assert!(e1.remove_internal_related_link(&mut e2, &Relation::Blocks).is_ok());
+
+        let e2_links = e2.get_internal_links().unwrap().collect::<Vec<_>>();
+        assert_eq!(e2_links.len(), 1, "Expected one link, got: {:?}", e2_links);
+        assert_eq!(Some(&Relation::ReferencedBy), e2_links[0].relation());
+        assert_eq!(e1.get_internal_related_links(&Relation::Blocks).unwrap().count(), 0);
+
+        assert!(e1.remove_internal_link(&mut e2).is_ok());
+        assert_eq!(e2.get_internal_links().unwrap().count(), 0);
+        assert_eq!(e1.get_internal_links().unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_link_deduplicated() {
+        setup_logging();
+        let store  = get_store();
+        let mut e1 = store.create(PathBuf::from("test_link_deduplicated-1")).unwrap();
+        let mut e2 = store.create(PathBuf::from("test_link_deduplicated-2")).unwrap();
+        let mut e3 = store.create(PathBuf::from("test_link_deduplicated-3")).unwrap();
+
+        // A plain link does not duplicate a related one, in either direction
+        assert!(e1.add_internal_related_link(&mut e2, Relation::DuplicateOf).is_ok());
+        assert!(e1.add_internal_link(&mut e2).is_ok());
+        assert!(e2.add_internal_link(&mut e1).is_ok());
+
+        let e1_links = e1.get_internal_links().unwrap().collect::<Vec<_>>();
+        assert_eq!(e1_links.len(), 1, "Expected one link, got: {:?}", e1_links);
+        assert_eq!(Some(&Relation::DuplicateOf), e1_links[0].relation());
+
+        let e2_links = e2.get_internal_links().unwrap().collect::<Vec<_>>();
+        assert_eq!(e2_links.len(), 1, "Expected one link, got: {:?}", e2_links);
+        assert_eq!(Some(&Relation::HasDuplicate), e2_links[0].relation());
+
+        // An annotation replaces a plain link
+        assert!(e1.add_internal_link(&mut e3).is_ok());
+        assert!(e1.add_internal_annotated_link(&mut e3, String::from("annotation")).is_ok());
+
+        let e3_link = e1.get_internal_links()
+            .unwrap()
+            .filter(|l| l.eq_store_id(e3.get_location()))
+            .collect::<Vec<_>>();
+        assert_eq!(e3_link.len(), 1, "Expected one link, got: {:?}", e3_link);
+        assert!(is_match!(e3_link[0], Link::Annotated { .. }));
+        assert_eq!(e3.get_internal_links().unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_link_network_consistency() {
+        use toml::Value;
+        use toml_query::insert::TomlValueInsertExt;
+        use super::store_check::StoreLinkConsistentExt;
+
+        setup_logging();
+        let store = get_store();
+
+        {
+            let mut e1 = store.create(PathBuf::from("test_link_network-1")).unwrap();
+            let mut e2 = store.create(PathBuf::from("test_link_network-2")).unwrap();
+            let _      = store.create(PathBuf::from("test_link_network-3")).unwrap();
+            assert!(e1.add_internal_link(&mut e2).is_ok());
+        }
+
+        let network = store.aggregate_link_network().unwrap();
+        assert_eq!(network.len(), 3);
+
+        let e1_id   = ::libimagstore::storeid::StoreId::new(PathBuf::from("test_link_network-1")).unwrap();
+        let linking = network.get(&e1_id).unwrap();
+        assert_eq!(linking.outgoing.len(), 1);
+        assert_eq!(linking.incoming.len(), 1);
+        assert!(store.find_link_issues().unwrap().is_empty());
+
+        {
+            // Link e3 to e1 without the link back
+            let mut e3 = store.get(PathBuf::from("test_link_network-3")).unwrap().unwrap();
+            let links  = Value::Array(vec![Value::String(String::from("test_link_network-1"))]);
+            let _      = e3.get_header_mut().insert("links.internal", links).unwrap();
+        }
+
+        assert!(!store.find_link_issues().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_link_issues_repair() {
+        use toml::Value;
+        use toml_query::insert::TomlValueInsertExt;
+        use libimagstore::storeid::StoreId;
+        use super::store_check::StoreLinkConsistentExt;
+        use super::store_check::LinkIssue;
+
+        setup_logging();
+        let store = get_store();
+        let id    = |s: &str| StoreId::new(PathBuf::from(s)).unwrap();
+
+        {
+            let mut e1 = store.create(PathBuf::from("test_repair-1")).unwrap();
+            let mut e2 = store.create(PathBuf::from("test_repair-2")).unwrap();
+            let mut e3 = store.create(PathBuf::from("test_repair-3")).unwrap();
+            assert!(e1.add_internal_link(&mut e2).is_ok());
+
+            // e3 blocks e1 without the link back, links to a non-existing entry and contains a
+            // plain link next to the related one
+            let mut blocks = ::std::collections::BTreeMap::new();
+            let _ = blocks.insert(String::from("link"), Value::String(String::from("test_repair-1")));
+            let _ = blocks.insert(String::from("relation"), Value::String(String::from("blocks")));
+            let links = Value::Array(vec![
+                Value::String(String::from("test_repair-nonexistent")),
+                Value::String(String::from("test_repair-1")),
+                Value::Table(blocks),
+            ]);
+            let _ = e3.get_header_mut().insert("links.internal", links).unwrap();
+        }
+
+        let issues = store.find_link_issues().unwrap();
+        assert_eq!(issues, vec![
+            LinkIssue::MissingBackLink {
+                from: id("test_repair-3"),
+                to: id("test_repair-1"),
+                relations: vec![Relation::Blocks],
+            },
+            LinkIssue::DeadLink {
+                from: id("test_repair-3"),
+                to: id("test_repair-nonexistent"),
+            },
+            LinkIssue::DuplicateLinks { entry: id("test_repair-3") },
+        ]);
+
+        // Skip the dead link, fix everything else
+        let report = store
+            .repair_link_issues(issues, |i| Ok(!is_match!(*i, LinkIssue::DeadLink { .. })))
+            .unwrap();
+        assert!(report.is_success());
+        assert_eq!(report.fixed.len(), 2);
+        assert_eq!(report.skipped.len(), 1);
+
+        let issues = store.find_link_issues().unwrap();
+        assert_eq!(issues, vec![LinkIssue::DeadLink {
+            from: id("test_repair-3"),
+            to: id("test_repair-nonexistent"),
+        }]);
+
+        for issue in issues {
+            assert!(store.repair_link_issue(&issue).is_ok());
+        }
+        assert!(store.find_link_issues().unwrap().is_empty());
+
+        let e1 = store.get(PathBuf::from("test_repair-1")).unwrap().unwrap();
+        let blocked_by = e1.get_internal_related_links(&Relation::BlockedBy).unwrap().collect::<Vec<_>>();
+        assert_eq!(blocked_by.len(), 1);
+        assert!(blocked_by[0].eq_store_id(&id("test_repair-3")));
+    }
+
+    #[test]
+    fn test_repair_link_issues_interactive() {
+        use std::io::Cursor;
+        use std::io::Empty;
+        use libimagstore::storeid::StoreId;
+        use super::store_check::StoreLinkConsistentExt;
+        use super::store_check::LinkIssue;
+        use super::store_check::repair_link_issues_interactive;
+
+        setup_logging();
+        let store  = get_store();
+        let from   = StoreId::new(PathBuf::from("test_interactive-1")).unwrap();
+        let to     = StoreId::new(PathBuf::from("test_interactive-nonexistent")).unwrap();
+        let issues = vec![LinkIssue::DeadLink { from: from.clone(), to: to.clone() }];
+        let _      = store.create(PathBuf::from("test_interactive-1")).unwrap();
+
+        // Without --yes, there has to be an input to ask on
+        let mut out = vec![];
+        assert!(repair_link_issues_interactive(&store, issues.clone(), false, None as Option<Empty>, &mut out).is_err());
+
+        // An empty answer skips the issue
+        let report = repair_link_issues_interactive(&store, issues.clone(), false, Some(Cursor::new("\n")), &mut out).unwrap();
+        assert!(!report.is_complete());
+        assert_eq!(report.skipped, issues);
+
+        let report = repair_link_issues_interactive(&store, issues.clone(), true, None as Option<Empty>, &mut out).unwrap();
+        assert!(report.is_complete());
+        assert_eq!(report.fixed, issues);
+    }
+
+}
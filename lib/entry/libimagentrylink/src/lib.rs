@@ -45,7 +45,13 @@ extern crate url;
 extern crate sha1;
 extern crate hex;
 #[macro_use] extern crate is_match;
-#[macro_use] extern crate failure;
+extern crate failure;
+#[macro_use] extern crate serde_json;
+#[macro_use] extern crate serde_derive;
+extern crate chrono;
+#[cfg(feature = "web")] extern crate reqwest;
+#[cfg(feature = "web")] extern crate regex;
+#[cfg(feature = "web")] extern crate base64;
 
 #[cfg(test)]
 extern crate env_logger;
@@ -53,9 +59,20 @@ extern crate env_logger;
 #[macro_use] extern crate libimagstore;
 extern crate libimagerror;
 extern crate libimagutil;
+extern crate libimaginteraction;
 
 module_entry_path_mod!("links");
 
+pub mod analysis;
+#[cfg(feature = "web")]
+pub mod archive;
+#[cfg(feature = "web")]
+pub mod check;
 pub mod external;
+pub mod graph;
 pub mod internal;
+pub mod relation;
+
+#[cfg(all(test, feature = "web"))]
+mod test_server;
 
@@ -0,0 +1,169 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2019 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+//! Relations of internal links
+//!
+//! An internal link can carry a relation, which tells what the linking entry is to the linked
+//! one. A link `A -> B` with the relation `blocks` reads "A blocks B", one with the relation
+//! `parent` reads "A is the parent of B".
+//!
+//! Relations come in pairs: the other end of a link always carries the inverse relation, so `B`
+//! links back to `A` with `blocked-by` or `child`. Custom relations, which imag does not know the
+//! inverse of, are their own inverse.
+
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::str::FromStr;
+
+use failure::Error;
+use failure::Fallible as Result;
+
+use libimagerror::kind::ErrorKind;
+use libimagerror::kind::kind_err;
+
+#[derive(Eq, PartialEq, PartialOrd, Ord, Hash, Debug, Clone)]
+pub enum Relation {
+    Parent,
+    Child,
+    Blocks,
+    BlockedBy,
+    References,
+    ReferencedBy,
+    DuplicateOf,
+    HasDuplicate,
+
+    /// A relation which is not known to imag. It is its own inverse.
+    Custom(String),
+}
+
+/// The names of the relations imag knows about
+pub const KNOWN_RELATIONS : &'static [&'static str] = &[
+    "parent",
+    "child",
+    "blocks",
+    "blocked-by",
+    "references",
+    "referenced-by",
+    "duplicate-of",
+    "has-duplicate",
+];
+
+impl Relation {
+
+    /// Get the relation the other end of a link carries
+    pub fn inverse(&self) -> Relation {
+        match *self {
+            Relation::Parent           => Relation::Child,
+            Relation::Child            => Relation::Parent,
+            Relation::Blocks           => Relation::BlockedBy,
+            Relation::BlockedBy        => Relation::Blocks,
+            Relation::References       => Relation::ReferencedBy,
+            Relation::ReferencedBy     => Relation::References,
+            Relation::DuplicateOf      => Relation::HasDuplicate,
+            Relation::HasDuplicate     => Relation::DuplicateOf,
+            Relation::Custom(ref name) => Relation::Custom(name.clone()),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match *self {
+            Relation::Parent           => "parent",
+            Relation::Child            => "child",
+            Relation::Blocks           => "blocks",
+            Relation::BlockedBy        => "blocked-by",
+            Relation::References       => "references",
+            Relation::ReferencedBy     => "referenced-by",
+            Relation::DuplicateOf      => "duplicate-of",
+            Relation::HasDuplicate     => "has-duplicate",
+            Relation::Custom(ref name) => name,
+        }
+    }
+
+}
+
+impl FromStr for Relation {
+    type Err = Error;
+
+    /// Parse a relation name
+    ///
+    /// Names which are not known are parsed as `Relation::Custom`. Fails if the name is empty or
+    /// contains whitespace.
+    fn from_str(s: &str) -> Result<Relation> {
+        match s {
+            "parent"        => Ok(Relation::Parent),
+            "child"         => Ok(Relation::Child),
+            "blocks"        => Ok(Relation::Blocks),
+            "blocked-by"    => Ok(Relation::BlockedBy),
+            "references"    => Ok(Relation::References),
+            "referenced-by" => Ok(Relation::ReferencedBy),
+            "duplicate-of"  => Ok(Relation::DuplicateOf),
+            "has-duplicate" => Ok(Relation::HasDuplicate),
+            ""              => Err(kind_err(ErrorKind::Parse, "Empty relation name")),
+            other           => if other.chars().any(char::is_whitespace) {
+                Err(kind_err(ErrorKind::Parse, format!("Invalid relation name: '{}'", other)))
+            } else {
+                Ok(Relation::Custom(String::from(other)))
+            },
+        }
+    }
+}
+
+impl Display for Relation {
+    fn fmt(&self, fmt: &mut Formatter) -> FmtResult {
+        write!(fmt, "{}", self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::Relation;
+    use super::KNOWN_RELATIONS;
+
+    #[test]
+    fn test_known_relations_roundtrip() {
+        for name in KNOWN_RELATIONS {
+            let rel = Relation::from_str(name).unwrap();
+            assert_eq!(*name, rel.as_str());
+            assert_eq!(rel, rel.inverse().inverse());
+            assert!(!is_match!(rel, Relation::Custom(_)));
+        }
+    }
+
+    #[test]
+    fn test_inverse() {
+        assert_eq!(Relation::Child, Relation::Parent.inverse());
+        assert_eq!(Relation::BlockedBy, Relation::Blocks.inverse());
+        assert_eq!(Relation::References, Relation::ReferencedBy.inverse());
+        assert_eq!(Relation::HasDuplicate, Relation::DuplicateOf.inverse());
+        assert_eq!(Relation::DuplicateOf, Relation::HasDuplicate.inverse());
+
+        let custom = Relation::from_str("inspired-by").unwrap();
+        assert_eq!(Relation::Custom(String::from("inspired-by")), custom);
+        assert_eq!(custom, custom.inverse());
+    }
+
+    #[test]
+    fn test_invalid_names() {
+        assert!(Relation::from_str("").is_err());
+        assert!(Relation::from_str("is part of").is_err());
+    }
+}
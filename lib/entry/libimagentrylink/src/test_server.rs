@@ -0,0 +1,77 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2019 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+//! A local HTTP stand-in for testing the code which fetches external links
+
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::io::Write;
+use std::net::TcpListener;
+use std::thread;
+
+/// A `200 OK` response with `body`
+pub fn ok(content_type: &str, body: &str) -> String {
+    format!("HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            content_type, body.len(), body)
+}
+
+/// A redirect to `location`
+pub fn redirect(location: &str) -> String {
+    format!("HTTP/1.1 301 Moved Permanently\r\nLocation: {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            location)
+}
+
+/// Start a server on localhost which answers requests for the paths in `routes` with the
+/// corresponding raw response and all other requests with 404, and return its base URL
+pub fn serve(routes: Vec<(&'static str, String)>) -> String {
+    let routes   = routes.into_iter().collect::<BTreeMap<_, _>>();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base     = format!("http://{}", listener.local_addr().unwrap());
+
+    let _ = thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = match stream {
+                Ok(s)  => s,
+                Err(_) => continue,
+            };
+
+            let mut request = Vec::new();
+            let mut buf     = [0; 1024];
+            while !String::from_utf8_lossy(&request).contains("\r\n\r\n") {
+                match stream.read(&mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n)          => request.extend_from_slice(&buf[..n]),
+                }
+            }
+
+            let request  = String::from_utf8_lossy(&request).into_owned();
+            let path     = request.split_whitespace().nth(1).unwrap_or("/").to_owned();
+            let response = routes
+                .get(path.as_str())
+                .cloned()
+                .unwrap_or_else(|| {
+                    String::from("HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+                });
+
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+
+    base
+}
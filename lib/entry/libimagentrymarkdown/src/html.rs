@@ -25,6 +25,10 @@ use failure::Fallible as Result;
 use failure::ResultExt;
 use failure::Error;
 use failure::err_msg;
+use regex::Captures;
+use regex::Regex;
+
+use outline::Anchors;
 
 pub type HTML = String;
 
@@ -39,6 +43,54 @@ pub fn to_html(buffer: &str) -> Result<HTML> {
         .map_err(Error::from)
 }
 
+/// Render markdown to HTML, with `id` attributes on the headings
+///
+/// The ids are the anchors from `libimagentrymarkdown::outline`, so links to a section of an entry
+/// (`wiki/foo#setup`) can be followed in the rendered HTML.
+pub fn to_html_with_anchors(buffer: &str) -> Result<HTML> {
+    let html        = to_html(buffer)?;
+    let mut anchors = Anchors::default();
+
+    let html = heading_regex().replace_all(&html, |caps: &Captures| {
+        let text = rendered_text(&caps[2]);
+        format!("<h{} id=\"{}\">{}</h{}>", &caps[1], anchors.unique(&text), &caps[2], &caps[3])
+    });
+
+    Ok(html.into_owned())
+}
+
+/// The level and the text of the headings in `html`, as rendered by `to_html()`
+///
+/// `libimagentrymarkdown::outline` takes its headings from here, so it sees exactly the headings
+/// `to_html_with_anchors()` sets ids on, including the ones in block quotes, lists and raw HTML.
+pub(crate) fn rendered_headings(html: &str) -> Vec<(usize, String)> {
+    heading_regex()
+        .captures_iter(html)
+        .map(|caps| (caps[1].parse().unwrap_or(1), rendered_text(&caps[2])))
+        .collect()
+}
+
+fn heading_regex() -> Regex {
+    Regex::new(r"(?s)<h([1-6])>(.*?)</h([1-6])>").unwrap() // constant, cannot fail
+}
+
+/// The text of rendered HTML: tags are removed, entities unescaped and whitespace collapsed
+///
+/// The anchors of `outline` are made from the text headings render to as well, so they are the
+/// same as the ids `to_html_with_anchors()` sets.
+pub(crate) fn rendered_text(html: &str) -> String {
+    let tag = Regex::new(r"<[^>]*>").unwrap(); // constant, cannot fail
+    tag.replace_all(html, "")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
 pub mod iter {
     use failure::Fallible as Result;
     use libimagstore::store::Entry;
@@ -101,3 +153,36 @@ pub mod iter {
     }
 
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_html_with_anchors() {
+        let html = to_html_with_anchors("# Setup & Configuration\n\n## Setup\n\n## Setup\n").unwrap();
+        assert!(html.contains(r#"<h1 id="setup--configuration">Setup &amp; Configuration</h1>"#), "{}", html);
+        assert!(html.contains(r#"<h2 id="setup">Setup</h2>"#), "{}", html);
+        assert!(html.contains(r#"<h2 id="setup-1">Setup</h2>"#), "{}", html);
+    }
+
+    #[test]
+    fn test_anchors_match_outline() {
+        use outline::Outline;
+
+        let content = "# _Setup_\n\n## **Bold** and `code`\n\n### [Link](wiki/foo) here\n\n> # Quoted\n\n* # Listed\n\n<h2>Raw</h2>\n\n# Quoted\n";
+        let html    = to_html_with_anchors(content).unwrap();
+        let outline = Outline::parse(content);
+
+        assert_eq!(html.matches("<h").count(), outline.headings.len());
+        assert!(outline.headings.len() >= 6, "{:?}", outline.headings);
+        for heading in outline.headings.iter() {
+            let id = format!("id=\"{}\"", heading.anchor);
+            assert!(html.contains(&id), "{} not in {}", id, html);
+        }
+        assert!(outline.has_anchor("setup"));
+        assert!(outline.has_anchor("quoted"));
+        assert!(outline.has_anchor("quoted-1"));
+    }
+
+}
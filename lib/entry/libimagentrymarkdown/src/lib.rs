@@ -38,6 +38,7 @@
 )]
 
 extern crate hoedown;
+extern crate regex;
 extern crate url;
 extern crate libimagstore;
 extern crate libimagerror;
@@ -53,5 +54,7 @@ extern crate env_logger;
 
 pub mod html;
 pub mod link;
+pub mod outline;
 pub mod processor;
+pub mod rewrite;
 
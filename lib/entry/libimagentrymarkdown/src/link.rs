@@ -20,13 +20,15 @@
 use failure::ResultExt;
 use failure::Fallible as Result;
 use failure::Error;
-use failure::err_msg;
 
 use hoedown::renderer::Render;
 use hoedown::Buffer;
 use hoedown::Markdown;
 use url::Url;
 
+use libimagerror::kind::ErrorKind;
+use libimagerror::kind::KindError;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Link {
     pub title: String,
@@ -39,7 +41,7 @@ impl Link {
     pub fn into_urllink(self) -> Result<UrlLink> {
         Url::parse(&self.link[..])
             .map(move |link| UrlLink { title: self.title, link: link, })
-            .context(err_msg("Link parsing error"))
+            .context(KindError::new(ErrorKind::Parse, "Link parsing error"))
             .map_err(Error::from)
     }
 
@@ -105,9 +107,20 @@ pub fn extract_links(buf: &str) -> Vec<Link> {
     le.links()
 }
 
+/// Split a link into its target and the anchor of a section in the target
+///
+/// `wiki/foo#setup` is split into `wiki/foo` and `setup`, `#setup` into an empty target and
+/// `setup`.
+pub fn split_anchor(link: &str) -> (&str, Option<&str>) {
+    match link.find('#') {
+        Some(pos) => (&link[..pos], Some(&link[pos + 1..])),
+        None      => (link, None),
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use super::{Link, extract_links};
+    use super::{Link, extract_links, split_anchor};
 
     #[test]
     fn test_one_link() {
@@ -164,5 +177,11 @@ Some more [foo](http://example.com/foo).
         assert_eq!(exp1, links.pop().unwrap());
     }
 
-}
+    #[test]
+    fn test_split_anchor() {
+        assert_eq!(("wiki/foo", Some("setup")), split_anchor("wiki/foo#setup"));
+        assert_eq!(("", Some("setup")), split_anchor("#setup"));
+        assert_eq!(("wiki/foo", None), split_anchor("wiki/foo"));
+    }
 
+}
@@ -0,0 +1,299 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2019 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+//! The outline of markdown content
+//!
+//! The outline of a document is the list of its headings, fenced code blocks and task list items
+//! (`- [ ] something to do`). Headings get an anchor, a slug of their text, which can be used to
+//! link to a section of an entry: `[setup](wiki/foo#setup)`.
+//!
+//! The headings are taken from the rendered document (`html::to_html()`), so the outline has
+//! exactly the headings `html::to_html_with_anchors()` sets ids on, wherever they are nested.
+
+use std::collections::HashMap;
+
+use regex::Regex;
+
+use html::rendered_headings;
+use html::to_html;
+
+/// A heading, as it is rendered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heading {
+    pub level: usize,
+    pub text: String,
+    pub anchor: String,
+}
+
+/// A fenced code block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeBlock {
+    pub language: Option<String>,
+    pub code: String,
+    pub line: usize,
+}
+
+/// A task list item, `- [ ] todo` or `- [x] done`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskItem {
+    pub done: bool,
+    pub text: String,
+    pub indent: usize,
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Outline {
+    pub headings: Vec<Heading>,
+    pub code_blocks: Vec<CodeBlock>,
+    pub tasks: Vec<TaskItem>,
+}
+
+impl Outline {
+
+    /// Parse the outline of markdown `content`
+    ///
+    /// Tasks inside fenced code blocks are ignored. Line numbers of tasks and code blocks start
+    /// at 1.
+    pub fn parse(content: &str) -> Outline {
+        let task_re = Regex::new(r"^(\s*)(?:[-*+]|\d+[.)])\s+\[([ xX])\]\s+(.*)$").unwrap(); // constant, cannot fail
+
+        let mut outline = Outline::default();
+        let mut anchors = Anchors::default();
+        let mut fence   = None;
+
+        if let Ok(html) = to_html(content) {
+            for (level, text) in rendered_headings(&html) {
+                outline.headings.push(Heading {
+                    level: level,
+                    anchor: anchors.unique(&text),
+                    text: text,
+                });
+            }
+        }
+
+        for (i, line) in content.lines().enumerate() {
+            let lineno = i + 1;
+
+            if let Some((marker, language, start, mut code)) = fence.take() {
+                if is_closing_fence(line, &marker) {
+                    outline.code_blocks.push(CodeBlock {
+                        language: language,
+                        code: code.join("\n"),
+                        line: start,
+                    });
+                } else {
+                    code.push(line);
+                    fence = Some((marker, language, start, code));
+                }
+                continue
+            }
+
+            if let Some((marker, info)) = opening_fence(line) {
+                let language = info.split_whitespace().next().map(String::from);
+                fence = Some((marker, language, lineno, vec![]));
+                continue
+            }
+
+            if let Some(caps) = task_re.captures(line) {
+                outline.tasks.push(TaskItem {
+                    done: &caps[2] != " ",
+                    text: String::from(caps[3].trim()),
+                    indent: caps[1].len(),
+                    line: lineno,
+                });
+            }
+        }
+
+        // An unclosed fence runs until the end of the document
+        if let Some((_, language, start, code)) = fence {
+            outline.code_blocks.push(CodeBlock {
+                language: language,
+                code: code.join("\n"),
+                line: start,
+            });
+        }
+
+        outline
+    }
+
+    /// Find the heading with the anchor `anchor`
+    pub fn find_anchor(&self, anchor: &str) -> Option<&Heading> {
+        self.headings.iter().find(|h| h.anchor == anchor)
+    }
+
+    /// Check whether there is a heading with the anchor `anchor`
+    pub fn has_anchor(&self, anchor: &str) -> bool {
+        self.find_anchor(anchor).is_some()
+    }
+
+}
+
+/// Make the anchor for a heading with the text `text`
+///
+/// The text is lowercased, everything but letters, digits, `-`, `_` and spaces is removed and
+/// spaces are replaced by `-`, so `Setup & Configuration` becomes `setup--configuration`. This is
+/// how many markdown renderers create anchors.
+pub fn slugify(text: &str) -> String {
+    text.trim()
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == '-' || *c == '_' || *c == ' ')
+        .map(|c| if c == ' ' { '-' } else { c })
+        .collect()
+}
+
+/// Makes anchors unique within a document by appending `-1`, `-2`, ... to repeated ones
+#[derive(Default)]
+pub(crate) struct Anchors(HashMap<String, usize>);
+
+impl Anchors {
+    pub(crate) fn unique(&mut self, text: &str) -> String {
+        let slug  = slugify(text);
+        let count = self.0.entry(slug.clone()).or_insert(0);
+        let anchor = if *count == 0 {
+            slug
+        } else {
+            format!("{}-{}", slug, count)
+        };
+        *count += 1;
+        anchor
+    }
+}
+
+/// Strip up to three spaces of indentation, which markdown allows for block elements
+fn strip_indent(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start_matches(' ');
+    if line.len() - trimmed.len() <= 3 {
+        Some(trimmed)
+    } else {
+        None
+    }
+}
+
+fn opening_fence(line: &str) -> Option<(String, &str)> {
+    let line = strip_indent(line)?;
+    let c    = line.chars().next()?;
+    if c != '`' && c != '~' {
+        return None
+    }
+
+    let len = line.chars().take_while(|x| *x == c).count();
+    if len < 3 {
+        return None
+    }
+
+    Some((line[..len].to_string(), line[len..].trim()))
+}
+
+fn is_closing_fence(line: &str, marker: &str) -> bool {
+    strip_indent(line)
+        .map(|l| l.trim_end())
+        .map(|l| l.starts_with(marker) && l.chars().all(|c| Some(c) == marker.chars().next()))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!("setup", slugify("Setup"));
+        assert_eq!("setup--configuration", slugify("Setup & Configuration"));
+        assert_eq!("what-is-imag", slugify(" What is imag? "));
+        assert_eq!("snake_case-works", slugify("snake_case works"));
+    }
+
+    #[test]
+    fn test_headings() {
+        let content = r#"# Title
+
+Some text
+
+## Setup ##
+
+Setup
+-----
+
+> # Quoted
+>
+> quoted text
+
+### [Linked](wiki/foo) `code`
+"#;
+
+        let outline = Outline::parse(content);
+        let headings = outline.headings
+            .iter()
+            .map(|h| (h.level, h.text.as_ref(), h.anchor.as_ref()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(vec![
+            (1, "Title", "title"),
+            (2, "Setup", "setup"),
+            (2, "Setup", "setup-1"),
+            (1, "Quoted", "quoted"),
+            (3, "Linked code", "linked-code"),
+        ], headings);
+
+        assert!(outline.has_anchor("setup-1"));
+        assert!(outline.has_anchor("quoted"));
+    }
+
+    #[test]
+    fn test_code_blocks_and_tasks() {
+        let content = r#"# Todo
+
+- [ ] Write tests
+- [x] Write code
+  * [X] nested
+1. [ ] numbered
+
+```rust
+- [ ] not a task
+```
+
+~~~
+unclosed
+"#;
+
+        let outline = Outline::parse(content);
+        assert_eq!(1, outline.headings.len());
+
+        let tasks = outline.tasks
+            .iter()
+            .map(|t| (t.done, t.text.as_ref(), t.indent, t.line))
+            .collect::<Vec<_>>();
+        assert_eq!(vec![
+            (false, "Write tests", 0, 3),
+            (true, "Write code", 0, 4),
+            (true, "nested", 2, 5),
+            (false, "numbered", 0, 6),
+        ], tasks);
+
+        assert_eq!(2, outline.code_blocks.len());
+        assert_eq!(Some(String::from("rust")), outline.code_blocks[0].language);
+        assert_eq!("- [ ] not a task", outline.code_blocks[0].code);
+        assert_eq!(8, outline.code_blocks[0].line);
+        assert_eq!(None, outline.code_blocks[1].language);
+        assert_eq!("unclosed", outline.code_blocks[1].code);
+    }
+
+}
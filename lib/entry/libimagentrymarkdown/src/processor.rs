@@ -23,6 +23,8 @@ use failure::Fallible as Result;
 use failure::ResultExt;
 use failure::Error;
 use link::extract_links;
+use link::split_anchor;
+use outline::Outline;
 
 use libimagentrylink::external::ExternalLinker;
 use libimagentrylink::internal::InternalLinker;
@@ -33,6 +35,8 @@ use libimagstore::store::Entry;
 use libimagstore::store::Store;
 use libimagstore::storeid::StoreId;
 use libimagerror::errors::ErrorMsg;
+use libimagerror::kind::ErrorKind;
+use libimagerror::kind::kind_err;
 
 use std::path::PathBuf;
 
@@ -50,6 +54,8 @@ use url::Url;
 ///  * Process external links (from store entry to URL)
 ///  * Process refs (from store entry to files on the filesystem and outside of the store)
 ///  (default: false)
+///  * Validate anchors of internal links (`wiki/foo#setup`) against the headings of the target
+///  (default: false)
 ///
 ///  # Note
 ///
@@ -59,7 +65,8 @@ pub struct LinkProcessor {
     process_internal_links: bool,
     create_internal_targets: bool,
     process_external_links: bool,
-    process_refs: bool
+    process_refs: bool,
+    validate_anchors: bool,
 }
 
 impl LinkProcessor {
@@ -99,6 +106,16 @@ impl LinkProcessor {
         self
     }
 
+    /// Switch anchor validation on/off
+    ///
+    /// An internal link may point to a section of the target, `wiki/foo#setup`, or to a section of
+    /// the processed entry itself, `#setup`. With validation switched on, `process()` fails if the
+    /// target has no heading with that anchor (see `libimagentrymarkdown::outline`).
+    pub fn validate_anchors(mut self, b: bool) -> Self {
+        self.validate_anchors = b;
+        self
+    }
+
     /// Process an Entry for its links
     ///
     ///
@@ -143,14 +160,27 @@ impl LinkProcessor {
                         continue
                     }
 
-                    let id         = StoreId::new(PathBuf::from(&link.link))?;
+                    let (path, anchor) = split_anchor(&link.link);
+                    if path.is_empty() {
+                        // a link to a section of this entry
+                        if let (true, Some(anchor)) = (self.validate_anchors, anchor) {
+                            check_anchor(entry, anchor)?;
+                        }
+                        continue
+                    }
+
+                    let id         = StoreId::new(PathBuf::from(path))?;
                     let mut target = if self.create_internal_targets {
                         store.retrieve(id)?
                     } else {
                         store.get(id.clone())?
-                            .ok_or_else(|| Error::from(format_err!("Store get error: {}", id)))?
+                            .ok_or_else(|| kind_err(ErrorKind::NotFound, format!("Store get error: {}", id)))?
                     };
 
+                    if let (true, Some(anchor)) = (self.validate_anchors, anchor) {
+                        check_anchor(&target, anchor)?;
+                    }
+
                     let _ = entry.add_internal_link(&mut target)?;
                 },
                 LinkQualification::ExternalLink(url) => {
@@ -228,6 +258,15 @@ impl LinkProcessor {
 
 }
 
+fn check_anchor(entry: &Entry, anchor: &str) -> Result<()> {
+    if Outline::parse(entry.get_content()).has_anchor(anchor) {
+        Ok(())
+    } else {
+        let msg = format!("Anchor '{}' not found in {}", anchor, entry.get_location());
+        Err(kind_err(ErrorKind::NotFound, msg))
+    }
+}
+
 /// Enum to tell what kind of link a string of text is
 enum LinkQualification {
     InternalLink,
@@ -274,7 +313,8 @@ impl Default for LinkProcessor {
             process_internal_links: true,
             create_internal_targets: false,
             process_external_links: true,
-            process_refs: false
+            process_refs: false,
+            validate_anchors: false,
         }
     }
 }
@@ -614,5 +654,48 @@ mod tests {
         assert_eq!(2, store.entries().unwrap().collect::<Vec<_>>().len());
     }
 
-}
+    #[test]
+    fn test_process_section_link_with_anchor_validation() {
+        setup_logging();
+        let store = get_store();
+
+        let mut base = store.create(PathBuf::from("test-3.1")).unwrap();
+        *base.get_content_mut() = format!("# Intro\n\nSee [setup](test-3.2#setup) and [intro](#intro)");
+
+        let mut target = store.create(PathBuf::from("test-3.2")).unwrap();
+        *target.get_content_mut() = format!("# Setup\n\nSome text");
+        let _ = store.update(&mut target).unwrap();
+        drop(target);
+
+        let processor = LinkProcessor::default()
+            .process_external_links(false)
+            .validate_anchors(true);
+
+        let result = processor.process(&mut base, &store);
+        assert!(result.is_ok(), "Should be Ok(()): {:?}", result);
+
+        let base_links : Vec<_> = base.get_internal_links().unwrap().collect();
+        assert_eq!(1, base_links.len());
+        assert_eq!("test-3.2", base_links[0].to_str().unwrap());
+    }
+
+    #[test]
+    fn test_process_section_link_with_missing_anchor() {
+        setup_logging();
+        let store = get_store();
 
+        let mut base = store.create(PathBuf::from("test-3.1")).unwrap();
+        *base.get_content_mut() = format!("See [setup](test-3.2#setup)");
+
+        let _ = store.create(PathBuf::from("test-3.2")).unwrap();
+
+        let processor = LinkProcessor::default().validate_anchors(false);
+        let result = processor.process(&mut base, &store);
+        assert!(result.is_ok(), "Should be Ok(()): {:?}", result);
+
+        let processor = LinkProcessor::default().validate_anchors(true);
+        let result = processor.process(&mut base, &store);
+        assert!(result.is_err(), "Should be Err(_), but is Ok(())");
+    }
+
+}
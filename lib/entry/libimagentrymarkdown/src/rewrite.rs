@@ -0,0 +1,378 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2019 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+//! Moving entries without breaking the markdown links pointing to them
+//!
+//! `LinkProcessor` turns `[text](path)` links in the content of an entry into internal links.
+//! `Store::move_by_id()` does not know about these links, so after a plain move the header links
+//! and the markdown links of other entries still point to the old id. The functions in this module
+//! move entries, re-create their header links and rewrite the markdown link targets in the content
+//! of all linked entries.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use failure::Fallible as Result;
+use failure::Error;
+use regex::Captures;
+use regex::Regex;
+use url::Url;
+
+use link::split_anchor;
+
+use libimagentrylink::internal::InternalLinker;
+use libimagentrylink::internal::Link;
+use libimagerror::kind::ErrorKind;
+use libimagerror::kind::kind_err;
+use libimagstore::store::Entry;
+use libimagstore::store::FileLockEntry;
+use libimagstore::store::Store;
+use libimagstore::storeid::StoreId;
+
+/// What a move did
+#[derive(Debug, Default)]
+pub struct MoveReport {
+    /// The moved entries, as `(old id, new id)`
+    pub moved: Vec<(StoreId, StoreId)>,
+
+    /// The entries whose content was rewritten, by their id after the move
+    pub rewritten: Vec<StoreId>,
+}
+
+/// Rewrite the markdown links in `text` which point to `from`, so that they point to `to`
+///
+/// Inline links (`[text](target "title")`) and link reference definitions (`[ref]: target`) are
+/// rewritten, the link text and title are kept. Links to a section of `from` (`from#anchor`) keep
+/// their anchor. Links inside fenced code blocks are not touched.
+///
+/// Returns `None` if no link in the text points to `from`.
+pub fn rewrite_link_targets(text: &str, from: &StoreId, to: &StoreId) -> Result<Option<String>> {
+    let new_target = to.to_str()?;
+    let inline     = Regex::new(r#"(\]\(\s*)([^()\s]+)((?:\s+(?:"[^"]*"|'[^']*'|\([^)]*\)))?\s*\))"#)?;
+    let reference  = Regex::new(r#"^( {0,3}\[[^\]]+\]:[ \t]*)(\S+)(.*)$"#)?;
+
+    let mut changed  = false;
+    let mut in_fence = false;
+
+    let lines = text
+        .split('\n')
+        .map(|line| {
+            let trimmed = line.trim();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                in_fence = !in_fence;
+                return String::from(line)
+            }
+
+            if in_fence {
+                return String::from(line)
+            }
+
+            let mut rewrite = |caps: &Captures| match split_anchor(&caps[2]) {
+                (target, anchor) if points_to(target, from) => {
+                    changed = true;
+                    let anchor = anchor.map(|a| format!("#{}", a)).unwrap_or_default();
+                    format!("{}{}{}{}", &caps[1], new_target, anchor, &caps[3])
+                },
+                _ => String::from(&caps[0]),
+            };
+
+            let line = inline.replace_all(line, |caps: &Captures| rewrite(caps)).into_owned();
+            reference.replace(&line, |caps: &Captures| rewrite(caps)).into_owned()
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    Ok(if changed { Some(lines) } else { None })
+}
+
+/// Check whether a link target is an internal link to `id`, the same way `LinkProcessor` decides
+/// whether a link is internal
+fn points_to(target: &str, id: &StoreId) -> bool {
+    match Url::parse(target) {
+        Err(::url::ParseError::RelativeUrlWithoutBase) => StoreId::new(PathBuf::from(target))
+            .map(|target| target == *id)
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
+/// Rewrite the markdown links in the content of `entry` which point to `from`
+///
+/// Returns whether the content was changed.
+pub fn rewrite_entry_links(entry: &mut Entry, from: &StoreId, to: &StoreId) -> Result<bool> {
+    match rewrite_link_targets(entry.get_content(), from, to)? {
+        Some(content) => {
+            *entry.get_content_mut() = content;
+            Ok(true)
+        },
+        None => Ok(false),
+    }
+}
+
+/// Move the entry `from` to `to`
+///
+/// The links of the entry are removed before the move and re-created afterwards, with their
+/// annotations and relations. Then the markdown links pointing to `from` are rewritten in the
+/// content of all linked entries and of the moved entry itself.
+///
+/// If the move fails, the links are re-created for `from` and the error is returned.
+pub fn move_entry(store: &Store, from: StoreId, to: StoreId) -> Result<MoveReport> {
+    let links = get_existing(store, &from)?
+        .get_internal_links()?
+        .collect::<Vec<Link>>();
+
+    let mut linked = HashMap::new();
+    for link in links.iter() {
+        let id = link.get_store_id();
+        if !linked.contains_key(id) {
+            let entry = store.get(id.clone())?
+                .ok_or_else(|| kind_err(ErrorKind::NotFound, format!("Linked entry does not exist: {}", id)))?;
+            linked.insert(id.clone(), entry);
+        }
+    }
+
+    {
+        let mut entry = get_existing(store, &from)?;
+        for target in linked.values_mut() {
+            let _ = entry.remove_internal_link(target)?;
+        }
+    }
+
+    if let Err(e) = store.move_by_id(from.clone(), to.clone()) {
+        debug!("Re-adding links to {} because moving failed", from);
+        let _ = relink(store, &from, &links, &mut linked)?;
+        return Err(e)
+    }
+
+    let _ = relink(store, &to, &links, &mut linked)?;
+
+    let mut rewritten = vec![];
+    for (id, entry) in linked.iter_mut() {
+        if rewrite_entry_links(entry, &from, &to)? {
+            debug!("Rewrote links to {} in {}", to, id);
+            rewritten.push(id.clone());
+        }
+    }
+
+    if rewrite_entry_links(&mut get_existing(store, &to)?, &from, &to)? {
+        rewritten.push(to.clone());
+    }
+
+    rewritten.sort();
+    Ok(MoveReport { moved: vec![(from, to)], rewritten: rewritten })
+}
+
+/// Move all entries in the collection `from` to the collection `to`
+///
+/// A collection is a directory in the store, so moving `notes/old` to `notes/new` moves
+/// `notes/old/a` to `notes/new/a` and `notes/old/b/c` to `notes/new/b/c`. Each entry is moved with
+/// `move_entry()`.
+pub fn move_collection(store: &Store, from: &StoreId, to: &StoreId) -> Result<MoveReport> {
+    let ids = store
+        .entries()?
+        .into_storeid_iter()
+        .filter(|id| id.as_ref().map(|id| id.local().starts_with(from.local())).unwrap_or(true))
+        .collect::<Result<Vec<StoreId>>>()?;
+
+    if ids.is_empty() {
+        return Err(kind_err(ErrorKind::NotFound, format!("No entries in collection: {}", from)))
+    }
+
+    let mut report = MoveReport::default();
+    for id in ids {
+        let rest   = id.local().strip_prefix(from.local()).map_err(Error::from)?;
+        let new_id = StoreId::new(to.local().join(rest))?;
+        let moved  = move_entry(store, id, new_id)?;
+
+        report.moved.extend(moved.moved);
+        report.rewritten.extend(moved.rewritten);
+    }
+
+    // entries which were rewritten before they were moved themselves are reported by their new id
+    let rewritten = report.rewritten
+        .drain(..)
+        .map(|id| report.moved
+             .iter()
+             .find(|&&(ref old, _)| *old == id)
+             .map(|&(_, ref new)| new.clone())
+             .unwrap_or(id))
+        .collect::<Vec<StoreId>>();
+
+    report.rewritten = rewritten;
+    report.rewritten.sort();
+    report.rewritten.dedup();
+    Ok(report)
+}
+
+fn relink<'a>(store: &'a Store,
+              id: &StoreId,
+              links: &[Link],
+              linked: &mut HashMap<StoreId, FileLockEntry<'a>>)
+    -> Result<()>
+{
+    let mut entry = get_existing(store, id)?;
+
+    for link in links {
+        let target = match linked.get_mut(link.get_store_id()) {
+            Some(target) => target,
+            None         => continue,
+        };
+
+        let _ = match *link {
+            Link::Id { .. }                       => entry.add_internal_link(target)?,
+            Link::Annotated { ref annotation, .. } => entry.add_internal_annotated_link(target, annotation.clone())?,
+            Link::Related { ref relation, .. }     => entry.add_internal_related_link(target, relation.clone())?,
+        };
+    }
+
+    Ok(())
+}
+
+fn get_existing<'a>(store: &'a Store, id: &StoreId) -> Result<FileLockEntry<'a>> {
+    store.get(id.clone())?.ok_or_else(|| kind_err(ErrorKind::NotFound, format!("Entry does not exist: {}", id)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::path::PathBuf;
+
+    use libimagstore::store::Store;
+    use libimagentrylink::internal::InternalLinker;
+    use libimagentrylink::relation::Relation;
+
+    fn setup_logging() {
+        let _ = ::env_logger::try_init();
+    }
+
+    pub fn get_store() -> Store {
+        Store::new_inmemory(PathBuf::from("/"), &None).unwrap()
+    }
+
+    fn id(s: &str) -> StoreId {
+        StoreId::new(PathBuf::from(s)).unwrap()
+    }
+
+    #[test]
+    fn test_rewrite_link_targets() {
+        let text = r#"See [the old page](notes/old "Title") and [other](notes/other).
+[also the old page](notes/old) and [its setup](notes/old#setup)
+
+[ref]: notes/old
+
+```
+[in code](notes/old)
+```
+[external](https://example.com/notes/old)"#;
+
+        let exp = r#"See [the old page](notes/new "Title") and [other](notes/other).
+[also the old page](notes/new) and [its setup](notes/new#setup)
+
+[ref]: notes/new
+
+```
+[in code](notes/old)
+```
+[external](https://example.com/notes/old)"#;
+
+        let result = rewrite_link_targets(text, &id("notes/old"), &id("notes/new")).unwrap();
+        assert_eq!(Some(String::from(exp)), result);
+    }
+
+    #[test]
+    fn test_rewrite_link_targets_without_links() {
+        let text = "No [links](notes/other) to the moved entry\n";
+        let result = rewrite_link_targets(text, &id("notes/old"), &id("notes/new")).unwrap();
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_move_entry() {
+        setup_logging();
+        let store = get_store();
+
+        {
+            let mut old   = store.create(PathBuf::from("notes/old")).unwrap();
+            let mut a     = store.create(PathBuf::from("notes/a")).unwrap();
+            let mut b     = store.create(PathBuf::from("notes/b")).unwrap();
+            let mut other = store.create(PathBuf::from("notes/other")).unwrap();
+
+            *a.get_content_mut()     = String::from("Link to [the old page](notes/old)\n");
+            *other.get_content_mut() = String::from("Not linked: [the old page](notes/old)\n");
+            a.add_internal_link(&mut old).unwrap();
+            b.add_internal_related_link(&mut old, Relation::Parent).unwrap();
+        }
+
+        let report = move_entry(&store, id("notes/old"), id("notes/new")).unwrap();
+        assert_eq!(vec![(id("notes/old"), id("notes/new"))], report.moved);
+        assert_eq!(vec![id("notes/a")], report.rewritten);
+
+        assert!(store.get(id("notes/old")).unwrap().is_none());
+
+        let a = store.get(id("notes/a")).unwrap().unwrap();
+        assert_eq!("Link to [the old page](notes/new)\n", a.get_content());
+        let links = a.get_internal_links().unwrap().collect::<Vec<_>>();
+        assert_eq!(1, links.len());
+        assert_eq!(&id("notes/new"), links[0].get_store_id());
+
+        let b = store.get(id("notes/b")).unwrap().unwrap();
+        let links = b.get_internal_links().unwrap().collect::<Vec<_>>();
+        assert_eq!(1, links.len());
+        assert_eq!(&id("notes/new"), links[0].get_store_id());
+        assert_eq!(Some(&Relation::Parent), links[0].relation());
+
+        let other = store.get(id("notes/other")).unwrap().unwrap();
+        assert_eq!("Not linked: [the old page](notes/old)\n", other.get_content());
+    }
+
+    #[test]
+    fn test_move_collection() {
+        setup_logging();
+        let store = get_store();
+
+        {
+            let mut a     = store.create(PathBuf::from("wiki/old/a")).unwrap();
+            let mut b     = store.create(PathBuf::from("wiki/old/b")).unwrap();
+            let mut index = store.create(PathBuf::from("wiki/index")).unwrap();
+
+            *a.get_content_mut()     = String::from("[b](wiki/old/b)");
+            *index.get_content_mut() = String::from("[a](wiki/old/a) [b](wiki/old/b)");
+            a.add_internal_link(&mut b).unwrap();
+            index.add_internal_link(&mut a).unwrap();
+            index.add_internal_link(&mut b).unwrap();
+        }
+
+        let report = move_collection(&store, &id("wiki/old"), &id("wiki/new")).unwrap();
+        assert_eq!(2, report.moved.len());
+        assert_eq!(vec![id("wiki/index"), id("wiki/new/a")], report.rewritten);
+
+        let a = store.get(id("wiki/new/a")).unwrap().unwrap();
+        assert_eq!("[b](wiki/new/b)", a.get_content());
+
+        let index = store.get(id("wiki/index")).unwrap().unwrap();
+        assert_eq!("[a](wiki/new/a) [b](wiki/new/b)", index.get_content());
+
+        let b = store.get(id("wiki/new/b")).unwrap().unwrap();
+        let mut links = b.get_internal_links().unwrap().map(|l| l.get_store_id().clone()).collect::<Vec<_>>();
+        links.sort();
+        assert_eq!(vec![id("wiki/index"), id("wiki/new/a")], links);
+    }
+
+}
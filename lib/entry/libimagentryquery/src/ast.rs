@@ -0,0 +1,108 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2019 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+//! The syntax tree of a query
+
+/// A query expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    Or(Box<Expr>, Box<Expr>),
+    Xor(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Predicate(Predicate),
+}
+
+/// A predicate on a single entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Predicate {
+    /// Compare a header value (or the result of a function on it)
+    Header(HeaderComparison),
+
+    /// The entry has the tag
+    Tag(String),
+
+    /// The entry is in the category
+    Category(String),
+
+    /// The entry is in the collection
+    Collection(String),
+
+    /// The entry is linked to the entry with this id
+    Linked(String),
+
+    /// The content of the entry matches the regex
+    Content(String),
+
+    /// Compare the date at a header location (`datetime.value` by default)
+    Date(DateComparison),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeaderComparison {
+    pub function : Option<Function>,
+    pub selector : String,
+    pub op       : CompareOp,
+    pub value    : CompareValue,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DateComparison {
+    pub selector : String,
+    pub op       : CompareOp,
+    pub value    : CompareValue,
+}
+
+/// Functions which can be applied to header values before comparing them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Function {
+    Length,
+    Keys,
+    Values,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Is,
+    In,
+    Eq,
+    Neq,
+    Gte,
+    Lte,
+    Lt,
+    Gt,
+    Any,
+    All,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Boolean(bool),
+    Integer(i64),
+    String(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompareValue {
+    Value(Value),
+    Values(Vec<Value>),
+
+    /// A range, the start is included, the end is not
+    Range(Value, Value),
+}
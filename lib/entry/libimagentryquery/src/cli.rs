@@ -0,0 +1,61 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2019 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+//! Helpers for commandline interfaces which accept queries
+
+use clap::{Arg, ArgMatches};
+
+use failure::Fallible as Result;
+use filters::failable::filter::FailableFilter;
+
+use libimagstore::store::Entry;
+
+use filter::Query;
+
+/// The name of the argument which holds the query
+pub const QUERY_ARG_NAME : &'static str = "where";
+
+/// An argument `--where <QUERY>` for filtering entries
+pub fn query_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(QUERY_ARG_NAME)
+        .long("where")
+        .takes_value(true)
+        .required(false)
+        .multiple(false)
+        .value_name("QUERY")
+        .help("Only use entries which match the query. See 'imag ids --help' for the query language")
+}
+
+/// Get the query passed via `query_arg()`, if any
+pub fn query_from_matches(matches: &ArgMatches) -> Result<Option<Query>> {
+    match matches.value_of(QUERY_ARG_NAME) {
+        Some(q) => Query::parse(q).map(Some),
+        None    => Ok(None),
+    }
+}
+
+/// Check whether an entry matches the query, if there is one
+///
+/// Without a query, all entries match.
+pub fn entry_matches(query: Option<&Query>, entry: &Entry) -> Result<bool> {
+    match query {
+        Some(q) => q.filter(entry),
+        None    => Ok(true),
+    }
+}
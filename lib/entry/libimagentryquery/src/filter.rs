@@ -0,0 +1,551 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2019 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+//! Compiling a query into a filter for entries
+
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use chrono::NaiveDate;
+use chrono::NaiveDateTime;
+use filters::failable::filter::FailableFilter;
+use filters::filter::Filter;
+use toml::Value as TVal;
+use toml_query::read::TomlValueReadExt;
+use failure::Fallible as Result;
+use failure::Error;
+
+use libimagstore::store::Entry;
+use libimagstore::storeid::StoreId;
+use libimagerror::kind::ErrorKind;
+use libimagerror::kind::kind_err;
+use libimagentryfilter::builtin::content::grep::ContentGrep;
+use libimagentryfilter::builtin::header::field_eq::FieldEq;
+use libimagentryfilter::tags::HasTag;
+use libimagentrytag::tag::is_tag_str;
+use libimagentrycategory::entry::EntryCategory;
+use libimagentrylink::internal::InternalLinker;
+
+use ast::*;
+use parser::parse;
+
+/// A query, compiled into a filter for entries
+///
+/// ```ignore
+/// let query = Query::parse("tag(work) and imag.version == \"0.10.0\"")?;
+/// if query.filter(&entry)? { ... }
+/// ```
+pub struct Query(Node);
+
+enum Node {
+    Or(Box<Node>, Box<Node>),
+    Xor(Box<Node>, Box<Node>),
+    And(Box<Node>, Box<Node>),
+    Not(Box<Node>),
+    Leaf(Box<FailableFilter<Entry, Error = Error>>),
+}
+
+impl Node {
+    fn eval(&self, e: &Entry) -> Result<bool> {
+        match *self {
+            Node::Or(ref a, ref b)  => Ok(a.eval(e)? || b.eval(e)?),
+            Node::Xor(ref a, ref b) => Ok(a.eval(e)? != b.eval(e)?),
+            Node::And(ref a, ref b) => Ok(a.eval(e)? && b.eval(e)?),
+            Node::Not(ref a)        => a.eval(e).map(|b| !b),
+            Node::Leaf(ref f)       => f.filter(e),
+        }
+    }
+}
+
+impl Query {
+
+    /// Parse and compile a query
+    pub fn parse(query: &str) -> Result<Query> {
+        Query::from_expr(parse(query)?)
+    }
+
+    /// Compile a syntax tree into a query
+    ///
+    /// Fails if the expression contains comparisons which cannot work, like a comparison of a
+    /// header value with a list via `==`, or invalid tags, regexes or dates.
+    pub fn from_expr(expr: Expr) -> Result<Query> {
+        compile(expr).map(Query)
+    }
+
+}
+
+impl FromStr for Query {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Query> {
+        Query::parse(s)
+    }
+}
+
+impl FailableFilter<Entry> for Query {
+    type Error = Error;
+
+    fn filter(&self, e: &Entry) -> Result<bool> {
+        self.0.eval(e)
+    }
+}
+
+fn compile(expr: Expr) -> Result<Node> {
+    let bx = |e: Expr| compile(e).map(Box::new);
+
+    match expr {
+        Expr::Or(a, b)     => Ok(Node::Or(bx(*a)?, bx(*b)?)),
+        Expr::Xor(a, b)    => Ok(Node::Xor(bx(*a)?, bx(*b)?)),
+        Expr::And(a, b)    => Ok(Node::And(bx(*a)?, bx(*b)?)),
+        Expr::Not(a)       => Ok(Node::Not(bx(*a)?)),
+        Expr::Predicate(p) => compile_predicate(p).map(Node::Leaf),
+    }
+}
+
+fn invalid(msg: String) -> Error {
+    kind_err(ErrorKind::Parse, msg)
+}
+
+fn compile_predicate(p: Predicate) -> Result<Box<FailableFilter<Entry, Error = Error>>> {
+    match p {
+        Predicate::Tag(tag) => {
+            let _ = is_tag_str(&tag).map_err(|e| invalid(format!("Invalid tag '{}': {}", tag, e)))?;
+            Ok(Box::new(Infallible(HasTag::new(tag))))
+        },
+
+        Predicate::Content(regex) => {
+            let grep = ContentGrep::new(regex.as_ref())
+                .map_err(|e| invalid(format!("Invalid regex '{}': {}", regex, e)))?;
+            Ok(Box::new(Infallible(grep)))
+        },
+
+        Predicate::Category(name) => Ok(Box::new(InCategory(name))),
+
+        Predicate::Collection(coll) => {
+            let parts = coll
+                .split('/')
+                .filter(|s| !s.is_empty())
+                .map(String::from)
+                .collect::<Vec<String>>();
+
+            if parts.is_empty() {
+                return Err(invalid(String::from("Empty collection name")))
+            }
+
+            Ok(Box::new(Infallible(InCollection(parts))))
+        },
+
+        Predicate::Linked(id) => {
+            let id = StoreId::new(PathBuf::from(id))?;
+            Ok(Box::new(LinkedTo(id)))
+        },
+
+        Predicate::Header(cmp) => {
+            let _ = validate_comparison(&cmp.selector, cmp.op, &cmp.value)?;
+
+            match (cmp.function, cmp.op, cmp.value) {
+                (None, CompareOp::Eq, CompareValue::Value(v)) => {
+                    Ok(Box::new(FieldEq::new(cmp.selector, to_toml(v))))
+                },
+                (function, op, value) => Ok(Box::new(HeaderFilter {
+                    function,
+                    selector: cmp.selector,
+                    op,
+                    value,
+                })),
+            }
+        },
+
+        Predicate::Date(cmp) => {
+            match cmp.op {
+                CompareOp::Is | CompareOp::Any | CompareOp::All => {
+                    return Err(invalid(String::from("'is', 'any' and 'all' cannot be used with dates")))
+                },
+                _ => {},
+            }
+            let _ = validate_comparison(&cmp.selector, cmp.op, &cmp.value)?;
+
+            let to_date = |v: Value| match v {
+                Value::String(s) => parse_date(&s)
+                    .ok_or_else(|| invalid(format!("Invalid date '{}', expected 'YYYY-MM-DD' or 'YYYY-MM-DDTHH:MM:SS'", s))),
+                other => Err(invalid(format!("Expected a date string, found {:?}", other))),
+            };
+
+            let value = match cmp.value {
+                CompareValue::Value(v)     => DateValue::Date(to_date(v)?),
+                CompareValue::Values(vs)   => DateValue::Dates(vs.into_iter().map(|v| to_date(v)).collect::<Result<_>>()?),
+                CompareValue::Range(a, b)  => DateValue::Range(to_date(a)?, to_date(b)?),
+            };
+
+            Ok(Box::new(DateFilter { selector: cmp.selector, op: cmp.op, value }))
+        },
+    }
+}
+
+/// Check whether a comparison operator can be used with a value
+fn validate_comparison(selector: &str, op: CompareOp, value: &CompareValue) -> Result<()> {
+    match (op, value) {
+        (CompareOp::In, _)                    => Ok(()),
+        (_, &CompareValue::Value(_))          => Ok(()),
+        (CompareOp::Any, &CompareValue::Values(_)) |
+        (CompareOp::All, &CompareValue::Values(_)) => Ok(()),
+        (_, &CompareValue::Values(_))         => {
+            Err(invalid(format!("At '{}': A list can only be used with 'in', 'any' or 'all'", selector)))
+        },
+        (_, &CompareValue::Range(_, _))       => {
+            Err(invalid(format!("At '{}': A range can only be used with 'in'", selector)))
+        },
+    }
+}
+
+fn to_toml(v: Value) -> TVal {
+    match v {
+        Value::Boolean(b) => TVal::Boolean(b),
+        Value::Integer(i) => TVal::Integer(i),
+        Value::String(s)  => TVal::String(s),
+    }
+}
+
+/// Parse a date, either with or without time
+pub fn parse_date(s: &str) -> Option<NaiveDateTime> {
+    s.parse::<NaiveDateTime>()
+        .ok()
+        .or_else(|| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").ok())
+        .or_else(|| NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M").ok())
+        .or_else(|| NaiveDate::parse_from_str(s, "%Y-%m-%d").ok().map(|d| d.and_hms(0, 0, 0)))
+}
+
+/// Helper for using a `Filter` where a `FailableFilter` is needed
+struct Infallible<F>(F);
+
+impl<F: Filter<Entry>> FailableFilter<Entry> for Infallible<F> {
+    type Error = Error;
+
+    fn filter(&self, e: &Entry) -> Result<bool> {
+        Ok(Filter::filter(&self.0, e))
+    }
+}
+
+struct InCategory(String);
+
+impl FailableFilter<Entry> for InCategory {
+    type Error = Error;
+
+    fn filter(&self, e: &Entry) -> Result<bool> {
+        if e.has_category()? {
+            e.get_category().map(|c| c == self.0)
+        } else {
+            Ok(false)
+        }
+    }
+}
+
+struct InCollection(Vec<String>);
+
+impl Filter<Entry> for InCollection {
+    fn filter(&self, e: &Entry) -> bool {
+        e.get_location().is_in_collection(&self.0)
+    }
+}
+
+struct LinkedTo(StoreId);
+
+impl FailableFilter<Entry> for LinkedTo {
+    type Error = Error;
+
+    fn filter(&self, e: &Entry) -> Result<bool> {
+        Ok(e.get_internal_links()?.any(|link| *link.get_store_id() == self.0))
+    }
+}
+
+/// Comparison of a header value, with an optional function applied before comparing
+struct HeaderFilter {
+    function: Option<Function>,
+    selector: String,
+    op: CompareOp,
+    value: CompareValue,
+}
+
+impl FailableFilter<Entry> for HeaderFilter {
+    type Error = Error;
+
+    fn filter(&self, e: &Entry) -> Result<bool> {
+        trace!("Filtering {} at {}", e.get_location(), self.selector);
+
+        let value = match e.get_header().read(&self.selector)? {
+            Some(v) => v,
+            None    => return Ok(false),
+        };
+
+        let computed;
+        let value = match self.function {
+            None                   => value,
+            Some(Function::Length) => {
+                computed = TVal::Integer(match *value {
+                    TVal::Array(ref a)  => a.len() as i64,
+                    TVal::String(ref s) => s.len() as i64,
+                    TVal::Table(ref t)  => t.len() as i64,
+                    _                   => 1,
+                });
+                &computed
+            },
+            Some(Function::Keys) => match *value {
+                TVal::Table(ref t) => {
+                    computed = TVal::Array(t.keys().cloned().map(TVal::String).collect());
+                    &computed
+                },
+                _ => return Ok(false),
+            },
+            Some(Function::Values) => match *value {
+                TVal::Table(ref t) => {
+                    computed = TVal::Array(t.values().cloned().collect());
+                    &computed
+                },
+                _ => return Ok(false),
+            },
+        };
+
+        Ok(compare(self.op, &self.value, value))
+    }
+}
+
+fn scalar_eq(v: &Value, t: &TVal) -> bool {
+    match (v, t) {
+        (&Value::Boolean(i), &TVal::Boolean(j))          => i == j,
+        (&Value::Integer(i), &TVal::Integer(j))          => i == j,
+        (&Value::String(ref s), &TVal::String(ref b))    => s == b,
+        _                                                => false,
+    }
+}
+
+/// Check whether the header array `t` contains `v`, or whether the scalar `t` equals `v`
+fn contains(t: &TVal, v: &Value) -> bool {
+    match *t {
+        TVal::Array(ref a) => a.iter().any(|e| scalar_eq(v, e)),
+        _                  => scalar_eq(v, t),
+    }
+}
+
+/// Compare the header value `t` with `value`, as in `t <op> value`
+fn compare(op: CompareOp, value: &CompareValue, t: &TVal) -> bool {
+    use self::CompareValue as CV;
+
+    match (op, value) {
+        (CompareOp::Is, &CV::Value(ref v)) => match *v {
+            Value::Boolean(_) => is_match!(*t, TVal::Boolean(_)),
+            Value::Integer(_) => is_match!(*t, TVal::Integer(_)),
+            Value::String(_)  => is_match!(*t, TVal::String(_)),
+        },
+
+        (CompareOp::In, &CV::Value(ref v)) => match (v, t) {
+            (_, &TVal::Array(ref a))                        => a.iter().any(|e| scalar_eq(v, e)),
+            (&Value::String(ref s), &TVal::String(ref b))   => s.contains(b.as_str()),
+            _                                               => scalar_eq(v, t),
+        },
+        (CompareOp::In, &CV::Values(ref vs)) => vs.iter().any(|v| scalar_eq(v, t)),
+        (CompareOp::In, &CV::Range(ref a, ref b)) => match (a, b, t) {
+            (&Value::Integer(a), &Value::Integer(b), &TVal::Integer(i)) => a <= i && i < b,
+            (&Value::String(ref a), &Value::String(ref b), &TVal::String(ref s)) => a <= s && s < b,
+            _ => false,
+        },
+
+        (CompareOp::Any, &CV::Value(ref v)) |
+        (CompareOp::All, &CV::Value(ref v)) => contains(t, v),
+        (CompareOp::Any, &CV::Values(ref vs)) => vs.iter().any(|v| contains(t, v)),
+        (CompareOp::All, &CV::Values(ref vs)) => vs.iter().all(|v| contains(t, v)),
+
+        (CompareOp::Eq, &CV::Value(ref v))  => scalar_eq(v, t),
+        (CompareOp::Neq, &CV::Value(ref v)) => match (v, t) {
+            (&Value::Boolean(_), &TVal::Boolean(_)) |
+            (&Value::Integer(_), &TVal::Integer(_)) |
+            (&Value::String(_), &TVal::String(_))   => !scalar_eq(v, t),
+            _                                       => false,
+        },
+
+        (CompareOp::Gte, &CV::Value(Value::Integer(i))) => as_int(t).map(|j| j >= i).unwrap_or(false),
+        (CompareOp::Lte, &CV::Value(Value::Integer(i))) => as_int(t).map(|j| j <= i).unwrap_or(false),
+        (CompareOp::Lt,  &CV::Value(Value::Integer(i))) => as_int(t).map(|j| j < i).unwrap_or(false),
+        (CompareOp::Gt,  &CV::Value(Value::Integer(i))) => as_int(t).map(|j| j > i).unwrap_or(false),
+
+        (CompareOp::Gte, &CV::Value(Value::String(ref s))) => as_str(t).map(|j| j >= s.as_str()).unwrap_or(false),
+        (CompareOp::Lte, &CV::Value(Value::String(ref s))) => as_str(t).map(|j| j <= s.as_str()).unwrap_or(false),
+        (CompareOp::Lt,  &CV::Value(Value::String(ref s))) => as_str(t).map(|j| j < s.as_str()).unwrap_or(false),
+        (CompareOp::Gt,  &CV::Value(Value::String(ref s))) => as_str(t).map(|j| j > s.as_str()).unwrap_or(false),
+
+        // Lists and ranges with other operators are rejected when compiling
+        _ => false,
+    }
+}
+
+fn as_int(t: &TVal) -> Option<i64> {
+    match *t {
+        TVal::Integer(i) => Some(i),
+        _                => None,
+    }
+}
+
+fn as_str(t: &TVal) -> Option<&str> {
+    match *t {
+        TVal::String(ref s) => Some(s),
+        _                   => None,
+    }
+}
+
+enum DateValue {
+    Date(NaiveDateTime),
+    Dates(Vec<NaiveDateTime>),
+    Range(NaiveDateTime, NaiveDateTime),
+}
+
+/// Comparison of a date in the header
+struct DateFilter {
+    selector: String,
+    op: CompareOp,
+    value: DateValue,
+}
+
+impl FailableFilter<Entry> for DateFilter {
+    type Error = Error;
+
+    fn filter(&self, e: &Entry) -> Result<bool> {
+        let date = match e.get_header().read(&self.selector)? {
+            Some(&TVal::String(ref s)) => match parse_date(s) {
+                Some(d) => d,
+                None    => return Ok(false),
+            },
+            Some(&TVal::Datetime(ref d)) => match parse_date(&d.to_string()) {
+                Some(d) => d,
+                None    => return Ok(false),
+            },
+            _ => return Ok(false),
+        };
+
+        Ok(match (self.op, &self.value) {
+            (CompareOp::Eq,  &DateValue::Date(d))         => date == d,
+            (CompareOp::Neq, &DateValue::Date(d))         => date != d,
+            (CompareOp::Gte, &DateValue::Date(d))         => date >= d,
+            (CompareOp::Lte, &DateValue::Date(d))         => date <= d,
+            (CompareOp::Lt,  &DateValue::Date(d))         => date < d,
+            (CompareOp::Gt,  &DateValue::Date(d))         => date > d,
+            (CompareOp::In,  &DateValue::Date(d))         => date == d,
+            (CompareOp::In,  &DateValue::Dates(ref ds))   => ds.contains(&date),
+            (CompareOp::In,  &DateValue::Range(a, b))     => a <= date && date < b,
+            _                                             => false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use libimagstore::store::Store;
+
+    fn get_store() -> Store {
+        Store::new_inmemory(PathBuf::from("/"), &None).unwrap()
+    }
+
+    fn matches(store: &Store, id: &str, header: &str, query: &str) -> bool {
+        let mut entry = store.retrieve(PathBuf::from(id)).unwrap();
+        *entry.get_header_mut() = ::toml::de::from_str(header).unwrap();
+        Query::parse(query).unwrap().filter(&entry).unwrap()
+    }
+
+    #[test]
+    fn test_header_comparisons() {
+        let store  = get_store();
+        let header = "[a]\nnum = 5\nname = \"foo\"\nlist = [1, 2, 3]\n";
+
+        assert!(matches(&store, "test", header, "a.num == 5"));
+        assert!(matches(&store, "test", header, "a.num > 4"));
+        assert!(!matches(&store, "test", header, "a.num > 5"));
+        assert!(matches(&store, "test", header, "a.num in 1..6"));
+        assert!(!matches(&store, "test", header, "a.num in 1..5"));
+        assert!(matches(&store, "test", header, "a.num in [4, 5]"));
+        assert!(matches(&store, "test", header, "a.name is \"\""));
+        assert!(matches(&store, "test", header, "a.name != \"bar\""));
+        assert!(matches(&store, "test", header, "a.list in 2"));
+        assert!(matches(&store, "test", header, "length(a.list) == 3"));
+        assert!(matches(&store, "test", header, "keys(a) in \"num\""));
+        assert!(!matches(&store, "test", header, "a.missing == 1"));
+    }
+
+    #[test]
+    fn test_any_all() {
+        let store  = get_store();
+        let header = "[a]\nnum = 5\nlist = [1, 2, 3]\n";
+
+        assert!(matches(&store, "test", header, "a.list any [3, 4]"));
+        assert!(!matches(&store, "test", header, "a.list any [4, 5]"));
+        assert!(matches(&store, "test", header, "a.list all [1, 3]"));
+        assert!(!matches(&store, "test", header, "a.list all [1, 4]"));
+        assert!(matches(&store, "test", header, "a.list all 2"));
+        assert!(matches(&store, "test", header, "a.num any [4, 5]"));
+        assert!(!matches(&store, "test", header, "a.num all [4, 5]"));
+    }
+
+    #[test]
+    fn test_boolean_operators() {
+        let store  = get_store();
+        let header = "[a]\nnum = 5\n";
+
+        assert!(matches(&store, "test", header, "a.num == 1 or a.num == 5"));
+        assert!(!matches(&store, "test", header, "a.num == 1 and a.num == 5"));
+        assert!(matches(&store, "test", header, "not a.num == 1"));
+        assert!(!matches(&store, "test", header, "a.num == 5 xor a.num > 1"));
+        assert!(matches(&store, "test", header, "a.num == 5 and_not a.num == 1"));
+        assert!(matches(&store, "test", header, "a.num == 1 or_not a.num == 1"));
+        assert!(!matches(&store, "test", header, "a.num == 1 or_not a.num == 5"));
+    }
+
+    #[test]
+    fn test_collection_and_content() {
+        let store = get_store();
+        {
+            let mut entry = store.retrieve(PathBuf::from("notes/foo")).unwrap();
+            *entry.get_content_mut() = String::from("some text");
+        }
+        let entry = store.get(PathBuf::from("notes/foo")).unwrap().unwrap();
+
+        assert!(Query::parse("collection(notes)").unwrap().filter(&entry).unwrap());
+        assert!(!Query::parse("collection(diary)").unwrap().filter(&entry).unwrap());
+        assert!(Query::parse("content(\"so.e\")").unwrap().filter(&entry).unwrap());
+    }
+
+    #[test]
+    fn test_dates() {
+        let store  = get_store();
+        let header = "[datetime]\nvalue = \"2019-01-15T12:00:00\"\n";
+
+        assert!(matches(&store, "test", header, "date in \"2019-01-01\"..\"2019-02-01\""));
+        assert!(matches(&store, "test", header, "date > \"2019-01-15\""));
+        assert!(!matches(&store, "test", header, "date(other.date) > \"2019-01-15\""));
+    }
+
+    #[test]
+    fn test_invalid_queries() {
+        assert!(Query::parse("a == [1, 2]").is_err());
+        assert!(Query::parse("a > 1..2").is_err());
+        assert!(Query::parse("a any 1..2").is_err());
+        assert!(Query::parse("date any [\"2019-01-01\"]").is_err());
+        assert!(Query::parse("content(\"(\")").is_err());
+        assert!(Query::parse("date > \"yesterday\"").is_err());
+        assert!(Query::parse("tag(\"not a tag\")").is_err());
+    }
+}
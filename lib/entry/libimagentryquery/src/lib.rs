@@ -0,0 +1,77 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2019 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+//! The imag query language
+//!
+//! This library parses queries like
+//!
+//! ```ignore
+//! tag(work) and (imag.version == "0.10.0" or date in "2019-01-01".."2019-02-01")
+//! ```
+//!
+//! and compiles them into filters for entries, built on the filters from libimagentryfilter.
+//! See `LANGUAGE_DOC` for the language definition.
+//!
+
+#![forbid(unsafe_code)]
+
+#![deny(
+    dead_code,
+    non_camel_case_types,
+    non_snake_case,
+    path_statements,
+    trivial_numeric_casts,
+    unstable_features,
+    unused_allocation,
+    unused_import_braces,
+    unused_imports,
+    unused_must_use,
+    unused_mut,
+    unused_qualifications,
+    while_true,
+)]
+
+extern crate clap;
+extern crate chrono;
+extern crate filters;
+extern crate toml;
+extern crate toml_query;
+extern crate failure;
+#[macro_use] extern crate log;
+#[macro_use] extern crate is_match;
+
+#[macro_use] extern crate libimagstore;
+extern crate libimagerror;
+extern crate libimagrt;
+extern crate libimagentryfilter;
+extern crate libimagentrytag;
+extern crate libimagentrycategory;
+extern crate libimagentrylink;
+#[macro_use] extern crate libimagentryutil;
+
+module_entry_path_mod!("search");
+
+pub mod ast;
+pub mod cli;
+pub mod filter;
+pub mod parser;
+pub mod search;
+
+/// The documentation of the query language, for use in the help texts of commands
+pub const LANGUAGE_DOC : &'static str = include_str!("../static/language-doc.md");
@@ -0,0 +1,659 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2019 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+//! The parser for the query language
+//!
+//! The parser is a small handwritten recursive descent parser over a list of tokens, so that it
+//! can point to the exact position of an error in the query.
+
+use failure::Fallible as Result;
+use failure::Error;
+
+use libimagerror::kind::ErrorKind;
+use libimagerror::kind::kind_err;
+
+use ast::*;
+
+/// The header location which is used by `date` if no location is given
+pub const DEFAULT_DATE_LOCATION : &'static str = "datetime.value";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Word(String),
+    Str(String),
+    Int(i64),
+    Op(CompareOp),
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Comma,
+    DotDot,
+    End,
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match *self {
+            Token::Word(ref w) => format!("'{}'", w),
+            Token::Str(ref s)  => format!("string \"{}\"", s),
+            Token::Int(i)      => format!("number {}", i),
+            Token::Op(ref op)  => format!("operator '{}'", op_str(op)),
+            Token::LParen      => String::from("'('"),
+            Token::RParen      => String::from("')'"),
+            Token::LBracket    => String::from("'['"),
+            Token::RBracket    => String::from("']'"),
+            Token::Comma       => String::from("','"),
+            Token::DotDot      => String::from("'..'"),
+            Token::End         => String::from("end of query"),
+        }
+    }
+}
+
+fn op_str(op: &CompareOp) -> &'static str {
+    match *op {
+        CompareOp::Is  => "is",
+        CompareOp::In  => "in",
+        CompareOp::Eq  => "==",
+        CompareOp::Neq => "!=",
+        CompareOp::Gte => ">=",
+        CompareOp::Lte => "<=",
+        CompareOp::Lt  => "<",
+        CompareOp::Gt  => ">",
+        CompareOp::Any => "any",
+        CompareOp::All => "all",
+    }
+}
+
+/// A token and its byte offset in the query
+#[derive(Debug)]
+struct Lexed {
+    token: Token,
+    pos: usize,
+}
+
+/// Build the error for a problem at byte offset `pos` in `query`
+///
+/// The error message shows the query and marks the position.
+fn error_at(query: &str, pos: usize, msg: String) -> Error {
+    let column = query[..pos].chars().count();
+    kind_err(ErrorKind::Parse, format!("{}\n    {}\n    {}^", msg, query, " ".repeat(column)))
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-' || c == '.' || c == '/'
+}
+
+fn lex(query: &str) -> Result<Vec<Lexed>> {
+    let chars     = query.char_indices().collect::<Vec<(usize, char)>>();
+    let mut out   = vec![];
+    let mut i     = 0;
+    let char_at   = |i: usize| chars.get(i).map(|&(_, c)| c);
+
+    while i < chars.len() {
+        let (pos, c) = chars[i];
+
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        let simple = match c {
+            '(' => Some(Token::LParen),
+            ')' => Some(Token::RParen),
+            '[' => Some(Token::LBracket),
+            ']' => Some(Token::RBracket),
+            ',' => Some(Token::Comma),
+            _   => None,
+        };
+
+        if let Some(token) = simple {
+            out.push(Lexed { token, pos });
+            i += 1;
+            continue;
+        }
+
+        match c {
+            '=' | '!' | '<' | '>' => {
+                let eq = char_at(i + 1) == Some('=');
+                let op = match (c, eq) {
+                    ('=', true)  => CompareOp::Eq,
+                    ('!', true)  => CompareOp::Neq,
+                    ('<', true)  => CompareOp::Lte,
+                    ('>', true)  => CompareOp::Gte,
+                    ('<', false) => CompareOp::Lt,
+                    ('>', false) => CompareOp::Gt,
+                    ('=', false) => return Err(error_at(query, pos, String::from("Unexpected '=', did you mean '=='?"))),
+                    _            => return Err(error_at(query, pos, String::from("Unexpected '!', did you mean '!=' or 'not'?"))),
+                };
+
+                out.push(Lexed { token: Token::Op(op), pos });
+                i += if eq { 2 } else { 1 };
+            },
+
+            '"' => {
+                let mut s = String::new();
+                i += 1;
+                loop {
+                    match char_at(i) {
+                        None       => return Err(error_at(query, pos, String::from("Unterminated string"))),
+                        Some('"')  => break,
+                        Some('\\') => {
+                            match char_at(i + 1) {
+                                Some(e @ '"') | Some(e @ '\\') => s.push(e),
+                                _ => {
+                                    let epos = chars[i].0;
+                                    return Err(error_at(query, epos, String::from("Invalid escape sequence, only '\\\"' and '\\\\' are allowed")))
+                                },
+                            }
+                            i += 2;
+                        },
+                        Some(c) => {
+                            s.push(c);
+                            i += 1;
+                        },
+                    }
+                }
+
+                out.push(Lexed { token: Token::Str(s), pos });
+                i += 1; // closing quote
+            },
+
+            '.' if char_at(i + 1) == Some('.') => {
+                out.push(Lexed { token: Token::DotDot, pos });
+                i += 2;
+            },
+
+            c if is_word_char(c) || c == '+' => {
+                // Numbers: a sign and digits, as long as they are not the start of a word
+                let digits_start = if c == '+' || c == '-' { i + 1 } else { i };
+                let mut j = digits_start;
+                while char_at(j).map(|c| c.is_ascii_digit()).unwrap_or(false) {
+                    j += 1;
+                }
+
+                let is_number = j > digits_start && match (char_at(j), char_at(j + 1)) {
+                    (Some('.'), Some('.')) => true,
+                    (Some(c), _)           => !is_word_char(c),
+                    (None, _)              => true,
+                };
+
+                if is_number {
+                    let end  = chars.get(j).map(|&(p, _)| p).unwrap_or(query.len());
+                    let text = &query[pos..end];
+                    let num  = (if text.starts_with('+') { &text[1..] } else { text })
+                        .parse::<i64>()
+                        .map_err(|_| error_at(query, pos, format!("Number out of range: {}", text)))?;
+
+                    out.push(Lexed { token: Token::Int(num), pos });
+                    i = j;
+                    continue;
+                }
+
+                if c == '+' {
+                    return Err(error_at(query, pos, String::from("Unexpected '+'")))
+                }
+
+                // Words: selectors and keywords. Brackets are allowed in selectors for indexing
+                // into arrays, like `foo.[0]`.
+                let mut j     = i;
+                let mut depth = 0;
+                loop {
+                    match char_at(j) {
+                        Some('[') if j > i && char_at(j - 1) == Some('.') => depth += 1,
+                        Some(']') if depth > 0 => depth -= 1,
+                        Some(c) if is_word_char(c) => {},
+                        _ => break,
+                    }
+                    j += 1;
+                }
+
+                let end = chars.get(j).map(|&(p, _)| p).unwrap_or(query.len());
+                out.push(Lexed { token: Token::Word(String::from(&query[pos..end])), pos });
+                i = j;
+            },
+
+            other => return Err(error_at(query, pos, format!("Unexpected character '{}'", other))),
+        }
+    }
+
+    out.push(Lexed { token: Token::End, pos: query.len() });
+    Ok(out)
+}
+
+const KEYWORDS : &'static [&'static str] = &[
+    "and", "or", "xor", "not", "and_not", "or_not", "is", "in", "eq", "neq", "any", "all",
+    "true", "false",
+];
+
+struct Parser<'a> {
+    query: &'a str,
+    tokens: Vec<Lexed>,
+    current: usize,
+}
+
+impl<'a> Parser<'a> {
+
+    fn peek(&self) -> &Token {
+        &self.tokens[self.current].token
+    }
+
+    fn peek_next(&self) -> &Token {
+        self.tokens
+            .get(self.current + 1)
+            .map(|l| &l.token)
+            .unwrap_or(&Token::End)
+    }
+
+    fn advance(&mut self) -> Token {
+        let token = self.tokens[self.current].token.clone();
+        if token != Token::End {
+            self.current += 1;
+        }
+        token
+    }
+
+    fn error(&self, msg: String) -> Error {
+        error_at(self.query, self.tokens[self.current].pos, msg)
+    }
+
+    fn unexpected(&self, expected: &str) -> Error {
+        self.error(format!("Expected {}, found {}", expected, self.peek().describe()))
+    }
+
+    fn eat_keyword(&mut self, kw: &str) -> bool {
+        let is_kw = match *self.peek() {
+            Token::Word(ref w) => w == kw,
+            _ => false,
+        };
+
+        if is_kw {
+            self.advance();
+        }
+        is_kw
+    }
+
+    fn expect(&mut self, token: Token) -> Result<()> {
+        if *self.peek() == token {
+            self.advance();
+            Ok(())
+        } else {
+            Err(self.unexpected(&token.describe()))
+        }
+    }
+
+    fn or_expr(&mut self) -> Result<Expr> {
+        let mut lhs = self.xor_expr()?;
+        loop {
+            if self.eat_keyword("or") {
+                let rhs = self.xor_expr()?;
+                lhs = Expr::Or(Box::new(lhs), Box::new(rhs));
+            } else if self.eat_keyword("or_not") {
+                let rhs = self.xor_expr()?;
+                lhs = Expr::Or(Box::new(lhs), Box::new(Expr::Not(Box::new(rhs))));
+            } else {
+                break;
+            }
+        }
+        Ok(lhs)
+    }
+
+    fn xor_expr(&mut self) -> Result<Expr> {
+        let mut lhs = self.and_expr()?;
+        while self.eat_keyword("xor") {
+            let rhs = self.and_expr()?;
+            lhs = Expr::Xor(Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn and_expr(&mut self) -> Result<Expr> {
+        let mut lhs = self.unary()?;
+        loop {
+            if self.eat_keyword("and") {
+                let rhs = self.unary()?;
+                lhs = Expr::And(Box::new(lhs), Box::new(rhs));
+            } else if self.eat_keyword("and_not") {
+                let rhs = self.unary()?;
+                lhs = Expr::And(Box::new(lhs), Box::new(Expr::Not(Box::new(rhs))));
+            } else {
+                break;
+            }
+        }
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<Expr> {
+        if self.eat_keyword("not") {
+            Ok(Expr::Not(Box::new(self.unary()?)))
+        } else {
+            self.primary()
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr> {
+        if *self.peek() == Token::LParen {
+            self.advance();
+            let expr = self.or_expr()?;
+            self.expect(Token::RParen)?;
+            return Ok(expr)
+        }
+
+        let word = match *self.peek() {
+            Token::Word(ref w) => w.clone(),
+            _ => return Err(self.unexpected("a filter")),
+        };
+
+        if KEYWORDS.contains(&word.as_ref()) {
+            return Err(self.error(format!("Expected a filter, found keyword '{}'", word)))
+        }
+
+        let called = *self.peek_next() == Token::LParen;
+        let predicate = match word.as_ref() {
+            "tag" | "category" | "collection" | "linked" | "content" if called => {
+                self.advance();
+                self.advance();
+                let arg = self.argument()?;
+                self.expect(Token::RParen)?;
+
+                match word.as_ref() {
+                    "tag"        => Predicate::Tag(arg),
+                    "category"   => Predicate::Category(arg),
+                    "collection" => Predicate::Collection(arg),
+                    "linked"     => Predicate::Linked(arg),
+                    _            => Predicate::Content(arg),
+                }
+            },
+
+            "length" | "keys" | "values" if called => {
+                let function = match word.as_ref() {
+                    "length" => Function::Length,
+                    "keys"   => Function::Keys,
+                    _        => Function::Values,
+                };
+
+                self.advance();
+                self.advance();
+                let selector = self.selector()?;
+                self.expect(Token::RParen)?;
+
+                Predicate::Header(HeaderComparison {
+                    function : Some(function),
+                    selector : selector,
+                    op       : self.compare_op()?,
+                    value    : self.compare_value()?,
+                })
+            },
+
+            "date" => {
+                self.advance();
+                let selector = if *self.peek() == Token::LParen {
+                    self.advance();
+                    let selector = self.selector()?;
+                    self.expect(Token::RParen)?;
+                    selector
+                } else {
+                    String::from(DEFAULT_DATE_LOCATION)
+                };
+
+                Predicate::Date(DateComparison {
+                    selector : selector,
+                    op       : self.compare_op()?,
+                    value    : self.compare_value()?,
+                })
+            },
+
+            _ => {
+                let selector = self.selector()?;
+                Predicate::Header(HeaderComparison {
+                    function : None,
+                    selector : selector,
+                    op       : self.compare_op()?,
+                    value    : self.compare_value()?,
+                })
+            },
+        };
+
+        Ok(Expr::Predicate(predicate))
+    }
+
+    fn selector(&mut self) -> Result<String> {
+        match self.peek().clone() {
+            Token::Word(w) => {
+                self.advance();
+                Ok(w)
+            },
+            _ => Err(self.unexpected("a header selector like 'imag.version'")),
+        }
+    }
+
+    /// The argument of a predicate, either a string or a single word
+    fn argument(&mut self) -> Result<String> {
+        match self.peek().clone() {
+            Token::Word(w) | Token::Str(w) => {
+                self.advance();
+                Ok(w)
+            },
+            _ => Err(self.unexpected("a name or a string")),
+        }
+    }
+
+    fn compare_op(&mut self) -> Result<CompareOp> {
+        let op = match *self.peek() {
+            Token::Op(op) => op,
+            Token::Word(ref w) if w == "is"  => CompareOp::Is,
+            Token::Word(ref w) if w == "in"  => CompareOp::In,
+            Token::Word(ref w) if w == "eq"  => CompareOp::Eq,
+            Token::Word(ref w) if w == "neq" => CompareOp::Neq,
+            Token::Word(ref w) if w == "any" => CompareOp::Any,
+            Token::Word(ref w) if w == "all" => CompareOp::All,
+            _ => return Err(self.unexpected("a comparison operator (==, !=, <, <=, >, >=, is, in, any, all)")),
+        };
+
+        self.advance();
+        Ok(op)
+    }
+
+    fn compare_value(&mut self) -> Result<CompareValue> {
+        if *self.peek() == Token::LBracket {
+            self.advance();
+            let mut values = vec![];
+            while *self.peek() != Token::RBracket {
+                values.push(self.value()?);
+                if *self.peek() == Token::Comma {
+                    self.advance();
+                }
+            }
+            self.advance();
+            return Ok(CompareValue::Values(values))
+        }
+
+        let value = self.value()?;
+        if *self.peek() == Token::DotDot {
+            self.advance();
+            Ok(CompareValue::Range(value, self.value()?))
+        } else {
+            Ok(CompareValue::Value(value))
+        }
+    }
+
+    fn value(&mut self) -> Result<Value> {
+        let value = match *self.peek() {
+            Token::Str(ref s)                     => Value::String(s.clone()),
+            Token::Int(i)                         => Value::Integer(i),
+            Token::Word(ref w) if w == "true"  => Value::Boolean(true),
+            Token::Word(ref w) if w == "false" => Value::Boolean(false),
+            _ => return Err(self.unexpected("a value (a \"string\", a number, true or false)")),
+        };
+
+        self.advance();
+        Ok(value)
+    }
+
+}
+
+/// Parse a query into its syntax tree
+pub fn parse(query: &str) -> Result<Expr> {
+    let tokens     = lex(query)?;
+    let mut parser = Parser { query, tokens, current: 0 };
+
+    let expr = parser.or_expr()?;
+    if *parser.peek() != Token::End {
+        return Err(parser.unexpected("'and', 'or', 'xor' or the end of the query"))
+    }
+
+    trace!("Parsed query '{}' = {:?}", query, expr);
+    Ok(expr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(selector: &str, op: CompareOp, value: CompareValue) -> Expr {
+        Expr::Predicate(Predicate::Header(HeaderComparison {
+            function : None,
+            selector : String::from(selector),
+            op       : op,
+            value    : value,
+        }))
+    }
+
+    fn int(i: i64) -> CompareValue {
+        CompareValue::Value(Value::Integer(i))
+    }
+
+    #[test]
+    fn test_compare_ops() {
+        let ops = [
+            ("is", CompareOp::Is), ("in", CompareOp::In), ("==", CompareOp::Eq),
+            ("eq", CompareOp::Eq), ("!=", CompareOp::Neq), ("neq", CompareOp::Neq),
+            (">=", CompareOp::Gte), ("<=", CompareOp::Lte), ("<", CompareOp::Lt),
+            (">", CompareOp::Gt), ("any", CompareOp::Any), ("all", CompareOp::All),
+        ];
+
+        for &(text, op) in ops.iter() {
+            let query = format!("a.b {} 1", text);
+            assert_eq!(parse(&query).unwrap(), header("a.b", op, int(1)));
+        }
+    }
+
+    #[test]
+    fn test_values() {
+        assert_eq!(parse("a == -12").unwrap(), header("a", CompareOp::Eq, int(-12)));
+        assert_eq!(parse("a == true").unwrap(),
+                   header("a", CompareOp::Eq, CompareValue::Value(Value::Boolean(true))));
+        assert_eq!(parse(r#"imag.version == "0.7.0""#).unwrap(),
+                   header("imag.version", CompareOp::Eq, CompareValue::Value(Value::String(String::from("0.7.0")))));
+        assert_eq!(parse(r#"a == "say \"hi\"""#).unwrap(),
+                   header("a", CompareOp::Eq, CompareValue::Value(Value::String(String::from("say \"hi\"")))));
+    }
+
+    #[test]
+    fn test_lists_and_ranges() {
+        let list = CompareValue::Values(vec![Value::Integer(1), Value::Integer(2)]);
+        assert_eq!(parse("imag.header in [1, 2]").unwrap(), header("imag.header", CompareOp::In, list.clone()));
+        assert_eq!(parse("imag.header in [1 2,]").unwrap(), header("imag.header", CompareOp::In, list));
+
+        let range = CompareValue::Range(Value::Integer(1), Value::Integer(5));
+        assert_eq!(parse("a in 1..5").unwrap(), header("a", CompareOp::In, range));
+    }
+
+    #[test]
+    fn test_array_index_selector() {
+        assert_eq!(parse("a.[0] == 1").unwrap(), header("a.[0]", CompareOp::Eq, int(1)));
+    }
+
+    #[test]
+    fn test_function() {
+        let exp = Expr::Predicate(Predicate::Header(HeaderComparison {
+            function : Some(Function::Length),
+            selector : String::from("imag.header"),
+            op       : CompareOp::Gt,
+            value    : int(12),
+        }));
+
+        assert_eq!(parse("length(imag.header) > 12").unwrap(), exp);
+    }
+
+    #[test]
+    fn test_predicates() {
+        assert_eq!(parse("tag(work)").unwrap(), Expr::Predicate(Predicate::Tag(String::from("work"))));
+        assert_eq!(parse(r#"content("fo+")"#).unwrap(),
+                   Expr::Predicate(Predicate::Content(String::from("fo+"))));
+        assert_eq!(parse("collection(notes)").unwrap(),
+                   Expr::Predicate(Predicate::Collection(String::from("notes"))));
+
+        let exp = Expr::Predicate(Predicate::Date(DateComparison {
+            selector : String::from(DEFAULT_DATE_LOCATION),
+            op       : CompareOp::In,
+            value    : CompareValue::Range(Value::String(String::from("2019-01-01")),
+                                           Value::String(String::from("2019-02-01"))),
+        }));
+        assert_eq!(parse(r#"date in "2019-01-01".."2019-02-01""#).unwrap(), exp);
+    }
+
+    #[test]
+    fn test_precedence() {
+        let a = header("a", CompareOp::Eq, int(1));
+        let b = header("b", CompareOp::Eq, int(2));
+        let c = header("c", CompareOp::Eq, int(3));
+
+        let exp = Expr::Or(Box::new(a.clone()),
+                           Box::new(Expr::And(Box::new(b.clone()), Box::new(c.clone()))));
+        assert_eq!(parse("a == 1 or b == 2 and c == 3").unwrap(), exp);
+
+        let exp = Expr::And(Box::new(Expr::Or(Box::new(a.clone()), Box::new(b.clone()))),
+                            Box::new(c.clone()));
+        assert_eq!(parse("(a == 1 or b == 2) and c == 3").unwrap(), exp);
+
+        let exp = Expr::And(Box::new(Expr::Not(Box::new(a))), Box::new(b));
+        assert_eq!(parse("not a == 1 and b == 2").unwrap(), exp);
+    }
+
+    #[test]
+    fn test_negated_connectives() {
+        let a = header("a", CompareOp::Eq, int(1));
+        let b = header("b", CompareOp::Eq, int(2));
+        let not_b = Expr::Not(Box::new(b.clone()));
+
+        let exp = Expr::And(Box::new(a.clone()), Box::new(not_b.clone()));
+        assert_eq!(parse("a == 1 and_not b == 2").unwrap(), exp);
+
+        let exp = Expr::Or(Box::new(a), Box::new(not_b));
+        assert_eq!(parse("a == 1 or_not b == 2").unwrap(), exp);
+    }
+
+    #[test]
+    fn test_error_position() {
+        let msg = format!("{}", parse("a == 1 and b 2").unwrap_err());
+        assert!(msg.starts_with("Expected a comparison operator"), "{}", msg);
+        assert!(msg.ends_with("\n    a == 1 and b 2\n                 ^"), "{}", msg);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(parse("").is_err());
+        assert!(parse("a = 1").is_err());
+        assert!(parse("a == \"foo").is_err());
+        assert!(parse("(a == 1").is_err());
+        assert!(parse("a == 1 b == 2").is_err());
+        assert!(parse("and == 1").is_err());
+    }
+}
@@ -0,0 +1,299 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2019 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+//! Saved searches
+//!
+//! A saved search is an entry in the "search" collection which holds a query. Commands which
+//! take ids accept a reference to a saved search in the form `@name`. The query is evaluated
+//! against the store each time the reference is used, so the search behaves like a collection
+//! which is always up to date.
+
+use toml::Value;
+use toml_query::insert::TomlValueInsertExt;
+use toml_query::read::TomlValueReadTypeExt;
+use failure::Fallible as Result;
+use filters::failable::filter::FailableFilter;
+
+use libimagstore::store::Entry;
+use libimagstore::store::FileLockEntry;
+use libimagstore::store::Store;
+use libimagstore::storeid::IntoStoreId;
+use libimagstore::storeid::StoreId;
+use libimagstore::storeid::StoreIdIterator;
+use libimagentryutil::isa::Is;
+use libimagentryutil::isa::IsKindHeaderPathProvider;
+use libimagerror::kind::ErrorKind;
+use libimagerror::kind::kind_err;
+use libimagrt::resolver::IdResolver;
+
+use filter::Query;
+use module_path::ModuleEntryPath;
+
+/// The character which marks a reference to a saved search, as in `@name`
+pub const SEARCH_REFERENCE_PREFIX : char = '@';
+
+/// The collection saved searches are stored in
+pub const SEARCH_COLLECTION : &'static str = "search";
+
+provide_kindflag_path!(IsSavedSearch, "search.is_search");
+
+/// Whether `name` can be used as name of a saved search
+///
+/// Names must not be empty and must not contain a `/`, so they can be referenced as `@name`.
+pub fn is_valid_search_name(name: &str) -> bool {
+    !name.is_empty() && !name.contains('/')
+}
+
+/// Get the name of the saved search referenced by `s`, if `s` is a reference (`@name`)
+pub fn search_reference_name(s: &str) -> Option<&str> {
+    if s.starts_with(SEARCH_REFERENCE_PREFIX) {
+        let name = &s[SEARCH_REFERENCE_PREFIX.len_utf8()..];
+        if is_valid_search_name(name) {
+            return Some(name)
+        }
+    }
+
+    None
+}
+
+/// Get the name of the saved search a StoreId refers to, if it is a reference (`@name`)
+pub fn storeid_search_reference_name(id: &StoreId) -> Option<&str> {
+    id.local().to_str().and_then(search_reference_name)
+}
+
+pub trait SavedSearch {
+    fn is_saved_search(&self) -> Result<bool>;
+    fn get_search_name(&self) -> Result<String>;
+    fn get_search_query_string(&self) -> Result<String>;
+    fn get_search_query(&self) -> Result<Query>;
+
+    /// Set the query of the saved search
+    ///
+    /// Fails without altering the entry if the query cannot be parsed.
+    fn set_search_query(&mut self, query: &str) -> Result<()>;
+}
+
+impl SavedSearch for Entry {
+
+    fn is_saved_search(&self) -> Result<bool> {
+        self.is::<IsSavedSearch>()
+    }
+
+    fn get_search_name(&self) -> Result<String> {
+        self.get_header()
+            .read_string("search.name")?
+            .ok_or_else(|| kind_err(ErrorKind::Parse, "Header field 'search.name' missing"))
+    }
+
+    fn get_search_query_string(&self) -> Result<String> {
+        self.get_header()
+            .read_string("search.query")?
+            .ok_or_else(|| kind_err(ErrorKind::Parse, "Header field 'search.query' missing"))
+    }
+
+    fn get_search_query(&self) -> Result<Query> {
+        self.get_search_query_string().and_then(|q| Query::parse(&q))
+    }
+
+    fn set_search_query(&mut self, query: &str) -> Result<()> {
+        let _ = Query::parse(query)?;
+        self.get_header_mut()
+            .insert("search.query", Value::String(String::from(query)))
+            .map_err(From::from)
+            .map(|_| ())
+    }
+
+}
+
+pub trait SearchStore<'a> {
+    fn new_search(&'a self, name: &str, query: &str) -> Result<FileLockEntry<'a>>;
+    fn get_search(&'a self, name: &str) -> Result<Option<FileLockEntry<'a>>>;
+    fn delete_search(&'a self, name: &str) -> Result<()>;
+    fn all_searches(&'a self) -> Result<StoreIdIterator>;
+
+    /// Evaluate the saved search `name` and get the ids of all entries which match it
+    ///
+    /// Saved searches themselves are never part of the result.
+    fn evaluate_search(&'a self, name: &str) -> Result<Vec<StoreId>>;
+}
+
+impl<'a> SearchStore<'a> for Store {
+
+    fn new_search(&'a self, name: &str, query: &str) -> Result<FileLockEntry<'a>> {
+        debug!("Creating saved search '{}': {}", name, query);
+        if !is_valid_search_name(name) {
+            let msg = format!("Invalid name for a saved search: '{}' (must not be empty or contain '/')", name);
+            return Err(kind_err(ErrorKind::Parse, msg))
+        }
+
+        let _ = Query::parse(query)?;
+
+        let mut entry = ModuleEntryPath::new(name).into_storeid().and_then(|id| self.create(id))?;
+        {
+            let _ = entry.set_isflag::<IsSavedSearch>()?;
+            let _ = entry
+                .get_header_mut()
+                .insert("search.name", Value::String(String::from(name)))?;
+            let _ = entry.set_search_query(query)?;
+        }
+
+        Ok(entry)
+    }
+
+    fn get_search(&'a self, name: &str) -> Result<Option<FileLockEntry<'a>>> {
+        ModuleEntryPath::new(name).into_storeid().and_then(|id| self.get(id))
+    }
+
+    fn delete_search(&'a self, name: &str) -> Result<()> {
+        let id = ModuleEntryPath::new(name).into_storeid()?;
+        if !self.exists(id.clone())? {
+            return Err(kind_err(ErrorKind::NotFound, format!("No saved search '{}'", name)))
+        }
+
+        self.delete(id)
+    }
+
+    fn all_searches(&'a self) -> Result<StoreIdIterator> {
+        self.entries().map(|es| es.in_collection(SEARCH_COLLECTION).into_storeid_iter())
+    }
+
+    fn evaluate_search(&'a self, name: &str) -> Result<Vec<StoreId>> {
+        let query = match self.get_search(name)? {
+            Some(search) => search.get_search_query()?,
+            None         => {
+                let msg = format!("No saved search '{}'", name);
+                return Err(kind_err(ErrorKind::NotFound, msg))
+            },
+        };
+
+        let mut ids = vec![];
+        for id in self.entries()?.into_storeid_iter() {
+            let id = id?;
+            if id.is_in_collection(&[SEARCH_COLLECTION]) {
+                continue
+            }
+
+            if let Some(entry) = self.get(id.clone())? {
+                if query.filter(&entry)? {
+                    ids.push(id);
+                }
+            }
+        }
+
+        ids.sort();
+        debug!("Saved search '{}' evaluated to {} entries", name, ids.len());
+        Ok(ids)
+    }
+
+}
+
+/// Resolves references to saved searches (`@name`) for the runtime
+///
+/// Binaries which take ids register it with `Runtime::register_id_resolver()`.
+#[derive(Debug)]
+pub struct SavedSearchResolver;
+
+impl IdResolver for SavedSearchResolver {
+    fn resolve(&self, store: &Store, id: &StoreId) -> Result<Option<Vec<StoreId>>> {
+        match storeid_search_reference_name(id) {
+            None       => Ok(None),
+            Some(name) => store.evaluate_search(name).map(Some),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn get_store() -> Store {
+        Store::new_inmemory(PathBuf::from("/"), &None).unwrap()
+    }
+
+    #[test]
+    fn test_search_reference_name() {
+        assert_eq!(Some("work"), search_reference_name("@work"));
+        assert_eq!(Some("work-open"), search_reference_name("@work-open"));
+        assert_eq!(None, search_reference_name("work"));
+        assert_eq!(None, search_reference_name("@"));
+        assert_eq!(None, search_reference_name("@a/b"));
+    }
+
+    #[test]
+    fn test_saved_search_resolver() {
+        let store = get_store();
+        let _     = store.retrieve(PathBuf::from("a")).unwrap();
+        let _     = store.new_search("all", "not collection(other)").unwrap();
+
+        let id = StoreId::new(PathBuf::from("a")).unwrap();
+        assert!(SavedSearchResolver.resolve(&store, &id).unwrap().is_none());
+
+        let reference = StoreId::new(PathBuf::from("@all")).unwrap();
+        let ids       = SavedSearchResolver.resolve(&store, &reference).unwrap().unwrap();
+        assert_eq!(vec![id], ids);
+
+        let reference = StoreId::new(PathBuf::from("@missing")).unwrap();
+        assert!(SavedSearchResolver.resolve(&store, &reference).is_err());
+    }
+
+    #[test]
+    fn test_new_search_rejects_invalid_name() {
+        let store = get_store();
+        assert!(store.new_search("a/b", "tag(work)").is_err());
+        assert!(store.new_search("", "tag(work)").is_err());
+        assert_eq!(0, store.all_searches().unwrap().count());
+    }
+
+    #[test]
+    fn test_new_search_rejects_invalid_query() {
+        let store = get_store();
+        assert!(store.new_search("broken", "tag(").is_err());
+        assert!(store.get_search("broken").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_evaluate_search() {
+        let store = get_store();
+
+        for (name, num) in vec![("a", 1), ("b", 2), ("c", 3)] {
+            let mut entry = store.retrieve(PathBuf::from(name)).unwrap();
+            let header    = format!("[test]\nnum = {}\n", num);
+            *entry.get_header_mut() = ::toml::de::from_str(&header).unwrap();
+        }
+
+        {
+            let search = store.new_search("big", "test.num >= 2").unwrap();
+            assert!(search.is_saved_search().unwrap());
+            assert_eq!("big", search.get_search_name().unwrap());
+            assert_eq!("test.num >= 2", search.get_search_query_string().unwrap());
+        }
+
+        let ids = store.evaluate_search("big").unwrap();
+        let ids = ids.iter().map(|id| id.local().clone()).collect::<Vec<_>>();
+        assert_eq!(vec![PathBuf::from("b"), PathBuf::from("c")], ids);
+
+        assert_eq!(1, store.all_searches().unwrap().count());
+        assert!(store.delete_search("big").is_ok());
+        assert!(store.evaluate_search("big").is_err());
+        assert!(store.delete_search("big").is_err());
+    }
+
+}
@@ -48,7 +48,7 @@ extern crate libimagstore;
 extern crate libimagrt;
 extern crate libimagerror;
 #[macro_use] extern crate libimagentryutil;
-#[macro_use] extern crate failure;
+extern crate failure;
 
 #[cfg(test)]
 extern crate env_logger;
@@ -25,6 +25,8 @@ use std::ops::Deref;
 use libimagentryutil::isa::Is;
 use libimagentryutil::isa::IsKindHeaderPathProvider;
 use libimagerror::errors::ErrorMsg as EM;
+use libimagerror::kind::ErrorKind;
+use libimagerror::kind::kind_err;
 
 use toml::Value;
 use toml_query::read::TomlValueReadExt;
@@ -32,7 +34,6 @@ use toml_query::delete::TomlValueDeleteExt;
 use toml_query::insert::TomlValueInsertExt;
 use failure::Fallible as Result;
 use failure::Error;
-use failure::err_msg;
 use failure::ResultExt;
 
 use hasher::Hasher;
@@ -209,19 +210,19 @@ impl<'a, H: Hasher> Ref for RefWithHasher<'a, H> {
         let ref_header = self.0
             .get_header()
             .read("ref")?
-            .ok_or_else(|| err_msg("Header missing at 'ref'"))?;
+            .ok_or_else(|| kind_err(ErrorKind::Parse, "Header missing at 'ref'"))?;
 
         let collection_name = ref_header
             .read("collection")
             .map_err(Error::from)?
-            .ok_or_else(|| err_msg("Header missing at 'ref.collection'"))?
+            .ok_or_else(|| kind_err(ErrorKind::Parse, "Header missing at 'ref.collection'"))?
             .as_str()
             .ok_or_else(|| Error::from(EM::EntryHeaderTypeError2("ref.hash.<hash>", "string")))?;
 
         let path = ref_header
             .read("relpath")
             .map_err(Error::from)?
-            .ok_or_else(|| err_msg("Header missing at 'ref.relpath'"))?
+            .ok_or_else(|| kind_err(ErrorKind::Parse, "Header missing at 'ref.relpath'"))?
             .as_str()
             .map(PathBuf::from)
             .ok_or_else(|| Error::from(EM::EntryHeaderTypeError2("ref.hash.<hash>", "string")))?;
@@ -232,7 +233,7 @@ impl<'a, H: Hasher> Ref for RefWithHasher<'a, H> {
         ref_header
             .read(H::NAME)
             .map_err(Error::from)?
-            .ok_or_else(|| format_err!("Header missing at 'ref.{}'", H::NAME))
+            .ok_or_else(|| kind_err(ErrorKind::Parse, format!("Header missing at 'ref.{}'", H::NAME)))
             .and_then(|v| {
                 v.as_str().ok_or_else(|| {
                     Error::from(EM::EntryHeaderTypeError2("ref.hash.<hash>", "string"))
@@ -313,13 +314,14 @@ impl<'a, H> MutRef for MutRefWithHasher<'a, H>
 
         if self.0.get_header().read("ref.is_ref")?.is_some() && !force {
             debug!("Entry is already a Ref!");
-            let _ = Err(err_msg("Entry is already a reference")).context("Making ref out of entry")?;
+            let _ = Err(kind_err(ErrorKind::AlreadyExists, "Entry is already a reference"))
+                .context("Making ref out of entry")?;
         }
 
         let file_path = get_file_path(config, collection_name.as_ref(), &path)?;
 
         if !file_path.exists() {
-            let msg = format_err!("File '{:?}' does not exist", file_path);
+            let msg = kind_err(ErrorKind::NotFound, format!("File '{:?}' does not exist", file_path));
             let _   = Err(msg).context("Making ref out of entry")?;
         }
 
@@ -368,10 +370,7 @@ pub(crate) fn make_header_section<P, C, H>(hash: String, hashname: H, relpath: P
             .as_ref()
             .to_str()
             .map(String::from)
-            .ok_or_else(|| {
-                let msg = format_err!("UTF Error in '{:?}'", relpath.as_ref());
-                Error::from(msg)
-            })?;
+            .ok_or_else(|| kind_err(ErrorKind::Parse, format!("UTF Error in '{:?}'", relpath.as_ref())))?;
 
         let _ = header_section.insert("relpath", Value::String(relpath))?;
     }
@@ -389,8 +388,9 @@ pub(crate) fn make_header_section<P, C, H>(hash: String, hashname: H, relpath: P
 
 fn get_basepath<'a, Coll: AsRef<str>>(collection_name: Coll, config: &'a Config) -> Result<&'a PathBuf> {
     config.get(collection_name.as_ref())
-        .ok_or_else(|| format_err!("Collection {} seems not to exist in config",
-                                   collection_name.as_ref()))
+        .ok_or_else(|| kind_err(ErrorKind::Config,
+                                format!("Collection {} seems not to exist in config",
+                                        collection_name.as_ref())))
         .map_err(Error::from)
 }
 
@@ -401,7 +401,8 @@ fn get_file_path<P>(config: &Config, collection_name: &str, path: P) -> Result<P
         .get(collection_name)
         .map(PathBuf::clone)
         .ok_or_else(|| {
-            format_err!("Configuration missing for collection: '{}'", collection_name)
+            kind_err(ErrorKind::Config,
+                     format!("Configuration missing for collection: '{}'", collection_name))
         })
         .context("Making ref out of entry")
         .map_err(Error::from)
@@ -422,6 +423,7 @@ mod test {
 
     use super::*;
     use hasher::Hasher;
+    use failure::err_msg;
 
     fn setup_logging() {
         let _ = ::env_logger::try_init();
@@ -19,6 +19,8 @@
 
 use failure::Fallible as Result;
 
+use libimagerror::kind::ErrorKind;
+use libimagerror::kind::kind_err;
 use libimagrt::runtime::Runtime;
 
 use reference::Config as RefConfig;
@@ -29,9 +31,11 @@ pub fn get_ref_config(rt: &Runtime, app_name: &'static str) -> Result<RefConfig>
     let setting_name = "ref.basepathes";
 
     rt.config()
-        .ok_or_else(|| format_err!("No configuration, cannot find collection name for {}", app_name))?
+        .ok_or_else(|| {
+            kind_err(ErrorKind::Config, format!("No configuration, cannot find collection name for {}", app_name))
+        })?
         .read_deserialized::<RefConfig>(setting_name)?
-        .ok_or_else(|| format_err!("Setting missing: {}", setting_name))
+        .ok_or_else(|| kind_err(ErrorKind::Config, format!("Setting missing: {}", setting_name)))
 }
 
 
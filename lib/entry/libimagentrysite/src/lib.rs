@@ -0,0 +1,68 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2019 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+//! Static HTML sites from store entries
+//!
+//! This library renders a set of entries, for example a wiki, a diary or the result of a query,
+//! to a static site: a page per entry, index pages per collection and per tag and a search index.
+//! The markdown links between the exported entries become relative links between their pages.
+//!
+//! The pages are rendered with handlebars templates, see `template::Templates`.
+//!
+
+#![forbid(unsafe_code)]
+
+#![deny(
+    dead_code,
+    non_camel_case_types,
+    non_snake_case,
+    path_statements,
+    trivial_numeric_casts,
+    unstable_features,
+    unused_allocation,
+    unused_import_braces,
+    unused_imports,
+    unused_must_use,
+    unused_mut,
+    unused_qualifications,
+    while_true,
+)]
+
+extern crate url;
+extern crate regex;
+extern crate handlebars;
+#[macro_use] extern crate serde_json;
+#[macro_use] extern crate failure;
+#[macro_use] extern crate log;
+
+extern crate libimagstore;
+extern crate libimagerror;
+extern crate libimagentrylink;
+extern crate libimagentrymarkdown;
+extern crate libimagentrytag;
+extern crate libimaginteraction;
+
+#[cfg(test)] extern crate env_logger;
+#[cfg(test)] extern crate tempfile;
+
+pub mod path;
+pub mod render;
+pub mod site;
+pub mod template;
+
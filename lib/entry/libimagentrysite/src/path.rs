@@ -0,0 +1,90 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2019 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+//! Where the pages of a site are, and how they link to each other
+//!
+//! All paths are relative to the root of the site and use `/` as separator. They are not
+//! URL-encoded, `relative_url()` does that when linking from one page to another.
+
+use libimagstore::storeid::StoreId;
+
+/// The path of the start page of the site
+pub const INDEX_PAGE : &'static str = "index.html";
+
+/// The path of the search index of the site
+pub const SEARCH_INDEX : &'static str = "search-index.json";
+
+/// The path of the page of the entry `id`
+pub fn entry_page(id: &StoreId) -> String {
+    format!("entries/{}.html", id.local_display_string())
+}
+
+/// The path of the index page of the collection `collection`, for example `wiki/default`
+pub fn collection_page(collection: &str) -> String {
+    format!("collections/{}.html", collection)
+}
+
+/// The path of the index page of the tag `tag`
+pub fn tag_page(tag: &str) -> String {
+    format!("tags/{}.html", tag)
+}
+
+/// The URL of the root of the site, relative to the page `from`
+///
+/// This is an empty string for pages in the root of the site, `../` for pages one level below and
+/// so on.
+pub fn root_url(from: &str) -> String {
+    "../".repeat(from.matches('/').count())
+}
+
+/// The URL of the page `to`, relative to the page `from`
+pub fn relative_url(from: &str, to: &str) -> String {
+    let encoded = to.split('/').map(encode_segment).collect::<Vec<String>>().join("/");
+    format!("{}{}", root_url(from), encoded)
+}
+
+/// Percent-encode everything but the unreserved characters of RFC 3986
+fn encode_segment(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|b| match b {
+            b if b.is_ascii_alphanumeric() || b"-._~".contains(&b) => (b as char).to_string(),
+            b => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_relative_url() {
+        let page = entry_page(&StoreId::new(PathBuf::from("wiki/default/cars/mustang")).unwrap());
+        assert_eq!("entries/wiki/default/cars/mustang.html", page);
+
+        assert_eq!("../../../../index.html", relative_url(&page, INDEX_PAGE));
+        assert_eq!("../../../../tags/cars.html", relative_url(&page, &tag_page("cars")));
+        assert_eq!("collections/wiki/default.html", relative_url(INDEX_PAGE, &collection_page("wiki/default")));
+        assert_eq!("entries/notes/a%20note%3F.html", relative_url(INDEX_PAGE, "entries/notes/a note?.html"));
+    }
+
+}
@@ -0,0 +1,184 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2019 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+//! Rendering entries to HTML
+
+use std::path::PathBuf;
+
+use failure::Fallible as Result;
+use regex::Captures;
+use regex::Regex;
+use url::Url;
+
+use libimagentrymarkdown::html::to_html_with_anchors;
+use libimagentrymarkdown::link::extract_links;
+use libimagentrymarkdown::link::split_anchor;
+use libimagentrymarkdown::outline::Outline;
+use libimagstore::store::Entry;
+use libimagstore::storeid::StoreId;
+
+/// Get the entry an internal markdown link points to
+///
+/// Links are qualified the same way `libimagentrymarkdown::processor::LinkProcessor` does it: a
+/// link which is not a URL is the id of an entry. The anchor of a link to a section of an entry
+/// (`wiki/foo#setup`) is ignored. Links to anchors in the same page (`#section`) are not internal
+/// links.
+pub fn internal_target(link: &str) -> Option<StoreId> {
+    let (path, _) = split_anchor(link);
+    if path.is_empty() {
+        return None
+    }
+
+    match Url::parse(path) {
+        Err(::url::ParseError::RelativeUrlWithoutBase) => StoreId::new(PathBuf::from(path)).ok(),
+        _ => None,
+    }
+}
+
+/// Get the entries the markdown content of an entry links to
+pub fn internal_links(content: &str) -> Vec<StoreId> {
+    extract_links(content)
+        .into_iter()
+        .filter_map(|link| internal_target(&link.link))
+        .collect()
+}
+
+/// Render markdown to HTML, resolving internal links with `resolve`
+///
+/// `resolve` gets the id an internal link points to and returns the URL to link to. If it returns
+/// `None`, for example because the linked entry is not part of the site, the link is replaced by
+/// its text, wrapped in `<span class="missing-link">`. Links to URLs are not changed, the anchor of
+/// links to a section of an entry is kept. Headings get `id` attributes, so these anchors work.
+pub fn render_content<F>(content: &str, resolve: F) -> Result<String>
+    where F: Fn(&StoreId) -> Option<String>
+{
+    let html   = to_html_with_anchors(content)?;
+    let anchor = Regex::new(r#"(?s)<a href="([^"]*)"([^>]*)>(.*?)</a>"#)?;
+
+    let html = anchor.replace_all(&html, |caps: &Captures| {
+        let link   = caps[1].replace("&amp;", "&");
+        let anchor = split_anchor(&link).1.map(|a| format!("#{}", a)).unwrap_or_default();
+
+        match internal_target(&link) {
+            None     => String::from(&caps[0]),
+            Some(id) => match resolve(&id) {
+                Some(url) => format!(r#"<a href="{}{}"{}>{}</a>"#, url, anchor, &caps[2], &caps[3]),
+                None      => {
+                    debug!("Link to {} cannot be resolved", id);
+                    format!(r#"<span class="missing-link">{}</span>"#, &caps[3])
+                },
+            },
+        }
+    });
+
+    Ok(html.into_owned())
+}
+
+/// Get the text of a piece of HTML, for the search index
+///
+/// Tags are removed and whitespace is collapsed.
+pub fn plain_text(html: &str) -> Result<String> {
+    let tag  = Regex::new(r"<[^>]*>")?;
+    let text = tag
+        .replace_all(html, " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+
+    Ok(text.split_whitespace().collect::<Vec<&str>>().join(" "))
+}
+
+/// Get the title of an entry
+///
+/// This is the first top level heading (`# Title`) in the content, or the last part of the id of
+/// the entry if there is none.
+pub fn entry_title(entry: &Entry) -> String {
+    Outline::parse(entry.get_content())
+        .headings
+        .into_iter()
+        .find(|heading| heading.level == 1)
+        .map(|heading| heading.text)
+        .filter(|title| !title.is_empty())
+        .unwrap_or_else(|| {
+            entry
+                .get_location()
+                .local()
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| entry.get_location().local_display_string())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::path::PathBuf;
+
+    use libimagstore::store::Store;
+
+    fn id(s: &str) -> StoreId {
+        StoreId::new(PathBuf::from(s)).unwrap()
+    }
+
+    #[test]
+    fn test_internal_target() {
+        assert_eq!(Some(id("wiki/default/page")), internal_target("wiki/default/page"));
+        assert_eq!(None, internal_target("https://imag-pim.org"));
+        assert_eq!(Some(id("wiki/default/page")), internal_target("wiki/default/page#setup"));
+        assert_eq!(None, internal_target("#section"));
+        assert_eq!(None, internal_target("/absolute/path"));
+    }
+
+    #[test]
+    fn test_render_content() {
+        let content = "# Links\n\n[page](wiki/page), [setup](wiki/page#setup), [gone](wiki/gone) and [imag](https://imag-pim.org)\n";
+        let html    = render_content(content, |target| if *target == id("wiki/page") {
+            Some(String::from("page.html"))
+        } else {
+            None
+        }).unwrap();
+
+        assert!(html.contains(r#"<h1 id="links">Links</h1>"#));
+        assert!(html.contains(r#"<a href="page.html">page</a>"#));
+        assert!(html.contains(r#"<a href="page.html#setup">setup</a>"#));
+        assert!(html.contains(r#"<span class="missing-link">gone</span>"#));
+        assert!(html.contains(r#"<a href="https://imag-pim.org">imag</a>"#));
+    }
+
+    #[test]
+    fn test_plain_text() {
+        let text = plain_text("<h1>Title</h1>\n<p>Some <em>text</em> &amp; more</p>\n").unwrap();
+        assert_eq!("Title Some text & more", text);
+    }
+
+    #[test]
+    fn test_entry_title() {
+        let store = Store::new_inmemory(PathBuf::from("/"), &None).unwrap();
+
+        let mut entry = store.create(PathBuf::from("notes/shopping")).unwrap();
+        assert_eq!("shopping", entry_title(&entry));
+
+        *entry.get_content_mut() = String::from("Intro\n\n# Shopping list #\n\n* milk\n");
+        assert_eq!("Shopping list", entry_title(&entry));
+    }
+
+}
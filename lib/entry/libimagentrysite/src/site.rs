@@ -0,0 +1,483 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2019 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+//! Exporting entries to a static site
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::fs::File;
+use std::fs::create_dir_all;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+use failure::Fallible as Result;
+use failure::ResultExt;
+use serde_json::Value;
+
+use libimagentrylink::external::is_external_link_storeid;
+use libimagerror::kind::ErrorKind;
+use libimagerror::kind::kind_err;
+use libimagentrylink::internal::InternalLinker;
+use libimagentrytag::tagable::Tagable;
+use libimagstore::store::Store;
+use libimagstore::storeid::StoreId;
+
+use path::INDEX_PAGE;
+use path::SEARCH_INDEX;
+use path::collection_page;
+use path::entry_page;
+use path::relative_url;
+use path::root_url;
+use path::tag_page;
+use render::entry_title;
+use render::internal_links;
+use render::plain_text;
+use render::render_content;
+use template::Templates;
+
+/// The collections which hold content and are exported if no entries are selected explicitly
+pub const DEFAULT_COLLECTIONS : &'static [&'static str] = &["notes", "wiki", "diary"];
+
+/// What an export wrote
+#[derive(Debug)]
+pub struct ExportReport {
+    pub entries: usize,
+    pub collections: usize,
+    pub tags: usize,
+}
+
+impl Display for ExportReport {
+    fn fmt(&self, fmt: &mut Formatter) -> FmtResult {
+        write!(fmt, "{} entries, {} collections, {} tags", self.entries, self.collections, self.tags)
+    }
+}
+
+/// Exports entries to a static site
+///
+/// The site contains
+///
+/// * a page for each exported entry, at `entries/<id>.html`
+/// * an index page for each collection an exported entry is in, at `collections/<name>.html`
+/// * an index page for each tag of an exported entry, at `tags/<tag>.html`
+/// * the start page, `index.html`
+/// * the search index, `search-index.json`, a list of all entries with their id, title, URL, tags
+///   and text
+///
+/// Markdown links between exported entries are rewritten to relative links between their pages,
+/// so the site can be served from any location or viewed from the filesystem. Links to entries
+/// which are not exported are replaced by their text.
+pub struct SiteExporter<'a> {
+    store: &'a Store,
+    title: String,
+    template_dir: Option<PathBuf>,
+}
+
+struct Page {
+    id: StoreId,
+    path: String,
+    title: String,
+    markdown: String,
+    tags: Vec<String>,
+    links: Vec<StoreId>,
+    content_links: Vec<StoreId>,
+}
+
+#[derive(Default)]
+struct Collection {
+    collections: BTreeSet<String>,
+    entries: Vec<StoreId>,
+}
+
+impl<'a> SiteExporter<'a> {
+
+    pub fn new(store: &'a Store) -> Self {
+        SiteExporter {
+            store: store,
+            title: String::from("imag"),
+            template_dir: None,
+        }
+    }
+
+    /// Set the title of the site
+    pub fn with_title(mut self, title: String) -> Self {
+        self.title = title;
+        self
+    }
+
+    /// Use the templates from `dir` instead of the default ones, see `Templates::from_dir()`
+    pub fn with_template_dir(mut self, dir: PathBuf) -> Self {
+        self.template_dir = Some(dir);
+        self
+    }
+
+    /// Export the entries `ids` to the directory `out`
+    ///
+    /// Files which already exist in `out` are overwritten.
+    pub fn export<I>(&self, ids: I, out: &Path) -> Result<ExportReport>
+        where I: IntoIterator<Item = StoreId>
+    {
+        let templates = match self.template_dir {
+            Some(ref dir) => Templates::from_dir(dir)?,
+            None          => Templates::new()?,
+        };
+
+        let pages = self.load_pages(ids)?;
+
+        let mut backlinks : BTreeMap<&StoreId, BTreeSet<&StoreId>> = BTreeMap::new();
+        let mut collections : BTreeMap<String, Collection>         = BTreeMap::new();
+        let mut root_entries                                        = vec![];
+        let mut tags : BTreeMap<&str, Vec<&StoreId>>                = BTreeMap::new();
+
+        for page in pages.values() {
+            for target in page.content_links.iter() {
+                if *target != page.id && pages.contains_key(target) {
+                    backlinks.entry(target).or_insert_with(BTreeSet::new).insert(&page.id);
+                }
+            }
+
+            let colls = collections_of(&page.id);
+            for (i, coll) in colls.iter().enumerate() {
+                let c = collections.entry(coll.clone()).or_insert_with(Collection::default);
+                match colls.get(i + 1) {
+                    Some(sub) => { let _ = c.collections.insert(sub.clone()); },
+                    None      => c.entries.push(page.id.clone()),
+                }
+            }
+
+            if colls.is_empty() {
+                root_entries.push(page.id.clone());
+            }
+
+            for tag in page.tags.iter() {
+                tags.entry(tag.as_str()).or_insert_with(Vec::new).push(&page.id);
+            }
+        }
+
+        let mut texts        = BTreeMap::new();
+        let mut search_index = vec![];
+
+        for page in pages.values() {
+            debug!("Exporting {}", page.id);
+            let html = render_content(&page.markdown, |target| {
+                pages.get(target).map(|t| relative_url(&page.path, &t.path))
+            })?;
+
+            let empty       = BTreeSet::new();
+            let linked_from = backlinks.get(&page.id).unwrap_or(&empty);
+            let links       = page.links
+                .iter()
+                .filter(|id| **id != page.id && !linked_from.contains(id) && !page.content_links.contains(id))
+                .filter_map(|id| pages.get(id))
+                .map(|p| self.item(&page.path, p, None))
+                .collect::<Vec<Value>>();
+
+            let data = json!({
+                "site": self.site_data(&page.path),
+                "id": page.id.local_display_string(),
+                "title": page.title,
+                "breadcrumbs": breadcrumbs(&page.path, &collections_of(&page.id)),
+                "content": html,
+                "tags": page.tags
+                    .iter()
+                    .map(|tag| json!({ "name": tag, "url": relative_url(&page.path, &tag_page(tag)) }))
+                    .collect::<Vec<Value>>(),
+                "backlinks": linked_from
+                    .iter()
+                    .filter_map(|id| pages.get(*id))
+                    .map(|p| self.item(&page.path, p, None))
+                    .collect::<Vec<Value>>(),
+                "links": links,
+            });
+
+            let _ = write_file(out, &page.path, &templates.render("entry", &data)?)?;
+
+            let text = plain_text(&html)?;
+            search_index.push(json!({
+                "id": page.id.local_display_string(),
+                "title": page.title,
+                "url": relative_url(INDEX_PAGE, &page.path),
+                "tags": page.tags,
+                "text": text,
+            }));
+            texts.insert(&page.id, text);
+        }
+
+        for (name, coll) in collections.iter() {
+            let path  = collection_page(name);
+            let colls = collections_of_collection(name);
+            let data  = json!({
+                "site": self.site_data(&path),
+                "title": name,
+                "breadcrumbs": breadcrumbs(&path, &colls[..colls.len() - 1]),
+                "collections": coll.collections
+                    .iter()
+                    .map(|sub| json!({ "name": last_segment(sub), "url": relative_url(&path, &collection_page(sub)) }))
+                    .collect::<Vec<Value>>(),
+                "entries": coll.entries
+                    .iter()
+                    .filter_map(|id| pages.get(id))
+                    .map(|p| self.item(&path, p, texts.get(&p.id).map(String::as_str)))
+                    .collect::<Vec<Value>>(),
+            });
+
+            let _ = write_file(out, &path, &templates.render("collection", &data)?)?;
+        }
+
+        for (tag, ids) in tags.iter() {
+            let path = tag_page(tag);
+            let data = json!({
+                "site": self.site_data(&path),
+                "title": tag,
+                "entries": ids
+                    .iter()
+                    .filter_map(|id| pages.get(*id))
+                    .map(|p| self.item(&path, p, texts.get(&p.id).map(String::as_str)))
+                    .collect::<Vec<Value>>(),
+            });
+
+            let _ = write_file(out, &path, &templates.render("tag", &data)?)?;
+        }
+
+        let data = json!({
+            "site": self.site_data(INDEX_PAGE),
+            "title": self.title,
+            "collections": collections
+                .keys()
+                .filter(|name| !name.contains('/'))
+                .map(|name| json!({ "name": name, "url": relative_url(INDEX_PAGE, &collection_page(name)) }))
+                .collect::<Vec<Value>>(),
+            "entries": root_entries
+                .iter()
+                .filter_map(|id| pages.get(id))
+                .map(|p| self.item(INDEX_PAGE, p, texts.get(&p.id).map(String::as_str)))
+                .collect::<Vec<Value>>(),
+            "tags": tags
+                .iter()
+                .map(|(tag, ids)| json!({
+                    "name": tag,
+                    "url": relative_url(INDEX_PAGE, &tag_page(tag)),
+                    "count": ids.len(),
+                }))
+                .collect::<Vec<Value>>(),
+        });
+
+        let _ = write_file(out, INDEX_PAGE, &templates.render("index", &data)?)?;
+        let _ = write_file(out, SEARCH_INDEX, &::serde_json::to_string_pretty(&search_index)?)?;
+
+        Ok(ExportReport {
+            entries: pages.len(),
+            collections: collections.len(),
+            tags: tags.len(),
+        })
+    }
+
+    fn load_pages<I>(&self, ids: I) -> Result<BTreeMap<StoreId, Page>>
+        where I: IntoIterator<Item = StoreId>
+    {
+        let mut pages = BTreeMap::new();
+
+        for id in ids {
+            if pages.contains_key(&id) {
+                continue
+            }
+
+            let entry = self.store
+                .get(id.clone())?
+                .ok_or_else(|| kind_err(ErrorKind::NotFound, format!("Entry does not exist: {}", id)))?;
+
+            let links = entry
+                .get_internal_links()?
+                .map(|link| link.get_store_id().clone())
+                .filter(|id| !is_external_link_storeid(id))
+                .collect();
+
+            let page = Page {
+                id: id.clone(),
+                path: entry_page(&id),
+                title: entry_title(&entry),
+                markdown: entry.get_content().clone(),
+                tags: entry.get_tags()?,
+                links: links,
+                content_links: internal_links(entry.get_content()),
+            };
+
+            pages.insert(id, page);
+        }
+
+        Ok(pages)
+    }
+
+    fn site_data(&self, from: &str) -> Value {
+        json!({
+            "title": self.title,
+            "root": root_url(from),
+            "search_index": relative_url(from, SEARCH_INDEX),
+        })
+    }
+
+    fn item(&self, from: &str, page: &Page, text: Option<&str>) -> Value {
+        json!({
+            "id": page.id.local_display_string(),
+            "title": page.title,
+            "url": relative_url(from, &page.path),
+            "summary": text.unwrap_or(""),
+        })
+    }
+
+}
+
+/// The collections an entry is in, outermost first
+///
+/// `wiki/default/cars/mustang` is in `wiki`, `wiki/default` and `wiki/default/cars`.
+fn collections_of(id: &StoreId) -> Vec<String> {
+    let parts = id
+        .local()
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect::<Vec<String>>();
+
+    (1..parts.len()).map(|i| parts[..i].join("/")).collect()
+}
+
+/// A collection and the collections it is in, outermost first
+fn collections_of_collection(name: &str) -> Vec<String> {
+    let parts = name.split('/').collect::<Vec<&str>>();
+    (1..parts.len() + 1).map(|i| parts[..i].join("/")).collect()
+}
+
+fn last_segment(collection: &str) -> &str {
+    collection.rsplit('/').next().unwrap_or(collection)
+}
+
+fn breadcrumbs(from: &str, collections: &[String]) -> Vec<Value> {
+    collections
+        .iter()
+        .map(|c| json!({ "name": last_segment(c), "url": relative_url(from, &collection_page(c)) }))
+        .collect()
+}
+
+fn write_file(out: &Path, path: &str, content: &str) -> Result<()> {
+    let path = out.join(path);
+    if let Some(parent) = path.parent() {
+        let _ = create_dir_all(parent)
+            .context(format_err!("Cannot create directory: {}", parent.display()))?;
+    }
+
+    File::create(&path)
+        .and_then(|mut file| file.write_all(content.as_bytes()))
+        .context(format_err!("Cannot write file: {}", path.display()))
+        .map_err(From::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs::File;
+    use std::io::Read;
+    use std::path::PathBuf;
+
+    use tempfile::tempdir;
+
+    use libimagstore::store::Store;
+    use libimagentrylink::internal::InternalLinker;
+    use libimagentrytag::tagable::Tagable;
+
+    fn setup_logging() {
+        let _ = ::env_logger::try_init();
+    }
+
+    fn read(path: PathBuf) -> String {
+        let mut s = String::new();
+        let _ = File::open(path).unwrap().read_to_string(&mut s).unwrap();
+        s
+    }
+
+    fn id(s: &str) -> StoreId {
+        StoreId::new(PathBuf::from(s)).unwrap()
+    }
+
+    #[test]
+    fn test_collections_of() {
+        let exp = vec!["wiki", "wiki/default", "wiki/default/cars"];
+        assert_eq!(exp, collections_of(&id("wiki/default/cars/mustang")));
+        assert!(collections_of(&id("toplevel")).is_empty());
+        assert_eq!(exp, collections_of_collection("wiki/default/cars"));
+    }
+
+    #[test]
+    fn test_export() {
+        setup_logging();
+        let store = Store::new_inmemory(PathBuf::from("/"), &None).unwrap();
+        let out   = tempdir().unwrap();
+
+        {
+            let mut index = store.create(PathBuf::from("wiki/default/index")).unwrap();
+            let mut page  = store.create(PathBuf::from("wiki/default/cars/mustang")).unwrap();
+            let mut other = store.create(PathBuf::from("notes/other")).unwrap();
+
+            *index.get_content_mut() = String::from("# Welcome\n\nSee [the mustang](wiki/default/cars/mustang).\n");
+            *page.get_content_mut()  = String::from("# Mustang\n\nA car. [Not exported](notes/other)\n");
+            page.add_tag(String::from("cars")).unwrap();
+            index.add_internal_link(&mut page).unwrap();
+            page.add_internal_link(&mut other).unwrap();
+        }
+
+        let report = SiteExporter::new(&store)
+            .with_title(String::from("My wiki"))
+            .export(vec![id("wiki/default/index"), id("wiki/default/cars/mustang")], out.path())
+            .unwrap();
+
+        assert_eq!(2, report.entries);
+        assert_eq!(3, report.collections);
+        assert_eq!(1, report.tags);
+
+        let index = read(out.path().join("entries/wiki/default/index.html"));
+        assert!(index.contains("<title>Welcome - My wiki</title>"));
+        assert!(index.contains(r#"<a href="../../../entries/wiki/default/cars/mustang.html">the mustang</a>"#));
+
+        let page = read(out.path().join("entries/wiki/default/cars/mustang.html"));
+        assert!(page.contains(r#"<span class="missing-link">Not exported</span>"#));
+        assert!(page.contains(r#"<a href="../../../../tags/cars.html">cars</a>"#));
+        assert!(page.contains(r#"<a href="../../../../entries/wiki/default/index.html">Welcome</a>"#));
+        assert!(page.contains(r#"<a href="../../../../collections/wiki/default/cars.html">cars</a>"#));
+
+        let coll = read(out.path().join("collections/wiki/default.html"));
+        assert!(coll.contains(r#"<a href="../../collections/wiki/default/cars.html">cars/</a>"#));
+        assert!(coll.contains(r#"<a href="../../entries/wiki/default/index.html">Welcome</a>"#));
+
+        let tag = read(out.path().join("tags/cars.html"));
+        assert!(tag.contains(r#"<a href="../entries/wiki/default/cars/mustang.html">Mustang</a>"#));
+
+        let start = read(out.path().join("index.html"));
+        assert!(start.contains(r#"<a href="collections/wiki.html">wiki/</a>"#));
+        assert!(start.contains(r#"<a href="tags/cars.html">cars</a> (1)"#));
+
+        let search : Value = ::serde_json::from_str(&read(out.path().join("search-index.json"))).unwrap();
+        let search = search.as_array().unwrap();
+        assert_eq!(2, search.len());
+        assert_eq!("entries/wiki/default/cars/mustang.html", search[0]["url"]);
+        assert_eq!("Mustang A car. Not exported", search[0]["text"]);
+    }
+
+}
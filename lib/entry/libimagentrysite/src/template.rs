@@ -0,0 +1,335 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2019 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+//! Templates for the pages of a site
+//!
+//! There are four page templates:
+//!
+//! * `entry`: the page of an entry
+//! * `collection`: the index page of a collection
+//! * `tag`: the index page of a tag
+//! * `index`: the start page of the site
+//!
+//! and two partials, `header` and `footer`, which the default page templates include with
+//! `{{> header}}` and `{{> footer}}`. Each of them can be replaced by a file `<name>.hbs` in a
+//! template directory.
+//!
+//! Besides the builtin handlebars helpers, the `abbrev`, `lpad` and `rpad` helpers from
+//! `libimaginteraction::format` can be used. Their output is HTML-escaped, like `{{value}}`.
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use failure::Fallible as Result;
+use failure::Error;
+use failure::ResultExt;
+use handlebars::Context;
+use handlebars::Handlebars;
+use handlebars::Helper;
+use handlebars::HelperDef;
+use handlebars::Output;
+use handlebars::RenderContext;
+use handlebars::RenderError;
+use handlebars::html_escape;
+use serde_json::Value;
+
+use libimagerror::kind::ErrorKind;
+use libimagerror::kind::KindError;
+use libimaginteraction::format::AbbrevHelper;
+use libimaginteraction::format::LeftPadHelper;
+use libimaginteraction::format::RightPadHelper;
+
+/// The names of all templates
+pub const TEMPLATE_NAMES : &'static [&'static str] = &[
+    "header",
+    "footer",
+    "entry",
+    "collection",
+    "tag",
+    "index",
+];
+
+pub const HEADER_TEMPLATE : &'static str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{{title}} - {{site.title}}</title>
+<style>
+body { max-width: 50em; margin: 2em auto; padding: 0 1em; font-family: sans-serif; line-height: 1.5; }
+nav, footer, .summary { color: #666; }
+.missing-link { color: #a00; }
+</style>
+</head>
+<body>
+<nav><a href="{{site.root}}index.html">{{site.title}}</a>{{#each breadcrumbs}} / <a href="{{url}}">{{name}}</a>{{/each}}</nav>
+"#;
+
+pub const FOOTER_TEMPLATE : &'static str = r#"<footer>Exported from imag</footer>
+</body>
+</html>
+"#;
+
+pub const ENTRY_TEMPLATE : &'static str = r#"{{> header}}
+<article>
+{{{content}}}
+</article>
+{{#if tags}}
+<section class="tags">
+<h2>Tags</h2>
+<ul>
+{{#each tags}}<li><a href="{{url}}">{{name}}</a></li>
+{{/each}}</ul>
+</section>
+{{/if}}
+{{#if backlinks}}
+<section class="backlinks">
+<h2>Linked from</h2>
+<ul>
+{{#each backlinks}}<li><a href="{{url}}">{{title}}</a></li>
+{{/each}}</ul>
+</section>
+{{/if}}
+{{#if links}}
+<section class="links">
+<h2>See also</h2>
+<ul>
+{{#each links}}<li><a href="{{url}}">{{title}}</a></li>
+{{/each}}</ul>
+</section>
+{{/if}}
+{{> footer}}
+"#;
+
+pub const COLLECTION_TEMPLATE : &'static str = r#"{{> header}}
+<h1>{{title}}</h1>
+{{#if collections}}
+<ul>
+{{#each collections}}<li><a href="{{url}}">{{name}}/</a></li>
+{{/each}}</ul>
+{{/if}}
+<ul>
+{{#each entries}}<li><a href="{{url}}">{{title}}</a> <span class="summary">{{abbrev 80 summary}}</span></li>
+{{/each}}</ul>
+{{> footer}}
+"#;
+
+pub const TAG_TEMPLATE : &'static str = r#"{{> header}}
+<h1>Tag: {{title}}</h1>
+<ul>
+{{#each entries}}<li><a href="{{url}}">{{title}}</a> <span class="summary">{{abbrev 80 summary}}</span></li>
+{{/each}}</ul>
+{{> footer}}
+"#;
+
+pub const INDEX_TEMPLATE : &'static str = r#"{{> header}}
+<h1>{{site.title}}</h1>
+<input id="search" type="search" placeholder="Search">
+<ul id="results"></ul>
+{{#if collections}}
+<h2>Collections</h2>
+<ul>
+{{#each collections}}<li><a href="{{url}}">{{name}}/</a></li>
+{{/each}}</ul>
+{{/if}}
+{{#if entries}}
+<h2>Entries</h2>
+<ul>
+{{#each entries}}<li><a href="{{url}}">{{title}}</a></li>
+{{/each}}</ul>
+{{/if}}
+{{#if tags}}
+<h2>Tags</h2>
+<ul>
+{{#each tags}}<li><a href="{{url}}">{{name}}</a> ({{count}})</li>
+{{/each}}</ul>
+{{/if}}
+<script>
+var index = [];
+fetch("{{site.search_index}}").then(function (r) { return r.json(); }).then(function (i) { index = i; });
+document.getElementById("search").addEventListener("input", function (ev) {
+    var query   = ev.target.value.toLowerCase();
+    var results = document.getElementById("results");
+    results.innerHTML = "";
+    if (query.length < 2) { return; }
+    index.filter(function (e) {
+        return e.title.toLowerCase().indexOf(query) !== -1 || e.text.toLowerCase().indexOf(query) !== -1;
+    }).forEach(function (e) {
+        var a = document.createElement("a");
+        a.href = e.url;
+        a.textContent = e.title;
+        var li = document.createElement("li");
+        li.appendChild(a);
+        results.appendChild(li);
+    });
+});
+</script>
+{{> footer}}
+"#;
+
+/// The templates a site is rendered with
+pub struct Templates(Handlebars);
+
+impl Templates {
+
+    /// The default templates
+    pub fn new() -> Result<Templates> {
+        let mut hb = Handlebars::new();
+        for name in TEMPLATE_NAMES {
+            let _ = hb
+                .register_template_string(name, default_template(name))
+                .map_err(Error::from)?;
+        }
+
+        hb.register_helper("abbrev", Box::new(Escaped(AbbrevHelper)));
+        hb.register_helper("lpad"  , Box::new(Escaped(LeftPadHelper)));
+        hb.register_helper("rpad"  , Box::new(Escaped(RightPadHelper)));
+
+        Ok(Templates(hb))
+    }
+
+    /// The default templates, with the templates from `dir` replacing them
+    ///
+    /// Only the templates for which a file `<name>.hbs` exists in `dir` are replaced.
+    pub fn from_dir(dir: &Path) -> Result<Templates> {
+        let mut templates = Templates::new()?;
+
+        for name in TEMPLATE_NAMES {
+            let path = dir.join(format!("{}.hbs", name));
+            if !path.exists() {
+                continue
+            }
+
+            debug!("Loading template {}", path.display());
+            let mut template = String::new();
+            let _ = File::open(&path)
+                .and_then(|mut file| file.read_to_string(&mut template))
+                .context(format_err!("Cannot read template: {}", path.display()))?;
+
+            let _ = templates.0
+                .register_template_string(name, template)
+                .map_err(Error::from)
+                .context(KindError::new(ErrorKind::Parse, format!("Cannot parse template: {}", path.display())))?;
+        }
+
+        Ok(templates)
+    }
+
+    /// Render the template `name` with `data`
+    pub fn render(&self, name: &str, data: &Value) -> Result<String> {
+        self.0
+            .render(name, data)
+            .map_err(Error::from)
+            .context(format_err!("Cannot render template: {}", name))
+            .map_err(Error::from)
+    }
+
+}
+
+/// Escapes the output of a helper
+///
+/// The helpers from `libimaginteraction::format` write their output unescaped. Escaping their
+/// arguments instead would not work, as `abbrev` could cut an entity in half.
+struct Escaped<H: HelperDef>(H);
+
+impl<H: HelperDef> HelperDef for Escaped<H> {
+    fn call<'reg: 'rc, 'rc>(&self, h: &Helper<'reg, 'rc>, r: &'reg Handlebars, ctx: &'rc Context, rc: &mut RenderContext<'reg>, out: &mut Output) -> ::std::result::Result<(), RenderError> {
+        let mut raw = StringOutput(String::new());
+        self.0.call(h, r, ctx, rc, &mut raw)?;
+        out.write(&html_escape(&raw.0))?;
+        Ok(())
+    }
+}
+
+struct StringOutput(String);
+
+impl Output for StringOutput {
+    fn write(&mut self, seg: &str) -> ::std::io::Result<()> {
+        self.0.push_str(seg);
+        Ok(())
+    }
+}
+
+fn default_template(name: &str) -> &'static str {
+    match name {
+        "header"     => HEADER_TEMPLATE,
+        "footer"     => FOOTER_TEMPLATE,
+        "entry"      => ENTRY_TEMPLATE,
+        "collection" => COLLECTION_TEMPLATE,
+        "tag"        => TAG_TEMPLATE,
+        _            => INDEX_TEMPLATE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs::File;
+    use std::io::Write;
+
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_default_templates() {
+        let templates = Templates::new().unwrap();
+        let data      = json!({
+            "site": { "title": "My site", "root": "../" },
+            "title": "Tag <one>",
+            "entries": [ { "title": "A", "url": "../entries/a.html", "summary": "Some summary" } ],
+        });
+
+        let html = templates.render("tag", &data).unwrap();
+        assert!(html.contains("<title>Tag &lt;one&gt; - My site</title>"));
+        assert!(html.contains(r#"<a href="../index.html">My site</a>"#));
+        assert!(html.contains(r#"<a href="../entries/a.html">A</a>"#));
+    }
+
+    #[test]
+    fn test_helper_output_is_escaped() {
+        let templates = Templates::new().unwrap();
+        let summary   = format!("{}& <b>", "x".repeat(79));
+        let data      = json!({
+            "site": { "title": "My site", "root": "../" },
+            "title": "tag",
+            "entries": [ { "title": "A", "url": "../entries/a.html", "summary": summary } ],
+        });
+
+        let html = templates.render("tag", &data).unwrap();
+        assert!(html.contains(&format!(r#"<span class="summary">{}&amp;</span>"#, "x".repeat(79))), "{}", html);
+        assert!(!html.contains("<b>"));
+    }
+
+    #[test]
+    fn test_templates_from_dir() {
+        let dir = tempdir().unwrap();
+        let _   = File::create(dir.path().join("footer.hbs"))
+            .unwrap()
+            .write_all(b"<footer>{{lpad 5 site.title}}</footer>")
+            .unwrap();
+
+        let templates = Templates::from_dir(dir.path()).unwrap();
+        let data      = json!({ "site": { "title": "imag", "root": "" }, "title": "Start" });
+
+        let html = templates.render("index", &data).unwrap();
+        assert!(html.contains("<footer> imag</footer>"));
+    }
+
+}
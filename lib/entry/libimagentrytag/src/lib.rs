@@ -45,7 +45,7 @@ extern crate toml;
 extern crate toml_query;
 #[macro_use] extern crate is_match;
 extern crate filters;
-#[macro_use] extern crate failure;
+extern crate failure;
 
 extern crate libimagstore;
 extern crate libimagerror;
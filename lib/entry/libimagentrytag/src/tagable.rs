@@ -21,6 +21,8 @@ use itertools::Itertools;
 
 use libimagstore::store::Entry;
 use libimagerror::errors::ErrorMsg as EM;
+use libimagerror::kind::ErrorKind;
+use libimagerror::kind::kind_err;
 
 use toml_query::read::TomlValueReadExt;
 use toml_query::insert::TomlValueInsertExt;
@@ -59,14 +61,15 @@ impl Tagable for Value {
                     .map(|tags| {
                         debug!("Got Array<T> of tags...");
                         if !tags.iter().all(|t| is_match!(*t, Value::String(_))) {
-                            return Err(format_err!("Tag type error: Got Array<T> where T is not a String: {:?}", tags));
+                            let msg = format!("Tag type error: Got Array<T> where T is not a String: {:?}", tags);
+                            return Err(kind_err(ErrorKind::Parse, msg));
                         }
                         debug!("Got Array<String> of tags...");
                         if tags.iter().any(|t| match *t {
                             Value::String(ref s) => !is_tag_str(s).is_ok(),
                             _ => unreachable!()})
                         {
-                            return Err(format_err!("At least one tag is not a valid tag string"));
+                            return Err(kind_err(ErrorKind::Parse, "At least one tag is not a valid tag string"));
                         }
 
                         Ok(tags.iter()
@@ -87,7 +90,7 @@ impl Tagable for Value {
     fn set_tags(&mut self, ts: &[Tag]) -> Result<()> {
         if ts.iter().any(|tag| !is_tag_str(tag).is_ok()) {
             let not_tag = ts.iter().filter(|t| !is_tag_str(t).is_ok()).next().unwrap();
-            return Err(format_err!("Not a tag: '{}'", not_tag));
+            return Err(kind_err(ErrorKind::Parse, format!("Not a tag: '{}'", not_tag)));
         }
 
         let a = ts.iter().unique().map(|t| Value::String(t.clone())).collect();
@@ -99,10 +102,10 @@ impl Tagable for Value {
 
     fn add_tag(&mut self, t: Tag) -> Result<()> {
         if !is_tag_str(&t).map(|_| true)
-            .map_err(|s| format_err!("{}", s))
+            .map_err(|s| kind_err(ErrorKind::Parse, s))
             .context(err_msg("Not a tag"))?
         {
-            return Err(format_err!("Not a tag: '{}'", t));
+            return Err(kind_err(ErrorKind::Parse, format!("Not a tag: '{}'", t)));
         }
 
         self.get_tags()
@@ -116,11 +119,11 @@ impl Tagable for Value {
 
     fn remove_tag(&mut self, t: Tag) -> Result<()> {
         if !is_tag_str(&t).map(|_| true)
-            .map_err(|s| format_err!("{}", s))
+            .map_err(|s| kind_err(ErrorKind::Parse, s))
             .context(err_msg("Not a tag"))?
         {
             debug!("Not a tag: '{}'", t);
-            return Err(format_err!("Not a tag: '{}'", t));
+            return Err(kind_err(ErrorKind::Parse, format!("Not a tag: '{}'", t)));
         }
 
         self.get_tags()
@@ -135,7 +138,7 @@ impl Tagable for Value {
         let tags = self.read("tag.values").context(EM::EntryHeaderReadError)?;
 
         if !tags.iter().all(|t| is_match!(*t, &Value::String(_))) {
-            return Err(err_msg("Tag type error"))
+            return Err(kind_err(ErrorKind::Parse, "Tag type error"))
         }
 
         Ok(tags
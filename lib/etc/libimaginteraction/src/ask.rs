@@ -27,9 +27,7 @@ use std::io::Write;
 
 use regex::Regex;
 use ansi_term::Colour::*;
-use interactor::*;
 use failure::Error;
-use failure::ResultExt;
 use failure::Fallible as Result;
 use failure::err_msg;
 
@@ -166,10 +164,14 @@ fn ask_string_<R: BufRead>(s: &str,
     }
 }
 
+/// Let the user select one item of `list` with the fuzzy finder (see `picker`)
 pub fn ask_select_from_list(list: &[&str]) -> Result<String> {
-    pick_from_list(default_menu_cmd().as_mut(), list, "Selection: ")
-        .context(err_msg("Unknown interaction error"))
-        .map_err(Error::from)
+    ::picker::Picker::new(list.iter().map(|s| String::from(*s)).collect())
+        .with_prompt("Selection: ")
+        .pick()?
+        .into_iter()
+        .next()
+        .ok_or_else(|| err_msg("Nothing selected"))
 }
 
 /// Helper function to print a imag question string. The `question` argument may not contain a
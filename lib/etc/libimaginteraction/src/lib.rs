@@ -37,7 +37,7 @@
     while_true,
 )]
 
-extern crate interactor;
+#[macro_use] extern crate log;
 extern crate ansi_term;
 #[macro_use] extern crate lazy_static;
 extern crate regex;
@@ -45,7 +45,10 @@ extern crate clap;
 extern crate toml;
 extern crate handlebars;
 extern crate serde_json;
-extern crate failure;
+extern crate toml_query;
+extern crate rustyline;
+extern crate termion;
+#[macro_use] extern crate failure;
 
 extern crate libimagstore;
 extern crate libimagerror;
@@ -53,5 +56,7 @@ extern crate libimagerror;
 pub mod ask;
 pub mod filter;
 pub mod format;
+pub mod picker;
+pub mod readline;
 pub mod ui;
 
@@ -0,0 +1,391 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2019 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+//! A fuzzy finder for picking items from a list
+//!
+//! The picker draws on the terminal (`/dev/tty`), so it also works if stdin or stdout of the
+//! process are redirected. The user types a pattern, the items are filtered and ranked by how well
+//! they match it. Optionally, a preview of the item under the cursor is shown next to the list.
+//!
+//! Keys:
+//!
+//! * Up/Down, Ctrl-p/Ctrl-n: move the cursor
+//! * Tab: select/deselect the item under the cursor (if multiple selections are allowed)
+//! * Enter: accept the selection, or the item under the cursor if nothing is selected
+//! * Ctrl-u: clear the pattern
+//! * Esc, Ctrl-c: abort
+//!
+
+use std::collections::BTreeSet;
+use std::io::Write;
+
+use termion::event::Key;
+use termion::input::TermRead;
+use termion::raw::IntoRawMode;
+use termion::screen::AlternateScreen;
+use termion::clear;
+use termion::cursor;
+use termion::style;
+
+use failure::Fallible as Result;
+use failure::ResultExt;
+use failure::Error;
+use failure::err_msg;
+
+/// Score how well `pattern` matches `candidate`
+///
+/// The characters of the pattern have to appear in the candidate in the same order, but not
+/// necessarily next to each other. Matching is case insensitive. Consecutive matches and matches at
+/// the start of a path component or word rank higher, characters skipped between matches rank
+/// lower.
+///
+/// Returns `None` if the pattern does not match.
+pub fn fuzzy_score(pattern: &str, candidate: &str) -> Option<i64> {
+    fn is_separator(c: char) -> bool {
+        c == '/' || c == '-' || c == '_' || c == '.' || c == ' '
+    }
+
+    let candidate = candidate.chars().collect::<Vec<char>>();
+    let mut score = 0;
+    let mut pos   = 0;
+    let mut last  = None;
+
+    for pc in pattern.chars().flat_map(char::to_lowercase) {
+        let idx = candidate[pos..]
+            .iter()
+            .position(|cc| cc.to_lowercase().any(|c| c == pc))
+            .map(|i| i + pos)?;
+
+        score += 1;
+
+        let gap = match last {
+            Some(l) => idx - l - 1,
+            None    => idx,
+        };
+
+        if gap == 0 && last.is_some() {
+            score += 5;
+        } else {
+            score -= gap as i64;
+        }
+
+        if idx == 0 || is_separator(candidate[idx - 1]) {
+            score += 3;
+        }
+
+        last = Some(idx);
+        pos  = idx + 1;
+    }
+
+    Some(score)
+}
+
+/// The state of the picker, independent of the terminal
+struct PickerState<'a> {
+    items: &'a [String],
+    multi: bool,
+    query: String,
+
+    /// Indices into `items`, best match first
+    matches: Vec<usize>,
+
+    /// Index into `matches`
+    cursor: usize,
+
+    /// Indices into `items`
+    selected: BTreeSet<usize>,
+}
+
+impl<'a> PickerState<'a> {
+
+    fn new(items: &'a [String], multi: bool) -> PickerState<'a> {
+        let mut state = PickerState {
+            items,
+            multi,
+            query:    String::new(),
+            matches:  vec![],
+            cursor:   0,
+            selected: BTreeSet::new(),
+        };
+        state.update();
+        state
+    }
+
+    fn update(&mut self) {
+        let mut scored = self.items
+            .iter()
+            .enumerate()
+            .filter_map(|(i, item)| fuzzy_score(&self.query, item).map(|score| (score, i)))
+            .collect::<Vec<_>>();
+
+        // best score first, original order for equal scores
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+
+        self.matches = scored.into_iter().map(|(_, i)| i).collect();
+        self.cursor  = 0;
+    }
+
+    fn push_char(&mut self, c: char) {
+        self.query.push(c);
+        self.update();
+    }
+
+    fn pop_char(&mut self) {
+        let _ = self.query.pop();
+        self.update();
+    }
+
+    fn clear_query(&mut self) {
+        self.query.clear();
+        self.update();
+    }
+
+    fn up(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+        }
+    }
+
+    fn down(&mut self) {
+        if self.cursor + 1 < self.matches.len() {
+            self.cursor += 1;
+        }
+    }
+
+    fn current(&self) -> Option<usize> {
+        self.matches.get(self.cursor).cloned()
+    }
+
+    fn toggle(&mut self) {
+        if !self.multi {
+            return
+        }
+
+        if let Some(i) = self.current() {
+            if !self.selected.remove(&i) {
+                let _ = self.selected.insert(i);
+            }
+            self.down();
+        }
+    }
+
+    /// The selected items, or the item under the cursor if nothing is selected
+    fn selection(&self) -> Vec<usize> {
+        if self.selected.is_empty() {
+            self.current().into_iter().collect()
+        } else {
+            self.selected.iter().cloned().collect()
+        }
+    }
+
+}
+
+/// An interactive fuzzy finder
+pub struct Picker<'a> {
+    items: Vec<String>,
+    multi: bool,
+    prompt: String,
+    preview: Option<Box<Fn(&str) -> String + 'a>>,
+}
+
+impl<'a> Picker<'a> {
+
+    pub fn new(items: Vec<String>) -> Picker<'a> {
+        Picker {
+            items,
+            multi: false,
+            prompt: String::from("> "),
+            preview: None,
+        }
+    }
+
+    /// Allow selecting more than one item
+    pub fn multi(mut self, multi: bool) -> Self {
+        self.multi = multi;
+        self
+    }
+
+    pub fn with_prompt<S: Into<String>>(mut self, prompt: S) -> Self {
+        self.prompt = prompt.into();
+        self
+    }
+
+    /// Show the string returned by `preview` for the item under the cursor next to the list
+    pub fn with_preview<F>(mut self, preview: F) -> Self
+        where F: Fn(&str) -> String + 'a
+    {
+        self.preview = Some(Box::new(preview));
+        self
+    }
+
+    /// Run the picker
+    ///
+    /// Returns the picked items, which is empty if the user aborted.
+    pub fn pick(self) -> Result<Vec<String>> {
+        let tty   = ::termion::get_tty().context(err_msg("Cannot open the terminal"))?;
+        let input = tty.try_clone().map_err(Error::from)?;
+        let raw   = tty.into_raw_mode().context(err_msg("Cannot switch the terminal to raw mode"))?;
+        let mut out = AlternateScreen::from(raw);
+
+        let mut state = PickerState::new(&self.items, self.multi);
+        self.render(&mut out, &state)?;
+
+        for key in input.keys() {
+            match key.map_err(Error::from)? {
+                Key::Esc | Key::Ctrl('c') | Key::Ctrl('g') => return Ok(vec![]),
+                Key::Char('\n') => {
+                    let picked = state
+                        .selection()
+                        .into_iter()
+                        .map(|i| self.items[i].clone())
+                        .collect();
+                    return Ok(picked)
+                },
+                Key::Char('\t')                => state.toggle(),
+                Key::Up   | Key::Ctrl('p')     => state.up(),
+                Key::Down | Key::Ctrl('n')     => state.down(),
+                Key::Backspace                 => state.pop_char(),
+                Key::Ctrl('u')                 => state.clear_query(),
+                Key::Char(c) if !c.is_control() => state.push_char(c),
+                _                              => {},
+            }
+
+            self.render(&mut out, &state)?;
+        }
+
+        Ok(vec![])
+    }
+
+    fn render<W: Write>(&self, out: &mut W, state: &PickerState) -> Result<()> {
+        let (width, height) = ::termion::terminal_size().unwrap_or((80, 24));
+        let width  = width as usize;
+        let rows   = (height as usize).saturating_sub(2);
+
+        // The preview takes the right half, if there is enough space
+        let list_width = match self.preview {
+            Some(_) if width >= 60 => width / 2,
+            _                      => width,
+        };
+
+        write!(out, "{}{}", clear::All, cursor::Goto(1, 2))?;
+        write!(out, "  {}/{}", state.matches.len(), self.items.len())?;
+        if !state.selected.is_empty() {
+            write!(out, " ({} selected)", state.selected.len())?;
+        }
+
+        let top = if state.cursor >= rows { state.cursor + 1 - rows } else { 0 };
+        for (row, &i) in state.matches.iter().skip(top).take(rows).enumerate() {
+            let is_cursor = top + row == state.cursor;
+            let marker    = match (is_cursor, state.selected.contains(&i)) {
+                (true, true)   => ">*",
+                (true, false)  => "> ",
+                (false, true)  => " *",
+                (false, false) => "  ",
+            };
+
+            let line = truncate(&format!("{}{}", marker, self.items[i]), list_width.saturating_sub(1));
+            write!(out, "{}", cursor::Goto(1, (row + 3) as u16))?;
+            if is_cursor {
+                write!(out, "{}{}{}", style::Invert, line, style::Reset)?;
+            } else {
+                write!(out, "{}", line)?;
+            }
+        }
+
+        if let (Some(preview), Some(i)) = (self.preview.as_ref(), state.current()) {
+            if list_width < width {
+                let preview_width = width - list_width - 2;
+                let text          = preview(&self.items[i]);
+
+                for (row, line) in text.lines().take(rows + 1).enumerate() {
+                    let line = truncate(&line.replace('\t', "    "), preview_width);
+                    write!(out, "{}| {}", cursor::Goto((list_width + 1) as u16, (row + 2) as u16), line)?;
+                }
+            }
+        }
+
+        let prompt = format!("{}{}", self.prompt, state.query);
+        write!(out, "{}{}", cursor::Goto(1, 1), prompt)?;
+        out.flush().map_err(Error::from)
+    }
+
+}
+
+fn truncate(s: &str, width: usize) -> String {
+    s.chars().take(width).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score_matches() {
+        assert!(fuzzy_score("", "anything").is_some());
+        assert!(fuzzy_score("nts", "notes/foo").is_some());
+        assert!(fuzzy_score("NOTES", "notes/foo").is_some());
+        assert!(fuzzy_score("xyz", "notes/foo").is_none());
+        assert!(fuzzy_score("oof", "notes/foo").is_none());
+    }
+
+    #[test]
+    fn test_fuzzy_score_ranking() {
+        // consecutive matches rank higher
+        assert!(fuzzy_score("foo", "notes/foo") > fuzzy_score("foo", "notes/f/o/o"));
+
+        // matches at the start of path components rank higher
+        assert!(fuzzy_score("f", "notes/foo") > fuzzy_score("f", "notesfoo"));
+    }
+
+    #[test]
+    fn test_picker_state() {
+        let items = vec![String::from("notes/foo"), String::from("diary/2019"), String::from("notes/bar")];
+        let mut state = PickerState::new(&items, true);
+        assert_eq!(vec![0, 1, 2], state.matches);
+
+        state.push_char('n');
+        state.push_char('o');
+        assert_eq!(vec![0, 2], state.matches);
+        assert_eq!(vec![0], state.selection());
+
+        state.toggle();
+        state.toggle();
+        assert_eq!(vec![0, 2], state.selection());
+
+        state.pop_char();
+        state.pop_char();
+        state.push_char('x');
+        assert!(state.matches.is_empty());
+        assert_eq!(vec![0, 2], state.selection());
+    }
+
+    #[test]
+    fn test_picker_state_single() {
+        let items = vec![String::from("a"), String::from("b")];
+        let mut state = PickerState::new(&items, false);
+        state.toggle();
+        assert!(state.selected.is_empty());
+
+        state.down();
+        state.down();
+        assert_eq!(vec![1], state.selection());
+        state.up();
+        assert_eq!(vec![0], state.selection());
+    }
+}
@@ -18,73 +18,91 @@
 //
 
 
+use std::path::PathBuf;
+use std::fs::File;
+
 use failure::ResultExt;
+use failure::Fallible as Result;
+use failure::Error;
+use failure::err_msg;
 use toml::Value;
+use toml_query::read::TomlValueReadExt;
 
 use rustyline::{Config, Editor};
-
-pub struct Readline {
-    editor: Editor,
+use rustyline::Helper;
+
+use libimagerror::errors::ErrorMsg as EM;
+use libimagerror::kind::ErrorKind;
+use libimagerror::kind::kind_err;
+
+/// A readline-like line editor with a history file, configured from the `ui.cli` section of the
+/// configuration
+///
+/// The `H` type can be used to provide completion, hinting and highlighting.
+pub struct Readline<H: Helper = ()> {
+    editor: Editor<H>,
     history_file: PathBuf,
     prompt: String,
 }
 
-impl Readline {
+impl Readline<()> {
+
+    pub fn new(config: Option<&Value>) -> Result<Readline<()>> {
+        Readline::with_helper(config, None)
+    }
+
+}
 
-    pub fn new(rt: &Runtime) -> Result<Readline> {
-        let c = rt.config().ok_or(IEK::NoConfigError)?;
+impl<H: Helper> Readline<H> {
 
-        let histfile     = c.lookup("ui.cli.readline_history_file").ok_or(IEK::ConfigError)?;
-        let histsize     = c.lookup("ui.cli.readline_history_size").ok_or(IEK::ConfigError)?;
-        let histigndups  = c.lookup("ui.cli.readline_history_ignore_dups").ok_or(IEK::ConfigError)?;
-        let histignspace = c.lookup("ui.cli.readline_history_ignore_space").ok_or(IEK::ConfigError)?;
-        let prompt       = c.lookup("ui.cli.readline_prompt").ok_or(IEK::ConfigError)?;
+    pub fn with_helper(config: Option<&Value>, helper: Option<H>) -> Result<Readline<H>> {
+        let c = config.ok_or_else(|| kind_err(ErrorKind::Config, "No configuration"))?;
+
+        let histfile     = read_value(c, "ui.cli.readline_history_file")?;
+        let histsize     = read_value(c, "ui.cli.readline_history_size")?;
+        let histigndups  = read_value(c, "ui.cli.readline_history_ignore_dups")?;
+        let histignspace = read_value(c, "ui.cli.readline_history_ignore_space")?;
+        let prompt       = read_value(c, "ui.cli.readline_prompt")?;
 
         let histfile = histfile
             .as_str()
             .map(PathBuf::from)
-            .ok_or(IE::from_kind(IEK::ConfigTypeError))
-            .context(IEK::ConfigError)
-            .context(IEK::ReadlineError)?;
+            .ok_or_else(|| Error::from(EM::ConfigTypeError("ui.cli.readline_history_file", "String")))?;
 
         let histsize = histsize
-            .as_int()
-            .ok_or(IE::from_kind(IEK::ConfigTypeError))
-            .context(IEK::ConfigError)
-            .context(IEK::ReadlineError)?;
+            .as_integer()
+            .ok_or_else(|| Error::from(EM::ConfigTypeError("ui.cli.readline_history_size", "Integer")))?;
 
         let histigndups = histigndups
             .as_bool()
-            .ok_or(IE::from_kind(IEK::ConfigTypeError))
-            .context(IEK::ConfigError)
-            .context(IEK::ReadlineError)?;
+            .ok_or_else(|| Error::from(EM::ConfigTypeError("ui.cli.readline_history_ignore_dups", "Boolean")))?;
 
         let histignspace = histignspace
             .as_bool()
-            .ok_or(IE::from_kind(IEK::ConfigTypeError))
-            .context(IEK::ConfigError)
-            .context(IEK::ReadlineError)?;
+            .ok_or_else(|| Error::from(EM::ConfigTypeError("ui.cli.readline_history_ignore_space", "Boolean")))?;
 
         let prompt = prompt
             .as_str()
-            .ok_or(IE::from_kind(IEK::ConfigTypeError))
-            .context(IEK::ConfigError)
-            .context(IEK::ReadlineError)?;
+            .map(String::from)
+            .ok_or_else(|| Error::from(EM::ConfigTypeError("ui.cli.readline_prompt", "String")))?;
 
-        let config = Config::builder().
-            .max_history_size(histsize)
+        let config = Config::builder()
+            .max_history_size(histsize as usize)
             .history_ignore_dups(histigndups)
             .history_ignore_space(histignspace)
             .build();
 
-        let mut editor = Editor::new(config);
+        let mut editor = Editor::with_config(config);
+        editor.set_helper(helper);
 
         if !histfile.exists() {
             let _ = File::create(histfile.clone())
-                         .context(IEK::ReadlineHistoryFileCreationError)?;
+                .context(err_msg("Readline history file creation error"))?;
         }
 
-        let _ = editor.load_history(&histfile).context(ReadlineError)?;
+        let _ = editor
+            .load_history(&histfile)
+            .map_err(|e| format_err!("Readline error: {:?}", e))?;
 
         Ok(Readline {
             editor: editor,
@@ -93,18 +111,24 @@ impl Readline {
         })
     }
 
+    /// Change the prompt
+    pub fn set_prompt<S: Into<String>>(&mut self, prompt: S) {
+        self.prompt = prompt.into();
+    }
+
+    /// Read a line
+    ///
+    /// Returns `None` if the user hits CTRL-C or CTRL-D.
     pub fn read_line(&mut self) -> Result<Option<String>> {
-        use rustyline::ReadlineError;
-        use libimagutil::warn_result::*;
+        use rustyline::error::ReadlineError;
 
         match self.editor.readline(&self.prompt) {
             Ok(line) => {
-                self.editor.add_history_line(&line);
-                self.editor
-                    .save_history(&self.history_file)
-                    .map_warn_err_str(|e| format!("Could not save history file {} -> {:?}",
-                                                  self.history_file.display(), e));
-                return line;
+                let _ = self.editor.add_history_entry(line.as_str());
+                if let Err(e) = self.editor.save_history(&self.history_file) {
+                    warn!("Could not save history file {} -> {:?}", self.history_file.display(), e);
+                }
+                Ok(Some(line))
             },
             Err(ReadlineError::Interrupted) => {
                 info!("CTRL-C");
@@ -114,10 +138,14 @@ impl Readline {
                 info!("CTRL-D");
                 Ok(None)
             },
-            Err(err) => Err(err).map_err_into(ReadlineError),
-
+            Err(err) => Err(format_err!("Readline error: {:?}", err)),
         }
     }
 
 }
 
+fn read_value<'a>(config: &'a Value, key: &'static str) -> Result<&'a Value> {
+    config.read(key)
+        .context(EM::TomlQueryError)?
+        .ok_or_else(|| kind_err(ErrorKind::Config, format!("Config missing: {}", key)))
+}
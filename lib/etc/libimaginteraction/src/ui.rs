@@ -21,8 +21,11 @@ use std::path::PathBuf;
 
 use clap::{Arg, ArgMatches};
 
+use libimagstore::store::Store;
 use libimagstore::storeid::StoreId;
 
+use picker::Picker;
+
 use failure::err_msg;
 use failure::Fallible as Result;
 
@@ -63,15 +66,66 @@ pub fn get_id(matches: &ArgMatches) -> Result<Vec<StoreId>> {
         })
 }
 
-pub fn get_or_select_id(matches: &ArgMatches, store_path: &PathBuf) -> Result<Vec<StoreId>> {
-    use interactor::{pick_file, default_menu_cmd};
+/// Get the ids from the commandline, or let the user pick them with the fuzzy finder
+pub fn get_or_select_id(matches: &ArgMatches, store: &Store) -> Result<Vec<StoreId>> {
+    get_id(matches).or_else(|_| pick_ids(store, None, true))
+}
+
+/// Let the user pick entries from the store with the fuzzy finder (see `picker`)
+///
+/// If `collection` is passed, only entries in this collection are offered. The header and content
+/// of the entry under the cursor are shown as preview.
+///
+/// Returns an empty list if the user aborted.
+pub fn pick_ids(store: &Store, collection: Option<&str>, multi: bool) -> Result<Vec<StoreId>> {
+    let entries = store.entries()?;
+    let entries = match collection {
+        Some(c) => entries.in_collection(c),
+        None    => entries,
+    };
+
+    let mut ids = entries
+        .map(|id| id.map(|id| id.local_display_string()))
+        .collect::<Result<Vec<String>>>()?;
+    ids.sort();
+
+    Picker::new(ids)
+        .multi(multi)
+        .with_prompt("id> ")
+        .with_preview(|id| preview_entry(store, id))
+        .pick()?
+        .into_iter()
+        .map(|id| StoreId::new(PathBuf::from(id)))
+        .collect()
+}
+
+/// Let the user pick entries of `collection` with the fuzzy finder, see `pick_ids()`
+///
+/// Returns the paths of the picked entries relative to the collection, as domain modules name
+/// their entries (a note "foo" for the id "notes/foo").
+pub fn pick_names(store: &Store, collection: &str, multi: bool) -> Result<Vec<String>> {
+    pick_ids(store, Some(collection), multi)?
+        .into_iter()
+        .map(|id| {
+            id.local()
+                .strip_prefix(collection)
+                .map_err(|_| format_err!("{} is not in {}", id, collection))
+                .and_then(|name| {
+                    name.to_str()
+                        .map(String::from)
+                        .ok_or_else(|| format_err!("Not UTF-8: {}", name.display()))
+                })
+        })
+        .collect()
+}
 
-    get_id(matches)
-        .or_else(|_| {
-            let path = store_path.clone();
-            let p    = pick_file(default_menu_cmd, path)?;
-            let id   = StoreId::new(p)?;
-            Ok(vec![id])
+fn preview_entry(store: &Store, id: &str) -> String {
+    StoreId::new(PathBuf::from(id))
+        .and_then(|id| store.get(id))
+        .and_then(|entry| match entry {
+            Some(entry) => entry.to_str(),
+            None        => Ok(String::new()),
         })
+        .unwrap_or_else(|e| format!("Cannot load entry: {}", e))
 }
 
@@ -42,6 +42,16 @@ pub fn is_integer<A: AsRef<str>>(s: A) -> Result<(), String> {
     i.map(|_| ()).map_err(|_| format!("Not an integer: {}", s.as_ref()))
 }
 
+pub fn is_positive_integer<A: AsRef<str>>(s: A) -> Result<(), String> {
+    use std::str::FromStr;
+
+    let i : Result<usize, _> = FromStr::from_str(s.as_ref());
+    match i {
+        Ok(i) if i > 0 => Ok(()),
+        _              => Err(format!("Not a positive integer: {}", s.as_ref())),
+    }
+}
+
 pub fn is_float<A: AsRef<str>>(s: A) -> Result<(), String> {
     use std::str::FromStr;
 
@@ -53,6 +53,7 @@ pub mod edit;
 pub mod info_result;
 pub mod info_option;
 pub mod key_value_split;
+pub mod shell_words;
 pub mod variants;
 pub mod warn_exit;
 pub mod warn_result;
@@ -0,0 +1,112 @@
+//
+// imag - the personal information management suite for the commandline
+// Copyright (C) 2015-2019 Matthias Beyer <mail@beyermatthias.de> and contributors
+//
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; version
+// 2.1 of the License.
+//
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301  USA
+//
+
+/// Split a line into words
+///
+/// Words are separated by whitespace. Single and double quotes can be used to include whitespace in
+/// a word, a backslash escapes the next character.
+///
+/// Returns an error message if a quote is not terminated or the line ends with a backslash.
+pub fn split_words(line: &str) -> Result<Vec<String>, String> {
+    let mut words   = vec![];
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut quote   = None;
+    let mut chars   = line.chars();
+
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (_, '\\') => {
+                let next = chars.next().ok_or_else(|| String::from("Trailing backslash"))?;
+                current.push(next);
+                in_word = true;
+            },
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c)           => current.push(c),
+            (None, '"') | (None, '\'') => {
+                quote   = Some(c);
+                in_word = true;
+            },
+            (None, c) if c.is_whitespace() => if in_word {
+                words.push(current.clone());
+                current.clear();
+                in_word = false;
+            },
+            (None, c) => {
+                current.push(c);
+                in_word = true;
+            },
+        }
+    }
+
+    if let Some(q) = quote {
+        return Err(format!("Unterminated quote: {}", q))
+    }
+
+    if in_word {
+        words.push(current);
+    }
+
+    Ok(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::split_words;
+
+    #[test]
+    fn test_split_simple() {
+        let words = split_words("tag add foo bar").unwrap();
+        assert_eq!(words, vec!["tag", "add", "foo", "bar"]);
+    }
+
+    #[test]
+    fn test_split_whitespace() {
+        let words = split_words("   ids    ").unwrap();
+        assert_eq!(words, vec!["ids"]);
+        assert!(split_words("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_split_quotes() {
+        let words = split_words(r#"ids where "imag.version == '0.10.0'""#).unwrap();
+        assert_eq!(words, vec!["ids", "where", "imag.version == '0.10.0'"]);
+
+        let words = split_words(r#"notes create 'a note'"#).unwrap();
+        assert_eq!(words, vec!["notes", "create", "a note"]);
+    }
+
+    #[test]
+    fn test_split_empty_quotes() {
+        let words = split_words(r#"foo "" bar"#).unwrap();
+        assert_eq!(words, vec!["foo", "", "bar"]);
+    }
+
+    #[test]
+    fn test_split_escape() {
+        let words = split_words(r#"foo\ bar baz"#).unwrap();
+        assert_eq!(words, vec!["foo bar", "baz"]);
+    }
+
+    #[test]
+    fn test_split_unterminated() {
+        assert!(split_words(r#"foo "bar"#).is_err());
+        assert!(split_words(r#"foo\"#).is_err());
+    }
+}
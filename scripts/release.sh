@@ -28,6 +28,8 @@ CRATES=(
     ./lib/entry/libimagentryref
     ./lib/entry/libimagentrymarkdown
     ./lib/entry/libimagentryannotation
+    ./lib/entry/libimagentryquery
+    ./lib/entry/libimagentrysite
     ./lib/domain/libimagbookmark
     ./lib/domain/libimaghabit
     ./lib/domain/libimagnotes
@@ -49,11 +51,15 @@ CRATES=(
     ./bin/domain/imag-log
     ./bin/domain/imag-wiki
     ./bin/core/imag-ref
+    ./bin/core/imag-search
+    ./bin/core/imag-shell
+    ./bin/core/imag-site
     ./bin/core/imag-gps
     ./bin/core/imag-diagnostics
     ./bin/core/imag-mv
     ./bin/core/imag-store
     ./bin/core/imag-tag
+    ./bin/core/imag-undo
     ./bin/core/imag-grep
     ./bin/core/imag-annotate
     ./bin/core/imag-link